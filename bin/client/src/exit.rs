@@ -0,0 +1,70 @@
+//! Classification of the errors shared by the fault proof programs into [ClientExitReason]s.
+
+use kona_derive::errors::{PipelineError, PipelineErrorKind};
+use kona_driver::DriverError;
+use kona_executor::ExecutorError;
+use kona_std_fpvm::exit::ClientExitReason;
+
+/// Returns the [ClientExitReason] for an error returned by the derivation [kona_driver::Driver].
+pub(crate) const fn driver_exit_reason(err: &DriverError<ExecutorError>) -> ClientExitReason {
+    match err {
+        DriverError::Pipeline(PipelineErrorKind::Critical(PipelineError::EndOfSource)) => {
+            ClientExitReason::DerivationExhausted
+        }
+        DriverError::Pipeline(PipelineErrorKind::Critical(PipelineError::Provider(_))) => {
+            ClientExitReason::OracleFailure
+        }
+        DriverError::Executor(_) => ClientExitReason::ExecutionFailure,
+        DriverError::Pipeline(_) | DriverError::FromBlock(_) | DriverError::Rlp(_) => {
+            ClientExitReason::Other
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{interop, single};
+    use alloc::string::ToString;
+    use alloy_primitives::B256;
+    use kona_proof::errors::OracleProviderError;
+    use kona_proof_interop::boot::BootstrapError;
+    use kona_std_fpvm::exit::ExitReason;
+
+    #[test]
+    fn test_driver_exit_reason() {
+        let exhausted = DriverError::Pipeline(PipelineError::EndOfSource.crit());
+        assert_eq!(driver_exit_reason(&exhausted), ClientExitReason::DerivationExhausted);
+
+        let provider = DriverError::Pipeline(PipelineError::Provider("boom".to_string()).crit());
+        assert_eq!(driver_exit_reason(&provider), ClientExitReason::OracleFailure);
+
+        let executor = DriverError::Executor(ExecutorError::MissingGasLimit);
+        assert_eq!(driver_exit_reason(&executor), ClientExitReason::ExecutionFailure);
+
+        let temporary = DriverError::Pipeline(PipelineError::Eof.temp());
+        assert_eq!(driver_exit_reason(&temporary), ClientExitReason::Other);
+    }
+
+    #[test]
+    fn test_program_exit_reasons() {
+        let invalid = single::FaultProofProgramError::InvalidClaim(B256::ZERO, B256::ZERO);
+        assert_eq!(invalid.exit_reason(), ClientExitReason::InvalidClaim);
+
+        let boot = single::FaultProofProgramError::BootInfo(OracleProviderError::UnknownChainId(1));
+        assert_eq!(boot.exit_reason(), ClientExitReason::BadBootInfo);
+
+        let oracle = single::FaultProofProgramError::OracleProviderError(
+            OracleProviderError::UnknownChainId(1),
+        );
+        assert_eq!(oracle.exit_reason(), ClientExitReason::OracleFailure);
+
+        let post_state = interop::FaultProofProgramError::Bootstrap(
+            BootstrapError::InvalidPostState(B256::ZERO),
+        );
+        assert_eq!(post_state.exit_reason(), ClientExitReason::InvalidClaim);
+
+        let transition = interop::FaultProofProgramError::StateTransitionFailed;
+        assert_eq!(transition.exit_reason(), ClientExitReason::ExecutionFailure);
+    }
+}
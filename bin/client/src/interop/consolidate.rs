@@ -1,11 +1,11 @@
 //! Consolidation phase of the interop proof program.
 
 use super::FaultProofProgramError;
-use crate::interop::util::fetch_output_block_hash;
+use crate::{interop::util::fetch_output_block_hash, profile::ProfiledOracle};
 use alloc::{sync::Arc, vec::Vec};
 use core::fmt::Debug;
 use kona_preimage::{HintWriterClient, PreimageOracleClient};
-use kona_proof::{l2::OracleL2ChainProvider, CachingOracle};
+use kona_proof::l2::OracleL2ChainProvider;
 use kona_proof_interop::{
     BootInfo, HintType, OracleInteropProvider, PreState, SuperchainConsolidator,
 };
@@ -20,14 +20,18 @@ use tracing::info;
 ///
 /// [OptimisticBlock]: kona_proof_interop::OptimisticBlock
 pub(crate) async fn consolidate_dependencies<P, H>(
-    oracle: Arc<CachingOracle<P, H>>,
+    oracle: Arc<ProfiledOracle<P, H>>,
     mut boot: BootInfo,
 ) -> Result<(), FaultProofProgramError>
 where
     P: PreimageOracleClient + Send + Sync + Debug + Clone,
     H: HintWriterClient + Send + Sync + Debug + Clone,
 {
-    let provider = OracleInteropProvider::new(oracle.clone(), boot.agreed_pre_state.clone());
+    let provider = OracleInteropProvider::new(
+        oracle.clone(),
+        boot.agreed_pre_state.clone(),
+        boot.rollup_configs.clone(),
+    );
 
     info!(target: "client_interop", "Deriving local-safe headers from prestate");
 
@@ -1,16 +1,22 @@
 //! Multi-chain, interoperable fault proof program entrypoint.
 
+use crate::{
+    exit::driver_exit_reason,
+    profile::{ExecutionProfile, Phase, ProfiledOracle},
+};
 use alloc::sync::Arc;
 use alloy_primitives::B256;
 use consolidate::consolidate_dependencies;
 use core::fmt::Debug;
 use kona_driver::DriverError;
 use kona_executor::{ExecutorError, KonaHandleRegister};
-use kona_preimage::{HintWriterClient, PreimageOracleClient};
+use kona_preimage::{HintWriterClient, PreimageKey, PreimageKeyType, PreimageOracleClient};
 use kona_proof::{errors::OracleProviderError, l2::OracleL2ChainProvider, CachingOracle};
 use kona_proof_interop::{
-    boot::BootstrapError, BootInfo, ConsolidationError, PreState, TRANSITION_STATE_MAX_STEPS,
+    boot::{BootstrapError, L1_HEAD_KEY, L2_ROLLUP_CONFIG_KEY},
+    BootInfo, ConsolidationError, PreState, TRANSITION_STATE_MAX_STEPS,
 };
+use kona_std_fpvm::exit::{ClientExitReason, ExitReason};
 use thiserror::Error;
 use tracing::{error, info};
 use transition::sub_transition;
@@ -45,27 +51,88 @@ pub enum FaultProofProgramError {
     MissingRollupConfig(u64),
 }
 
+impl ExitReason for FaultProofProgramError {
+    fn exit_reason(&self) -> ClientExitReason {
+        match self {
+            Self::InvalidClaim(_, _) |
+            Self::Bootstrap(
+                BootstrapError::InvalidPostState(_) | BootstrapError::InvalidToInvalid,
+            ) => ClientExitReason::InvalidClaim,
+            Self::Bootstrap(BootstrapError::Oracle(_)) |
+            Self::MissingRollupConfig(_) |
+            Self::Consolidation(
+                ConsolidationError::InvalidPreStateVariant |
+                ConsolidationError::MissingRollupConfig(_),
+            ) => ClientExitReason::BadBootInfo,
+            Self::OracleProvider(_) |
+            Self::Consolidation(ConsolidationError::OracleProvider(_)) => {
+                ClientExitReason::OracleFailure
+            }
+            Self::StateTransitionFailed | Self::Consolidation(ConsolidationError::Executor(_)) => {
+                ClientExitReason::ExecutionFailure
+            }
+            Self::Driver(e) => driver_exit_reason(e),
+            Self::Consolidation(ConsolidationError::MessageGraph(_)) => ClientExitReason::Other,
+        }
+    }
+}
+
 /// Executes the interop fault proof program with the given [PreimageOracleClient] and
 /// [HintWriterClient].
+///
+/// The preimage traffic of each phase of the program is recorded in an [ExecutionProfile], which
+/// is reported once the program exits when the `client-profile` feature is enabled.
+///
+/// The handle register is bound to the [OracleL2ChainProvider] over the [ProfiledOracle].
 #[inline]
 pub async fn run<P, H>(
     oracle_client: P,
     hint_client: H,
     handle_register: Option<
         KonaHandleRegister<
-            OracleL2ChainProvider<CachingOracle<P, H>>,
-            OracleL2ChainProvider<CachingOracle<P, H>>,
+            OracleL2ChainProvider<ProfiledOracle<P, H>>,
+            OracleL2ChainProvider<ProfiledOracle<P, H>>,
+        >,
+    >,
+) -> Result<(), FaultProofProgramError>
+where
+    P: PreimageOracleClient + Send + Sync + Debug + Clone,
+    H: HintWriterClient + Send + Sync + Debug + Clone,
+{
+    let profile = ExecutionProfile::default();
+    let result = run_profiled(oracle_client, hint_client, handle_register, &profile).await;
+    profile.report();
+    result
+}
+
+/// Executes the interop fault proof program, recording its preimage traffic in the given
+/// [ExecutionProfile].
+async fn run_profiled<P, H>(
+    oracle_client: P,
+    hint_client: H,
+    handle_register: Option<
+        KonaHandleRegister<
+            OracleL2ChainProvider<ProfiledOracle<P, H>>,
+            OracleL2ChainProvider<ProfiledOracle<P, H>>,
         >,
     >,
+    profile: &ExecutionProfile,
 ) -> Result<(), FaultProofProgramError>
 where
     P: PreimageOracleClient + Send + Sync + Debug + Clone,
     H: HintWriterClient + Send + Sync + Debug + Clone,
 {
-    const ORACLE_LRU_SIZE: usize = 1024;
+    const ORACLE_CACHE_BUDGET: usize = 1 << 20;
+    const ORACLE_KECCAK_CACHE_BUDGET: usize = 16 << 20;
 
     // Instantiate the oracle and bootstrap the program from local inputs.
-    let oracle = Arc::new(CachingOracle::new(ORACLE_LRU_SIZE, oracle_client, hint_client));
+    profile.enter(Phase::Prologue);
+    let caching_oracle =
+        CachingOracle::with_byte_budget(ORACLE_CACHE_BUDGET, oracle_client, hint_client)
+            .with_budget(PreimageKeyType::Keccak256, ORACLE_KECCAK_CACHE_BUDGET);
+    caching_oracle.pin(PreimageKey::new_local(L1_HEAD_KEY.to()));
+    caching_oracle.pin(PreimageKey::new_local(L2_ROLLUP_CONFIG_KEY.to()));
+    let oracle = Arc::new(ProfiledOracle::new(caching_oracle, profile.clone()));
     let boot = match BootInfo::load(oracle.as_ref()).await {
         Ok(boot) => boot,
         Err(BootstrapError::InvalidToInvalid) => {
@@ -113,6 +180,7 @@ where
             if transition_state.step < TRANSITION_STATE_MAX_STEPS {
                 sub_transition(oracle, handle_register, boot).await
             } else {
+                profile.enter(Phase::Consolidation);
                 consolidate_dependencies(oracle, boot).await
             }
         }
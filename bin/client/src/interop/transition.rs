@@ -1,7 +1,10 @@
 //! Single chain sub-transition phase of the interop proof.
 
 use super::FaultProofProgramError;
-use crate::interop::util::fetch_l2_safe_head_hash;
+use crate::{
+    interop::util::fetch_l2_safe_head_hash,
+    profile::{Phase, ProfiledExecutor, ProfiledOracle},
+};
 use alloc::sync::Arc;
 use alloy_consensus::Sealed;
 use alloy_primitives::B256;
@@ -15,7 +18,6 @@ use kona_proof::{
     l1::{OracleBlobProvider, OracleL1ChainProvider, OraclePipeline},
     l2::OracleL2ChainProvider,
     sync::new_pipeline_cursor,
-    CachingOracle,
 };
 use kona_proof_interop::{BootInfo, OptimisticBlock, PreState, INVALID_TRANSITION_HASH};
 use tracing::{error, info, warn};
@@ -23,11 +25,11 @@ use tracing::{error, info, warn};
 /// Executes a sub-transition of the interop proof with the given [PreimageOracleClient] and
 /// [HintWriterClient].
 pub(crate) async fn sub_transition<P, H>(
-    oracle: Arc<CachingOracle<P, H>>,
+    oracle: Arc<ProfiledOracle<P, H>>,
     handle_register: Option<
         KonaHandleRegister<
-            OracleL2ChainProvider<CachingOracle<P, H>>,
-            OracleL2ChainProvider<CachingOracle<P, H>>,
+            OracleL2ChainProvider<ProfiledOracle<P, H>>,
+            OracleL2ChainProvider<ProfiledOracle<P, H>>,
         >,
     >,
     boot: BootInfo,
@@ -92,6 +94,8 @@ where
         );
     }
 
+    oracle.profile().enter(Phase::Derivation);
+
     // Create a new derivation driver with the given boot information and oracle.
     let cursor =
         new_pipeline_cursor(rollup_config.as_ref(), safe_head, &mut l1_provider, &mut l2_provider)
@@ -106,18 +110,24 @@ where
         l1_provider.clone(),
         l2_provider.clone(),
     );
-    let executor = KonaExecutor::new(
-        rollup_config.as_ref(),
-        l2_provider.clone(),
-        l2_provider,
-        handle_register,
-        None,
+    let executor = ProfiledExecutor::new(
+        KonaExecutor::new(
+            rollup_config.as_ref(),
+            l2_provider.clone(),
+            l2_provider,
+            handle_register,
+            None,
+        ),
+        oracle.profile().clone(),
     );
     let mut driver = Driver::new(cursor, executor, pipeline);
 
     // Run the derivation pipeline until we are able to produce the output root of the claimed
     // L2 block.
-    match driver.advance_to_target(rollup_config.as_ref(), Some(disputed_l2_block_number)).await {
+    let result =
+        driver.advance_to_target(rollup_config.as_ref(), Some(disputed_l2_block_number)).await;
+    oracle.profile().enter(Phase::Epilogue);
+    match result {
         Ok((safe_head, output_root)) => {
             let optimistic_block = OptimisticBlock::new(safe_head.block_info.hash, output_root);
             transition_and_check(
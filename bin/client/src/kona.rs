@@ -43,5 +43,6 @@ fn main() -> Result<(), String> {
         ORACLE_READER,
         HINT_WRITER,
         Some(precompiles::fpvm_handle_register),
+        None,
     ))
 }
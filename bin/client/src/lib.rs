@@ -7,5 +7,7 @@
 
 extern crate alloc;
 
+pub(crate) mod exit;
 pub mod interop;
+pub mod profile;
 pub mod single;
@@ -3,7 +3,7 @@
 //! [KonaHandleRegister]: kona_executor::KonaHandleRegister
 
 use alloc::sync::Arc;
-use kona_executor::{TrieDB, TrieDBProvider};
+use kona_executor::{KonaEvmContext, TrieDB, TrieDBProvider};
 use kona_mpt::TrieHinter;
 use revm::{
     handler::register::EvmHandler,
@@ -28,7 +28,7 @@ mod bls12_pairing;
 ///
 /// [KonaHandleRegister]: kona_executor::KonaHandleRegister
 pub(crate) fn fpvm_handle_register<F, H>(
-    handler: &mut EvmHandler<'_, (), &mut State<&mut TrieDB<F, H>>>,
+    handler: &mut EvmHandler<'_, KonaEvmContext<'_, F, H>, State<&mut TrieDB<F, H>>>,
 ) where
     F: TrieDBProvider,
     H: TrieHinter,
@@ -37,7 +37,7 @@ pub(crate) fn fpvm_handle_register<F, H>(
 
     handler.pre_execution.load_precompiles = Arc::new(move || {
         let mut ctx_precompiles = spec_to_generic!(spec_id, {
-            revm::optimism::load_precompiles::<SPEC, (), &mut State<&mut TrieDB<F, H>>>()
+            revm::optimism::load_precompiles::<SPEC, (), State<&mut TrieDB<F, H>>>()
         });
 
         // Extend with FPVM-accelerated precompiles
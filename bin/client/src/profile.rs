@@ -0,0 +1,436 @@
+//! Execution profiling for the client program.
+//!
+//! The [ExecutionProfile] records the preimage oracle traffic and, where the FPVM exposes it, the
+//! instruction count of each [Phase] of the program. It is fed by the [ProfiledOracle], which wraps
+//! the [CachingOracle], and the [ProfiledExecutor], which wraps the driver's [Executor] to split
+//! derivation from execution. Both wrappers only record when the `client-profile` feature is
+//! enabled, and otherwise forward to the wrapped types.
+
+use alloc::{
+    boxed::Box,
+    collections::BTreeMap,
+    string::{String, ToString},
+    sync::Arc,
+    vec::Vec,
+};
+use alloy_consensus::{Header, Sealed};
+use alloy_primitives::B256;
+use async_trait::async_trait;
+use core::fmt::{Debug, Display, Write};
+use kona_driver::Executor;
+use kona_executor::ExecutionArtifacts;
+use kona_preimage::{
+    errors::PreimageOracleResult, HintWriterClient, PreimageKey, PreimageOracleClient,
+};
+use kona_proof::{CachingOracle, FlushableCache};
+use op_alloy_rpc_types_engine::OpPayloadAttributes;
+use spin::Mutex;
+
+/// Whether the [ProfiledOracle] and [ProfiledExecutor] record into their [ExecutionProfile].
+const ENABLED: bool = cfg!(feature = "client-profile");
+
+/// A phase of the client program.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Phase {
+    /// Loading the boot information and the agreed upon safe head.
+    #[default]
+    Prologue,
+    /// Deriving payload attributes from L1 data.
+    Derivation,
+    /// Executing payload attributes.
+    Execution,
+    /// Checking the dependencies between optimistic blocks in the superchain.
+    Consolidation,
+    /// Validating the claim.
+    Epilogue,
+}
+
+impl Display for Phase {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Prologue => write!(f, "prologue"),
+            Self::Derivation => write!(f, "derivation"),
+            Self::Execution => write!(f, "execution"),
+            Self::Consolidation => write!(f, "consolidation"),
+            Self::Epilogue => write!(f, "epilogue"),
+        }
+    }
+}
+
+/// The preimage oracle traffic recorded during a single [Phase].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct PhaseProfile {
+    /// The number of preimage requests.
+    pub preimage_requests: u64,
+    /// The number of preimage requests served from the cache.
+    pub cache_hits: u64,
+    /// The number of preimage bytes returned to the program.
+    pub preimage_bytes: u64,
+    /// The number of hints sent to the host, keyed by hint type.
+    pub hints: BTreeMap<String, u64>,
+    /// The number of payloads executed.
+    pub payloads_executed: u64,
+    /// The number of instructions executed, if the FPVM exposes an instruction count.
+    pub cycles: u64,
+}
+
+impl PhaseProfile {
+    /// Returns the total number of hints sent to the host.
+    pub fn total_hints(&self) -> u64 {
+        self.hints.values().sum()
+    }
+}
+
+/// The inner state of an [ExecutionProfile].
+#[derive(Debug, Default)]
+struct ProfileState {
+    /// The active [Phase].
+    phase: Phase,
+    /// The recorded [PhaseProfile]s.
+    phases: BTreeMap<Phase, PhaseProfile>,
+    /// The instruction count when the cycles were last accounted, if known.
+    last_cycles: Option<u64>,
+}
+
+impl ProfileState {
+    fn current(&mut self) -> &mut PhaseProfile {
+        self.phases.entry(self.phase).or_default()
+    }
+
+    /// Attributes the instructions executed since the last call to the active [Phase].
+    fn account_cycles(&mut self, cycles: Option<u64>) {
+        if let (Some(now), Some(last)) = (cycles, self.last_cycles) {
+            self.current().cycles += now.saturating_sub(last);
+        }
+        self.last_cycles = cycles;
+    }
+}
+
+/// A shared recorder of the preimage oracle traffic of the client program, split by [Phase].
+#[derive(Debug, Default, Clone)]
+pub struct ExecutionProfile {
+    state: Arc<Mutex<ProfileState>>,
+}
+
+impl ExecutionProfile {
+    /// Returns the active [Phase].
+    pub fn phase(&self) -> Phase {
+        self.state.lock().phase
+    }
+
+    /// Sets the active [Phase]. All subsequent traffic is attributed to it.
+    pub fn enter(&self, phase: Phase) {
+        let cycles = if ENABLED { kona_std_fpvm::io::cycles() } else { None };
+        self.enter_at(phase, cycles);
+    }
+
+    /// Sets the active [Phase], attributing the instructions executed up to the given instruction
+    /// count to the previously active [Phase].
+    fn enter_at(&self, phase: Phase, cycles: Option<u64>) {
+        let mut state = self.state.lock();
+        state.account_cycles(cycles);
+        state.phase = phase;
+    }
+
+    /// Returns the [PhaseProfile] recorded for the given [Phase].
+    pub fn get(&self, phase: Phase) -> PhaseProfile {
+        self.state.lock().phases.get(&phase).cloned().unwrap_or_default()
+    }
+
+    /// Returns the [PhaseProfile]s recorded so far, in phase order.
+    pub fn phases(&self) -> Vec<(Phase, PhaseProfile)> {
+        self.state.lock().phases.iter().map(|(p, s)| (*p, s.clone())).collect()
+    }
+
+    /// Records a preimage request in the active [Phase].
+    pub fn record_preimage(&self, bytes: usize, cache_hit: bool) {
+        let mut state = self.state.lock();
+        let current = state.current();
+        current.preimage_requests += 1;
+        current.preimage_bytes += bytes as u64;
+        current.cache_hits += cache_hit as u64;
+    }
+
+    /// Records a hint in the active [Phase]. The hint type is taken from the hint's prefix.
+    pub fn record_hint(&self, hint: &str) {
+        let ty = hint.split(' ').next().unwrap_or_default();
+        let mut state = self.state.lock();
+        *state.current().hints.entry(ty.to_string()).or_default() += 1;
+    }
+
+    /// Records an executed payload in the active [Phase].
+    pub fn record_payload(&self) {
+        self.state.lock().current().payloads_executed += 1;
+    }
+
+    /// Renders a human-readable summary of the profile.
+    pub fn summary(&self) -> String {
+        let mut out = String::new();
+        let mut total = PhaseProfile::default();
+
+        let _ = writeln!(out, "=== kona client profile ===");
+        let _ = writeln!(
+            out,
+            "{:<14} {:>10} {:>10} {:>14} {:>8} {:>9} {:>16}",
+            "phase", "requests", "hits", "bytes", "hints", "payloads", "cycles"
+        );
+        for (phase, profile) in self.phases() {
+            let _ = writeln!(
+                out,
+                "{:<14} {:>10} {:>10} {:>14} {:>8} {:>9} {:>16}",
+                phase,
+                profile.preimage_requests,
+                profile.cache_hits,
+                profile.preimage_bytes,
+                profile.total_hints(),
+                profile.payloads_executed,
+                profile.cycles
+            );
+            for (ty, count) in profile.hints.iter() {
+                let _ = writeln!(out, "  {ty:<28} {count:>8}");
+                *total.hints.entry(ty.clone()).or_default() += count;
+            }
+            total.preimage_requests += profile.preimage_requests;
+            total.cache_hits += profile.cache_hits;
+            total.preimage_bytes += profile.preimage_bytes;
+            total.payloads_executed += profile.payloads_executed;
+            total.cycles += profile.cycles;
+        }
+        let _ = writeln!(
+            out,
+            "{:<14} {:>10} {:>10} {:>14} {:>8} {:>9} {:>16}",
+            "total",
+            total.preimage_requests,
+            total.cache_hits,
+            total.preimage_bytes,
+            total.total_hints(),
+            total.payloads_executed,
+            total.cycles
+        );
+        out
+    }
+
+    /// Emits the [Self::summary] through the FPVM's standard output when the `client-profile`
+    /// feature is enabled. Otherwise, this is a no-op.
+    pub fn report(&self) {
+        #[cfg(feature = "client-profile")]
+        {
+            // Attribute the instructions executed in the active phase before reporting.
+            self.enter(self.phase());
+            kona_std_fpvm::io::print(&self.summary());
+        }
+    }
+}
+
+/// A wrapper around a [CachingOracle] that records its traffic in an [ExecutionProfile] when the
+/// `client-profile` feature is enabled.
+#[derive(Debug, Clone)]
+pub struct ProfiledOracle<OR, HW>
+where
+    OR: PreimageOracleClient,
+    HW: HintWriterClient,
+{
+    /// The wrapped [CachingOracle].
+    inner: CachingOracle<OR, HW>,
+    /// The [ExecutionProfile] to record into.
+    profile: ExecutionProfile,
+}
+
+impl<OR, HW> ProfiledOracle<OR, HW>
+where
+    OR: PreimageOracleClient,
+    HW: HintWriterClient,
+{
+    /// Creates a new [ProfiledOracle] that records the traffic of `inner` into `profile`.
+    pub const fn new(inner: CachingOracle<OR, HW>, profile: ExecutionProfile) -> Self {
+        Self { inner, profile }
+    }
+
+    /// Returns the [ExecutionProfile] that the oracle records into.
+    pub const fn profile(&self) -> &ExecutionProfile {
+        &self.profile
+    }
+}
+
+impl<OR, HW> FlushableCache for ProfiledOracle<OR, HW>
+where
+    OR: PreimageOracleClient,
+    HW: HintWriterClient,
+{
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
+
+#[async_trait]
+impl<OR, HW> PreimageOracleClient for ProfiledOracle<OR, HW>
+where
+    OR: PreimageOracleClient + Sync,
+    HW: HintWriterClient + Sync,
+{
+    async fn get(&self, key: PreimageKey) -> PreimageOracleResult<Vec<u8>> {
+        if !ENABLED {
+            return self.inner.get(key).await;
+        }
+
+        let cache_hit = self.inner.contains(&key);
+        let value = self.inner.get(key).await?;
+        self.profile.record_preimage(value.len(), cache_hit);
+        Ok(value)
+    }
+
+    async fn get_exact(&self, key: PreimageKey, buf: &mut [u8]) -> PreimageOracleResult<()> {
+        if !ENABLED {
+            return self.inner.get_exact(key, buf).await;
+        }
+
+        let cache_hit = self.inner.contains(&key);
+        self.inner.get_exact(key, buf).await?;
+        self.profile.record_preimage(buf.len(), cache_hit);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<OR, HW> HintWriterClient for ProfiledOracle<OR, HW>
+where
+    OR: PreimageOracleClient + Sync,
+    HW: HintWriterClient + Sync,
+{
+    async fn write(&self, hint: &str) -> PreimageOracleResult<()> {
+        if ENABLED {
+            self.profile.record_hint(hint);
+        }
+        self.inner.write(hint).await
+    }
+}
+
+/// A wrapper around an [Executor] that attributes the traffic of each executed payload to
+/// [Phase::Execution] in an [ExecutionProfile] when the `client-profile` feature is enabled.
+#[derive(Debug)]
+pub struct ProfiledExecutor<E> {
+    /// The wrapped [Executor].
+    inner: E,
+    /// The [ExecutionProfile] to record into.
+    profile: ExecutionProfile,
+}
+
+impl<E> ProfiledExecutor<E> {
+    /// Creates a new [ProfiledExecutor] that attributes the traffic of `inner` to
+    /// [Phase::Execution] in `profile`.
+    pub const fn new(inner: E, profile: ExecutionProfile) -> Self {
+        Self { inner, profile }
+    }
+}
+
+#[async_trait]
+impl<E> Executor for ProfiledExecutor<E>
+where
+    E: Executor + Send + Sync,
+{
+    type Error = E::Error;
+
+    async fn wait_until_ready(&mut self) {
+        self.inner.wait_until_ready().await
+    }
+
+    fn update_safe_head(&mut self, header: Sealed<Header>) {
+        self.inner.update_safe_head(header)
+    }
+
+    async fn execute_payload(
+        &mut self,
+        attributes: OpPayloadAttributes,
+    ) -> Result<ExecutionArtifacts, Self::Error> {
+        if !ENABLED {
+            return self.inner.execute_payload(attributes).await;
+        }
+
+        let previous = self.profile.phase();
+        self.profile.enter(Phase::Execution);
+        self.profile.record_payload();
+        let result = self.inner.execute_payload(attributes).await;
+        self.profile.enter(previous);
+        result
+    }
+
+    fn compute_output_root(&mut self) -> Result<B256, Self::Error> {
+        if !ENABLED {
+            return self.inner.compute_output_root();
+        }
+
+        let previous = self.profile.phase();
+        self.profile.enter(Phase::Execution);
+        let result = self.inner.compute_output_root();
+        self.profile.enter(previous);
+        result
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_profile_attributes_traffic_to_active_phase() {
+        let profile = ExecutionProfile::default();
+        profile.record_preimage(32, false);
+        profile.record_hint("l1-block-header deadbeef");
+
+        profile.enter(Phase::Derivation);
+        profile.record_preimage(64, false);
+        profile.record_preimage(64, true);
+        profile.record_hint("l1-blob beef");
+        profile.record_hint("l1-blob cafe");
+
+        let prologue = profile.get(Phase::Prologue);
+        assert_eq!(prologue.preimage_requests, 1);
+        assert_eq!(prologue.preimage_bytes, 32);
+        assert_eq!(prologue.cache_hits, 0);
+        assert_eq!(prologue.hints.get("l1-block-header"), Some(&1));
+
+        let derivation = profile.get(Phase::Derivation);
+        assert_eq!(derivation.preimage_requests, 2);
+        assert_eq!(derivation.preimage_bytes, 128);
+        assert_eq!(derivation.cache_hits, 1);
+        assert_eq!(derivation.hints.get("l1-blob"), Some(&2));
+        assert_eq!(derivation.total_hints(), 2);
+
+        assert_eq!(profile.get(Phase::Execution), PhaseProfile::default());
+    }
+
+    #[test]
+    fn test_profile_attributes_cycles_to_previous_phase() {
+        let profile = ExecutionProfile::default();
+        profile.enter_at(Phase::Prologue, Some(100));
+        profile.enter_at(Phase::Derivation, Some(250));
+        profile.enter_at(Phase::Execution, Some(1_000));
+        profile.enter_at(Phase::Derivation, Some(1_100));
+        profile.enter_at(Phase::Epilogue, Some(1_300));
+
+        assert_eq!(profile.get(Phase::Prologue).cycles, 150);
+        assert_eq!(profile.get(Phase::Derivation).cycles, 950);
+        assert_eq!(profile.get(Phase::Execution).cycles, 100);
+        assert_eq!(profile.get(Phase::Epilogue).cycles, 0);
+
+        // Without an instruction count, no cycles are attributed.
+        profile.enter_at(Phase::Prologue, None);
+        profile.enter_at(Phase::Epilogue, Some(2_000));
+        assert_eq!(profile.get(Phase::Prologue).cycles, 150);
+    }
+
+    #[test]
+    fn test_profile_summary() {
+        let profile = ExecutionProfile::default();
+        profile.record_preimage(32, true);
+        profile.enter(Phase::Execution);
+        profile.record_payload();
+        profile.record_hint("l2-payload-witness 00");
+
+        let summary = profile.summary();
+        assert!(summary.contains("prologue"));
+        assert!(summary.contains("execution"));
+        assert!(summary.contains("l2-payload-witness"));
+        assert!(!summary.contains("derivation"));
+    }
+}
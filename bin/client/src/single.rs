@@ -1,13 +1,20 @@
 //! Single-chain fault proof program entrypoint.
 
+use crate::{
+    exit::driver_exit_reason,
+    profile::{ExecutionProfile, Phase, ProfiledExecutor, ProfiledOracle},
+};
 use alloc::sync::Arc;
 use alloy_consensus::Sealed;
 use alloy_primitives::B256;
 use core::fmt::Debug;
 use kona_driver::{Driver, DriverError};
-use kona_executor::{ExecutorError, KonaHandleRegister, TrieDBProvider};
-use kona_preimage::{CommsClient, HintWriterClient, PreimageKey, PreimageOracleClient};
+use kona_executor::{BlockProfiler, ExecutorError, KonaHandleRegister, TrieDBProvider};
+use kona_preimage::{
+    CommsClient, HintWriterClient, PreimageKey, PreimageKeyType, PreimageOracleClient,
+};
 use kona_proof::{
+    boot::{L1_HEAD_KEY, L2_ROLLUP_CONFIG_KEY},
     errors::OracleProviderError,
     executor::KonaExecutor,
     l1::{OracleBlobProvider, OracleL1ChainProvider, OraclePipeline},
@@ -15,6 +22,7 @@ use kona_proof::{
     sync::new_pipeline_cursor,
     BootInfo, CachingOracle, HintType,
 };
+use kona_std_fpvm::exit::{ClientExitReason, ExitReason};
 use thiserror::Error;
 use tracing::{error, info};
 
@@ -24,6 +32,9 @@ pub enum FaultProofProgramError {
     /// The claim is invalid.
     #[error("Invalid claim. Expected {0}, actual {1}")]
     InvalidClaim(B256, B256),
+    /// The boot information could not be loaded.
+    #[error("Failed to load boot info: {0}")]
+    BootInfo(OracleProviderError),
     /// An error occurred in the Oracle provider.
     #[error(transparent)]
     OracleProviderError(#[from] OracleProviderError),
@@ -32,30 +43,85 @@ pub enum FaultProofProgramError {
     Driver(#[from] DriverError<ExecutorError>),
 }
 
+impl ExitReason for FaultProofProgramError {
+    fn exit_reason(&self) -> ClientExitReason {
+        match self {
+            Self::InvalidClaim(_, _) => ClientExitReason::InvalidClaim,
+            Self::BootInfo(_) => ClientExitReason::BadBootInfo,
+            Self::OracleProviderError(_) => ClientExitReason::OracleFailure,
+            Self::Driver(e) => driver_exit_reason(e),
+        }
+    }
+}
+
 /// Executes the fault proof program with the given [PreimageOracleClient] and [HintWriterClient].
+///
+/// The preimage traffic of each phase of the program is recorded in an [ExecutionProfile], which
+/// is reported once the program exits when the `client-profile` feature is enabled. If a
+/// [BlockProfiler] is provided, the executed blocks are profiled with it.
+///
+/// The handle register is bound to the [OracleL2ChainProvider] over the [ProfiledOracle] that
+/// wraps the [CachingOracle], rather than over the [CachingOracle] itself. Registers that are
+/// generic over the [TrieDBProvider] and [TrieHinter], such as the FPVM precompile register, are
+/// unaffected.
+///
+/// [TrieHinter]: kona_mpt::TrieHinter
 #[inline]
 pub async fn run<P, H>(
     oracle_client: P,
     hint_client: H,
     handle_register: Option<
         KonaHandleRegister<
-            OracleL2ChainProvider<CachingOracle<P, H>>,
-            OracleL2ChainProvider<CachingOracle<P, H>>,
+            OracleL2ChainProvider<ProfiledOracle<P, H>>,
+            OracleL2ChainProvider<ProfiledOracle<P, H>>,
         >,
     >,
+    block_profiler: Option<BlockProfiler>,
 ) -> Result<(), FaultProofProgramError>
 where
     P: PreimageOracleClient + Send + Sync + Debug + Clone,
     H: HintWriterClient + Send + Sync + Debug + Clone,
 {
-    const ORACLE_LRU_SIZE: usize = 1024;
+    let profile = ExecutionProfile::default();
+    let result =
+        run_profiled(oracle_client, hint_client, handle_register, block_profiler, &profile).await;
+    profile.report();
+    result
+}
+
+/// Executes the fault proof program, recording its preimage traffic in the given
+/// [ExecutionProfile].
+async fn run_profiled<P, H>(
+    oracle_client: P,
+    hint_client: H,
+    handle_register: Option<
+        KonaHandleRegister<
+            OracleL2ChainProvider<ProfiledOracle<P, H>>,
+            OracleL2ChainProvider<ProfiledOracle<P, H>>,
+        >,
+    >,
+    block_profiler: Option<BlockProfiler>,
+    profile: &ExecutionProfile,
+) -> Result<(), FaultProofProgramError>
+where
+    P: PreimageOracleClient + Send + Sync + Debug + Clone,
+    H: HintWriterClient + Send + Sync + Debug + Clone,
+{
+    const ORACLE_CACHE_BUDGET: usize = 1 << 20;
+    const ORACLE_KECCAK_CACHE_BUDGET: usize = 16 << 20;
 
     ////////////////////////////////////////////////////////////////
     //                          PROLOGUE                          //
     ////////////////////////////////////////////////////////////////
 
-    let oracle = Arc::new(CachingOracle::new(ORACLE_LRU_SIZE, oracle_client, hint_client));
-    let boot = BootInfo::load(oracle.as_ref()).await?;
+    profile.enter(Phase::Prologue);
+    let caching_oracle =
+        CachingOracle::with_byte_budget(ORACLE_CACHE_BUDGET, oracle_client, hint_client)
+            .with_budget(PreimageKeyType::Keccak256, ORACLE_KECCAK_CACHE_BUDGET);
+    caching_oracle.pin(PreimageKey::new_local(L1_HEAD_KEY.to()));
+    caching_oracle.pin(PreimageKey::new_local(L2_ROLLUP_CONFIG_KEY.to()));
+    let oracle = Arc::new(ProfiledOracle::new(caching_oracle, profile.clone()));
+    let boot = BootInfo::load(oracle.as_ref()).await.map_err(FaultProofProgramError::BootInfo)?;
     let rollup_config = Arc::new(boot.rollup_config);
     let safe_head_hash = fetch_safe_head_hash(oracle.as_ref(), boot.agreed_l2_output_root).await?;
 
@@ -98,6 +164,8 @@ where
     //                   DERIVATION & EXECUTION                   //
     ////////////////////////////////////////////////////////////////
 
+    profile.enter(Phase::Derivation);
+
     // Create a new derivation driver with the given boot information and oracle.
     let cursor =
         new_pipeline_cursor(rollup_config.as_ref(), safe_head, &mut l1_provider, &mut l2_provider)
@@ -112,13 +180,17 @@ where
         l1_provider.clone(),
         l2_provider.clone(),
     );
-    let executor = KonaExecutor::new(
+    let mut executor = KonaExecutor::new(
         rollup_config.as_ref(),
         l2_provider.clone(),
         l2_provider,
         handle_register,
         None,
     );
+    if let Some(block_profiler) = block_profiler {
+        executor = executor.with_block_profiler(block_profiler);
+    }
+    let executor = ProfiledExecutor::new(executor, profile.clone());
     let mut driver = Driver::new(cursor, executor, pipeline);
 
     // Run the derivation pipeline until we are able to produce the output root of the claimed
@@ -131,6 +203,8 @@ where
     //                          EPILOGUE                          //
     ////////////////////////////////////////////////////////////////
 
+    profile.enter(Phase::Epilogue);
+
     if output_root != boot.claimed_l2_output_root {
         error!(
             target: "client",
@@ -0,0 +1,85 @@
+//! Contains the `check` subcommand, which re-derives [PipelineFixture]s offline.
+
+use anyhow::{anyhow, Result};
+use clap::Parser;
+use kona_derive::test_utils::{read_golden_attributes, PipelineFixture, PIPELINE_FIXTURE_FILE};
+use std::path::{Path, PathBuf};
+use tracing::{error, info};
+
+/// The `check` subcommand.
+#[derive(Parser, Clone, Debug)]
+pub(crate) struct CheckCommand {
+    /// The fixture directories, or directories of fixture directories, to check.
+    #[clap(required = true)]
+    pub(crate) fixtures: Vec<PathBuf>,
+}
+
+impl CheckCommand {
+    /// Checks the fixtures, failing if any of them does not reproduce its golden attributes.
+    pub(crate) async fn run(self) -> Result<()> {
+        let dirs = collect_fixtures(&self.fixtures)?;
+
+        let mut failures = 0;
+        for dir in &dirs {
+            let fixture = PipelineFixture::read(dir)?;
+            let golden = read_golden_attributes(dir)?;
+            match fixture.derive(dir).await {
+                Ok(derived) => {
+                    let mismatch = (0..derived.len().max(golden.len()))
+                        .find(|&i| derived.get(i) != golden.get(i));
+                    match mismatch {
+                        None => info!(
+                            target: "fixture",
+                            "{}: {} attributes match",
+                            dir.display(),
+                            derived.len()
+                        ),
+                        Some(i) => {
+                            failures += 1;
+                            error!(
+                                target: "fixture",
+                                "{}: attributes #{i} mismatch",
+                                dir.display()
+                            );
+                            error!(target: "fixture", "  expected {:?}", golden.get(i));
+                            error!(target: "fixture", "  got {:?}", derived.get(i));
+                        }
+                    }
+                }
+                Err(e) => {
+                    failures += 1;
+                    error!(target: "fixture", "{}: derivation failed: {e}", dir.display());
+                }
+            }
+        }
+
+        if failures > 0 {
+            return Err(anyhow!("{failures} of {} fixtures failed", dirs.len()));
+        }
+        Ok(())
+    }
+}
+
+/// Expands the given paths into fixture directories, descending into directories that do not
+/// hold a [PIPELINE_FIXTURE_FILE] themselves.
+fn collect_fixtures(paths: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    let mut dirs = Vec::new();
+    for path in paths {
+        if is_fixture(path) {
+            dirs.push(path.clone());
+        } else {
+            let mut entries = std::fs::read_dir(path)?
+                .map(|entry| entry.map(|e| e.path()))
+                .collect::<Result<Vec<_>, _>>()?;
+            entries.retain(|entry| is_fixture(entry));
+            entries.sort();
+            dirs.extend(entries);
+        }
+    }
+    Ok(dirs)
+}
+
+/// Returns whether the path is a fixture directory.
+fn is_fixture(path: &Path) -> bool {
+    path.join(PIPELINE_FIXTURE_FILE).is_file()
+}
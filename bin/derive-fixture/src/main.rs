@@ -0,0 +1,63 @@
+//! Main entrypoint for the derivation fixture binary.
+
+#![warn(missing_debug_implementations, missing_docs, unreachable_pub, rustdoc::all)]
+#![deny(unused_must_use, rust_2018_idioms)]
+#![cfg_attr(docsrs, feature(doc_cfg, doc_auto_cfg))]
+
+use anyhow::{anyhow, Result};
+use clap::{ArgAction, Parser, Subcommand};
+use tracing::Level;
+
+mod check;
+mod record;
+mod recorder;
+mod synthetic;
+
+const ABOUT: &str = "
+kona-derive-fixture records and checks golden fixtures for the derivation pipeline. A fixture is a
+directory holding the raw L1 execution and beacon node responses of a derivation range, the L2 safe
+head and canonical L2 blocks it starts from, and the payload attributes derived from them, so that
+the derivation of the range can be reproduced offline.
+";
+
+/// The derivation fixture CLI application arguments.
+#[derive(Parser, Clone, Debug)]
+#[command(about = ABOUT, version)]
+struct FixtureCli {
+    /// Verbosity level (0-2)
+    #[arg(long, short, action = ArgAction::Count)]
+    v: u8,
+    /// The subcommand to run.
+    #[clap(subcommand)]
+    command: FixtureCommand,
+}
+
+/// The subcommands of the derivation fixture binary.
+#[derive(Subcommand, Clone, Debug)]
+enum FixtureCommand {
+    /// Record a fixture for an L2 block range from L1 and L2 archive nodes.
+    Record(record::RecordCommand),
+    /// Re-derive fixtures offline, reporting the first attributes that do not match.
+    Check(check::CheckCommand),
+    /// Generate the synthetic fixture from fixed keys and hand-built L1 blocks.
+    Synthetic(synthetic::SyntheticCommand),
+}
+
+#[tokio::main(flavor = "multi_thread")]
+async fn main() -> Result<()> {
+    let cli = FixtureCli::parse();
+    let subscriber = tracing_subscriber::fmt()
+        .with_max_level(match cli.v {
+            0 => Level::INFO,
+            1 => Level::DEBUG,
+            _ => Level::TRACE,
+        })
+        .finish();
+    tracing::subscriber::set_global_default(subscriber).map_err(|e| anyhow!(e))?;
+
+    match cli.command {
+        FixtureCommand::Record(cmd) => cmd.run().await,
+        FixtureCommand::Check(cmd) => cmd.run().await,
+        FixtureCommand::Synthetic(cmd) => cmd.run().await,
+    }
+}
@@ -0,0 +1,162 @@
+//! Contains the `record` subcommand, which records a [PipelineFixture] from L1 and L2 archive
+//! nodes.
+
+use crate::recorder::RecordingL1Provider;
+use alloy_eips::eip2718::Encodable2718;
+use alloy_primitives::map::HashMap;
+use alloy_provider::{Provider, RootProvider};
+use alloy_rpc_client::RpcClient;
+use alloy_transport_http::{Client, Http};
+use anyhow::{anyhow, Result};
+use clap::Parser;
+use kona_derive::{
+    test_utils::{pipeline_origin, write_golden_attributes, PipelineFixture, L1_FIXTURE_DIR},
+    traits::L2ChainProvider,
+};
+use kona_providers_alloy::{AlloyL2ChainProvider, OnlineBeaconClient};
+use maili_genesis::RollupConfig;
+use maili_protocol::BatchValidationProvider;
+use maili_registry::ROLLUP_CONFIGS;
+use std::{path::PathBuf, sync::Arc};
+use tracing::info;
+
+/// The `record` subcommand.
+#[derive(Parser, Clone, Debug)]
+pub(crate) struct RecordCommand {
+    /// Address of the L1 execution node JSON-RPC endpoint (eth and debug namespaces required).
+    #[clap(long, visible_alias = "l1", env)]
+    pub(crate) l1_node_address: String,
+    /// Address of the L1 beacon node API endpoint.
+    #[clap(long, visible_alias = "beacon", env)]
+    pub(crate) l1_beacon_address: String,
+    /// Address of the L2 archive node JSON-RPC endpoint.
+    #[clap(long, visible_alias = "l2", env)]
+    pub(crate) l2_node_address: String,
+    /// The number of the L2 safe head to start deriving from.
+    #[clap(long)]
+    pub(crate) start_block: u64,
+    /// The number of the last L2 block to derive.
+    #[clap(long)]
+    pub(crate) end_block: u64,
+    /// Path to the rollup config. If not provided, the rollup config of the L2 node's chain is
+    /// looked up in the superchain registry.
+    #[clap(long, alias = "rollup-cfg", env)]
+    pub(crate) rollup_config_path: Option<PathBuf>,
+    /// The directory to write the fixture to.
+    #[clap(long, short)]
+    pub(crate) output: PathBuf,
+}
+
+impl RecordCommand {
+    /// Derives the L2 block range from the L1 node, recording the fixture.
+    pub(crate) async fn run(self) -> Result<()> {
+        if self.end_block <= self.start_block {
+            return Err(anyhow!("The end block must follow the start block"));
+        }
+
+        let l1_url: reqwest::Url =
+            self.l1_node_address.parse().map_err(|e| anyhow!("Invalid L1 node URL: {e}"))?;
+        let l2_url: reqwest::Url =
+            self.l2_node_address.parse().map_err(|e| anyhow!("Invalid L2 node URL: {e}"))?;
+        let l1_provider: RootProvider =
+            RootProvider::new(RpcClient::new(Http::<Client>::new(l1_url), false));
+
+        let rollup_config = match &self.rollup_config_path {
+            Some(path) => serde_json::from_slice::<RollupConfig>(&std::fs::read(path)?)
+                .map_err(|e| anyhow!("Error deserializing RollupConfig: {e}"))?,
+            None => {
+                let l2_provider: RootProvider =
+                    RootProvider::new(RpcClient::new(Http::<Client>::new(l2_url.clone()), false));
+                let chain_id = l2_provider.get_chain_id().await?;
+                ROLLUP_CONFIGS
+                    .get(&chain_id)
+                    .cloned()
+                    .ok_or_else(|| anyhow!("Rollup config not found for chain ID {chain_id}"))?
+            }
+        };
+        let rollup_config = Arc::new(rollup_config);
+
+        // Fetch the safe head and the canonical L2 blocks the attributes are derived for.
+        let mut l2_provider = AlloyL2ChainProvider::new_http(l2_url, rollup_config.clone());
+        let mut system_configs = HashMap::default();
+        let mut l2_blocks = Vec::new();
+        for number in self.start_block..=self.end_block {
+            l2_blocks.push(l2_provider.l2_block_info_by_number(number).await?);
+            system_configs.insert(
+                number,
+                l2_provider.system_config_by_number(number, rollup_config.clone()).await?,
+            );
+        }
+        let l2_safe_head = l2_blocks.remove(0);
+
+        let beacon = OnlineBeaconClient::new_http(self.l1_beacon_address.clone());
+        let recorder = RecordingL1Provider::new(l1_provider, beacon).await?;
+        let l1_origin =
+            pipeline_origin(&rollup_config, &l2_safe_head, &mut recorder.clone()).await?;
+
+        let fixture = PipelineFixture {
+            rollup_config: rollup_config.as_ref().clone(),
+            l1_origin,
+            l2_safe_head,
+            l2_blocks,
+            system_configs,
+        };
+        info!(
+            target: "fixture",
+            "Deriving L2 blocks #{}-#{} from L1 block #{}",
+            self.start_block + 1,
+            self.end_block,
+            l1_origin.number
+        );
+        let derived = fixture.derive_with(recorder.clone()).await?;
+        if derived.len() != fixture.l2_blocks.len() {
+            return Err(anyhow!(
+                "Only derived {} of {} L2 blocks before the L1 node ran out of blocks",
+                derived.len(),
+                fixture.l2_blocks.len()
+            ));
+        }
+
+        // The golden attributes must reproduce the canonical L2 blocks.
+        for (attributes, l2_block) in derived.iter().zip(&fixture.l2_blocks) {
+            let number = l2_block.block_info.number;
+            let block = l2_provider.block_by_number(number).await?;
+            let transactions = block
+                .body
+                .transactions
+                .iter()
+                .map(|tx| tx.encoded_2718().into())
+                .collect::<Vec<_>>();
+            if attributes.attributes.payload_attributes.timestamp != block.header.timestamp ||
+                attributes.attributes.transactions.as_ref() != Some(&transactions)
+            {
+                return Err(anyhow!("Derived attributes do not match canonical L2 block #{number}"));
+            }
+        }
+
+        let l1_dir = self.output.join(L1_FIXTURE_DIR);
+        std::fs::create_dir_all(&l1_dir)?;
+        let l1_blocks = recorder.fixtures().await;
+        for l1_block in &l1_blocks {
+            l1_block.save(&l1_dir)?;
+        }
+        fixture.write(&self.output)?;
+        write_golden_attributes(&self.output, &derived)?;
+
+        // The recorded responses must be sufficient to derive the range offline.
+        if fixture.derive(&self.output).await? != derived {
+            return Err(anyhow!(
+                "Derivation from the recorded L1 blocks does not reproduce the fixture"
+            ));
+        }
+
+        info!(
+            target: "fixture",
+            "Wrote fixture with {} L1 blocks and {} attributes to {}",
+            l1_blocks.len(),
+            derived.len(),
+            self.output.display()
+        );
+        Ok(())
+    }
+}
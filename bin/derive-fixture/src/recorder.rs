@@ -0,0 +1,236 @@
+//! Contains the [RecordingL1Provider], which records the raw L1 responses read by the pipeline.
+
+use alloy_consensus::{Header, Receipt, TxEnvelope};
+use alloy_eips::eip4844::{Blob, IndexedBlobHash};
+use alloy_primitives::{map::HashMap, Bytes, B256, U64};
+use alloy_provider::{Provider, RootProvider};
+use alloy_rpc_types_beacon::sidecar::BeaconBlobBundle;
+use async_trait::async_trait;
+use kona_derive::{
+    errors::{PipelineError, PipelineErrorKind},
+    test_utils::{FixtureProvider, FixtureProviderError, L1BlockFixture},
+    traits::{BlobProvider, ChainProvider},
+};
+use kona_providers_alloy::{BeaconClient, OnlineBeaconClient, OnlineBlobProvider};
+use maili_protocol::BlockInfo;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tracing::debug;
+
+/// The blob sidecars beacon API method prefix.
+const SIDECARS_METHOD_PREFIX: &str = "eth/v1/beacon/blob_sidecars";
+
+/// A [ChainProvider] and [BlobProvider] that fetches every L1 block the pipeline reads from the L1
+/// execution and beacon nodes, recording the raw responses as [L1BlockFixture]s.
+///
+/// The recorded blocks are served through a [FixtureProvider], so that the pipeline reads them in
+/// the same way as when the fixture is replayed offline.
+#[derive(Debug, Clone)]
+pub(crate) struct RecordingL1Provider {
+    /// The L1 execution node's RPC provider.
+    l1: RootProvider,
+    /// The L1 beacon node's client.
+    beacon: OnlineBeaconClient,
+    /// The beacon genesis time, used for the time to slot conversion.
+    genesis_time: u64,
+    /// The slot interval, used for the time to slot conversion.
+    slot_interval: u64,
+    /// The recorded blocks.
+    recorded: Arc<Mutex<RecordedBlocks>>,
+}
+
+/// The L1 blocks recorded by a [RecordingL1Provider].
+#[derive(Debug, Default)]
+struct RecordedBlocks {
+    /// The recorded fixtures, by block hash.
+    fixtures: HashMap<B256, L1BlockFixture>,
+    /// The provider serving the recorded fixtures.
+    provider: FixtureProvider,
+}
+
+impl RecordingL1Provider {
+    /// Creates a new [RecordingL1Provider], loading the beacon genesis time and slot interval from
+    /// the beacon node.
+    pub(crate) async fn new(
+        l1: RootProvider,
+        beacon: OnlineBeaconClient,
+    ) -> Result<Self, RecordingProviderError> {
+        let genesis_time = beacon
+            .beacon_genesis()
+            .await
+            .map_err(|e| RecordingProviderError::Beacon(e.to_string()))?
+            .data
+            .genesis_time;
+        let slot_interval = beacon
+            .config_spec()
+            .await
+            .map_err(|e| RecordingProviderError::Beacon(e.to_string()))?
+            .data
+            .seconds_per_slot;
+        Ok(Self { l1, beacon, genesis_time, slot_interval, recorded: Default::default() })
+    }
+
+    /// Returns the [L1BlockFixture]s recorded so far.
+    pub(crate) async fn fixtures(&self) -> Vec<L1BlockFixture> {
+        self.recorded.lock().await.fixtures.values().cloned().collect()
+    }
+
+    /// Performs an RPC request with the given method and block identifier.
+    async fn request<R>(
+        &self,
+        method: &'static str,
+        block: RawBlockId,
+    ) -> Result<R, RecordingProviderError>
+    where
+        R: serde::de::DeserializeOwned + core::fmt::Debug + Send + Sync + Unpin + 'static,
+    {
+        self.l1
+            .client()
+            .request::<_, R>(method, [block])
+            .await
+            .map_err(|e| RecordingProviderError::Rpc(method, e.to_string()))
+    }
+
+    /// Fetches the raw block and receipts of the given block, recording them, and returns the
+    /// block's hash.
+    async fn record(
+        &self,
+        recorded: &mut RecordedBlocks,
+        block: RawBlockId,
+    ) -> Result<B256, RecordingProviderError> {
+        let raw_block: Bytes = self.request("debug_getRawBlock", block).await?;
+        let mut fixture =
+            L1BlockFixture { raw_block, raw_receipts: Vec::new(), blob_sidecars: None };
+        let hash = fixture.block()?.header.hash_slow();
+        fixture.raw_receipts = self.request("debug_getRawReceipts", RawBlockId::Hash(hash)).await?;
+
+        recorded.provider.insert_block(&fixture)?;
+        recorded.fixtures.insert(hash, fixture);
+        debug!(target: "recorder", "Recorded L1 block {hash}");
+        Ok(hash)
+    }
+
+    /// Records the block with the given hash, unless it has already been recorded.
+    async fn record_hash(
+        &self,
+        recorded: &mut RecordedBlocks,
+        hash: B256,
+    ) -> Result<(), RecordingProviderError> {
+        if !recorded.fixtures.contains_key(&hash) {
+            self.record(recorded, RawBlockId::Hash(hash)).await?;
+        }
+        Ok(())
+    }
+}
+
+/// A block identifier accepted by the raw `debug` RPC methods.
+#[derive(Debug, Clone, Copy, serde::Serialize)]
+#[serde(untagged)]
+enum RawBlockId {
+    /// The block hash.
+    Hash(B256),
+    /// The block number.
+    Number(U64),
+}
+
+/// An error for the [RecordingL1Provider].
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum RecordingProviderError {
+    /// An L1 execution node RPC request failed.
+    #[error("{0} request failed: {1}")]
+    Rpc(&'static str, String),
+    /// A beacon node request failed.
+    #[error("Beacon request failed: {0}")]
+    Beacon(String),
+    /// The slot of a block could not be derived from its timestamp.
+    #[error("Failed to derive the slot of block {0}")]
+    SlotDerivation(B256),
+    /// A recorded response could not be served.
+    #[error(transparent)]
+    Fixture(#[from] FixtureProviderError),
+}
+
+impl From<RecordingProviderError> for PipelineErrorKind {
+    fn from(val: RecordingProviderError) -> Self {
+        PipelineError::Provider(val.to_string()).temp()
+    }
+}
+
+#[async_trait]
+impl ChainProvider for RecordingL1Provider {
+    type Error = RecordingProviderError;
+
+    async fn header_by_hash(&mut self, hash: B256) -> Result<Header, Self::Error> {
+        let mut recorded = self.recorded.lock().await;
+        self.record_hash(&mut recorded, hash).await?;
+        Ok(recorded.provider.header_by_hash(hash).await?)
+    }
+
+    async fn block_info_by_number(&mut self, number: u64) -> Result<BlockInfo, Self::Error> {
+        let mut recorded = self.recorded.lock().await;
+        if let Ok(block_info) = recorded.provider.block_info_by_number(number).await {
+            return Ok(block_info);
+        }
+        self.record(&mut recorded, RawBlockId::Number(U64::from(number))).await?;
+        Ok(recorded.provider.block_info_by_number(number).await?)
+    }
+
+    async fn receipts_by_hash(&mut self, hash: B256) -> Result<Vec<Receipt>, Self::Error> {
+        let mut recorded = self.recorded.lock().await;
+        self.record_hash(&mut recorded, hash).await?;
+        Ok(recorded.provider.receipts_by_hash(hash).await?)
+    }
+
+    async fn block_info_and_transactions_by_hash(
+        &mut self,
+        hash: B256,
+    ) -> Result<(BlockInfo, Vec<TxEnvelope>), Self::Error> {
+        let mut recorded = self.recorded.lock().await;
+        self.record_hash(&mut recorded, hash).await?;
+        Ok(recorded.provider.block_info_and_transactions_by_hash(hash).await?)
+    }
+}
+
+#[async_trait]
+impl BlobProvider for RecordingL1Provider {
+    type Error = RecordingProviderError;
+
+    async fn get_blobs(
+        &mut self,
+        block_ref: &BlockInfo,
+        blob_hashes: &[IndexedBlobHash],
+    ) -> Result<Vec<Box<Blob>>, Self::Error> {
+        let mut recorded = self.recorded.lock().await;
+        self.record_hash(&mut recorded, block_ref.hash).await?;
+
+        // Record the full sidecars response of the block's slot.
+        let RecordedBlocks { fixtures, provider } = &mut *recorded;
+        let fixture = fixtures
+            .get_mut(&block_ref.hash)
+            .ok_or(FixtureProviderError::BlockNotFound(block_ref.hash))?;
+        if fixture.blob_sidecars.is_none() {
+            let slot = OnlineBlobProvider::<OnlineBeaconClient>::slot(
+                self.genesis_time,
+                self.slot_interval,
+                block_ref.timestamp,
+            )
+            .map_err(|_| RecordingProviderError::SlotDerivation(block_ref.hash))?;
+            let bundle = self
+                .beacon
+                .inner
+                .get(format!("{}/{SIDECARS_METHOD_PREFIX}/{slot}", self.beacon.base))
+                .send()
+                .await
+                .and_then(|response| response.error_for_status())
+                .map_err(|e| RecordingProviderError::Beacon(e.to_string()))?
+                .json::<BeaconBlobBundle>()
+                .await
+                .map_err(|e| RecordingProviderError::Beacon(e.to_string()))?;
+            fixture.blob_sidecars = Some(bundle);
+            provider.insert_block(fixture)?;
+            debug!(target: "recorder", "Recorded blob sidecars of slot {slot}");
+        }
+
+        Ok(provider.get_blobs(block_ref, blob_hashes).await?)
+    }
+}
@@ -0,0 +1,501 @@
+//! Contains the `synthetic` subcommand, which generates the synthetic [PipelineFixture] checked in
+//! at `crates/derive/testdata/pipeline/synthetic-ecotone`.
+
+use alloy_consensus::{
+    constants::EMPTY_WITHDRAWALS,
+    proofs::{calculate_receipt_root, calculate_transaction_root},
+    Block, BlockBody, Eip658Value, Header, Receipt, ReceiptEnvelope, SignableTransaction,
+    TxEip1559, TxEip4844, TxEip4844Variant, TxEnvelope,
+};
+use alloy_eips::{
+    eip2718::Encodable2718,
+    eip4844::{Blob, DATA_GAS_PER_BLOB},
+    BlockNumHash,
+};
+use alloy_primitives::{
+    keccak256, map::HashMap, Address, Bytes, Log, LogData, PrimitiveSignature, TxKind, B256, U256,
+};
+use alloy_rlp::Encodable;
+use alloy_rpc_types_beacon::{
+    header::{BeaconBlockHeader, Header as BeaconHeader},
+    sidecar::{BeaconBlobBundle, BlobData},
+};
+use anyhow::{anyhow, Result};
+use clap::Parser;
+use k256::ecdsa::SigningKey;
+use kona_derive::{
+    sources::{blob_kzg_commitment, blob_kzg_proof, encode_blob_data},
+    test_utils::{
+        pipeline_origin, sidecar_versioned_hash, write_golden_attributes, FixtureProvider,
+        L1BlockFixture, PipelineFixture, L1_FIXTURE_DIR,
+    },
+};
+use maili_genesis::{ChainGenesis, RollupConfig, SystemConfig};
+use maili_protocol::{
+    Batch, BlockInfo, ChannelId, Frame, L2BlockInfo, SingleBatch, SpanBatch, DEPOSIT_EVENT_ABI_HASH,
+};
+use maili_registry::ROLLUP_CONFIGS;
+use op_alloy_consensus::OpTxType;
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+/// The number of the first L1 block of the range, which is the L1 genesis of the chain.
+const L1_GENESIS_NUMBER: u64 = 1000;
+
+/// The timestamp of the L1 and L2 genesis blocks.
+const GENESIS_TIME: u64 = 1_700_000_000;
+
+/// The L1 block time.
+const L1_BLOCK_TIME: u64 = 12;
+
+/// The number of L1 blocks in the range. The L2 blocks of the epochs of the first three L1 blocks
+/// are batched in the following block, and the last L1 block is read by the pipeline to close the
+/// last epoch.
+const L1_BLOCKS: u64 = 5;
+
+/// The amount minted and transferred by the user deposit.
+const DEPOSIT_VALUE: u64 = 1_000_000_000_000_000;
+
+/// The `synthetic` subcommand.
+#[derive(Parser, Clone, Debug)]
+pub(crate) struct SyntheticCommand {
+    /// The directory to write the fixture to.
+    #[clap(long, short)]
+    pub(crate) output: PathBuf,
+}
+
+impl SyntheticCommand {
+    /// Generates the synthetic fixture.
+    pub(crate) async fn run(self) -> Result<()> {
+        let derived = generate(&self.output).await?;
+        info!(
+            target: "fixture",
+            "Wrote synthetic fixture with {derived} attributes to {}",
+            self.output.display()
+        );
+        Ok(())
+    }
+}
+
+/// Generates the synthetic fixture in the given directory, returning the number of derived
+/// attributes.
+///
+/// The L1 blocks of the range carry a user deposit, a calldata channel of singular batches, a blob
+/// channel of singular batches, and a calldata span batch, with every fork up to Granite active.
+/// The transactions are signed with fixed keys, so that the fixture is reproducible. The derived
+/// attributes are checked against the L2 blocks that were batched before they are written.
+pub(crate) async fn generate(dir: &Path) -> Result<usize> {
+    let l1_dir = dir.join(L1_FIXTURE_DIR);
+    std::fs::create_dir_all(&l1_dir)?;
+
+    let batcher_key = SigningKey::from_slice(keccak256("kona batcher").as_slice())?;
+    let user_key = SigningKey::from_slice(keccak256("kona user").as_slice())?;
+    let user = Address::from_private_key(&user_key);
+
+    let mut rollup_config = rollup_config()?;
+    let system_config = system_config(Address::from_private_key(&batcher_key));
+
+    let user_transaction = {
+        let tx = TxEip1559 {
+            chain_id: rollup_config.l2_chain_id,
+            gas_limit: 21_000,
+            max_fee_per_gas: 1_000_000_000,
+            max_priority_fee_per_gas: 1_000_000,
+            to: TxKind::Call(Address::repeat_byte(0x42)),
+            value: U256::from(1),
+            ..Default::default()
+        };
+        let signature = sign(&user_key, tx.signature_hash())?;
+        Bytes::from(TxEnvelope::from(tx.into_signed(signature)).encoded_2718())
+    };
+
+    let mut headers: Vec<Header> = Vec::new();
+    let mut fixtures = Vec::new();
+    let mut l2_blocks: Vec<L2BlockInfo> = Vec::new();
+    let mut l2_transactions: HashMap<u64, Vec<Bytes>> = HashMap::default();
+    for i in 0..L1_BLOCKS {
+        let number = L1_GENESIS_NUMBER + i;
+        let mut transactions: Vec<(TxEnvelope, ReceiptEnvelope)> = Vec::new();
+        let mut sidecars = Vec::new();
+        let mut blob_gas_used = 0;
+
+        // Batch the L2 blocks of the previous L1 block's epoch.
+        if (1..=3).contains(&i) {
+            let epoch = &headers[i as usize - 1];
+            let epoch_id = BlockNumHash { number: epoch.number, hash: epoch.hash_slow() };
+            let mut batched = Vec::new();
+            for n in l2_blocks.len() as u64 + 1.. {
+                let timestamp = GENESIS_TIME + n * rollup_config.block_time;
+                if timestamp >= epoch.timestamp + L1_BLOCK_TIME {
+                    break;
+                }
+                let parent_hash =
+                    if n == 1 { rollup_config.genesis.l2.hash } else { l2_block_hash(n - 1) };
+                batched.push(L2BlockInfo::new(
+                    BlockInfo::new(l2_block_hash(n), n, parent_hash, timestamp),
+                    epoch_id,
+                    (timestamp - epoch.timestamp) / rollup_config.block_time,
+                ));
+            }
+            l2_blocks.extend(batched.iter().copied());
+
+            match i {
+                // A user deposit and a calldata channel of singular batches, one of which carries
+                // a user transaction.
+                1 => {
+                    let deposit = TxEip1559 {
+                        chain_id: rollup_config.l1_chain_id,
+                        gas_limit: 200_000,
+                        max_fee_per_gas: 100_000_000_000,
+                        max_priority_fee_per_gas: 1_000_000_000,
+                        to: TxKind::Call(rollup_config.deposit_contract_address),
+                        value: U256::from(DEPOSIT_VALUE),
+                        ..Default::default()
+                    };
+                    let signature = sign(&user_key, deposit.signature_hash())?;
+                    let log = deposit_log(rollup_config.deposit_contract_address, user);
+                    transactions.push((
+                        TxEnvelope::from(deposit.into_signed(signature)),
+                        ReceiptEnvelope::Eip1559(receipt(80_000, vec![log]).with_bloom()),
+                    ));
+
+                    l2_transactions.insert(3, vec![user_transaction.clone()]);
+                    let batches = batched
+                        .iter()
+                        .map(|block| {
+                            let transactions = l2_transactions
+                                .get(&block.block_info.number)
+                                .cloned()
+                                .unwrap_or_default();
+                            Batch::Single(singular_batch(block, transactions))
+                        })
+                        .collect::<Vec<_>>();
+                    let calldata = frame_data(1, &batches)?;
+                    transactions.push((
+                        calldata_transaction(&rollup_config, &batcher_key, 0, calldata)?,
+                        ReceiptEnvelope::Eip1559(receipt(200_000, vec![]).with_bloom()),
+                    ));
+                }
+                // A blob channel of singular batches.
+                2 => {
+                    let batches = batched
+                        .iter()
+                        .map(|block| Batch::Single(singular_batch(block, vec![])))
+                        .collect::<Vec<_>>();
+                    let blob = encode_blob_data(&frame_data(2, &batches)?)?;
+                    let sidecar = blob_sidecar(number, i, blob)?;
+                    let tx = TxEip4844 {
+                        chain_id: rollup_config.l1_chain_id,
+                        nonce: 1,
+                        gas_limit: 21_000,
+                        max_fee_per_gas: 100_000_000_000,
+                        max_priority_fee_per_gas: 1_000_000_000,
+                        to: rollup_config.batch_inbox_address,
+                        blob_versioned_hashes: vec![sidecar_versioned_hash(&sidecar)],
+                        max_fee_per_blob_gas: 1_000_000_000,
+                        ..Default::default()
+                    };
+                    let signature = sign(&batcher_key, tx.signature_hash())?;
+                    transactions.push((
+                        TxEnvelope::Eip4844(TxEip4844Variant::TxEip4844(tx).into_signed(signature)),
+                        ReceiptEnvelope::Eip4844(receipt(21_000, vec![]).with_bloom()),
+                    ));
+                    sidecars.push(sidecar);
+                    blob_gas_used = DATA_GAS_PER_BLOB;
+                }
+                // A calldata span batch.
+                _ => {
+                    let mut span = SpanBatch {
+                        genesis_timestamp: rollup_config.genesis.l2_time,
+                        chain_id: rollup_config.l2_chain_id,
+                        ..Default::default()
+                    };
+                    for block in &batched {
+                        span.append_singular_batch(singular_batch(block, vec![]), block.seq_num)
+                            .map_err(|e| anyhow!("Failed to append to the span batch: {e:?}"))?;
+                    }
+                    let calldata = frame_data(3, &[Batch::Span(span)])?;
+                    transactions.push((
+                        calldata_transaction(&rollup_config, &batcher_key, 2, calldata)?,
+                        ReceiptEnvelope::Eip1559(receipt(40_000, vec![]).with_bloom()),
+                    ));
+                }
+            }
+        }
+
+        let parent_hash =
+            headers.last().map_or_else(|| keccak256("kona l1 parent"), |header| header.hash_slow());
+        let (transactions, receipts): (Vec<_>, Vec<_>) = transactions.into_iter().unzip();
+        let header = Header {
+            parent_hash,
+            beneficiary: Address::repeat_byte(0xfe),
+            number,
+            timestamp: GENESIS_TIME + i * L1_BLOCK_TIME,
+            gas_limit: 30_000_000,
+            gas_used: receipts.last().map_or(0, |receipt| receipt.cumulative_gas_used()),
+            base_fee_per_gas: Some(7 + i),
+            mix_hash: keccak256(format!("kona randao {number}")),
+            transactions_root: calculate_transaction_root(&transactions),
+            receipts_root: calculate_receipt_root(&receipts),
+            withdrawals_root: Some(EMPTY_WITHDRAWALS),
+            blob_gas_used: Some(blob_gas_used),
+            excess_blob_gas: Some(0),
+            parent_beacon_block_root: Some(keccak256(format!("kona beacon root {number}"))),
+            ..Default::default()
+        };
+        let block = Block::<TxEnvelope> {
+            header: header.clone(),
+            body: BlockBody { transactions, ommers: vec![], withdrawals: Some(Default::default()) },
+        };
+        let mut raw_block = Vec::new();
+        block.encode(&mut raw_block);
+        fixtures.push(L1BlockFixture {
+            raw_block: raw_block.into(),
+            raw_receipts: receipts.iter().map(|receipt| receipt.encoded_2718().into()).collect(),
+            blob_sidecars: (!sidecars.is_empty()).then(|| BeaconBlobBundle::new(sidecars)),
+        });
+
+        // The first L1 block is the L1 genesis of the chain.
+        if i == 0 {
+            rollup_config.genesis = ChainGenesis {
+                l1: BlockNumHash { number, hash: header.hash_slow() },
+                l2: BlockNumHash { number: 0, hash: keccak256("kona l2 genesis") },
+                l2_time: GENESIS_TIME,
+                system_config: Some(system_config),
+            };
+        }
+        headers.push(header);
+    }
+
+    let mut provider = FixtureProvider::default();
+    for fixture in &fixtures {
+        fixture.save(&l1_dir)?;
+        provider.insert_block(fixture)?;
+    }
+
+    let l2_safe_head = L2BlockInfo::new(
+        BlockInfo::new(rollup_config.genesis.l2.hash, 0, B256::ZERO, GENESIS_TIME),
+        rollup_config.genesis.l1,
+        0,
+    );
+    let system_configs =
+        (0..=l2_blocks.len() as u64).map(|number| (number, system_config)).collect();
+    let l1_origin = pipeline_origin(&rollup_config, &l2_safe_head, &mut provider).await?;
+    let fixture =
+        PipelineFixture { rollup_config, l1_origin, l2_safe_head, l2_blocks, system_configs };
+    fixture.write(dir)?;
+
+    // The derived attributes must build the batched L2 blocks, with the user deposit in the first
+    // block of its epoch.
+    let derived = fixture.derive(dir).await?;
+    if derived.len() != fixture.l2_blocks.len() {
+        return Err(anyhow!("Derived {} of {} L2 blocks", derived.len(), fixture.l2_blocks.len()));
+    }
+    for (attributes, l2_block) in derived.iter().zip(&fixture.l2_blocks) {
+        let number = l2_block.block_info.number;
+        let transactions = attributes.attributes.transactions.clone().unwrap_or_default();
+        let (deposits, sequenced): (Vec<_>, Vec<_>) =
+            transactions.into_iter().partition(|tx| tx.first() == Some(&(OpTxType::Deposit as u8)));
+        let expected_deposits =
+            if l2_block.l1_origin.number == L1_GENESIS_NUMBER + 1 && l2_block.seq_num == 0 {
+                2
+            } else {
+                1
+            };
+        if attributes.attributes.payload_attributes.timestamp != l2_block.block_info.timestamp ||
+            attributes.parent.block_info.number + 1 != number ||
+            deposits.len() != expected_deposits ||
+            sequenced != l2_transactions.get(&number).cloned().unwrap_or_default()
+        {
+            return Err(anyhow!("Derived attributes do not build L2 block #{number}"));
+        }
+    }
+    write_golden_attributes(dir, &derived)?;
+    Ok(derived.len())
+}
+
+/// Returns the rollup config of the synthetic chain: OP Mainnet's, with every fork up to Granite
+/// active at genesis. The genesis is set once the first L1 block is built.
+fn rollup_config() -> Result<RollupConfig> {
+    let mut rollup_config =
+        ROLLUP_CONFIGS.get(&10).cloned().ok_or_else(|| anyhow!("OP Mainnet config not found"))?;
+    rollup_config.l1_chain_id = 1;
+    rollup_config.l2_chain_id = 10;
+    rollup_config.regolith_time = Some(0);
+    rollup_config.canyon_time = Some(0);
+    rollup_config.delta_time = Some(0);
+    rollup_config.ecotone_time = Some(0);
+    rollup_config.fjord_time = Some(0);
+    rollup_config.granite_time = Some(0);
+    rollup_config.holocene_time = None;
+    rollup_config.isthmus_time = None;
+    rollup_config.interop_time = None;
+    Ok(rollup_config)
+}
+
+/// Returns the system config of the synthetic chain, with an Ecotone fee scalar.
+fn system_config(batcher_address: Address) -> SystemConfig {
+    let mut scalar = [0u8; 32];
+    scalar[0] = 1;
+    scalar[24..28].copy_from_slice(&810_949u32.to_be_bytes());
+    scalar[28..32].copy_from_slice(&1_368u32.to_be_bytes());
+    SystemConfig {
+        batcher_address,
+        overhead: U256::ZERO,
+        scalar: U256::from_be_bytes(scalar),
+        gas_limit: 30_000_000,
+        base_fee_scalar: None,
+        blob_base_fee_scalar: None,
+        eip1559_denominator: None,
+        eip1559_elasticity: None,
+        operator_fee_scalar: None,
+        operator_fee_constant: None,
+    }
+}
+
+/// Returns the hash of the synthetic L2 block with the given number.
+fn l2_block_hash(number: u64) -> B256 {
+    keccak256(format!("kona l2 block {number}"))
+}
+
+/// Signs the given signature hash with the key.
+fn sign(key: &SigningKey, hash: B256) -> Result<PrimitiveSignature> {
+    let (signature, recovery_id) = key.sign_prehash_recoverable(hash.as_slice())?;
+    Ok(PrimitiveSignature::from((signature, recovery_id)))
+}
+
+/// Returns a successful receipt with the given cumulative gas used and logs.
+const fn receipt(cumulative_gas_used: u64, logs: Vec<Log>) -> Receipt {
+    Receipt { status: Eip658Value::Eip658(true), cumulative_gas_used, logs }
+}
+
+/// Returns the [SingleBatch] of the given L2 block.
+const fn singular_batch(block: &L2BlockInfo, transactions: Vec<Bytes>) -> SingleBatch {
+    SingleBatch {
+        parent_hash: block.block_info.parent_hash,
+        epoch_num: block.l1_origin.number,
+        epoch_hash: block.l1_origin.hash,
+        timestamp: block.block_info.timestamp,
+        transactions,
+    }
+}
+
+/// Returns the batcher transaction data of a channel with the given ID holding the batches, in a
+/// single frame.
+fn frame_data(id: u8, batches: &[Batch]) -> Result<Vec<u8>> {
+    let mut channel = Vec::new();
+    for batch in batches {
+        let mut encoded = Vec::new();
+        batch.encode(&mut encoded).map_err(|e| anyhow!("Failed to encode batch: {e:?}"))?;
+        Bytes::from(encoded).encode(&mut channel);
+    }
+    let channel = miniz_oxide::deflate::compress_to_vec_zlib(&channel, 9);
+    let frame = Frame::new(ChannelId::from([id; 16]), 0, channel, true);
+
+    // The derivation version byte, followed by the frame.
+    let mut data = vec![0u8];
+    data.extend(frame.encode());
+    Ok(data)
+}
+
+/// Returns a batcher transaction posting the given calldata to the batch inbox.
+fn calldata_transaction(
+    rollup_config: &RollupConfig,
+    batcher_key: &SigningKey,
+    nonce: u64,
+    calldata: Vec<u8>,
+) -> Result<TxEnvelope> {
+    let tx = TxEip1559 {
+        chain_id: rollup_config.l1_chain_id,
+        nonce,
+        gas_limit: 1_000_000,
+        max_fee_per_gas: 100_000_000_000,
+        max_priority_fee_per_gas: 1_000_000_000,
+        to: TxKind::Call(rollup_config.batch_inbox_address),
+        input: calldata.into(),
+        ..Default::default()
+    };
+    let signature = sign(batcher_key, tx.signature_hash())?;
+    Ok(TxEnvelope::from(tx.into_signed(signature)))
+}
+
+/// Returns the sidecar of the given blob, as served by the beacon node for the L1 block.
+fn blob_sidecar(number: u64, slot_offset: u64, blob: Blob) -> Result<BlobData> {
+    let kzg_commitment = blob_kzg_commitment(&blob)?;
+    let kzg_proof = blob_kzg_proof(&blob, &kzg_commitment)?;
+    Ok(BlobData {
+        index: 0,
+        blob: Box::new(blob),
+        kzg_commitment,
+        kzg_proof,
+        signed_block_header: BeaconHeader {
+            message: BeaconBlockHeader {
+                slot: 8_000_000 + slot_offset,
+                proposer_index: 42,
+                parent_root: keccak256(format!("kona beacon root {number}")),
+                state_root: keccak256(format!("kona beacon state {number}")),
+                body_root: keccak256(format!("kona beacon body {number}")),
+            },
+            signature: Bytes::from(vec![0xab; 96]),
+        },
+        kzg_commitment_inclusion_proof: (0..17)
+            .map(|j| keccak256(format!("kona inclusion proof {j}")))
+            .collect(),
+    })
+}
+
+/// Returns the `TransactionDeposited` log of a deposit by `user` to itself, minting and
+/// transferring [DEPOSIT_VALUE].
+fn deposit_log(deposit_contract: Address, user: Address) -> Log {
+    let mut opaque = Vec::new();
+    opaque.extend(U256::from(DEPOSIT_VALUE).to_be_bytes::<32>()); // mint
+    opaque.extend(U256::from(DEPOSIT_VALUE).to_be_bytes::<32>()); // value
+    opaque.extend(100_000u64.to_be_bytes()); // gas limit
+    opaque.push(0); // is creation
+    let len = opaque.len();
+    opaque.resize(len.div_ceil(32) * 32, 0);
+
+    // The ABI encoding of the opaque data.
+    let mut data = Vec::new();
+    data.extend(U256::from(32).to_be_bytes::<32>());
+    data.extend(U256::from(len).to_be_bytes::<32>());
+    data.extend(opaque);
+    Log {
+        address: deposit_contract,
+        data: LogData::new_unchecked(
+            vec![DEPOSIT_EVENT_ABI_HASH, user.into_word(), user.into_word(), B256::ZERO],
+            data.into(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    /// Reads the JSON files of the fixture directory, by path relative to the directory.
+    fn read_json_files(dir: &Path) -> HashMap<PathBuf, Value> {
+        let mut files = HashMap::default();
+        for sub_dir in [dir.to_path_buf(), dir.join(L1_FIXTURE_DIR)] {
+            for entry in std::fs::read_dir(&sub_dir).unwrap() {
+                let path = entry.unwrap().path();
+                if path.extension().is_some_and(|extension| extension == "json") {
+                    let value = serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+                    files.insert(path.strip_prefix(dir).unwrap().to_path_buf(), value);
+                }
+            }
+        }
+        files
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_synthetic_fixture_matches_testdata() {
+        let dir = tempfile::tempdir().unwrap();
+        generate(dir.path()).await.unwrap();
+
+        let testdata = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../../crates/derive/testdata/pipeline/synthetic-ecotone");
+        assert_eq!(read_json_files(dir.path()), read_json_files(&testdata));
+    }
+}
@@ -0,0 +1,222 @@
+//! Contains the `create` subcommand, which records an [ExecutorFixture] from an L2 archive node.
+
+use crate::fixture::ExecutorFixture;
+use alloy_consensus::{Header, Sealable};
+use alloy_primitives::{Bytes, B256};
+use alloy_provider::{
+    network::primitives::{BlockTransactions, BlockTransactionsKind},
+    Provider, RootProvider,
+};
+use alloy_rlp::Decodable;
+use alloy_rpc_client::RpcClient;
+use alloy_rpc_types_engine::PayloadAttributes;
+use alloy_transport_http::{Client, Http};
+use anyhow::{anyhow, Result};
+use clap::Parser;
+use kona_executor::{
+    ExecutorError, RawTrieDBProvider, StatelessL2BlockExecutor, TrieDBProvider, WitnessRecorder,
+};
+use kona_mpt::{NoopTrieHinter, TrieNode, TrieProvider};
+use maili_genesis::RollupConfig;
+use maili_registry::ROLLUP_CONFIGS;
+use op_alloy_rpc_types_engine::OpPayloadAttributes;
+use std::path::PathBuf;
+use tokio::runtime::Handle;
+use tracing::{info, warn};
+
+/// The `create` subcommand.
+#[derive(Parser, Clone, Debug)]
+pub(crate) struct CreateCommand {
+    /// Address of the L2 archive node JSON-RPC endpoint (eth and debug namespaces required).
+    #[clap(long, visible_alias = "l2", env)]
+    pub(crate) l2_node_address: String,
+    /// The number of the block to create the fixture for.
+    #[clap(long)]
+    pub(crate) block_number: u64,
+    /// Path to the rollup config. If not provided, the rollup config of the L2 node's chain is
+    /// looked up in the superchain registry.
+    #[clap(long, alias = "rollup-cfg", env)]
+    pub(crate) rollup_config_path: Option<PathBuf>,
+    /// The path to write the fixture to. Defaults to `block-<block_number>.json`.
+    #[clap(long, short)]
+    pub(crate) output: Option<PathBuf>,
+}
+
+impl CreateCommand {
+    /// Executes the block against the L2 node, recording the fixture.
+    pub(crate) async fn run(self) -> Result<()> {
+        if self.block_number == 0 {
+            return Err(anyhow!("The genesis block has no parent to execute on top of"));
+        }
+
+        let url = self.l2_node_address.parse().map_err(|e| anyhow!("Invalid L2 node URL: {e}"))?;
+        let provider: RootProvider =
+            RootProvider::new(RpcClient::new(Http::<Client>::new(url), false));
+
+        let rollup_config = match &self.rollup_config_path {
+            Some(path) => serde_json::from_slice::<RollupConfig>(&std::fs::read(path)?)
+                .map_err(|e| anyhow!("Error deserializing RollupConfig: {e}"))?,
+            None => {
+                let chain_id = provider.get_chain_id().await?;
+                ROLLUP_CONFIGS
+                    .get(&chain_id)
+                    .cloned()
+                    .ok_or_else(|| anyhow!("Rollup config not found for chain ID {chain_id}"))?
+            }
+        };
+
+        let executing_block = provider
+            .get_block_by_number(self.block_number.into(), BlockTransactionsKind::Hashes)
+            .await?
+            .ok_or_else(|| anyhow!("Block {} not found", self.block_number))?;
+        let expected_header = executing_block.header.inner;
+
+        let BlockTransactions::Hashes(tx_hashes) = executing_block.transactions else {
+            return Err(anyhow!("Expected the block's transaction hashes"));
+        };
+        let mut transactions = Vec::with_capacity(tx_hashes.len());
+        for tx_hash in tx_hashes {
+            transactions.push(
+                provider.client().request::<_, Bytes>("debug_getRawTransaction", [tx_hash]).await?,
+            );
+        }
+
+        let executing_payload = OpPayloadAttributes {
+            payload_attributes: PayloadAttributes {
+                timestamp: expected_header.timestamp,
+                parent_beacon_block_root: expected_header.parent_beacon_block_root,
+                prev_randao: expected_header.mix_hash,
+                withdrawals: Default::default(),
+                suggested_fee_recipient: expected_header.beneficiary,
+            },
+            gas_limit: Some(expected_header.gas_limit),
+            transactions: Some(transactions),
+            no_tx_pool: None,
+            eip_1559_params: rollup_config
+                .is_holocene_active(expected_header.timestamp)
+                .then(|| expected_header.extra_data.get(1..9).and_then(|p| p.try_into().ok()))
+                .flatten(),
+        };
+
+        // Execute the block against the L2 node, recording the parent header and the preimages
+        // the block touches.
+        let recorder = WitnessRecorder::new(RpcTrieProvider { provider });
+        let parent_header =
+            tokio::task::block_in_place(|| recorder.record_header(expected_header.parent_hash))
+                .map_err(|e| anyhow!("Failed to fetch the parent header: {e}"))?
+                .seal_slow();
+        let mut executor =
+            StatelessL2BlockExecutor::builder(&rollup_config, recorder.clone(), NoopTrieHinter)
+                .with_parent_header(parent_header.clone())
+                .build();
+        let result = tokio::task::block_in_place(|| {
+            executor.execute_and_validate(executing_payload.clone(), &expected_header, None)
+        });
+        match result {
+            Ok(_) => info!(target: "fixture", "Produced block matches the canonical block"),
+            Err(ExecutorError::BlockMismatch(diff)) => {
+                warn!(target: "fixture", "Produced block does not match the canonical block: {diff}")
+            }
+            Err(e) => return Err(anyhow!("Failed to execute block {}: {e}", self.block_number)),
+        }
+
+        let fixture = ExecutorFixture {
+            rollup_config,
+            parent_header: parent_header.into_inner(),
+            executing_payload,
+            expected_block_hash: expected_header.hash_slow(),
+            expected_header,
+            witness: recorder.take_witness(),
+        };
+        let output = self
+            .output
+            .unwrap_or_else(|| PathBuf::from(format!("block-{}.json", self.block_number)));
+        fixture.write(&output)?;
+        info!(target: "fixture", "Wrote fixture to {}", output.display());
+        Ok(())
+    }
+}
+
+/// An error fetching a preimage from the L2 node.
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum RpcProviderError {
+    /// The RPC request failed.
+    #[error("RPC request failed: {0}")]
+    Rpc(String),
+    /// The preimage could not be decoded.
+    #[error("Failed to decode preimage: {0}")]
+    Rlp(alloy_rlp::Error),
+}
+
+/// A [TrieDBProvider] fetching preimages from an L2 archive node.
+#[derive(Debug, Clone)]
+struct RpcTrieProvider {
+    /// The L2 node's RPC provider.
+    provider: RootProvider,
+}
+
+impl RpcTrieProvider {
+    /// Performs a blocking RPC request with the given method and parameter.
+    fn request(&self, method: &'static str, param: Bytes) -> Result<Bytes, RpcProviderError> {
+        Handle::current().block_on(async {
+            self.provider
+                .client()
+                .request::<_, Bytes>(method, [param])
+                .await
+                .map_err(|e| RpcProviderError::Rpc(e.to_string()))
+        })
+    }
+}
+
+impl TrieProvider for RpcTrieProvider {
+    type Error = RpcProviderError;
+
+    fn trie_node_by_hash(&self, key: B256) -> Result<TrieNode, Self::Error> {
+        let preimage = self.raw_trie_node_by_hash(key)?;
+        TrieNode::decode(&mut preimage.as_ref()).map_err(RpcProviderError::Rlp)
+    }
+}
+
+impl TrieDBProvider for RpcTrieProvider {
+    fn bytecode_by_hash(&self, hash: B256) -> Result<Bytes, Self::Error> {
+        // geth hashdb scheme code hash key prefix
+        const CODE_PREFIX: u8 = b'c';
+
+        // Fall back to the code hash without the geth hashdb scheme prefix.
+        let prefixed = [&[CODE_PREFIX], hash.as_slice()].concat();
+        self.request("debug_dbGet", prefixed.into())
+            .or_else(|_| self.request("debug_dbGet", Bytes::copy_from_slice(hash.as_slice())))
+    }
+
+    fn header_by_hash(&self, hash: B256) -> Result<Header, Self::Error> {
+        let encoded = self.raw_header_by_hash(hash)?;
+        Header::decode(&mut encoded.as_ref()).map_err(RpcProviderError::Rlp)
+    }
+}
+
+impl RawTrieDBProvider for RpcTrieProvider {
+    fn raw_trie_node_by_hash(&self, key: B256) -> Result<Bytes, Self::Error> {
+        self.request("debug_dbGet", Bytes::copy_from_slice(key.as_slice()))
+    }
+
+    fn raw_header_by_hash(&self, hash: B256) -> Result<Bytes, Self::Error> {
+        self.request("debug_getRawHeader", Bytes::copy_from_slice(hash.as_slice()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_create_genesis_block() {
+        let command = CreateCommand {
+            l2_node_address: "http://127.0.0.1:1".to_string(),
+            block_number: 0,
+            rollup_config_path: None,
+            output: None,
+        };
+        let err = command.run().await.unwrap_err();
+        assert!(err.to_string().contains("genesis block"));
+    }
+}
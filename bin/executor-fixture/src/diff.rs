@@ -0,0 +1,173 @@
+//! Contains the `diff` subcommand, which checks the state produced by executing an
+//! [ExecutorFixture] against a reference trace.
+
+use crate::{
+    fixture::ExecutorFixture,
+    reference::{first_divergence, touched_counts, ReferenceTrace, StateDivergence},
+};
+use alloy_consensus::Sealable;
+use anyhow::{anyhow, Result};
+use clap::Parser;
+use kona_executor::{StatelessL2BlockExecutor, WitnessProvider};
+use kona_mpt::NoopTrieHinter;
+use std::path::PathBuf;
+use tracing::{error, info, warn};
+
+/// The `diff` subcommand.
+#[derive(Parser, Clone, Debug)]
+pub(crate) struct DiffCommand {
+    /// The fixture file of the block to execute.
+    pub(crate) fixture: PathBuf,
+    /// The reference trace of the block, as returned by `debug_traceBlockByNumber` with geth's
+    /// `prestateTracer` in diff mode.
+    pub(crate) reference: PathBuf,
+}
+
+impl DiffCommand {
+    /// Executes the fixture and checks every account and storage slot touched by the reference
+    /// trace, failing on the first divergence.
+    pub(crate) fn run(self) -> Result<()> {
+        let fixture = ExecutorFixture::read(&self.fixture)?;
+        let reference = ReferenceTrace::read(&self.reference)?;
+        let (accounts, slots) = touched_counts(&reference);
+
+        match diff(&fixture, &reference)? {
+            None => {
+                info!(
+                    target: "fixture",
+                    "Block #{}: {accounts} accounts and {slots} storage slots match the reference",
+                    fixture.expected_header.number
+                );
+                Ok(())
+            }
+            Some(divergence) => {
+                error!(target: "fixture", "Block #{}: {divergence}", fixture.expected_header.number);
+                Err(anyhow!("State diverges from the reference: {divergence}"))
+            }
+        }
+    }
+}
+
+/// Executes the block of the [ExecutorFixture] offline, returning the first divergence of the
+/// produced state from the [ReferenceTrace], if any.
+pub(crate) fn diff(
+    fixture: &ExecutorFixture,
+    reference: &ReferenceTrace,
+) -> Result<Option<StateDivergence>> {
+    let mut executor = StatelessL2BlockExecutor::builder(
+        &fixture.rollup_config,
+        WitnessProvider::new(&fixture.witness),
+        NoopTrieHinter,
+    )
+    .with_parent_header(fixture.parent_header.clone().seal_slow())
+    .build();
+
+    let artifacts = executor.execute_payload(fixture.executing_payload.clone())?;
+    if artifacts.block_header.hash() != fixture.expected_block_hash {
+        warn!(
+            target: "fixture",
+            "Block #{} does not match the expected header",
+            fixture.expected_header.number
+        );
+    }
+
+    first_divergence(&mut executor, reference)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::reference::StateField;
+    use alloy_consensus::Header;
+    use alloy_eips::eip2718::Encodable2718;
+    use alloy_primitives::{Address, TxKind, B256, U256};
+    use alloy_rpc_types_engine::PayloadAttributes;
+    use kona_executor::ExecutionWitness;
+    use maili_genesis::RollupConfig;
+    use op_alloy_consensus::{OpTxEnvelope, TxDeposit};
+    use op_alloy_rpc_types_engine::OpPayloadAttributes;
+
+    /// Creates a fixture for a block with a single deposit, minting 100 wei to the depositor.
+    fn deposit_fixture() -> ExecutorFixture {
+        let deposit = OpTxEnvelope::Deposit(
+            TxDeposit {
+                from: Address::with_last_byte(1),
+                to: TxKind::Call(Address::with_last_byte(2)),
+                mint: Some(100),
+                gas_limit: 100_000,
+                ..Default::default()
+            }
+            .seal_slow(),
+        );
+        ExecutorFixture {
+            rollup_config: RollupConfig::default(),
+            parent_header: Header::default(),
+            executing_payload: OpPayloadAttributes {
+                payload_attributes: PayloadAttributes { timestamp: 1, ..Default::default() },
+                gas_limit: Some(30_000_000),
+                transactions: Some(vec![deposit.encoded_2718().into()]),
+                ..Default::default()
+            },
+            expected_header: Header::default(),
+            expected_block_hash: B256::ZERO,
+            witness: ExecutionWitness::default(),
+        }
+    }
+
+    #[test]
+    fn test_diff_reports_first_divergence() {
+        let fixture = deposit_fixture();
+        let reference = |balance: u64| {
+            ReferenceTrace::from_json(
+                format!(
+                    r#"[{{"txHash":"0x{hash}","result":{{
+                        "pre":{{"0x0000000000000000000000000000000000000001":{{"balance":"0x0"}}}},
+                        "post":{{"0x0000000000000000000000000000000000000001":{{"balance":"{balance:#x}","nonce":1}}}}
+                    }}}}]"#,
+                    hash = B256::ZERO.to_string().trim_start_matches("0x"),
+                )
+                .as_bytes(),
+            )
+            .unwrap()
+        };
+
+        assert_eq!(diff(&fixture, &reference(100)).unwrap(), None);
+
+        let divergence = diff(&fixture, &reference(99)).unwrap().unwrap();
+        assert_eq!(divergence.address, Address::with_last_byte(1));
+        assert_eq!(divergence.field, StateField::Balance);
+        assert_eq!(divergence.expected, "99");
+        assert_eq!(divergence.actual, "100");
+    }
+
+    #[test]
+    fn test_expected_state_folds_transactions() {
+        let account = "0x0000000000000000000000000000000000000abc";
+        let deleted = "0x0000000000000000000000000000000000000def";
+        let reference = ReferenceTrace::from_json(
+            format!(
+                r#"[
+                    {{"pre":{{"{account}":{{"balance":"0x1","storage":{{
+                        "0x0000000000000000000000000000000000000000000000000000000000000001":"0x0000000000000000000000000000000000000000000000000000000000000005"
+                    }}}}}},
+                    "post":{{"{account}":{{"storage":{{
+                        "0x0000000000000000000000000000000000000000000000000000000000000001":"0x0000000000000000000000000000000000000000000000000000000000000006"
+                    }}}}}}}},
+                    {{"pre":{{"{account}":{{"balance":"0x1","storage":{{
+                        "0x0000000000000000000000000000000000000000000000000000000000000001":"0x0000000000000000000000000000000000000000000000000000000000000006"
+                    }}}},"{deleted}":{{"balance":"0x2","nonce":3}}}},
+                    "post":{{"{account}":{{"balance":"0x4"}}}}}}
+                ]"#
+            )
+            .as_bytes(),
+        )
+        .unwrap();
+
+        let state = reference.expected_state();
+        let expected = state[&account.parse::<Address>().unwrap()].as_ref().unwrap();
+        assert_eq!(expected.balance, U256::from(4));
+        assert_eq!(expected.nonce, 0);
+        assert_eq!(expected.storage.values().collect::<Vec<_>>(), vec![&B256::ZERO]);
+        assert_eq!(state[&deleted.parse::<Address>().unwrap()], None);
+    }
+}
@@ -0,0 +1,45 @@
+//! Contains the [ExecutorFixture] format.
+
+use alloy_consensus::Header;
+use alloy_primitives::B256;
+use anyhow::{anyhow, Result};
+use kona_executor::ExecutionWitness;
+use maili_genesis::RollupConfig;
+use op_alloy_rpc_types_engine::OpPayloadAttributes;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// A self-contained fixture for executing a single L2 block offline.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ExecutorFixture {
+    /// The rollup configuration of the executing chain.
+    pub(crate) rollup_config: RollupConfig,
+    /// The parent block header.
+    pub(crate) parent_header: Header,
+    /// The payload attributes of the executing block.
+    pub(crate) executing_payload: OpPayloadAttributes,
+    /// The canonical header of the executing block.
+    pub(crate) expected_header: Header,
+    /// The hash of the canonical header of the executing block.
+    pub(crate) expected_block_hash: B256,
+    /// The preimages required to execute the block.
+    pub(crate) witness: ExecutionWitness,
+}
+
+impl ExecutorFixture {
+    /// Reads an [ExecutorFixture] from the file at the given path.
+    pub(crate) fn read(path: &Path) -> Result<Self> {
+        let fixture = std::fs::read(path)
+            .map_err(|e| anyhow!("Error reading fixture {}: {e}", path.display()))?;
+        serde_json::from_slice(&fixture)
+            .map_err(|e| anyhow!("Error deserializing fixture {}: {e}", path.display()))
+    }
+
+    /// Writes the [ExecutorFixture] to the file at the given path.
+    pub(crate) fn write(&self, path: &Path) -> Result<()> {
+        let fixture = serde_json::to_vec(self).map_err(|e| anyhow!("Error serializing: {e}"))?;
+        std::fs::write(path, fixture)
+            .map_err(|e| anyhow!("Error writing fixture {}: {e}", path.display()))
+    }
+}
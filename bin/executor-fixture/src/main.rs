@@ -0,0 +1,64 @@
+//! Main entrypoint for the executor fixture binary.
+
+#![warn(missing_debug_implementations, missing_docs, unreachable_pub, rustdoc::all)]
+#![deny(unused_must_use, rust_2018_idioms)]
+#![cfg_attr(docsrs, feature(doc_cfg, doc_auto_cfg))]
+
+use anyhow::{anyhow, Result};
+use clap::{ArgAction, Parser, Subcommand};
+use tracing::Level;
+
+mod create;
+mod diff;
+mod fixture;
+mod reference;
+mod replay;
+
+const ABOUT: &str = "
+kona-executor-fixture creates and replays self-contained fixtures for the stateless L2 block
+executor. A fixture is a single JSON file holding the rollup config, parent header, payload
+attributes, expected header and execution witness of one L2 block, so that its execution can be
+reproduced offline, and checked against the state diff of a reference execution.
+";
+
+/// The executor fixture CLI application arguments.
+#[derive(Parser, Clone, Debug)]
+#[command(about = ABOUT, version)]
+struct FixtureCli {
+    /// Verbosity level (0-2)
+    #[arg(long, short, action = ArgAction::Count)]
+    v: u8,
+    /// The subcommand to run.
+    #[clap(subcommand)]
+    command: FixtureCommand,
+}
+
+/// The subcommands of the executor fixture binary.
+#[derive(Subcommand, Clone, Debug)]
+enum FixtureCommand {
+    /// Create a fixture for a block from an L2 archive node.
+    Create(create::CreateCommand),
+    /// Replay fixtures offline, reporting the execution time and any header mismatch.
+    Replay(replay::ReplayCommand),
+    /// Execute a fixture and check its state against a `prestateTracer` diff mode trace.
+    Diff(diff::DiffCommand),
+}
+
+#[tokio::main(flavor = "multi_thread")]
+async fn main() -> Result<()> {
+    let cli = FixtureCli::parse();
+    let subscriber = tracing_subscriber::fmt()
+        .with_max_level(match cli.v {
+            0 => Level::INFO,
+            1 => Level::DEBUG,
+            _ => Level::TRACE,
+        })
+        .finish();
+    tracing::subscriber::set_global_default(subscriber).map_err(|e| anyhow!(e))?;
+
+    match cli.command {
+        FixtureCommand::Create(cmd) => cmd.run().await,
+        FixtureCommand::Replay(cmd) => cmd.run(),
+        FixtureCommand::Diff(cmd) => cmd.run(),
+    }
+}
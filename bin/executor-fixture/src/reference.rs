@@ -0,0 +1,258 @@
+//! Contains the [ReferenceTrace] format, a block trace of geth's `prestateTracer` in diff mode,
+//! and the comparison of the state produced by the executor against it.
+
+use alloy_primitives::{keccak256, Address, Bytes, B256, U256};
+use anyhow::{anyhow, Result};
+use kona_executor::{StatelessL2BlockExecutor, TrieDBProvider};
+use kona_mpt::TrieHinter;
+use serde::Deserialize;
+use std::{collections::BTreeMap, fmt, path::Path};
+
+/// An account in a [DiffModeTrace]. Fields that are unchanged by the transaction are omitted from
+/// the post-state, as are storage slots that are cleared.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub(crate) struct DiffAccount {
+    /// The balance of the account.
+    #[serde(default)]
+    pub(crate) balance: Option<U256>,
+    /// The nonce of the account.
+    #[serde(default)]
+    pub(crate) nonce: Option<u64>,
+    /// The code of the account.
+    #[serde(default)]
+    pub(crate) code: Option<Bytes>,
+    /// The storage slots of the account.
+    #[serde(default)]
+    pub(crate) storage: BTreeMap<B256, B256>,
+}
+
+/// The result of geth's `prestateTracer` in diff mode for a single transaction.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub(crate) struct DiffModeTrace {
+    /// The state of the accounts modified by the transaction, prior to its execution.
+    #[serde(default)]
+    pub(crate) pre: BTreeMap<Address, DiffAccount>,
+    /// The changes made by the transaction to the modified accounts.
+    #[serde(default)]
+    pub(crate) post: BTreeMap<Address, DiffAccount>,
+}
+
+/// A transaction entry of a [ReferenceTrace], either as returned by `debug_traceBlockByNumber` or
+/// as the bare result of `debug_traceTransaction`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+enum TraceEntry {
+    /// An entry of `debug_traceBlockByNumber`, wrapping the trace of the transaction.
+    Block {
+        /// The trace of the transaction.
+        result: DiffModeTrace,
+    },
+    /// The trace of the transaction.
+    Transaction(DiffModeTrace),
+}
+
+/// The reference state transition of a block, as the `prestateTracer` diff mode traces of its
+/// transactions, in order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct ReferenceTrace {
+    /// The traces of the transactions of the block, in order.
+    pub(crate) transactions: Vec<DiffModeTrace>,
+}
+
+impl ReferenceTrace {
+    /// Parses a [ReferenceTrace] from the JSON array of transaction traces.
+    pub(crate) fn from_json(json: &[u8]) -> Result<Self> {
+        let entries: Vec<TraceEntry> = serde_json::from_slice(json)?;
+        let transactions = entries
+            .into_iter()
+            .map(|entry| match entry {
+                TraceEntry::Block { result } | TraceEntry::Transaction(result) => result,
+            })
+            .collect();
+        Ok(Self { transactions })
+    }
+
+    /// Reads a [ReferenceTrace] from the file at the given path.
+    pub(crate) fn read(path: &Path) -> Result<Self> {
+        let json = std::fs::read(path)
+            .map_err(|e| anyhow!("Error reading reference trace {}: {e}", path.display()))?;
+        Self::from_json(&json)
+            .map_err(|e| anyhow!("Error deserializing reference trace {}: {e}", path.display()))
+    }
+
+    /// Folds the transaction traces into the expected state of every touched account after the
+    /// block, keyed by address. Accounts that are deleted by the block are `None`.
+    pub(crate) fn expected_state(&self) -> BTreeMap<Address, Option<ExpectedAccount>> {
+        let mut state = BTreeMap::<Address, Option<ExpectedAccount>>::new();
+        for trace in &self.transactions {
+            for (address, pre) in &trace.pre {
+                let account = state.entry(*address).or_insert_with(|| {
+                    Some(ExpectedAccount {
+                        balance: pre.balance.unwrap_or_default(),
+                        nonce: pre.nonce.unwrap_or_default(),
+                        code: pre.code.clone().unwrap_or_default(),
+                        storage: BTreeMap::new(),
+                    })
+                });
+
+                // Modified accounts that are missing from the post-state were deleted, and slots
+                // that are missing from it were cleared.
+                match (account.as_mut(), trace.post.get(address)) {
+                    (_, None) => *account = None,
+                    (Some(account), Some(post)) => {
+                        for slot in pre.storage.keys() {
+                            if !post.storage.contains_key(slot) {
+                                account.storage.insert(*slot, B256::ZERO);
+                            }
+                        }
+                    }
+                    (None, Some(_)) => {}
+                }
+            }
+
+            for (address, post) in &trace.post {
+                let account =
+                    state.entry(*address).or_default().get_or_insert_with(ExpectedAccount::default);
+                if let Some(balance) = post.balance {
+                    account.balance = balance;
+                }
+                if let Some(nonce) = post.nonce {
+                    account.nonce = nonce;
+                }
+                if let Some(code) = post.code.as_ref() {
+                    account.code = code.clone();
+                }
+                account.storage.extend(post.storage.iter().map(|(slot, value)| (*slot, *value)));
+            }
+        }
+        state
+    }
+}
+
+/// The expected state of an account after the block, with the storage slots touched by it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct ExpectedAccount {
+    /// The balance of the account.
+    pub(crate) balance: U256,
+    /// The nonce of the account.
+    pub(crate) nonce: u64,
+    /// The code of the account.
+    pub(crate) code: Bytes,
+    /// The values of the storage slots touched by the block.
+    pub(crate) storage: BTreeMap<B256, B256>,
+}
+
+/// A field of the state of an account.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum StateField {
+    /// Whether the account exists.
+    Existence,
+    /// The balance.
+    Balance,
+    /// The nonce.
+    Nonce,
+    /// The code hash.
+    CodeHash,
+    /// A storage slot.
+    Storage(B256),
+}
+
+impl fmt::Display for StateField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Existence => f.write_str("existence"),
+            Self::Balance => f.write_str("balance"),
+            Self::Nonce => f.write_str("nonce"),
+            Self::CodeHash => f.write_str("code hash"),
+            Self::Storage(slot) => write!(f, "storage slot {slot}"),
+        }
+    }
+}
+
+/// A field of an account's state that differs between the reference and the executor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct StateDivergence {
+    /// The address of the account.
+    pub(crate) address: Address,
+    /// The differing field.
+    pub(crate) field: StateField,
+    /// The value of the field in the reference.
+    pub(crate) expected: String,
+    /// The value of the field produced by the executor.
+    pub(crate) actual: String,
+}
+
+impl fmt::Display for StateDivergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {}: expected {}, got {}",
+            self.address, self.field, self.expected, self.actual
+        )
+    }
+}
+
+/// Checks every account and storage slot touched by the [ReferenceTrace] against the state of the
+/// last block executed by the [StatelessL2BlockExecutor], returning the first divergence in
+/// address order, if any.
+pub(crate) fn first_divergence<F, H>(
+    executor: &mut StatelessL2BlockExecutor<'_, F, H>,
+    reference: &ReferenceTrace,
+) -> Result<Option<StateDivergence>>
+where
+    F: TrieDBProvider,
+    H: TrieHinter,
+{
+    let block_number = executor.parent_block_header().number;
+    let trie_db = executor.trie_db_mut();
+
+    for (address, expected) in reference.expected_state() {
+        let diverge = |field, expected: &dyn fmt::Display, actual: &dyn fmt::Display| {
+            Ok(Some(StateDivergence {
+                address,
+                field,
+                expected: expected.to_string(),
+                actual: actual.to_string(),
+            }))
+        };
+
+        let actual = trie_db
+            .get_trie_account(&address, block_number)
+            .map_err(|e| anyhow!("Error reading account {address}: {e}"))?;
+        let (expected, actual) = match (expected, actual) {
+            (None, None) => continue,
+            (Some(expected), Some(actual)) => (expected, actual),
+            (expected, actual) => {
+                return diverge(StateField::Existence, &expected.is_some(), &actual.is_some())
+            }
+        };
+
+        if expected.balance != actual.balance {
+            return diverge(StateField::Balance, &expected.balance, &actual.balance);
+        }
+        if expected.nonce != actual.nonce {
+            return diverge(StateField::Nonce, &expected.nonce, &actual.nonce);
+        }
+        let code_hash = keccak256(&expected.code);
+        if code_hash != actual.code_hash {
+            return diverge(StateField::CodeHash, &code_hash, &actual.code_hash);
+        }
+
+        for (slot, value) in &expected.storage {
+            let actual = trie_db
+                .get_storage_slot(&address, (*slot).into(), block_number)
+                .map_err(|e| anyhow!("Error reading slot {slot} of {address}: {e}"))?;
+            if B256::from(actual) != *value {
+                return diverge(StateField::Storage(*slot), value, &B256::from(actual));
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Returns the number of accounts and storage slots checked against the [ReferenceTrace].
+pub(crate) fn touched_counts(reference: &ReferenceTrace) -> (usize, usize) {
+    let state = reference.expected_state();
+    let slots = state.values().flatten().map(|account| account.storage.len()).sum();
+    (state.len(), slots)
+}
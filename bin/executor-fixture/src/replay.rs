@@ -0,0 +1,190 @@
+//! Contains the `replay` subcommand, which re-executes [ExecutorFixture]s offline.
+
+use crate::fixture::ExecutorFixture;
+use alloy_consensus::Sealable;
+use anyhow::{anyhow, Result};
+use clap::Parser;
+use kona_executor::{
+    ExecutionArtifacts, ExecutorError, ExecutorResult, StatelessL2BlockExecutor, WitnessProvider,
+};
+use kona_mpt::NoopTrieHinter;
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+use tracing::{error, info};
+
+/// The `replay` subcommand.
+#[derive(Parser, Clone, Debug)]
+pub(crate) struct ReplayCommand {
+    /// The fixture files, or directories of `*.json` fixtures, to replay.
+    #[clap(required = true)]
+    pub(crate) fixtures: Vec<PathBuf>,
+}
+
+impl ReplayCommand {
+    /// Replays the fixtures, failing if any of them does not reproduce its expected block.
+    pub(crate) fn run(self) -> Result<()> {
+        let paths = collect_fixtures(&self.fixtures)?;
+
+        let mut failures = 0;
+        for path in &paths {
+            let fixture = ExecutorFixture::read(path)?;
+            let (elapsed, result) = replay(&fixture);
+            let block_number = fixture.expected_header.number;
+            match result {
+                Ok(_) => info!(
+                    target: "fixture",
+                    "{}: block #{block_number} matches in {elapsed:?}",
+                    path.display()
+                ),
+                Err(ExecutorError::BlockMismatch(diff)) => {
+                    failures += 1;
+                    error!(
+                        target: "fixture",
+                        "{}: block #{block_number} mismatch in {elapsed:?}",
+                        path.display()
+                    );
+                    for field in &diff.fields {
+                        error!(
+                            target: "fixture",
+                            "  {}: expected {}, got {}",
+                            field.field, field.expected, field.actual
+                        );
+                    }
+                }
+                Err(e) => {
+                    failures += 1;
+                    error!(
+                        target: "fixture",
+                        "{}: block #{block_number} failed in {elapsed:?}: {e}",
+                        path.display()
+                    );
+                }
+            }
+        }
+
+        if failures > 0 {
+            return Err(anyhow!("{failures} of {} fixtures failed", paths.len()));
+        }
+        Ok(())
+    }
+}
+
+/// Executes the block of the [ExecutorFixture] offline, returning the execution time and the
+/// result of validating the produced block against the expected header.
+pub(crate) fn replay(fixture: &ExecutorFixture) -> (Duration, ExecutorResult<ExecutionArtifacts>) {
+    let mut executor = StatelessL2BlockExecutor::builder(
+        &fixture.rollup_config,
+        WitnessProvider::new(&fixture.witness),
+        NoopTrieHinter,
+    )
+    .with_parent_header(fixture.parent_header.clone().seal_slow())
+    .build();
+
+    let start = Instant::now();
+    let result = executor.execute_and_validate(
+        fixture.executing_payload.clone(),
+        &fixture.expected_header,
+        None,
+    );
+    (start.elapsed(), result)
+}
+
+/// Expands the given paths into the list of fixture files, replacing each directory with the
+/// `*.json` files within it.
+fn collect_fixtures(paths: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    let mut fixtures = Vec::new();
+    for path in paths {
+        if !path.is_dir() {
+            fixtures.push(path.clone());
+            continue;
+        }
+
+        let mut entries = std::fs::read_dir(path)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .filter(|entry| entry.as_ref().map_or(true, |path| is_fixture(path)))
+            .collect::<Result<Vec<_>, _>>()?;
+        entries.sort();
+        fixtures.extend(entries);
+    }
+    Ok(fixtures)
+}
+
+/// Returns `true` if the path is a JSON file.
+fn is_fixture(path: &Path) -> bool {
+    path.is_file() && path.extension().is_some_and(|ext| ext == "json")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloy_consensus::Header;
+    use alloy_rpc_types_engine::PayloadAttributes;
+    use kona_executor::{ExecutionWitness, HeaderField};
+    use maili_genesis::RollupConfig;
+    use op_alloy_rpc_types_engine::OpPayloadAttributes;
+
+    /// Creates a fixture for an empty block on top of an empty genesis state, expecting the
+    /// header produced by the executor.
+    fn empty_block_fixture() -> ExecutorFixture {
+        let executing_payload = OpPayloadAttributes {
+            payload_attributes: PayloadAttributes { timestamp: 1, ..Default::default() },
+            gas_limit: Some(30_000_000),
+            transactions: Some(Vec::new()),
+            ..Default::default()
+        };
+        let mut fixture = ExecutorFixture {
+            rollup_config: RollupConfig::default(),
+            parent_header: Header::default(),
+            executing_payload,
+            expected_header: Header::default(),
+            expected_block_hash: Default::default(),
+            witness: ExecutionWitness::default(),
+        };
+
+        let mut executor = StatelessL2BlockExecutor::builder(
+            &fixture.rollup_config,
+            WitnessProvider::new(&fixture.witness),
+            NoopTrieHinter,
+        )
+        .with_parent_header(fixture.parent_header.clone().seal_slow())
+        .build();
+        let produced =
+            executor.execute_payload(fixture.executing_payload.clone()).unwrap().block_header;
+        fixture.expected_block_hash = produced.hash();
+        fixture.expected_header = produced.into_inner();
+        fixture
+    }
+
+    #[test]
+    fn test_replay_reports_header_diff() {
+        let mut fixture = empty_block_fixture();
+        assert_eq!(fixture.expected_header.number, 1);
+        assert_eq!(fixture.expected_header.gas_limit, 30_000_000);
+        assert!(replay(&fixture).1.is_ok());
+
+        let produced_hash = fixture.expected_block_hash;
+        fixture.expected_header.gas_used = 1;
+        let Err(ExecutorError::BlockMismatch(diff)) = replay(&fixture).1 else {
+            panic!("expected a mismatch");
+        };
+        assert_eq!(diff.expected_hash, fixture.expected_header.hash_slow());
+        assert_eq!(diff.actual_hash, produced_hash);
+        assert_eq!(diff.fields.len(), 1);
+        assert_eq!(diff.fields[0].field, HeaderField::GasUsed);
+    }
+
+    #[test]
+    fn test_collect_fixtures_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let fixture = empty_block_fixture();
+        fixture.write(&dir.path().join("b.json")).unwrap();
+        fixture.write(&dir.path().join("a.json")).unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "not a fixture").unwrap();
+
+        let paths = collect_fixtures(&[dir.path().to_path_buf()]).unwrap();
+        assert_eq!(paths, vec![dir.path().join("a.json"), dir.path().join("b.json")]);
+        assert_eq!(ExecutorFixture::read(&paths[0]).unwrap(), fixture);
+    }
+}
@@ -18,8 +18,11 @@ use kona_preimage::{
     BidirectionalChannel, Channel, HintReader, HintWriter, OracleReader, OracleServer,
 };
 use kona_proof_interop::HintType;
-use kona_providers_alloy::{OnlineBeaconClient, OnlineBlobProvider};
-use kona_std_fpvm::{FileChannel, FileDescriptor};
+use kona_providers_alloy::{BlobArchiverClient, OnlineBeaconClient, OnlineBlobProvider};
+use kona_std_fpvm::{
+    exit::{ClientExitReason, ExitReason},
+    FileChannel, FileDescriptor,
+};
 use maili_genesis::RollupConfig;
 use op_alloy_network::Optimism;
 use serde::Serialize;
@@ -28,6 +31,7 @@ use tokio::{
     sync::RwLock,
     task::{self, JoinHandle},
 };
+use tracing::error;
 
 /// The interop host application.
 #[derive(Default, Parser, Serialize, Clone, Debug)]
@@ -76,6 +80,16 @@ pub struct InteropHost {
         env
     )]
     pub l1_beacon_address: Option<String>,
+    /// Addresses of blob archiver API endpoints to fetch blob sidecars from, in order, when the
+    /// L1 Beacon API endpoint does not hold them, e.g. because they have been pruned.
+    #[clap(
+        long,
+        visible_alias = "blob-archivers",
+        requires = "l1_beacon_address",
+        value_delimiter = ',',
+        env
+    )]
+    pub l1_blob_archiver_addresses: Option<Vec<String>>,
     /// The Data Directory for preimage data storage. Optional if running in online mode,
     /// required if running in offline mode.
     #[clap(
@@ -166,8 +180,17 @@ impl InteropHost {
 
         let (_, client_result) = tokio::try_join!(server_task, client_task)?;
 
-        // Bubble up the exit status of the client program if execution completes.
-        std::process::exit(client_result.is_err() as i32)
+        // Bubble up the exit status of the client program if execution completes, along with the
+        // machine-readable record of its exit reason.
+        let reason = match client_result {
+            Ok(()) => ClientExitReason::Success,
+            Err(e) => {
+                error!(target: "host", "Client program failed: {e}");
+                e.exit_reason()
+            }
+        };
+        eprint!("{}", reason.record());
+        std::process::exit(reason.code() as i32)
     }
 
     /// Returns `true` if the host is running in offline mode.
@@ -223,10 +246,14 @@ impl InteropHost {
         let l1_provider =
             http_provider(self.l1_node_address.as_ref().ok_or(anyhow!("Provider must be set"))?);
 
-        let blob_provider = OnlineBlobProvider::init(OnlineBeaconClient::new_http(
+        let mut blob_provider = OnlineBlobProvider::init(OnlineBeaconClient::new_http(
             self.l1_beacon_address.clone().ok_or(anyhow!("Beacon API URL must be set"))?,
         ))
         .await;
+        for archiver in self.l1_blob_archiver_addresses.iter().flatten() {
+            blob_provider = blob_provider
+                .with_fallback(archiver.clone(), BlobArchiverClient::new_http(archiver.clone()));
+        }
 
         // Resolve all chain IDs to their corresponding providers.
         let l2_node_addresses =
@@ -445,8 +445,8 @@ impl HintHandler for InteropHintHandler {
                     let l1_head = cfg.l1_head;
 
                     async move {
-                        let oracle = Arc::new(CachingOracle::new(
-                            1024,
+                        let oracle = Arc::new(CachingOracle::with_byte_budget(
+                            16 << 20,
                             OracleReader::new(preimage.client),
                             HintWriter::new(hint.client),
                         ));
@@ -11,20 +11,30 @@ use alloy_primitives::B256;
 use alloy_provider::RootProvider;
 use anyhow::{anyhow, Result};
 use clap::Parser;
+use kona_executor::BlockProfiler;
+use kona_fpvm_emu::Emulator;
 use kona_preimage::{
     BidirectionalChannel, Channel, HintReader, HintWriter, OracleReader, OracleServer,
 };
 use kona_proof::HintType;
-use kona_providers_alloy::{OnlineBeaconClient, OnlineBlobProvider};
-use kona_std_fpvm::{FileChannel, FileDescriptor};
+use kona_providers_alloy::{BlobArchiverClient, OnlineBeaconClient, OnlineBlobProvider};
+use kona_std_fpvm::{
+    exit::{ClientExitReason, ExitReason},
+    FileChannel, FileDescriptor,
+};
 use maili_genesis::RollupConfig;
 use op_alloy_network::Optimism;
 use serde::Serialize;
-use std::{path::PathBuf, sync::Arc};
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 use tokio::{
     sync::RwLock,
     task::{self, JoinHandle},
 };
+use tracing::{error, info};
 
 /// The host binary CLI application arguments.
 #[derive(Default, Parser, Serialize, Clone, Debug)]
@@ -72,6 +82,16 @@ pub struct SingleChainHost {
         env
     )]
     pub l1_beacon_address: Option<String>,
+    /// Addresses of blob archiver API endpoints to fetch blob sidecars from, in order, when the
+    /// L1 Beacon API endpoint does not hold them, e.g. because they have been pruned.
+    #[clap(
+        long,
+        visible_alias = "blob-archivers",
+        requires = "l1_beacon_address",
+        value_delimiter = ',',
+        env
+    )]
+    pub l1_blob_archiver_addresses: Option<Vec<String>>,
     /// The Data Directory for preimage data storage. Optional if running in online mode,
     /// required if running in offline mode.
     #[clap(
@@ -82,12 +102,29 @@ pub struct SingleChainHost {
     )]
     pub data_dir: Option<PathBuf>,
     /// Run the client program natively.
-    #[clap(long, conflicts_with = "server", required_unless_present = "server")]
+    #[clap(
+        long,
+        conflicts_with_all = ["server", "emu"],
+        required_unless_present_any = ["server", "emu"]
+    )]
     pub native: bool,
     /// Run in pre-image server mode without executing any client program. If not provided, the
     /// host will run the client program in the host process.
-    #[clap(long, conflicts_with = "native", required_unless_present = "native")]
+    #[clap(
+        long,
+        conflicts_with_all = ["native", "emu"],
+        required_unless_present_any = ["native", "emu"]
+    )]
     pub server: bool,
+    /// Path to a client program ELF cross-compiled for an FPVM target, to run in the host process
+    /// on the userspace emulator instead of running the client program natively.
+    #[clap(
+        long,
+        conflicts_with_all = ["native", "server"],
+        required_unless_present_any = ["native", "server"],
+        env
+    )]
+    pub emu: Option<PathBuf>,
     /// The L2 chain ID of a supported chain. If provided, the host will look for the corresponding
     /// rollup config in the superchain registry.
     #[clap(
@@ -107,6 +144,10 @@ pub struct SingleChainHost {
         env
     )]
     pub rollup_config_path: Option<PathBuf>,
+    /// Path to write a JSON report of the gas used, precompile calls, state accesses and trie
+    /// fetches of each executed block to. Only supported when running the client natively.
+    #[clap(long, requires = "native", env)]
+    pub block_profile: Option<PathBuf>,
 }
 
 impl SingleChainHost {
@@ -118,6 +159,8 @@ impl SingleChainHost {
                 FileChannel::new(FileDescriptor::PreimageRead, FileDescriptor::PreimageWrite);
 
             self.start_server(hint, preimage).await?.await?
+        } else if let Some(elf) = self.emu.as_ref() {
+            self.start_emulated(elf).await
         } else {
             self.start_native().await
         }
@@ -167,17 +210,64 @@ impl SingleChainHost {
         let hint = BidirectionalChannel::new()?;
         let preimage = BidirectionalChannel::new()?;
 
+        let block_profiler = self.block_profile.is_some().then(BlockProfiler::new);
         let server_task = self.start_server(hint.host, preimage.host).await?;
         let client_task = task::spawn(kona_client::single::run(
             OracleReader::new(preimage.client),
             HintWriter::new(hint.client),
             None,
+            block_profiler.clone(),
         ));
 
         let (_, client_result) = tokio::try_join!(server_task, client_task)?;
 
-        // Bubble up the exit status of the client program if execution completes.
-        std::process::exit(client_result.is_err() as i32)
+        // Write the profiles of the executed blocks, if requested.
+        if let (Some(path), Some(profiler)) = (self.block_profile.as_ref(), block_profiler) {
+            std::fs::write(path, serde_json::to_vec_pretty(&profiler.take_profiles())?)
+                .map_err(|e| anyhow!("Failed to write block profile: {e}"))?;
+        }
+
+        // Bubble up the exit status of the client program if execution completes, along with the
+        // machine-readable record of its exit reason.
+        let reason = match client_result {
+            Ok(()) => ClientExitReason::Success,
+            Err(e) => {
+                error!(target: "host", "Client program failed: {e}");
+                e.exit_reason()
+            }
+        };
+        eprint!("{}", reason.record());
+        std::process::exit(reason.code() as i32)
+    }
+
+    /// Starts the host in emulated mode, running the given client program ELF on the userspace
+    /// emulator and the preimage server in the same process.
+    async fn start_emulated(&self, elf: &Path) -> Result<()> {
+        let hint = BidirectionalChannel::new()?;
+        let preimage = BidirectionalChannel::new()?;
+
+        let elf = std::fs::read(elf).map_err(|e| anyhow!("Failed to read client program: {e}"))?;
+        let emulator = Emulator::new(&elf, hint.client, preimage.client)?;
+        let server_task = self.start_server(hint.host, preimage.host).await?;
+        let status = tokio::select! {
+            status = emulator.run() => status?,
+            server_result = server_task => {
+                server_result??;
+                anyhow::bail!("Preimage server exited before the client program");
+            }
+        };
+
+        // Forward the output of the client program, which includes the machine-readable record
+        // of its exit reason, and bubble up its exit code.
+        std::io::stdout().write_all(&status.stdout)?;
+        std::io::stderr().write_all(&status.stderr)?;
+        info!(
+            target: "host",
+            "Client program exited with code {} after {} steps",
+            status.code,
+            status.steps
+        );
+        std::process::exit(status.code as i32)
     }
 
     /// Returns `true` if the host is running in offline mode.
@@ -226,10 +316,14 @@ impl SingleChainHost {
     async fn create_providers(&self) -> Result<SingleChainProviders> {
         let l1_provider =
             http_provider(self.l1_node_address.as_ref().ok_or(anyhow!("Provider must be set"))?);
-        let blob_provider = OnlineBlobProvider::init(OnlineBeaconClient::new_http(
+        let mut blob_provider = OnlineBlobProvider::init(OnlineBeaconClient::new_http(
             self.l1_beacon_address.clone().ok_or(anyhow!("Beacon API URL must be set"))?,
         ))
         .await;
+        for archiver in self.l1_blob_archiver_addresses.iter().flatten() {
+            blob_provider = blob_provider
+                .with_fallback(archiver.clone(), BlobArchiverClient::new_http(archiver.clone()));
+        }
         let l2_provider = http_provider::<Optimism>(
             self.l2_node_address.as_ref().ok_or(anyhow!("L2 node address must be set"))?,
         );
@@ -283,6 +377,7 @@ mod test {
             (["--server", "--rollup-config-path", "dummy", "--data-dir", "dummy"].as_slice(), true),
             (["--native", "--l2-chain-id", "0", "--data-dir", "dummy"].as_slice(), true),
             (["--native", "--rollup-config-path", "dummy", "--data-dir", "dummy"].as_slice(), true),
+            (["--emu", "kona.elf", "--l2-chain-id", "0", "--data-dir", "dummy"].as_slice(), true),
             (
                 [
                     "--l1-node-address",
@@ -300,6 +395,8 @@ mod test {
             ),
             // invalid
             (["--server", "--native", "--l2-chain-id", "0"].as_slice(), false),
+            (["--emu", "kona.elf", "--native", "--l2-chain-id", "0"].as_slice(), false),
+            (["--emu", "kona.elf", "--server", "--l2-chain-id", "0"].as_slice(), false),
             (["--l2-chain-id", "0", "--rollup-config-path", "dummy", "--server"].as_slice(), false),
             (["--server"].as_slice(), false),
             (["--native"].as_slice(), false),
@@ -21,6 +21,23 @@ pub enum BlobDecodingError {
     MissingData,
 }
 
+/// Blob Encoding Error
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum BlobEncodingError {
+    /// The data exceeds the maximum size that can be encoded into a single blob.
+    #[error("Data too large: {0} bytes")]
+    DataTooLarge(usize),
+}
+
+/// An error returned when registering a source with a
+/// [DataSourceMux](crate::sources::DataSourceMux).
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum DataSourceMuxError {
+    /// A source is already registered with the same activation timestamp and type byte.
+    #[error("Duplicate source for activation {0} and type byte {1:?}")]
+    DuplicateSource(u64, Option<u8>),
+}
+
 /// An error returned by the [BlobProviderError].
 #[derive(Error, Debug, PartialEq, Eq)]
 pub enum BlobProviderError {
@@ -9,6 +9,9 @@
 
 extern crate alloc;
 
+#[cfg(any(feature = "std", feature = "test-fixtures"))]
+extern crate std;
+
 #[macro_use]
 extern crate tracing;
 
@@ -5,3 +5,8 @@ pub use builder::PipelineBuilder;
 
 mod core;
 pub use core::DerivationPipeline;
+
+#[cfg(feature = "std")]
+mod stream;
+#[cfg(feature = "std")]
+pub use stream::{PipelineEvent, PipelineStream};
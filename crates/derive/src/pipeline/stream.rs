@@ -0,0 +1,635 @@
+//! Contains the [PipelineStream], which drives a [Pipeline] and exposes the derived attributes as
+//! a [Stream].
+
+use crate::{
+    errors::{PipelineError, PipelineErrorKind, ResetError},
+    traits::{ChainProvider, Pipeline, SignalReceiver},
+    types::{ActivationSignal, PipelineResult, ResetSignal, StepResult},
+};
+use core::time::Duration;
+use futures::{stream, Stream, StreamExt};
+use maili_protocol::{BlockInfo, L2BlockInfo};
+use maili_rpc::OpAttributesWithParent;
+use tokio::sync::{mpsc, watch};
+
+/// An event emitted by the [PipelineStream].
+#[derive(Debug, Clone, PartialEq)]
+pub enum PipelineEvent {
+    /// The pipeline derived the next [OpAttributesWithParent].
+    Attributes(OpAttributesWithParent),
+    /// The pipeline advanced its L1 origin to the given block.
+    OriginAdvanced(BlockInfo),
+    /// The pipeline was reset to the L2 safe head, due to the given [ResetError].
+    ///
+    /// On a [ResetError::ReorgDetected], the pipeline is reset onto the L1 origin of the L2 safe
+    /// head on the canonical L1 chain.
+    Reset {
+        /// The error that caused the reset.
+        error: ResetError,
+        /// The L2 safe head the pipeline was reset to.
+        l2_safe_head: L2BlockInfo,
+        /// The L1 origin the pipeline was reset to.
+        l1_origin: BlockInfo,
+    },
+    /// The pipeline was signalled of the Holocene activation.
+    Activation {
+        /// The L2 safe head at the activation.
+        l2_safe_head: L2BlockInfo,
+        /// The L1 origin at the activation.
+        l1_origin: BlockInfo,
+    },
+}
+
+/// Drives a [Pipeline], handling its resets and activation signals, and emits the derived
+/// attributes and the pipeline's state changes as [PipelineEvent]s.
+///
+/// The L2 safe head is read from a [watch::Receiver] that the caller updates as it processes the
+/// derived attributes. After emitting a [PipelineEvent::Attributes], the stream waits for the next
+/// update of the safe head before deriving further, so the caller must send the new safe head for
+/// every emitted [OpAttributesWithParent], whether or not the block was accepted.
+///
+/// When the pipeline's L1 origin cannot be advanced, the stream waits for the L1 chain to progress.
+/// By default, it polls the L1 chain every idle interval. With [PipelineStream::with_l1_heads], it
+/// is instead woken by new L1 heads, e.g. from a subscription over WebSocket or IPC, and resets the
+/// pipeline when a new head shows that the L1 origin has been reorged out.
+///
+/// When the pipeline reports an L1 reorg, its L1 origin is no longer canonical, so the pipeline is
+/// reset onto the L1 origin of the L2 safe head, looked up through the given L1 [ChainProvider].
+/// The provider is reset first, through [ChainProvider::reset], so that it does not serve blocks
+/// from the reorged chain.
+///
+/// The stream ends after a critical error, if the L1 origin of the L2 safe head has itself been
+/// reorged out, or when the sender of the safe head is dropped.
+#[derive(Debug)]
+pub struct PipelineStream<P, C>
+where
+    P: Pipeline + SignalReceiver + Send,
+    C: ChainProvider + Send,
+{
+    /// The derivation pipeline.
+    pipeline: P,
+    /// The L1 chain provider, used to look up the canonical L1 origin on reorgs.
+    l1_provider: C,
+    /// The receiver of the L2 safe head.
+    safe_head: watch::Receiver<L2BlockInfo>,
+    /// The interval to wait before retrying when the pipeline's L1 origin cannot be advanced.
+    idle_interval: Duration,
+    /// The receiver of new L1 heads, if the stream is woken by new L1 heads rather than polling.
+    l1_heads: Option<mpsc::Receiver<BlockInfo>>,
+    /// Whether the stream is waiting for the safe head to be updated.
+    awaiting_safe_head: bool,
+    /// Whether the stream has ended.
+    done: bool,
+}
+
+impl<P, C> PipelineStream<P, C>
+where
+    P: Pipeline + SignalReceiver + Send,
+    C: ChainProvider + Send,
+{
+    /// The default interval to wait before retrying when the pipeline's L1 origin cannot be
+    /// advanced.
+    pub const DEFAULT_IDLE_INTERVAL: Duration = Duration::from_secs(1);
+
+    /// Creates a new [PipelineStream] over the given pipeline and its L1 chain provider, reading
+    /// the L2 safe head from the given [watch::Receiver].
+    pub const fn new(pipeline: P, l1_provider: C, safe_head: watch::Receiver<L2BlockInfo>) -> Self {
+        Self {
+            pipeline,
+            l1_provider,
+            safe_head,
+            idle_interval: Self::DEFAULT_IDLE_INTERVAL,
+            l1_heads: None,
+            awaiting_safe_head: false,
+            done: false,
+        }
+    }
+
+    /// Sets the interval to wait before retrying when the pipeline's L1 origin cannot be
+    /// advanced, e.g. because the L1 chain has not progressed yet.
+    pub const fn with_idle_interval(mut self, idle_interval: Duration) -> Self {
+        self.idle_interval = idle_interval;
+        self
+    }
+
+    /// Wakes the stream on the new L1 heads received from the given [mpsc::Receiver], rather than
+    /// polling the L1 chain every idle interval. The heads are expected in chain order, without
+    /// gaps.
+    ///
+    /// Whenever the stream is woken, the latest received head is checked against the pipeline's
+    /// L1 origin, and the pipeline is reset with a [ResetError::ReorgDetected] if the head
+    /// conflicts with the origin. If the sender is dropped, the stream falls back to polling.
+    pub fn with_l1_heads(mut self, l1_heads: mpsc::Receiver<BlockInfo>) -> Self {
+        self.l1_heads = Some(l1_heads);
+        self
+    }
+
+    /// Returns a reference to the inner pipeline.
+    pub const fn pipeline(&self) -> &P {
+        &self.pipeline
+    }
+
+    /// Consumes the [PipelineStream], returning the inner pipeline.
+    pub fn into_inner(self) -> P {
+        self.pipeline
+    }
+
+    /// Steps the pipeline until it emits the next [PipelineEvent].
+    ///
+    /// Returns `None` once the stream has ended.
+    pub async fn next_event(&mut self) -> Option<PipelineResult<PipelineEvent>> {
+        if self.done {
+            return None;
+        }
+
+        if self.awaiting_safe_head {
+            if self.safe_head.changed().await.is_err() {
+                self.done = true;
+                return None;
+            }
+            self.awaiting_safe_head = false;
+        }
+
+        loop {
+            let l2_safe_head = *self.safe_head.borrow_and_update();
+            match self.pipeline.step(l2_safe_head).await {
+                StepResult::PreparedAttributes => {
+                    if let Some(attributes) = self.pipeline.next() {
+                        self.awaiting_safe_head = true;
+                        return Some(Ok(PipelineEvent::Attributes(attributes)));
+                    }
+                }
+                StepResult::AdvancedOrigin => {
+                    if let Some(origin) = self.pipeline.origin() {
+                        return Some(Ok(PipelineEvent::OriginAdvanced(origin)));
+                    }
+                }
+                StepResult::OriginAdvanceErr(PipelineErrorKind::Temporary(e)) => {
+                    // The L1 origin cannot be advanced yet. Wait for the L1 chain to progress
+                    // before retrying, rather than polling it in a busy loop.
+                    trace!(target: "pipeline_stream", "Failed to advance origin temporarily: {:?}", e);
+                    if let Some(error) = self.wait_for_l1().await {
+                        warn!(target: "pipeline_stream", "L1 origin reorged, resetting pipeline: {:?}", error);
+                        let event = self.reset(l2_safe_head, error).await;
+                        self.done = event.is_err();
+                        return Some(event);
+                    }
+                }
+                StepResult::OriginAdvanceErr(e) | StepResult::StepFailed(e) => match e {
+                    PipelineErrorKind::Temporary(_) => {
+                        trace!(target: "pipeline_stream", "Failed to step pipeline temporarily: {:?}", e);
+                    }
+                    PipelineErrorKind::Reset(error) => {
+                        warn!(target: "pipeline_stream", "Resetting pipeline: {:?}", error);
+                        let event = self.reset(l2_safe_head, error).await;
+                        self.done = event.is_err();
+                        return Some(event);
+                    }
+                    PipelineErrorKind::Critical(_) => {
+                        warn!(target: "pipeline_stream", "Failed to step pipeline: {:?}", e);
+                        self.done = true;
+                        return Some(Err(e));
+                    }
+                },
+            }
+        }
+    }
+
+    /// Converts the [PipelineStream] into a [Stream] of [PipelineEvent]s.
+    pub fn into_stream(self) -> impl Stream<Item = PipelineResult<PipelineEvent>> + Send {
+        stream::unfold(self, |mut this| async move {
+            this.next_event().await.map(|event| (event, this))
+        })
+    }
+
+    /// Converts the [PipelineStream] into a [Stream] of the derived [OpAttributesWithParent],
+    /// handling all other [PipelineEvent]s internally.
+    pub fn into_attributes_stream(
+        self,
+    ) -> impl Stream<Item = PipelineResult<OpAttributesWithParent>> + Send {
+        self.into_stream().filter_map(|event| async move {
+            match event {
+                Ok(PipelineEvent::Attributes(attributes)) => Some(Ok(attributes)),
+                Ok(_) => None,
+                Err(e) => Some(Err(e)),
+            }
+        })
+    }
+
+    /// Waits for the L1 chain to progress.
+    ///
+    /// Without an L1 head receiver, this waits for the idle interval. Otherwise, this waits for
+    /// the next L1 head, and returns a [ResetError::ReorgDetected] if the latest received head
+    /// conflicts with the pipeline's L1 origin.
+    async fn wait_for_l1(&mut self) -> Option<ResetError> {
+        let origin = self.pipeline.origin();
+        let Some(l1_heads) = self.l1_heads.as_mut() else {
+            tokio::time::sleep(self.idle_interval).await;
+            return None;
+        };
+        let Some(mut head) = l1_heads.recv().await else {
+            // The L1 heads sender was dropped, fall back to polling.
+            self.l1_heads = None;
+            return None;
+        };
+
+        // Only the latest head is checked, as the heads queued while the pipeline was catching up
+        // may be behind the origin, or from a chain that has since been reorged out. A reorg below
+        // the latest head is detected by the traversal stage when it advances its origin.
+        while let Ok(next) = l1_heads.try_recv() {
+            head = next;
+        }
+        origin.and_then(|origin| check_l1_head(&origin, &head))
+    }
+
+    /// Resets the pipeline to the L2 safe head, or signals the Holocene activation, depending on
+    /// the [ResetError].
+    async fn reset(
+        &mut self,
+        l2_safe_head: L2BlockInfo,
+        error: ResetError,
+    ) -> PipelineResult<PipelineEvent> {
+        let system_config =
+            self.pipeline.system_config_by_number(l2_safe_head.block_info.number).await?;
+        let l1_origin = if matches!(error, ResetError::ReorgDetected(..)) {
+            self.canonical_safe_head_origin(&l2_safe_head).await?
+        } else {
+            self.pipeline.origin().ok_or(PipelineError::MissingOrigin.crit())?
+        };
+
+        if matches!(error, ResetError::HoloceneActivation) {
+            self.pipeline
+                .signal(
+                    ActivationSignal {
+                        l2_safe_head,
+                        l1_origin,
+                        system_config: Some(system_config),
+                    }
+                    .signal(),
+                )
+                .await?;
+            Ok(PipelineEvent::Activation { l2_safe_head, l1_origin })
+        } else {
+            self.pipeline
+                .signal(
+                    ResetSignal { l2_safe_head, l1_origin, system_config: Some(system_config) }
+                        .signal(),
+                )
+                .await?;
+            Ok(PipelineEvent::Reset { error, l2_safe_head, l1_origin })
+        }
+    }
+
+    /// Returns the L1 origin of the given L2 safe head, looked up on the canonical L1 chain.
+    ///
+    /// Returns a [ResetError::ReorgDetected] if the L1 origin of the safe head has been reorged
+    /// out, in which case the caller must roll back the safe head.
+    async fn canonical_safe_head_origin(
+        &mut self,
+        l2_safe_head: &L2BlockInfo,
+    ) -> PipelineResult<BlockInfo> {
+        self.l1_provider.reset();
+        let l1_origin = self
+            .l1_provider
+            .block_info_by_number(l2_safe_head.l1_origin.number)
+            .await
+            .map_err(Into::into)?;
+        if l1_origin.hash != l2_safe_head.l1_origin.hash {
+            return Err(
+                ResetError::ReorgDetected(l2_safe_head.l1_origin.hash, l1_origin.hash).reset()
+            );
+        }
+        Ok(l1_origin)
+    }
+}
+
+/// Returns a [ResetError::ReorgDetected] if the given L1 head conflicts with the L1 origin, i.e.
+/// if it replaces the origin, or if it is the next block but does not build on the origin.
+fn check_l1_head(origin: &BlockInfo, head: &BlockInfo) -> Option<ResetError> {
+    if head.number == origin.number && head.hash != origin.hash {
+        return Some(ResetError::ReorgDetected(origin.hash, head.hash));
+    }
+    if head.number == origin.number + 1 && head.parent_hash != origin.hash {
+        return Some(ResetError::ReorgDetected(origin.hash, head.parent_hash));
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_utils::TestChainProvider, traits::OriginProvider, types::Signal};
+    use alloc::{boxed::Box, collections::VecDeque, vec, vec::Vec};
+    use alloy_primitives::B256;
+    use async_trait::async_trait;
+    use maili_genesis::{RollupConfig, SystemConfig};
+
+    /// A [Pipeline] returning scripted [StepResult]s.
+    #[derive(Debug, Default)]
+    struct ScriptedPipeline {
+        steps: VecDeque<StepResult>,
+        prepared: VecDeque<OpAttributesWithParent>,
+        cursors: Vec<L2BlockInfo>,
+        signals: Vec<Signal>,
+        origin: BlockInfo,
+        rollup_config: RollupConfig,
+    }
+
+    impl ScriptedPipeline {
+        fn new(steps: impl IntoIterator<Item = StepResult>) -> Self {
+            Self { steps: steps.into_iter().collect(), ..Default::default() }
+        }
+    }
+
+    impl OriginProvider for ScriptedPipeline {
+        fn origin(&self) -> Option<BlockInfo> {
+            Some(self.origin)
+        }
+    }
+
+    impl Iterator for ScriptedPipeline {
+        type Item = OpAttributesWithParent;
+
+        fn next(&mut self) -> Option<Self::Item> {
+            self.prepared.pop_front()
+        }
+    }
+
+    #[async_trait]
+    impl SignalReceiver for ScriptedPipeline {
+        async fn signal(&mut self, signal: Signal) -> PipelineResult<()> {
+            self.signals.push(signal);
+            Ok(())
+        }
+    }
+
+    #[async_trait]
+    impl Pipeline for ScriptedPipeline {
+        fn peek(&self) -> Option<&OpAttributesWithParent> {
+            self.prepared.front()
+        }
+
+        async fn step(&mut self, cursor: L2BlockInfo) -> StepResult {
+            self.cursors.push(cursor);
+            let step = self
+                .steps
+                .pop_front()
+                .unwrap_or(StepResult::OriginAdvanceErr(PipelineError::Eof.temp()));
+            match step {
+                StepResult::PreparedAttributes => {
+                    self.prepared.push_back(OpAttributesWithParent {
+                        attributes: Default::default(),
+                        parent: cursor,
+                        is_last_in_span: false,
+                    });
+                }
+                StepResult::AdvancedOrigin => self.origin.number += 1,
+                _ => {}
+            }
+            step
+        }
+
+        fn rollup_config(&self) -> &RollupConfig {
+            &self.rollup_config
+        }
+
+        async fn system_config_by_number(
+            &mut self,
+            _: u64,
+        ) -> Result<SystemConfig, PipelineErrorKind> {
+            Ok(SystemConfig::default())
+        }
+    }
+
+    /// Returns the canonical L1 origin of the [safe_head]s.
+    fn canonical_origin() -> BlockInfo {
+        BlockInfo { timestamp: 12, ..Default::default() }
+    }
+
+    /// Returns an L1 provider serving the canonical L1 origin of the [safe_head]s.
+    fn l1_provider() -> TestChainProvider {
+        let mut provider = TestChainProvider::default();
+        provider.insert_block(0, canonical_origin());
+        provider
+    }
+
+    fn safe_head(number: u64) -> L2BlockInfo {
+        let mut head = L2BlockInfo::default();
+        head.block_info.number = number;
+        head
+    }
+
+    #[tokio::test]
+    async fn test_stream_waits_for_safe_head() {
+        let pipeline = ScriptedPipeline::new([
+            StepResult::AdvancedOrigin,
+            StepResult::StepFailed(PipelineError::NotEnoughData.temp()),
+            StepResult::PreparedAttributes,
+            StepResult::PreparedAttributes,
+        ]);
+        let (sender, receiver) = watch::channel(safe_head(0));
+        let mut stream = PipelineStream::new(pipeline, l1_provider(), receiver);
+
+        assert_eq!(
+            stream.next_event().await,
+            Some(Ok(PipelineEvent::OriginAdvanced(BlockInfo { number: 1, ..Default::default() })))
+        );
+        let Some(Ok(PipelineEvent::Attributes(attributes))) = stream.next_event().await else {
+            panic!("expected attributes");
+        };
+        assert_eq!(attributes.parent, safe_head(0));
+
+        // The stream does not derive further until the safe head is updated.
+        let pending = tokio::time::timeout(Duration::from_millis(50), stream.next_event()).await;
+        assert!(pending.is_err());
+
+        sender.send(safe_head(1)).unwrap();
+        let Some(Ok(PipelineEvent::Attributes(attributes))) = stream.next_event().await else {
+            panic!("expected attributes");
+        };
+        assert_eq!(attributes.parent, safe_head(1));
+
+        drop(sender);
+        assert_eq!(stream.next_event().await, None);
+        assert_eq!(stream.pipeline().cursors.len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_stream_resets_and_activates() {
+        let reorg = ResetError::ReorgDetected(B256::ZERO, B256::with_last_byte(1));
+        let pipeline = ScriptedPipeline::new([
+            StepResult::StepFailed(reorg.clone().reset()),
+            StepResult::OriginAdvanceErr(ResetError::HoloceneActivation.reset()),
+            StepResult::StepFailed(PipelineError::MissingOrigin.crit()),
+        ]);
+        let (_sender, receiver) = watch::channel(safe_head(5));
+        let events = PipelineStream::new(pipeline, l1_provider(), receiver)
+            .into_stream()
+            .collect::<Vec<_>>()
+            .await;
+
+        assert_eq!(
+            events,
+            vec![
+                Ok(PipelineEvent::Reset {
+                    error: reorg,
+                    l2_safe_head: safe_head(5),
+                    l1_origin: canonical_origin(),
+                }),
+                Ok(PipelineEvent::Activation {
+                    l2_safe_head: safe_head(5),
+                    l1_origin: BlockInfo::default(),
+                }),
+                Err(PipelineError::MissingOrigin.crit()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_attributes_stream_backs_off_on_eof() {
+        let pipeline = ScriptedPipeline::new([
+            StepResult::OriginAdvanceErr(PipelineError::Eof.temp()),
+            StepResult::AdvancedOrigin,
+            StepResult::PreparedAttributes,
+        ]);
+        let (sender, receiver) = watch::channel(safe_head(0));
+        let stream = PipelineStream::new(pipeline, l1_provider(), receiver)
+            .with_idle_interval(Duration::from_millis(10))
+            .into_attributes_stream();
+        futures::pin_mut!(stream);
+
+        let attributes = stream.next().await.unwrap().unwrap();
+        assert_eq!(attributes.parent, safe_head(0));
+        drop(sender);
+        assert!(stream.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_stream_wakes_on_l1_heads() {
+        let pipeline = ScriptedPipeline::new([
+            StepResult::OriginAdvanceErr(PipelineError::Eof.temp()),
+            StepResult::AdvancedOrigin,
+        ]);
+        let (_sender, receiver) = watch::channel(safe_head(0));
+        let (heads, l1_heads) = mpsc::channel(8);
+        let mut stream = PipelineStream::new(pipeline, l1_provider(), receiver)
+            .with_idle_interval(Duration::from_secs(3600))
+            .with_l1_heads(l1_heads);
+
+        heads.send(BlockInfo { number: 1, ..Default::default() }).await.unwrap();
+        let event = tokio::time::timeout(Duration::from_secs(5), stream.next_event()).await;
+        assert_eq!(
+            event.unwrap(),
+            Some(Ok(PipelineEvent::OriginAdvanced(BlockInfo { number: 1, ..Default::default() })))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stream_resets_on_conflicting_l1_head() {
+        let mut pipeline =
+            ScriptedPipeline::new([StepResult::OriginAdvanceErr(PipelineError::Eof.temp())]);
+        let origin = BlockInfo { hash: B256::with_last_byte(5), number: 5, ..Default::default() };
+        pipeline.origin = origin;
+        let (_sender, receiver) = watch::channel(safe_head(3));
+        let (heads, l1_heads) = mpsc::channel(8);
+        let mut stream =
+            PipelineStream::new(pipeline, l1_provider(), receiver).with_l1_heads(l1_heads);
+
+        // The new head does not build on the origin, so the origin has been reorged out, and the
+        // pipeline is reset onto the canonical L1 origin of the safe head.
+        let parent_hash = B256::with_last_byte(0xFF);
+        heads.send(BlockInfo { number: 6, parent_hash, ..Default::default() }).await.unwrap();
+        assert_eq!(
+            stream.next_event().await,
+            Some(Ok(PipelineEvent::Reset {
+                error: ResetError::ReorgDetected(origin.hash, parent_hash),
+                l2_safe_head: safe_head(3),
+                l1_origin: canonical_origin(),
+            }))
+        );
+        let [Signal::Reset(signal)] = &stream.pipeline().signals[..] else {
+            panic!("expected a reset signal");
+        };
+        assert_eq!(signal.l1_origin, canonical_origin());
+    }
+
+    #[tokio::test]
+    async fn test_stream_ends_on_reorged_safe_head_origin() {
+        let reorg = ResetError::ReorgDetected(B256::ZERO, B256::with_last_byte(1));
+        let pipeline = ScriptedPipeline::new([StepResult::StepFailed(reorg.reset())]);
+        let mut head = safe_head(3);
+        head.l1_origin.hash = B256::with_last_byte(9);
+        let (_sender, receiver) = watch::channel(head);
+        let mut stream = PipelineStream::new(pipeline, l1_provider(), receiver);
+
+        let expected = ResetError::ReorgDetected(head.l1_origin.hash, B256::ZERO).reset();
+        assert_eq!(stream.next_event().await, Some(Err(expected)));
+        assert_eq!(stream.next_event().await, None);
+        assert!(stream.pipeline().signals.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_stream_ignores_stale_l1_heads() {
+        let mut pipeline = ScriptedPipeline::new([
+            StepResult::OriginAdvanceErr(PipelineError::Eof.temp()),
+            StepResult::AdvancedOrigin,
+        ]);
+        let origin = BlockInfo { hash: B256::with_last_byte(5), number: 5, ..Default::default() };
+        pipeline.origin = origin;
+        let (_sender, receiver) = watch::channel(safe_head(3));
+        let (heads, l1_heads) = mpsc::channel(8);
+        let mut stream =
+            PipelineStream::new(pipeline, l1_provider(), receiver).with_l1_heads(l1_heads);
+
+        // Heads queued while catching up, including a block 5 that was reorged out, are skipped.
+        let stale = BlockInfo { hash: B256::with_last_byte(0xAA), number: 5, ..Default::default() };
+        heads.send(BlockInfo { number: 3, ..Default::default() }).await.unwrap();
+        heads.send(stale).await.unwrap();
+        heads.send(origin).await.unwrap();
+        assert_eq!(
+            stream.next_event().await,
+            Some(Ok(PipelineEvent::OriginAdvanced(BlockInfo { number: 6, ..origin })))
+        );
+        assert!(stream.pipeline().signals.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_stream_polls_without_l1_heads_sender() {
+        let pipeline = ScriptedPipeline::new([
+            StepResult::OriginAdvanceErr(PipelineError::Eof.temp()),
+            StepResult::AdvancedOrigin,
+        ]);
+        let (_sender, receiver) = watch::channel(safe_head(0));
+        let (heads, l1_heads) = mpsc::channel(8);
+        let mut stream = PipelineStream::new(pipeline, l1_provider(), receiver)
+            .with_idle_interval(Duration::from_millis(10))
+            .with_l1_heads(l1_heads);
+        drop(heads);
+
+        assert_eq!(
+            stream.next_event().await,
+            Some(Ok(PipelineEvent::OriginAdvanced(BlockInfo { number: 1, ..Default::default() })))
+        );
+        assert!(stream.l1_heads.is_none());
+    }
+
+    #[test]
+    fn test_check_l1_head() {
+        let origin = BlockInfo { hash: B256::with_last_byte(1), number: 5, ..Default::default() };
+        let next = BlockInfo { number: 6, parent_hash: origin.hash, ..Default::default() };
+        assert_eq!(check_l1_head(&origin, &origin), None);
+        assert_eq!(check_l1_head(&origin, &next), None);
+        assert_eq!(check_l1_head(&origin, &BlockInfo { number: 8, ..Default::default() }), None);
+
+        let replaced = BlockInfo { hash: B256::with_last_byte(2), number: 5, ..Default::default() };
+        assert_eq!(
+            check_l1_head(&origin, &replaced),
+            Some(ResetError::ReorgDetected(origin.hash, replaced.hash))
+        );
+        let orphaned = BlockInfo { number: 6, ..Default::default() };
+        assert_eq!(
+            check_l1_head(&origin, &orphaned),
+            Some(ResetError::ReorgDetected(origin.hash, B256::ZERO))
+        );
+    }
+}
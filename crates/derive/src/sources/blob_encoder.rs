@@ -0,0 +1,183 @@
+//! Contains the blob encoder, the counterpart to `BlobData::decode`.
+
+use crate::{
+    errors::BlobEncodingError,
+    sources::blob_data::{BLOB_ENCODING_ROUNDS, BLOB_ENCODING_VERSION, BLOB_MAX_DATA_SIZE},
+};
+use alloy_eips::eip4844::Blob;
+
+/// Encodes the given data into a [Blob], using version 0 of the OP blob encoding.
+///
+/// The data is packed into rounds of 4 field elements, where each round holds 127 bytes of data.
+/// The low 31 bytes of each field element hold data, and the remaining byte of each round is split
+/// into 6 bit chunks that are placed in the high order byte of each of the 4 field elements. The
+/// first field element additionally holds the encoding version and the 3 byte big endian length of
+/// the data. Field elements past the last round are left zeroed.
+///
+/// Returns a [BlobEncodingError] if the data is larger than [BLOB_MAX_DATA_SIZE].
+pub fn encode_blob_data(data: &[u8]) -> Result<Blob, BlobEncodingError> {
+    if data.len() > BLOB_MAX_DATA_SIZE {
+        return Err(BlobEncodingError::DataTooLarge(data.len()));
+    }
+
+    let mut blob = Blob::ZERO;
+    let mut read_pos = 0;
+    let mut round = 0;
+    while round < BLOB_ENCODING_ROUNDS && read_pos < data.len() {
+        // The first field element of round 0 holds the version and length in place of 4 bytes of
+        // data.
+        let body = if round == 0 {
+            let mut body = [0u8; 31];
+            body[0] = BLOB_ENCODING_VERSION;
+            body[1..4].copy_from_slice(&(data.len() as u32).to_be_bytes()[1..]);
+            let n = data.len().min(27);
+            body[4..4 + n].copy_from_slice(&data[..n]);
+            read_pos = n;
+            body
+        } else {
+            read_padded(data, &mut read_pos)
+        };
+        let [x] = read_padded(data, &mut read_pos);
+        write_field_element(&mut blob, round * 4, x & 0b0011_1111, &body);
+
+        let body = read_padded(data, &mut read_pos);
+        let [y] = read_padded(data, &mut read_pos);
+        let high = (y & 0b0000_1111) | ((x & 0b1100_0000) >> 2);
+        write_field_element(&mut blob, round * 4 + 1, high, &body);
+
+        let body = read_padded(data, &mut read_pos);
+        let [z] = read_padded(data, &mut read_pos);
+        write_field_element(&mut blob, round * 4 + 2, z & 0b0011_1111, &body);
+
+        let body = read_padded(data, &mut read_pos);
+        let high = ((z & 0b1100_0000) >> 2) | ((y & 0b1111_0000) >> 4);
+        write_field_element(&mut blob, round * 4 + 3, high, &body);
+
+        round += 1;
+    }
+
+    Ok(blob)
+}
+
+/// Reads the next `N` bytes of `data` starting at `pos`, zero padding any bytes past the end of the
+/// data, and advances `pos` by the number of bytes read.
+fn read_padded<const N: usize>(data: &[u8], pos: &mut usize) -> [u8; N] {
+    let mut buf = [0u8; N];
+    let start = (*pos).min(data.len());
+    let end = (start + N).min(data.len());
+    buf[..end - start].copy_from_slice(&data[start..end]);
+    *pos = end;
+    buf
+}
+
+/// Writes the field element at `index` of the blob from its high order byte and 31 byte body.
+fn write_field_element(blob: &mut Blob, index: usize, high: u8, body: &[u8; 31]) {
+    let offset = index << 5;
+    blob[offset] = high;
+    blob[offset + 1..offset + 32].copy_from_slice(body);
+}
+
+/// Computes the KZG commitment to the given [Blob], using the Ethereum trusted setup.
+#[cfg(feature = "kzg")]
+pub fn blob_kzg_commitment(blob: &Blob) -> Result<alloy_eips::eip4844::Bytes48, c_kzg::Error> {
+    let settings = alloy_eips::eip4844::env_settings::EnvKzgSettings::Default;
+    let blob = c_kzg::Blob::from_bytes(blob.as_slice())?;
+    let commitment = c_kzg::KzgCommitment::blob_to_kzg_commitment(&blob, settings.get())?;
+    Ok(commitment.to_bytes().into_inner().into())
+}
+
+/// Computes the KZG proof for the given [Blob] and its commitment, using the Ethereum trusted
+/// setup.
+#[cfg(feature = "kzg")]
+pub fn blob_kzg_proof(
+    blob: &Blob,
+    commitment: &alloy_eips::eip4844::Bytes48,
+) -> Result<alloy_eips::eip4844::Bytes48, c_kzg::Error> {
+    let settings = alloy_eips::eip4844::env_settings::EnvKzgSettings::Default;
+    let blob = c_kzg::Blob::from_bytes(blob.as_slice())?;
+    let commitment = c_kzg::Bytes48::from_bytes(commitment.as_slice())?;
+    let proof = c_kzg::KzgProof::compute_blob_kzg_proof(&blob, &commitment, settings.get())?;
+    Ok(proof.to_bytes().into_inner().into())
+}
+
+/// Computes the versioned hash of the given [Blob], as referenced by blob transactions.
+#[cfg(feature = "kzg")]
+pub fn blob_versioned_hash(blob: &Blob) -> Result<alloy_primitives::B256, c_kzg::Error> {
+    let commitment = blob_kzg_commitment(blob)?;
+    Ok(alloy_eips::eip4844::kzg_to_versioned_hash(commitment.as_slice()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{errors::BlobDecodingError, sources::BlobData};
+    use alloy_eips::eip4844::FIELD_ELEMENTS_PER_BLOB;
+    use alloy_primitives::Bytes;
+
+    fn decode(blob: Blob) -> Result<Bytes, BlobDecodingError> {
+        BlobData { data: Some(Bytes::from(blob)), ..Default::default() }.decode()
+    }
+
+    #[test]
+    fn test_encode_empty() {
+        let blob = encode_blob_data(&[]).unwrap();
+        assert_eq!(blob, Blob::ZERO);
+        assert_eq!(decode(blob), Ok(Bytes::new()));
+    }
+
+    #[test]
+    fn test_encode_header() {
+        let blob = encode_blob_data(&[0xFF; 300]).unwrap();
+        assert_eq!(blob[1], BLOB_ENCODING_VERSION);
+        assert_eq!(&blob[2..5], &[0x00, 0x01, 0x2C]);
+    }
+
+    #[test]
+    fn test_encode_max_size() {
+        let data = vec![0xFF; BLOB_MAX_DATA_SIZE];
+        let blob = encode_blob_data(&data).unwrap();
+        assert_eq!(decode(blob).unwrap(), Bytes::from(data));
+    }
+
+    #[test]
+    fn test_encode_too_large() {
+        let data = vec![0u8; BLOB_MAX_DATA_SIZE + 1];
+        assert_eq!(
+            encode_blob_data(&data),
+            Err(BlobEncodingError::DataTooLarge(BLOB_MAX_DATA_SIZE + 1))
+        );
+    }
+
+    #[cfg(feature = "kzg")]
+    #[test]
+    fn test_blob_versioned_hash() {
+        let blob = encode_blob_data(b"kona").unwrap();
+        let commitment = blob_kzg_commitment(&blob).unwrap();
+        let hash = blob_versioned_hash(&blob).unwrap();
+        assert_eq!(hash[0], alloy_eips::eip4844::VERSIONED_HASH_VERSION_KZG);
+        assert_eq!(hash, alloy_eips::eip4844::kzg_to_versioned_hash(commitment.as_slice()));
+        assert!(blob_kzg_proof(&blob, &commitment).is_ok());
+    }
+
+    proptest::proptest! {
+        /// Encoding and then decoding arbitrary data yields the original data.
+        #[test]
+        fn roundtrip_encode_decode(data in proptest::collection::vec(proptest::prelude::any::<u8>(), 0..=BLOB_MAX_DATA_SIZE)) {
+            let blob = encode_blob_data(&data).unwrap();
+            proptest::prop_assert_eq!(decode(blob).unwrap(), Bytes::from(data));
+        }
+
+        /// Setting either of the two high order bits of any field element after the first makes the
+        /// blob non-canonical, and it is rejected by the decoder.
+        #[test]
+        fn reject_non_canonical_field_element(
+            data in proptest::collection::vec(proptest::prelude::any::<u8>(), 0..=BLOB_MAX_DATA_SIZE),
+            index in 1..FIELD_ELEMENTS_PER_BLOB as usize,
+            bits in 1u8..=3,
+        ) {
+            let mut blob = encode_blob_data(&data).unwrap();
+            blob[index << 5] |= bits << 6;
+            proptest::prop_assert_eq!(decode(blob), Err(BlobDecodingError::InvalidFieldElement));
+        }
+    }
+}
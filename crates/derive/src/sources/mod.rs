@@ -8,7 +8,12 @@
 //! [BlockInfo]: maili_protocol::BlockInfo
 
 mod blob_data;
-pub use blob_data::BlobData;
+pub use blob_data::{BlobData, BLOB_MAX_DATA_SIZE};
+
+mod blob_encoder;
+pub use blob_encoder::encode_blob_data;
+#[cfg(feature = "kzg")]
+pub use blob_encoder::{blob_kzg_commitment, blob_kzg_proof, blob_versioned_hash};
 
 mod ethereum;
 pub use ethereum::EthereumDataSource;
@@ -18,3 +23,11 @@ pub use blobs::BlobSource;
 
 mod calldata;
 pub use calldata::CalldataSource;
+
+mod mux;
+pub use mux::{BoxedDataSource, DataSourceMux, MuxMode, SourceSelector};
+
+#[cfg(feature = "std")]
+mod prefetch;
+#[cfg(feature = "std")]
+pub use prefetch::{L1Prefetcher, PrefetchConfig};
@@ -0,0 +1,408 @@
+//! Contains the [DataSourceMux], a [DataAvailabilityProvider] that multiplexes over any number of
+//! registered data sources.
+
+use crate::{
+    errors::{DataSourceMuxError, PipelineError, PipelineErrorKind},
+    sources::{BlobSource, CalldataSource},
+    traits::{BlobProvider, ChainProvider, DataAvailabilityProvider},
+    types::PipelineResult,
+};
+use alloc::{boxed::Box, vec::Vec};
+use alloy_primitives::{Address, Bytes};
+use async_trait::async_trait;
+use core::fmt::Debug;
+use maili_genesis::RollupConfig;
+use maili_protocol::BlockInfo;
+use tracing::warn;
+
+/// A boxed [DataAvailabilityProvider] that can be registered with a [DataSourceMux].
+pub type BoxedDataSource = Box<dyn DataAvailabilityProvider<Item = Bytes> + Send + Sync>;
+
+/// Selects the L1 blocks and data that a source registered with a [DataSourceMux] serves.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SourceSelector {
+    /// The L1 timestamp at which the source activates. The source serves L1 blocks with a
+    /// timestamp at or after the activation timestamp.
+    pub activation: u64,
+    /// The type byte of the data served by the source. If set, the data with the leading type
+    /// byte is routed to the source. If not, the source serves the data whose type byte is not
+    /// routed to another source.
+    pub type_byte: Option<u8>,
+}
+
+impl SourceSelector {
+    /// Creates a [SourceSelector] for a source that activates at the given L1 timestamp.
+    pub const fn activation(activation: u64) -> Self {
+        Self { activation, type_byte: None }
+    }
+
+    /// Routes the data with the given leading type byte to the source.
+    pub const fn with_type_byte(mut self, type_byte: u8) -> Self {
+        self.type_byte = Some(type_byte);
+        self
+    }
+
+    /// Returns `true` if the source serves the L1 block with the given timestamp.
+    pub const fn is_active(&self, timestamp: u64) -> bool {
+        timestamp >= self.activation
+    }
+
+    /// Returns `true` if the given data has the source's leading type byte.
+    pub fn matches(&self, data: &Bytes) -> bool {
+        self.type_byte.is_some_and(|b| data.first() == Some(&b))
+    }
+}
+
+/// How a [DataSourceMux] picks among the sources that are active for an L1 block.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MuxMode {
+    /// Of the active sources with the same type byte, only the source with the latest activation
+    /// timestamp serves the block. A newly activated source replaces the sources with its type
+    /// byte that were activated before it.
+    #[default]
+    Latest,
+    /// All active sources serve the block.
+    Aggregate,
+}
+
+/// A [DataAvailabilityProvider] that multiplexes over any number of registered data sources.
+///
+/// Each source is registered with a [SourceSelector], which decides the L1 blocks it serves by
+/// activation timestamp, and the data it serves by leading type byte. For each L1 block, the
+/// sources picked by the [MuxMode] are drained one after another, in registration order, and the
+/// mux returns [PipelineError::Eof] once the last of them is exhausted.
+///
+/// Data is routed between the picked sources by its leading type byte. It is served by the picked
+/// sources registered with its type byte or, if there are none, by the picked sources registered
+/// without a type byte. Data returned by a source without a type byte is skipped if it is routed
+/// to a source with a type byte, which serves it instead. Data returned by a source with a type
+/// byte that it does not lead with is logged and dropped.
+///
+/// This allows chains with custom data availability to plug their own sources into the
+/// derivation pipeline next to, or in place of, the Ethereum calldata and blob sources.
+pub struct DataSourceMux {
+    /// The registered sources, in registration order.
+    pub sources: Vec<(SourceSelector, BoxedDataSource)>,
+    /// The mode used to pick among the active sources.
+    pub mode: MuxMode,
+    /// The index, into the sources picked for the current block, of the source being drained.
+    cursor: usize,
+}
+
+impl Debug for DataSourceMux {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("DataSourceMux")
+            .field("selectors", &self.sources.iter().map(|(s, _)| s).collect::<Vec<_>>())
+            .field("mode", &self.mode)
+            .field("cursor", &self.cursor)
+            .finish()
+    }
+}
+
+impl DataSourceMux {
+    /// Creates a new [DataSourceMux] with no registered sources.
+    pub const fn new(mode: MuxMode) -> Self {
+        Self { sources: Vec::new(), mode, cursor: 0 }
+    }
+
+    /// Creates a [DataSourceMux] that serves the Ethereum calldata source from genesis, and the
+    /// blob source from the Ecotone activation, matching the
+    /// [EthereumDataSource](crate::sources::EthereumDataSource).
+    pub fn ethereum<C, B>(provider: C, blobs: B, cfg: &RollupConfig) -> Self
+    where
+        C: ChainProvider + Send + Sync + Clone + 'static,
+        B: BlobProvider + Send + Sync + 'static,
+    {
+        let mut mux = Self::new(MuxMode::Latest);
+
+        // If Ecotone is active at genesis, the blob source serves every block.
+        if cfg.ecotone_time != Some(0) {
+            let calldata = CalldataSource::new(provider.clone(), cfg.batch_inbox_address);
+            mux.sources.push((SourceSelector::activation(0), Box::new(calldata)));
+        }
+        if let Some(ecotone_time) = cfg.ecotone_time {
+            let blobs = BlobSource::new(provider, blobs, cfg.batch_inbox_address);
+            mux.sources.push((SourceSelector::activation(ecotone_time), Box::new(blobs)));
+        }
+        mux
+    }
+
+    /// Registers a source with the given [SourceSelector]. See [DataSourceMux::register].
+    pub fn with_source<S>(
+        mut self,
+        selector: SourceSelector,
+        source: S,
+    ) -> Result<Self, DataSourceMuxError>
+    where
+        S: DataAvailabilityProvider<Item = Bytes> + Send + Sync + 'static,
+    {
+        self.register(selector, source)?;
+        Ok(self)
+    }
+
+    /// Registers a source with the given [SourceSelector].
+    ///
+    /// Returns [DataSourceMuxError::DuplicateSource] if a source is already registered with the
+    /// same activation timestamp and type byte.
+    pub fn register<S>(
+        &mut self,
+        selector: SourceSelector,
+        source: S,
+    ) -> Result<(), DataSourceMuxError>
+    where
+        S: DataAvailabilityProvider<Item = Bytes> + Send + Sync + 'static,
+    {
+        if self.sources.iter().any(|(s, _)| *s == selector) {
+            return Err(DataSourceMuxError::DuplicateSource(
+                selector.activation,
+                selector.type_byte,
+            ));
+        }
+        self.sources.push((selector, Box::new(source)));
+        Ok(())
+    }
+
+    /// Returns the indices of the sources that serve the L1 block with the given timestamp, in
+    /// the order that they are drained.
+    fn picked(&self, timestamp: u64) -> Vec<usize> {
+        let active = self.sources.iter().enumerate().filter(|(_, (s, _))| s.is_active(timestamp));
+        match self.mode {
+            MuxMode::Latest => active
+                .clone()
+                .filter(|(_, (s, _))| {
+                    active.clone().all(|(_, (other, _))| {
+                        other.type_byte != s.type_byte || other.activation <= s.activation
+                    })
+                })
+                .map(|(i, _)| i)
+                .collect(),
+            MuxMode::Aggregate => active.map(|(i, _)| i).collect(),
+        }
+    }
+
+    /// Returns `true` if the given data is routed to the source at `index`, out of the `picked`
+    /// sources.
+    fn routes_to(&self, picked: &[usize], index: usize, data: &Bytes) -> bool {
+        let selector = &self.sources[index].0;
+        let typed = picked.iter().any(|&i| self.sources[i].0.matches(data));
+        if typed {
+            selector.matches(data)
+        } else {
+            selector.type_byte.is_none()
+        }
+    }
+}
+
+#[async_trait]
+impl DataAvailabilityProvider for DataSourceMux {
+    type Item = Bytes;
+
+    async fn next(
+        &mut self,
+        block_ref: &BlockInfo,
+        batcher_address: Address,
+    ) -> PipelineResult<Self::Item> {
+        let picked = self.picked(block_ref.timestamp);
+        while let Some(&index) = picked.get(self.cursor) {
+            match self.sources[index].1.next(block_ref, batcher_address).await {
+                Ok(data) if self.routes_to(&picked, index, &data) => return Ok(data),
+                Ok(data) => {
+                    if let Some(type_byte) = self.sources[index].0.type_byte {
+                        warn!(
+                            target: "data-source-mux",
+                            "Dropping data with type byte {:?} from the source of type byte {type_byte}",
+                            data.first()
+                        );
+                    }
+                }
+                Err(PipelineErrorKind::Temporary(PipelineError::Eof)) => self.cursor += 1,
+                Err(e) => return Err(e),
+            }
+        }
+        Err(PipelineError::Eof.temp())
+    }
+
+    fn clear(&mut self) {
+        self.sources.iter_mut().for_each(|(_, source)| source.clear());
+        self.cursor = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{TestBlobProvider, TestChainProvider, TestDAP};
+    use alloc::vec;
+
+    fn source(items: &[&'static [u8]]) -> TestDAP {
+        // The test source pops its results from the back.
+        let results = items.iter().rev().copied().map(|i| Ok(Bytes::from_static(i))).collect();
+        TestDAP { results, ..Default::default() }
+    }
+
+    fn bytes(items: &[&'static [u8]]) -> Vec<Bytes> {
+        items.iter().copied().map(Bytes::from_static).collect()
+    }
+
+    async fn drain(mux: &mut DataSourceMux, block_ref: &BlockInfo) -> Vec<Bytes> {
+        let mut items = Vec::new();
+        loop {
+            match mux.next(block_ref, Address::ZERO).await {
+                Ok(data) => items.push(data),
+                Err(e) => {
+                    assert_eq!(e, PipelineError::Eof.temp());
+                    mux.clear();
+                    return items;
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mux_latest_activation() {
+        let mut mux = DataSourceMux::new(MuxMode::Latest)
+            .with_source(SourceSelector::activation(0), source(&[b"a"]))
+            .unwrap()
+            .with_source(SourceSelector::activation(10), source(&[b"b"]))
+            .unwrap();
+
+        let block_ref = BlockInfo { timestamp: 9, ..Default::default() };
+        assert_eq!(drain(&mut mux, &block_ref).await, bytes(&[b"a"]));
+
+        let mut mux = DataSourceMux::new(MuxMode::Latest)
+            .with_source(SourceSelector::activation(0), source(&[b"a"]))
+            .unwrap()
+            .with_source(SourceSelector::activation(10), source(&[b"b"]))
+            .unwrap();
+        let block_ref = BlockInfo { timestamp: 10, ..Default::default() };
+        assert_eq!(drain(&mut mux, &block_ref).await, bytes(&[b"b"]));
+    }
+
+    #[tokio::test]
+    async fn test_mux_aggregate_in_order() {
+        let mut mux = DataSourceMux::new(MuxMode::Aggregate)
+            .with_source(SourceSelector::activation(0), source(&[b"a", b"b"]))
+            .unwrap()
+            .with_source(SourceSelector::activation(20), source(&[b"x"]))
+            .unwrap()
+            .with_source(SourceSelector::activation(5), source(&[b"c"]))
+            .unwrap();
+
+        let block_ref = BlockInfo { timestamp: 10, ..Default::default() };
+        assert_eq!(drain(&mut mux, &block_ref).await, bytes(&[b"a", b"b", b"c"]));
+    }
+
+    #[tokio::test]
+    async fn test_mux_rejects_duplicate_sources() {
+        let mut mux = DataSourceMux::new(MuxMode::Latest)
+            .with_source(SourceSelector::activation(5), source(&[b"a"]))
+            .unwrap();
+
+        let err = mux.register(SourceSelector::activation(5), source(&[b"b"])).unwrap_err();
+        assert_eq!(err, DataSourceMuxError::DuplicateSource(5, None));
+        let selector = SourceSelector::activation(5).with_type_byte(0x01);
+        mux.register(selector, source(&[b"\x01c"])).unwrap();
+        let err = mux.register(selector, source(&[b"\x01d"])).unwrap_err();
+        assert_eq!(err, DataSourceMuxError::DuplicateSource(5, Some(0x01)));
+        assert_eq!(mux.sources.len(), 2);
+
+        // The sources registered first serve the block.
+        let block_ref = BlockInfo { timestamp: 10, ..Default::default() };
+        assert_eq!(drain(&mut mux, &block_ref).await, bytes(&[b"a", b"\x01c"]));
+    }
+
+    #[tokio::test]
+    async fn test_mux_routes_by_type_byte() {
+        let frames: &[&[u8]] = &[b"\x00frame", b"\x01commitment", b"", b"\x00frame2"];
+        let mut mux = DataSourceMux::new(MuxMode::Latest)
+            .with_source(SourceSelector::activation(0), source(frames))
+            .unwrap()
+            .with_source(
+                SourceSelector::activation(10).with_type_byte(0x01),
+                source(&[b"\x01resolved", b"\x02unrouted"]),
+            )
+            .unwrap();
+
+        // The commitment is served by the untyped source until the typed source activates.
+        let block_ref = BlockInfo { timestamp: 9, ..Default::default() };
+        let expected = bytes(&[b"\x00frame", b"\x01commitment", b"", b"\x00frame2"]);
+        assert_eq!(drain(&mut mux, &block_ref).await, expected);
+
+        // Once it activates, the commitment is routed to the typed source, next to the untyped
+        // source, and the data of the typed source without its type byte is dropped.
+        let mut mux = DataSourceMux::new(MuxMode::Latest)
+            .with_source(SourceSelector::activation(0), source(frames))
+            .unwrap()
+            .with_source(
+                SourceSelector::activation(10).with_type_byte(0x01),
+                source(&[b"\x01resolved", b"\x02unrouted"]),
+            )
+            .unwrap();
+        let block_ref = BlockInfo { timestamp: 10, ..Default::default() };
+        let expected = bytes(&[b"\x00frame", b"", b"\x00frame2", b"\x01resolved"]);
+        assert_eq!(drain(&mut mux, &block_ref).await, expected);
+    }
+
+    #[tokio::test]
+    async fn test_mux_latest_per_type_byte() {
+        let mut mux = DataSourceMux::new(MuxMode::Latest)
+            .with_source(SourceSelector::activation(0).with_type_byte(0x01), source(&[b"\x01a"]))
+            .unwrap()
+            .with_source(SourceSelector::activation(5), source(&[b"\x00b"]))
+            .unwrap()
+            .with_source(SourceSelector::activation(10).with_type_byte(0x01), source(&[b"\x01c"]))
+            .unwrap();
+
+        let block_ref = BlockInfo { timestamp: 10, ..Default::default() };
+        assert_eq!(mux.picked(block_ref.timestamp), vec![1, 2]);
+        assert_eq!(drain(&mut mux, &block_ref).await, bytes(&[b"\x00b", b"\x01c"]));
+    }
+
+    #[tokio::test]
+    async fn test_mux_propagates_errors() {
+        let results = vec![Err(PipelineError::MissingL1Data.temp())];
+        let mut mux = DataSourceMux::new(MuxMode::Latest)
+            .with_source(SourceSelector::activation(0), TestDAP { results, ..Default::default() })
+            .unwrap();
+
+        let err = mux.next(&BlockInfo::default(), Address::ZERO).await.unwrap_err();
+        assert_eq!(err, PipelineError::MissingL1Data.temp());
+    }
+
+    #[tokio::test]
+    async fn test_mux_ethereum_sources() {
+        let cfg = RollupConfig { ecotone_time: Some(100), ..Default::default() };
+        let mux = DataSourceMux::ethereum(
+            TestChainProvider::default(),
+            TestBlobProvider::default(),
+            &cfg,
+        );
+        assert_eq!(mux.picked(99), vec![0]);
+        assert_eq!(mux.picked(100), vec![1]);
+
+        let cfg = RollupConfig { ecotone_time: Some(0), ..Default::default() };
+        let mux = DataSourceMux::ethereum(
+            TestChainProvider::default(),
+            TestBlobProvider::default(),
+            &cfg,
+        );
+        assert_eq!(mux.sources.len(), 1);
+
+        let mux = DataSourceMux::ethereum(
+            TestChainProvider::default(),
+            TestBlobProvider::default(),
+            &RollupConfig::default(),
+        );
+        assert_eq!(mux.sources.len(), 1);
+    }
+
+    #[test]
+    fn test_source_selector_matches() {
+        let selector = SourceSelector::activation(0);
+        assert!(!selector.matches(&Bytes::new()));
+        let selector = selector.with_type_byte(0x01);
+        assert!(selector.matches(&Bytes::from_static(&[0x01, 0x02])));
+        assert!(!selector.matches(&Bytes::from_static(&[0x00, 0x01])));
+        assert!(!selector.matches(&Bytes::new()));
+    }
+}
@@ -0,0 +1,605 @@
+//! Contains the [L1Prefetcher], a [ChainProvider] and [BlobProvider] that fetches the L1 data of
+//! upcoming origins ahead of the derivation pipeline.
+
+use crate::{
+    sources::EthereumDataSource,
+    traits::{BlobProvider, ChainProvider},
+};
+use alloc::{boxed::Box, collections::BTreeMap, sync::Arc, vec::Vec};
+use alloy_consensus::{Header, Receipt, Transaction, TxEnvelope};
+use alloy_eips::{
+    eip2718::Encodable2718,
+    eip4844::{Blob, IndexedBlobHash, BYTES_PER_BLOB},
+};
+use alloy_primitives::{Address, B256};
+use async_trait::async_trait;
+use core::fmt::Debug;
+use futures::{
+    future::{BoxFuture, Shared},
+    FutureExt,
+};
+use maili_genesis::RollupConfig;
+use maili_protocol::BlockInfo;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use tokio::task::AbortHandle;
+
+/// The configuration of an [L1Prefetcher].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PrefetchConfig {
+    /// The number of L1 blocks past the current origin to prefetch.
+    pub depth: u64,
+    /// The approximate number of bytes of prefetched data to hold. No further blocks are
+    /// prefetched while the data that has already been fetched exceeds this limit.
+    pub max_bytes: usize,
+}
+
+impl Default for PrefetchConfig {
+    fn default() -> Self {
+        Self { depth: 8, max_bytes: 64 * 1024 * 1024 }
+    }
+}
+
+/// The L1 data of a prefetched block.
+#[derive(Debug)]
+struct PrefetchedBlock {
+    /// The block info.
+    info: BlockInfo,
+    /// The block header.
+    header: Header,
+    /// The receipts of the block.
+    receipts: Vec<Receipt>,
+    /// The transactions in the block.
+    transactions: Vec<TxEnvelope>,
+    /// The blobs sent to the batch inbox in the block.
+    blobs: Vec<(IndexedBlobHash, Box<Blob>)>,
+}
+
+impl PrefetchedBlock {
+    /// Returns the approximate size of the prefetched data, in bytes.
+    fn size(&self) -> usize {
+        let receipts = self
+            .receipts
+            .iter()
+            .flat_map(|r| &r.logs)
+            .map(|log| log.data.data.len() + log.data.topics().len() * 32)
+            .sum::<usize>();
+        let transactions = self.transactions.iter().map(|tx| tx.encode_2718_len()).sum::<usize>();
+        size_of::<Self>() + receipts + transactions + self.blobs.len() * BYTES_PER_BLOB
+    }
+}
+
+/// The prefetch of a block, shared between the spawned task and every caller waiting on it.
+type PrefetchFuture = Shared<BoxFuture<'static, Option<Arc<PrefetchedBlock>>>>;
+
+/// A block that is being, or has been, prefetched.
+struct Slot {
+    /// The prefetch of the block.
+    fetch: PrefetchFuture,
+    /// The handle to abort the prefetch task.
+    abort: AbortHandle,
+}
+
+/// The prefetch state, shared between the clones of an [L1Prefetcher].
+#[derive(Default)]
+struct PrefetchState {
+    /// The prefetched blocks, by number.
+    slots: BTreeMap<u64, Slot>,
+    /// The number of the last block requested by number.
+    last: Option<u64>,
+}
+
+impl PrefetchState {
+    /// Aborts and drops the prefetched blocks.
+    fn clear(&mut self) {
+        self.slots.values().for_each(|slot| slot.abort.abort());
+        self.slots.clear();
+        self.last = None;
+    }
+}
+
+/// A [ChainProvider] and [BlobProvider] that fetches the L1 data of upcoming origins ahead of the
+/// derivation pipeline.
+///
+/// Each time a block is requested by number, as the [L1Traversal] stage does when it advances its
+/// origin, the header, receipts, transactions and batch inbox blobs of the next
+/// [PrefetchConfig::depth] blocks are fetched concurrently in the background. Requests that hit a
+/// prefetched block are served from memory, and all other requests fall through to the inner
+/// providers. Prefetching failures are not surfaced: failed prefetches, such as those of blocks
+/// past the L1 head, are evicted once they complete, and fetched again on the next request.
+///
+/// The prefetch state is shared between clones, so a single prefetcher serves as both the chain
+/// provider of the [L1Traversal] stage and the providers of the data source, see
+/// [L1Prefetcher::data_source]. The prefetched blocks are dropped when the [L1Traversal] stage is
+/// reset, through [ChainProvider::reset], and whenever a block is requested out of sequence.
+///
+/// The prefetcher spawns [tokio] tasks, and must be used from within a [tokio] runtime.
+///
+/// [L1Traversal]: crate::stages::L1Traversal
+#[derive(Clone)]
+pub struct L1Prefetcher<C, B>
+where
+    C: ChainProvider + Clone + Send + Sync + 'static,
+    B: BlobProvider + Clone + Send + Sync + 'static,
+{
+    /// The inner chain provider.
+    pub chain_provider: C,
+    /// The inner blob provider.
+    pub blob_provider: B,
+    /// The batch inbox address, used to select the blobs to prefetch.
+    pub batch_inbox_address: Address,
+    /// The prefetch configuration.
+    pub config: PrefetchConfig,
+    /// The prefetch state.
+    state: Arc<Mutex<PrefetchState>>,
+}
+
+impl<C, B> Debug for L1Prefetcher<C, B>
+where
+    C: ChainProvider + Clone + Send + Sync + Debug + 'static,
+    B: BlobProvider + Clone + Send + Sync + Debug + 'static,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("L1Prefetcher")
+            .field("chain_provider", &self.chain_provider)
+            .field("blob_provider", &self.blob_provider)
+            .field("batch_inbox_address", &self.batch_inbox_address)
+            .field("config", &self.config)
+            .field("prefetched", &self.state().slots.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl<C, B> L1Prefetcher<C, B>
+where
+    C: ChainProvider + Clone + Send + Sync + 'static,
+    B: BlobProvider + Clone + Send + Sync + 'static,
+{
+    /// Creates a new [L1Prefetcher] over the given providers, with the default
+    /// [PrefetchConfig].
+    pub fn new(chain_provider: C, blob_provider: B, batch_inbox_address: Address) -> Self {
+        Self {
+            chain_provider,
+            blob_provider,
+            batch_inbox_address,
+            config: PrefetchConfig::default(),
+            state: Default::default(),
+        }
+    }
+
+    /// Sets the [PrefetchConfig].
+    pub const fn with_config(mut self, config: PrefetchConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Aborts any in-flight prefetches and drops all prefetched blocks.
+    ///
+    /// This is called when the pipeline is reset, see [ChainProvider::reset].
+    pub fn clear(&self) {
+        self.state().clear();
+    }
+
+    /// Locks the prefetch state.
+    fn state(&self) -> MutexGuard<'_, PrefetchState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Moves the prefetch window to start at the block with the given number, and returns the
+    /// prefetch task of the block.
+    fn advance(&self, number: u64) -> Option<PrefetchFuture> {
+        let mut state = self.state();
+
+        // The pipeline requests origins in sequence, retrying the next origin until it is
+        // available, unless it has been reset. The number to hash mappings of the prefetched
+        // blocks may not be canonical anymore after a reset.
+        if state.last.is_some_and(|last| number != last && number != last + 1) {
+            state.clear();
+        }
+        state.last = Some(number);
+
+        // Drop the blocks that the pipeline has moved past. The previous origin is kept, as its
+        // data may still be read by the data source.
+        let kept = state.slots.split_off(&number.saturating_sub(1));
+        state.slots.values().for_each(|slot| slot.abort.abort());
+        state.slots = kept;
+
+        let mut bytes = state
+            .slots
+            .values()
+            .filter_map(|slot| slot.fetch.peek().cloned().flatten())
+            .map(|block| block.size())
+            .sum::<usize>();
+        for n in number..=number.saturating_add(self.config.depth) {
+            if bytes >= self.config.max_bytes {
+                break;
+            }
+            let slot = state.slots.entry(n).or_insert_with(|| self.spawn(n));
+            bytes += slot.fetch.peek().cloned().flatten().map_or(0, |b| b.size());
+        }
+
+        state.slots.get(&number).map(|slot| slot.fetch.clone())
+    }
+
+    /// Returns the prefetched block with the given hash, if it has been fetched.
+    fn prefetched(&self, hash: B256) -> Option<Arc<PrefetchedBlock>> {
+        self.state()
+            .slots
+            .values()
+            .filter_map(|slot| slot.fetch.peek().cloned().flatten())
+            .find(|block| block.info.hash == hash)
+    }
+
+    /// Spawns the prefetch task of the block with the given number.
+    ///
+    /// The task drives the shared future to completion, so the prefetched block can be read
+    /// without being awaited. If the prefetch fails, the task evicts its slot, so that the block
+    /// is fetched again on the next request rather than being missed for good.
+    fn spawn(&self, number: u64) -> Slot {
+        let fetch = Self::fetch(
+            self.chain_provider.clone(),
+            self.blob_provider.clone(),
+            self.batch_inbox_address,
+            number,
+        )
+        .boxed()
+        .shared();
+
+        let (task, state) = (fetch.clone(), Arc::downgrade(&self.state));
+        let abort = tokio::spawn(async move {
+            if task.clone().await.is_some() {
+                return;
+            }
+            let Some(state) = state.upgrade() else { return };
+            let mut state = state.lock().unwrap_or_else(PoisonError::into_inner);
+            if state.slots.get(&number).is_some_and(|slot| slot.fetch.ptr_eq(&task)) {
+                state.slots.remove(&number);
+            }
+        })
+        .abort_handle();
+        Slot { fetch, abort }
+    }
+
+    /// Fetches the L1 data of the block with the given number.
+    async fn fetch(
+        mut chain_provider: C,
+        mut blob_provider: B,
+        batch_inbox_address: Address,
+        number: u64,
+    ) -> Option<Arc<PrefetchedBlock>> {
+        let info = chain_provider.block_info_by_number(number).await.ok()?;
+
+        let (mut header_provider, mut receipts_provider) =
+            (chain_provider.clone(), chain_provider.clone());
+        let (header, receipts, transactions) = futures::join!(
+            async { header_provider.header_by_hash(info.hash).await.ok() },
+            async { receipts_provider.receipts_by_hash(info.hash).await.ok() },
+            async { chain_provider.block_info_and_transactions_by_hash(info.hash).await.ok() },
+        );
+        let (header, receipts, (_, transactions)) = (header?, receipts?, transactions?);
+
+        // Blobs that could not be fetched are fetched again on request.
+        let blob_hashes = inbox_blob_hashes(&transactions, batch_inbox_address);
+        let blobs = if blob_hashes.is_empty() {
+            Vec::new()
+        } else {
+            blob_provider
+                .get_blobs(&info, &blob_hashes)
+                .await
+                .ok()
+                .filter(|blobs| blobs.len() == blob_hashes.len())
+                .map(|blobs| blob_hashes.into_iter().zip(blobs).collect())
+                .unwrap_or_default()
+        };
+
+        Some(Arc::new(PrefetchedBlock { info, header, receipts, transactions, blobs }))
+    }
+}
+
+impl<C, B> L1Prefetcher<C, B>
+where
+    C: ChainProvider + Clone + Send + Sync + Debug + 'static,
+    B: BlobProvider + Clone + Send + Sync + Debug + 'static,
+{
+    /// Returns an [EthereumDataSource] that reads the L1 data through the prefetcher, to be used
+    /// as the data source of a pipeline whose chain provider is the prefetcher.
+    pub fn data_source(&self, cfg: &RollupConfig) -> EthereumDataSource<Self, Self> {
+        EthereumDataSource::new_from_parts(self.clone(), self.clone(), cfg)
+    }
+}
+
+/// Returns the indexed hashes of the blobs sent to the batch inbox by the given transactions.
+///
+/// Blobs are indexed by their position among all blobs in the block, as in the [BlobSource].
+///
+/// [BlobSource]: crate::sources::BlobSource
+fn inbox_blob_hashes(txs: &[TxEnvelope], batch_inbox_address: Address) -> Vec<IndexedBlobHash> {
+    let mut index = 0;
+    let mut hashes = Vec::new();
+    for tx in txs {
+        let blob_hashes = tx.blob_versioned_hashes().unwrap_or_default();
+        if tx.to() == Some(batch_inbox_address) {
+            hashes.extend(
+                blob_hashes
+                    .iter()
+                    .zip(index..)
+                    .map(|(hash, index)| IndexedBlobHash { index, hash: *hash }),
+            );
+        }
+        index += blob_hashes.len() as u64;
+    }
+    hashes
+}
+
+#[async_trait]
+impl<C, B> ChainProvider for L1Prefetcher<C, B>
+where
+    C: ChainProvider + Clone + Send + Sync + 'static,
+    B: BlobProvider + Clone + Send + Sync + 'static,
+{
+    type Error = C::Error;
+
+    async fn header_by_hash(&mut self, hash: B256) -> Result<Header, Self::Error> {
+        if let Some(block) = self.prefetched(hash) {
+            return Ok(block.header.clone());
+        }
+        self.chain_provider.header_by_hash(hash).await
+    }
+
+    async fn block_info_by_number(&mut self, number: u64) -> Result<BlockInfo, Self::Error> {
+        if let Some(fetch) = self.advance(number) {
+            if let Some(block) = fetch.await {
+                return Ok(block.info);
+            }
+        }
+        self.chain_provider.block_info_by_number(number).await
+    }
+
+    async fn receipts_by_hash(&mut self, hash: B256) -> Result<Vec<Receipt>, Self::Error> {
+        if let Some(block) = self.prefetched(hash) {
+            return Ok(block.receipts.clone());
+        }
+        self.chain_provider.receipts_by_hash(hash).await
+    }
+
+    async fn block_info_and_transactions_by_hash(
+        &mut self,
+        hash: B256,
+    ) -> Result<(BlockInfo, Vec<TxEnvelope>), Self::Error> {
+        if let Some(block) = self.prefetched(hash) {
+            return Ok((block.info, block.transactions.clone()));
+        }
+        self.chain_provider.block_info_and_transactions_by_hash(hash).await
+    }
+
+    fn reset(&mut self) {
+        self.clear();
+        self.chain_provider.reset();
+    }
+}
+
+#[async_trait]
+impl<C, B> BlobProvider for L1Prefetcher<C, B>
+where
+    C: ChainProvider + Clone + Send + Sync + 'static,
+    B: BlobProvider + Clone + Send + Sync + 'static,
+{
+    type Error = B::Error;
+
+    async fn get_blobs(
+        &mut self,
+        block_ref: &BlockInfo,
+        blob_hashes: &[IndexedBlobHash],
+    ) -> Result<Vec<Box<Blob>>, Self::Error> {
+        if let Some(block) = self.prefetched(block_ref.hash) {
+            let blobs = blob_hashes
+                .iter()
+                .map(|hash| block.blobs.iter().find(|(h, _)| h == hash).map(|(_, b)| b.clone()))
+                .collect::<Option<Vec<_>>>();
+            if let Some(blobs) = blobs {
+                return Ok(blobs);
+            }
+        }
+        self.blob_provider.get_blobs(block_ref, blob_hashes).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        sources::blobs::tests::valid_blob_txs,
+        stages::L1Traversal,
+        test_utils::{TestBlobProvider, TestChainProvider},
+        traits::{OriginAdvancer, OriginProvider, SignalReceiver},
+        types::ResetSignal,
+    };
+    use alloc::vec;
+    use alloy_primitives::address;
+
+    const BATCH_INBOX: Address = address!("11E9CA82A3a762b4B5bd264d4173a242e7a77064");
+
+    /// Returns a chain of `count` blocks, with the blob transactions in block 1.
+    fn test_chain(count: u64) -> (TestChainProvider, Vec<BlockInfo>) {
+        let mut provider = TestChainProvider::default();
+        let mut blocks = Vec::new();
+        let mut parent_hash = B256::ZERO;
+        for number in 0..count {
+            let header =
+                Header { number, parent_hash, timestamp: number * 12, ..Default::default() };
+            let info =
+                BlockInfo { hash: header.hash_slow(), number, parent_hash, timestamp: number * 12 };
+            let txs = if number == 1 { valid_blob_txs() } else { Vec::new() };
+            provider.insert_header(info.hash, header);
+            provider.insert_receipts(info.hash, vec![Receipt::default()]);
+            provider.insert_block_with_transactions(number, info, txs);
+            parent_hash = info.hash;
+            blocks.push(info);
+        }
+        (provider, blocks)
+    }
+
+    /// Waits for the in-flight prefetches to complete.
+    async fn settle<C, B>(prefetcher: &L1Prefetcher<C, B>)
+    where
+        C: ChainProvider + Clone + Send + Sync + 'static,
+        B: BlobProvider + Clone + Send + Sync + 'static,
+    {
+        let fetches =
+            prefetcher.state().slots.values().map(|s| s.fetch.clone()).collect::<Vec<_>>();
+        futures::future::join_all(fetches).await;
+    }
+
+    #[tokio::test]
+    async fn test_prefetch_ahead() {
+        let (chain, blocks) = test_chain(6);
+        let config = PrefetchConfig { depth: 2, ..Default::default() };
+        let mut prefetcher =
+            L1Prefetcher::new(chain, TestBlobProvider::default(), BATCH_INBOX).with_config(config);
+
+        assert_eq!(prefetcher.block_info_by_number(1).await.unwrap(), blocks[1]);
+        settle(&prefetcher).await;
+        assert_eq!(prefetcher.state().slots.keys().copied().collect::<Vec<_>>(), vec![1, 2, 3]);
+        assert!(prefetcher.prefetched(blocks[3].hash).is_some());
+
+        // Prefetched data is served without the inner provider.
+        prefetcher.chain_provider.clear();
+        assert_eq!(prefetcher.receipts_by_hash(blocks[3].hash).await.unwrap().len(), 1);
+        assert_eq!(prefetcher.header_by_hash(blocks[2].hash).await.unwrap().number, 2);
+        assert_eq!(prefetcher.block_info_by_number(2).await.unwrap(), blocks[2]);
+        assert!(prefetcher.receipts_by_hash(blocks[5].hash).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_prefetch_drops_passed_blocks() {
+        let (chain, _) = test_chain(8);
+        let config = PrefetchConfig { depth: 1, ..Default::default() };
+        let mut prefetcher =
+            L1Prefetcher::new(chain, TestBlobProvider::default(), BATCH_INBOX).with_config(config);
+
+        for number in 1..=4 {
+            prefetcher.block_info_by_number(number).await.unwrap();
+        }
+        assert_eq!(prefetcher.state().slots.keys().copied().collect::<Vec<_>>(), vec![3, 4, 5]);
+    }
+
+    #[tokio::test]
+    async fn test_prefetch_invalidated_out_of_sequence() {
+        let (chain, blocks) = test_chain(6);
+        let mut prefetcher = L1Prefetcher::new(chain, TestBlobProvider::default(), BATCH_INBOX);
+
+        prefetcher.block_info_by_number(1).await.unwrap();
+        let fetch = prefetcher.state().slots[&2].fetch.clone();
+
+        // In sequence, the prefetched blocks are kept.
+        assert_eq!(prefetcher.block_info_by_number(2).await.unwrap(), blocks[2]);
+        assert!(prefetcher.state().slots[&2].fetch.ptr_eq(&fetch));
+
+        // Retrying the same block, as at the L1 head, keeps the prefetched blocks.
+        assert_eq!(prefetcher.block_info_by_number(2).await.unwrap(), blocks[2]);
+        assert!(prefetcher.state().slots[&2].fetch.ptr_eq(&fetch));
+
+        // Out of sequence, as after a reset, the prefetched blocks are fetched again.
+        assert_eq!(prefetcher.block_info_by_number(1).await.unwrap(), blocks[1]);
+        assert!(!prefetcher.state().slots[&2].fetch.ptr_eq(&fetch));
+
+        prefetcher.clear();
+        assert!(prefetcher.state().slots.is_empty());
+        assert_eq!(prefetcher.state().last, None);
+    }
+
+    #[tokio::test]
+    async fn test_prefetch_evicts_failed_blocks() {
+        let (chain, blocks) = test_chain(4);
+        let mut head = chain.clone();
+        head.blocks.retain(|(number, _)| *number < 3);
+        let config = PrefetchConfig { depth: 2, ..Default::default() };
+        let mut prefetcher =
+            L1Prefetcher::new(head, TestBlobProvider::default(), BATCH_INBOX).with_config(config);
+
+        // Block 3 is past the L1 head, so its prefetch fails and is evicted.
+        prefetcher.block_info_by_number(1).await.unwrap();
+        settle(&prefetcher).await;
+        while prefetcher.state().slots.contains_key(&3) {
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(prefetcher.state().slots.keys().copied().collect::<Vec<_>>(), vec![1, 2]);
+
+        // Once the L1 head advances, the block is prefetched on the next request.
+        prefetcher.chain_provider = chain;
+        prefetcher.block_info_by_number(2).await.unwrap();
+        settle(&prefetcher).await;
+        assert!(prefetcher.prefetched(blocks[3].hash).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_prefetch_cleared_on_traversal_reset() {
+        let (chain, blocks) = test_chain(6);
+        let prefetcher = L1Prefetcher::new(chain, TestBlobProvider::default(), BATCH_INBOX);
+        let mut traversal = L1Traversal::new(prefetcher.clone(), Arc::new(RollupConfig::default()));
+        traversal.block = Some(blocks[0]);
+
+        traversal.advance_origin().await.unwrap();
+        traversal.advance_origin().await.unwrap();
+        assert_eq!(traversal.origin(), Some(blocks[2]));
+        assert_eq!(prefetcher.state().last, Some(2));
+        assert!(!prefetcher.state().slots.is_empty());
+
+        let signal = ResetSignal {
+            l2_safe_head: Default::default(),
+            l1_origin: blocks[1],
+            system_config: Some(Default::default()),
+        };
+        traversal.signal(signal.signal()).await.unwrap();
+        assert!(prefetcher.state().slots.is_empty());
+        assert_eq!(prefetcher.state().last, None);
+    }
+
+    #[tokio::test]
+    async fn test_prefetch_max_bytes() {
+        let (chain, _) = test_chain(8);
+        let config = PrefetchConfig { depth: 4, max_bytes: 1 };
+        let mut prefetcher =
+            L1Prefetcher::new(chain, TestBlobProvider::default(), BATCH_INBOX).with_config(config);
+
+        prefetcher.block_info_by_number(1).await.unwrap();
+        settle(&prefetcher).await;
+        assert_eq!(prefetcher.state().slots.len(), 5);
+
+        // The prefetched blocks exceed the limit, so no further blocks are prefetched.
+        prefetcher.block_info_by_number(2).await.unwrap();
+        assert_eq!(
+            prefetcher.state().slots.keys().copied().collect::<Vec<_>>(),
+            vec![1, 2, 3, 4, 5]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_prefetch_blobs() {
+        let (chain, blocks) = test_chain(3);
+        let txs = valid_blob_txs();
+        let blob_hashes = inbox_blob_hashes(&txs, BATCH_INBOX);
+        let expected = txs[0].blob_versioned_hashes().map_or(0, |hashes| hashes.len());
+        assert_eq!(blob_hashes.len(), expected);
+        let mut blob_provider = TestBlobProvider::default();
+        for (i, hash) in blob_hashes.iter().enumerate() {
+            blob_provider.insert_blob(hash.hash, Blob::repeat_byte(i as u8));
+        }
+        let mut prefetcher = L1Prefetcher::new(chain, blob_provider, BATCH_INBOX);
+
+        prefetcher.block_info_by_number(1).await.unwrap();
+        assert_eq!(prefetcher.prefetched(blocks[1].hash).unwrap().blobs.len(), expected);
+
+        prefetcher.blob_provider.should_error = true;
+        let blobs = prefetcher.get_blobs(&blocks[1], &blob_hashes[1..3]).await.unwrap();
+        assert_eq!(blobs, vec![Box::new(Blob::repeat_byte(1)), Box::new(Blob::repeat_byte(2))]);
+
+        // Blobs that were not prefetched fall through to the inner provider.
+        let missing = IndexedBlobHash { index: expected as u64, hash: B256::ZERO };
+        assert!(prefetcher.get_blobs(&blocks[1], &[missing]).await.is_err());
+    }
+
+    #[test]
+    fn test_inbox_blob_hashes_other_address() {
+        assert!(inbox_blob_hashes(&valid_blob_txs(), Address::ZERO).is_empty());
+    }
+}
@@ -92,7 +92,10 @@ where
         // SAFETY: The above check ensures that `next` is not None.
         let next = self.next.as_ref().expect("infallible");
 
-        match self.provider.next(next).await {
+        // The batcher address is taken from the system config after it has been updated with the
+        // receipts of the `next` block, so batcher rotations apply from the block that they occur
+        // in.
+        match self.provider.next(next, self.prev.batcher_addr()).await {
             Ok(data) => Ok(data),
             Err(e) => {
                 if let PipelineErrorKind::Temporary(PipelineError::Eof) = e {
@@ -144,7 +147,7 @@ mod tests {
     #[tokio::test]
     async fn test_l1_retrieval_flush_channel() {
         let traversal = new_populated_test_traversal();
-        let dap = TestDAP { results: vec![] };
+        let dap = TestDAP { results: vec![], ..Default::default() };
         let mut retrieval = L1Retrieval::new(traversal, dap);
         retrieval.prev.block = None;
         assert!(retrieval.prev.block.is_none());
@@ -157,7 +160,7 @@ mod tests {
     #[tokio::test]
     async fn test_l1_retrieval_activation_signal() {
         let traversal = new_populated_test_traversal();
-        let dap = TestDAP { results: vec![] };
+        let dap = TestDAP { results: vec![], ..Default::default() };
         let mut retrieval = L1Retrieval::new(traversal, dap);
         retrieval.prev.block = None;
         assert!(retrieval.prev.block.is_none());
@@ -176,7 +179,7 @@ mod tests {
     #[tokio::test]
     async fn test_l1_retrieval_reset_signal() {
         let traversal = new_populated_test_traversal();
-        let dap = TestDAP { results: vec![] };
+        let dap = TestDAP { results: vec![], ..Default::default() };
         let mut retrieval = L1Retrieval::new(traversal, dap);
         retrieval.prev.block = None;
         assert!(retrieval.prev.block.is_none());
@@ -195,7 +198,7 @@ mod tests {
     #[tokio::test]
     async fn test_l1_retrieval_origin() {
         let traversal = new_populated_test_traversal();
-        let dap = TestDAP { results: vec![] };
+        let dap = TestDAP { results: vec![], ..Default::default() };
         let retrieval = L1Retrieval::new(traversal, dap);
         let expected = BlockInfo::default();
         assert_eq!(retrieval.origin(), Some(expected));
@@ -205,7 +208,7 @@ mod tests {
     async fn test_l1_retrieval_next_data() {
         let traversal = new_populated_test_traversal();
         let results = vec![Err(PipelineError::Eof.temp()), Ok(Bytes::default())];
-        let dap = TestDAP { results };
+        let dap = TestDAP { results, ..Default::default() };
         let mut retrieval = L1Retrieval::new(traversal, dap);
         assert_eq!(retrieval.next, None);
         let data = retrieval.next_data().await.unwrap();
@@ -217,7 +220,7 @@ mod tests {
         let mut traversal = new_populated_test_traversal();
         traversal.done = true;
         let results = vec![Err(PipelineError::Eof.temp()), Ok(Bytes::default())];
-        let dap = TestDAP { results };
+        let dap = TestDAP { results, ..Default::default() };
         let mut retrieval = L1Retrieval::new(traversal, dap);
         retrieval.next = Some(BlockInfo::default());
         let data = retrieval.next_data().await.unwrap();
@@ -227,12 +230,34 @@ mod tests {
         assert!(retrieval.next.is_none());
     }
 
+    #[tokio::test]
+    async fn test_l1_retrieval_next_data_rotated_batcher() {
+        let traversal = new_populated_test_traversal();
+        let results = vec![Err(PipelineError::Eof.temp()), Ok(Bytes::default())];
+        let dap = TestDAP { results, ..Default::default() };
+        let mut retrieval = L1Retrieval::new(traversal, dap);
+
+        // Data in the first block is filtered by the batcher in the initial system config.
+        assert_eq!(retrieval.next_data().await.unwrap(), Bytes::default());
+        assert_eq!(retrieval.next_data().await.unwrap_err(), PipelineError::Eof.temp());
+
+        // The receipts of the next block rotate the batcher, which applies to the same block.
+        retrieval.advance_origin().await.unwrap();
+        assert_eq!(retrieval.next_data().await.unwrap_err(), PipelineError::Eof.temp());
+
+        let rotated = alloy_primitives::address!("000000000000000000000000000000000000bEEF");
+        assert_eq!(
+            retrieval.provider.batcher_addresses,
+            vec![Address::ZERO, Address::ZERO, rotated]
+        );
+    }
+
     #[tokio::test]
     async fn test_l1_retrieval_next_data_l1_block_errors() {
         let mut traversal = new_populated_test_traversal();
         traversal.done = true;
         let results = vec![Err(PipelineError::Eof.temp()), Ok(Bytes::default())];
-        let dap = TestDAP { results };
+        let dap = TestDAP { results, ..Default::default() };
         let mut retrieval = L1Retrieval::new(traversal, dap);
         assert_eq!(retrieval.next, None);
         let err = retrieval.next_data().await.unwrap_err();
@@ -243,7 +268,7 @@ mod tests {
     #[tokio::test]
     async fn test_l1_retrieval_existing_data_errors() {
         let traversal = new_populated_test_traversal();
-        let dap = TestDAP { results: vec![Err(PipelineError::Eof.temp())] };
+        let dap = TestDAP { results: vec![Err(PipelineError::Eof.temp())], ..Default::default() };
         let mut retrieval =
             L1Retrieval { prev: traversal, provider: dap, next: Some(BlockInfo::default()) };
         let data = retrieval.next_data().await.unwrap_err();
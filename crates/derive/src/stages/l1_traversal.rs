@@ -123,6 +123,11 @@ impl<F: ChainProvider> OriginProvider for L1Traversal<F> {
 #[async_trait]
 impl<F: ChainProvider + Send> SignalReceiver for L1Traversal<F> {
     async fn signal(&mut self, signal: Signal) -> PipelineResult<()> {
+        if matches!(signal, Signal::Reset(_)) {
+            // The number to hash mappings of the provider may not be canonical after a reset.
+            self.data_source.reset();
+        }
+
         match signal {
             Signal::Reset(ResetSignal { l1_origin, system_config, .. }) |
             Signal::Activation(ActivationSignal { l1_origin, system_config, .. }) => {
@@ -2,7 +2,7 @@
 
 use crate::{errors::PipelineError, traits::DataAvailabilityProvider, types::PipelineResult};
 use alloc::{boxed::Box, vec::Vec};
-use alloy_primitives::Bytes;
+use alloy_primitives::{Address, Bytes};
 use async_trait::async_trait;
 use core::fmt::Debug;
 use maili_protocol::BlockInfo;
@@ -12,13 +12,20 @@ use maili_protocol::BlockInfo;
 pub struct TestDAP {
     /// Specifies the stage results.
     pub results: Vec<PipelineResult<Bytes>>,
+    /// The batcher addresses passed to each call of `next`.
+    pub batcher_addresses: Vec<Address>,
 }
 
 #[async_trait]
 impl DataAvailabilityProvider for TestDAP {
     type Item = Bytes;
 
-    async fn next(&mut self, _: &BlockInfo) -> PipelineResult<Self::Item> {
+    async fn next(
+        &mut self,
+        _: &BlockInfo,
+        batcher_address: Address,
+    ) -> PipelineResult<Self::Item> {
+        self.batcher_addresses.push(batcher_address);
         self.results.pop().unwrap_or(Err(PipelineError::Eof.temp()))
     }
 
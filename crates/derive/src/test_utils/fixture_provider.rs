@@ -0,0 +1,303 @@
+//! A [ChainProvider] and [BlobProvider] backed by recorded L1 data, for offline pipeline tests.
+
+use crate::{
+    errors::{PipelineError, PipelineErrorKind},
+    traits::{BlobProvider, ChainProvider},
+};
+use alloc::{boxed::Box, format, string::ToString, vec::Vec};
+use alloy_consensus::{Block, Header, Receipt, ReceiptWithBloom, TxEnvelope, TxType};
+use alloy_eips::eip4844::{Blob, IndexedBlobHash, VERSIONED_HASH_VERSION_KZG};
+use alloy_primitives::{map::HashMap, Bytes, B256};
+use alloy_rlp::{Buf, Decodable};
+use alloy_rpc_types_beacon::sidecar::{BeaconBlobBundle, BlobData};
+use async_trait::async_trait;
+use maili_protocol::BlockInfo;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use thiserror::Error;
+
+/// A recorded L1 block, holding the raw responses of the L1 execution and beacon nodes for
+/// everything the derivation pipeline reads from L1 for the block.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct L1BlockFixture {
+    /// The RLP-encoded block, as returned by `debug_getRawBlock`.
+    pub raw_block: Bytes,
+    /// The EIP-2718 encoded receipts of the block, as returned by `debug_getRawReceipts`.
+    #[serde(default)]
+    pub raw_receipts: Vec<Bytes>,
+    /// The blob sidecars of the block, as returned by the beacon node's `blob_sidecars` endpoint
+    /// for the block's slot. Omitted for blocks without blob transactions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blob_sidecars: Option<BeaconBlobBundle>,
+}
+
+impl L1BlockFixture {
+    /// Decodes the raw block.
+    pub fn block(&self) -> Result<Block<TxEnvelope>, FixtureProviderError> {
+        Block::decode(&mut self.raw_block.as_ref()).map_err(FixtureProviderError::Rlp)
+    }
+
+    /// Decodes the raw receipts, skipping the transaction type byte of typed receipts in the
+    /// same way as the online chain provider.
+    pub fn receipts(&self) -> Result<Vec<Receipt>, FixtureProviderError> {
+        self.raw_receipts
+            .iter()
+            .map(|raw| {
+                let buf = &mut raw.as_ref();
+                if !buf.is_empty() && buf[0] <= TxType::Eip7702 as u8 {
+                    buf.advance(1);
+                }
+                Ok(ReceiptWithBloom::decode(buf).map_err(FixtureProviderError::Rlp)?.receipt)
+            })
+            .collect()
+    }
+
+    /// Writes the fixture to `<dir>/<block number>.json`.
+    pub fn save(&self, dir: impl AsRef<Path>) -> Result<(), FixtureProviderError> {
+        let number = self.block()?.header.number;
+        let path = dir.as_ref().join(format!("{number}.json"));
+        std::fs::write(path, serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// Returns the versioned hash of the blob in the sidecar, as referenced by the blob transaction.
+pub fn sidecar_versioned_hash(sidecar: &BlobData) -> B256 {
+    let mut hash: [u8; 32] = Sha256::digest(sidecar.kzg_commitment).into();
+    hash[0] = VERSIONED_HASH_VERSION_KZG;
+    B256::from(hash)
+}
+
+/// An L1 block decoded from an [L1BlockFixture].
+#[derive(Debug, Clone)]
+struct RecordedBlock {
+    /// The block header.
+    header: Header,
+    /// The transactions in the block.
+    transactions: Vec<TxEnvelope>,
+    /// The receipts of the transactions in the block.
+    receipts: Vec<Receipt>,
+    /// The blob sidecars of the block.
+    blob_sidecars: Vec<BlobData>,
+}
+
+impl RecordedBlock {
+    /// Returns the [BlockInfo] of the block with the given hash.
+    const fn block_info(&self, hash: B256) -> BlockInfo {
+        BlockInfo {
+            hash,
+            number: self.header.number,
+            parent_hash: self.header.parent_hash,
+            timestamp: self.header.timestamp,
+        }
+    }
+}
+
+/// A [ChainProvider] and [BlobProvider] serving recorded L1 blocks.
+///
+/// Fixtures are loaded from a directory of [L1BlockFixture] JSON files with
+/// [FixtureProvider::from_dir], which allows full pipeline runs over recorded L1 ranges to be
+/// tested offline. The raw responses are decoded in the same way as by the online providers.
+#[derive(Debug, Clone, Default)]
+pub struct FixtureProvider {
+    /// Maps block hashes to recorded blocks.
+    blocks: HashMap<B256, RecordedBlock>,
+    /// Maps block numbers to block hashes.
+    numbers: HashMap<u64, B256>,
+}
+
+impl FixtureProvider {
+    /// Loads every `*.json` [L1BlockFixture] in the given directory.
+    pub fn from_dir(dir: impl AsRef<Path>) -> Result<Self, FixtureProviderError> {
+        let mut provider = Self::default();
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                provider.insert_block(&serde_json::from_slice(&std::fs::read(path)?)?)?;
+            }
+        }
+        Ok(provider)
+    }
+
+    /// Decodes a recorded block and inserts it into the provider, returning its hash.
+    pub fn insert_block(&mut self, fixture: &L1BlockFixture) -> Result<B256, FixtureProviderError> {
+        let block = fixture.block()?;
+        let recorded = RecordedBlock {
+            transactions: block.body.transactions,
+            receipts: fixture.receipts()?,
+            blob_sidecars: fixture.blob_sidecars.clone().map(|b| b.data).unwrap_or_default(),
+            header: block.header,
+        };
+        let hash = recorded.header.hash_slow();
+        self.numbers.insert(recorded.header.number, hash);
+        self.blocks.insert(hash, recorded);
+        Ok(hash)
+    }
+
+    /// Returns the recorded block with the given hash.
+    fn block(&self, hash: B256) -> Result<&RecordedBlock, FixtureProviderError> {
+        self.blocks.get(&hash).ok_or(FixtureProviderError::BlockNotFound(hash))
+    }
+}
+
+/// An error for the [FixtureProvider].
+#[derive(Error, Debug)]
+pub enum FixtureProviderError {
+    /// The fixture could not be read or written.
+    #[error("Fixture IO error: {0}")]
+    Io(#[from] std::io::Error),
+    /// The fixture could not be (de)serialized.
+    #[error("Fixture serialization error: {0}")]
+    Serde(#[from] serde_json::Error),
+    /// A raw response in the fixture could not be decoded.
+    #[error("Fixture RLP error: {0}")]
+    Rlp(alloy_rlp::Error),
+    /// The block with the given hash was not recorded.
+    #[error("Block not found: {0}")]
+    BlockNotFound(B256),
+    /// The block with the given number was not recorded.
+    #[error("Block number not found: {0}")]
+    BlockNumberNotFound(u64),
+    /// The blob with the given versioned hash was not recorded.
+    #[error("Blob not found: {0}")]
+    BlobNotFound(B256),
+}
+
+impl From<FixtureProviderError> for PipelineErrorKind {
+    fn from(val: FixtureProviderError) -> Self {
+        PipelineError::Provider(val.to_string()).temp()
+    }
+}
+
+#[async_trait]
+impl ChainProvider for FixtureProvider {
+    type Error = FixtureProviderError;
+
+    async fn header_by_hash(&mut self, hash: B256) -> Result<Header, Self::Error> {
+        Ok(self.block(hash)?.header.clone())
+    }
+
+    async fn block_info_by_number(&mut self, number: u64) -> Result<BlockInfo, Self::Error> {
+        let hash =
+            self.numbers.get(&number).ok_or(FixtureProviderError::BlockNumberNotFound(number))?;
+        Ok(self.block(*hash)?.block_info(*hash))
+    }
+
+    async fn receipts_by_hash(&mut self, hash: B256) -> Result<Vec<Receipt>, Self::Error> {
+        Ok(self.block(hash)?.receipts.clone())
+    }
+
+    async fn block_info_and_transactions_by_hash(
+        &mut self,
+        hash: B256,
+    ) -> Result<(BlockInfo, Vec<TxEnvelope>), Self::Error> {
+        let block = self.block(hash)?;
+        Ok((block.block_info(hash), block.transactions.clone()))
+    }
+}
+
+#[async_trait]
+impl BlobProvider for FixtureProvider {
+    type Error = FixtureProviderError;
+
+    async fn get_blobs(
+        &mut self,
+        block_ref: &BlockInfo,
+        blob_hashes: &[IndexedBlobHash],
+    ) -> Result<Vec<Box<Blob>>, Self::Error> {
+        let block = self.block(block_ref.hash)?;
+        blob_hashes
+            .iter()
+            .map(|blob_hash| {
+                block
+                    .blob_sidecars
+                    .iter()
+                    .find(|sidecar| {
+                        sidecar.index == blob_hash.index &&
+                            sidecar_versioned_hash(sidecar) == blob_hash.hash
+                    })
+                    .map(|sidecar| sidecar.blob.clone())
+                    .ok_or(FixtureProviderError::BlobNotFound(blob_hash.hash))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sources::encode_blob_data;
+    use alloy_consensus::{BlockBody, Eip658Value, ReceiptEnvelope};
+    use alloy_eips::{eip2718::Encodable2718, eip4844::Bytes48};
+    use alloy_rlp::Encodable;
+
+    fn test_sidecar() -> BlobData {
+        BlobData {
+            index: 0,
+            blob: Box::new(encode_blob_data(b"kona").unwrap()),
+            kzg_commitment: Bytes48::repeat_byte(0xAB),
+            kzg_proof: Bytes48::ZERO,
+            signed_block_header: Default::default(),
+            kzg_commitment_inclusion_proof: Vec::new(),
+        }
+    }
+
+    fn test_fixture() -> L1BlockFixture {
+        let header = Header { number: 7, timestamp: 84, ..Default::default() };
+        let block = Block::<TxEnvelope> { header, body: BlockBody::default() };
+        let mut raw_block = Vec::new();
+        block.encode(&mut raw_block);
+
+        let receipt = Receipt { status: Eip658Value::Eip658(true), ..Default::default() };
+        let raw_receipt = ReceiptEnvelope::Eip1559(receipt.with_bloom()).encoded_2718();
+
+        L1BlockFixture {
+            raw_block: raw_block.into(),
+            raw_receipts: vec![raw_receipt.into()],
+            blob_sidecars: Some(BeaconBlobBundle::new(vec![test_sidecar()])),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fixture_provider_from_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let fixture = test_fixture();
+        fixture.save(dir.path()).unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "not a fixture").unwrap();
+
+        let mut provider = FixtureProvider::from_dir(dir.path()).unwrap();
+        let header = fixture.block().unwrap().header;
+        let hash = header.hash_slow();
+        assert_eq!(provider.header_by_hash(hash).await.unwrap(), header);
+
+        let receipts = provider.receipts_by_hash(hash).await.unwrap();
+        assert_eq!(receipts.len(), 1);
+        assert_eq!(receipts[0].status, Eip658Value::Eip658(true));
+
+        let block_info = provider.block_info_by_number(7).await.unwrap();
+        assert_eq!(block_info.hash, hash);
+        assert_eq!(block_info.timestamp, 84);
+        assert!(provider.block_info_by_number(8).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_fixture_provider_get_blobs() {
+        let fixture = test_fixture();
+        let sidecar = test_sidecar();
+        let mut provider = FixtureProvider::default();
+        let hash = provider.insert_block(&fixture).unwrap();
+
+        let block_ref = BlockInfo { hash, ..Default::default() };
+        let blob_hash = IndexedBlobHash { index: 0, hash: sidecar_versioned_hash(&sidecar) };
+        let blobs = provider.get_blobs(&block_ref, &[blob_hash]).await.unwrap();
+        assert_eq!(blobs, vec![sidecar.blob.clone()]);
+
+        let missing = IndexedBlobHash { index: 1, hash: sidecar_versioned_hash(&sidecar) };
+        assert!(matches!(
+            provider.get_blobs(&block_ref, &[missing]).await,
+            Err(FixtureProviderError::BlobNotFound(_))
+        ));
+    }
+}
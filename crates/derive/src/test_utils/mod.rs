@@ -10,6 +10,22 @@ pub use pipeline::{
 mod blob_provider;
 pub use blob_provider::TestBlobProvider;
 
+#[cfg(feature = "test-fixtures")]
+mod fixture_provider;
+#[cfg(feature = "test-fixtures")]
+pub use fixture_provider::{
+    sidecar_versioned_hash, FixtureProvider, FixtureProviderError, L1BlockFixture,
+};
+
+#[cfg(feature = "test-fixtures")]
+mod pipeline_fixture;
+#[cfg(feature = "test-fixtures")]
+pub use pipeline_fixture::{
+    pipeline_origin, read_golden_attributes, write_golden_attributes, GoldenAttributes,
+    PipelineFixture, PipelineFixtureError, GOLDEN_ATTRIBUTES_FILE, L1_FIXTURE_DIR,
+    PIPELINE_FIXTURE_FILE,
+};
+
 mod chain_providers;
 pub use chain_providers::{TestChainProvider, TestL2ChainProvider, TestProviderError};
 
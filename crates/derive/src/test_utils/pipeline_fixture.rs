@@ -0,0 +1,285 @@
+//! Contains the [PipelineFixture], which runs the derivation pipeline over a recorded L1 range and
+//! compares the derived attributes against golden outputs.
+
+use crate::{
+    attributes::StatefulAttributesBuilder,
+    errors::{PipelineError, PipelineErrorKind, ResetError},
+    pipeline::PipelineBuilder,
+    sources::EthereumDataSource,
+    test_utils::{FixtureProvider, FixtureProviderError, TestL2ChainProvider},
+    traits::{BlobProvider, ChainProvider, OriginProvider, Pipeline, SignalReceiver},
+    types::{ActivationSignal, ResetSignal, StepResult},
+};
+use alloc::{sync::Arc, vec::Vec};
+use alloy_primitives::map::HashMap;
+use core::fmt::Debug;
+use maili_genesis::{RollupConfig, SystemConfig};
+use maili_protocol::{BlockInfo, L2BlockInfo};
+use maili_rpc::OpAttributesWithParent;
+use op_alloy_rpc_types_engine::OpPayloadAttributes;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use thiserror::Error;
+
+/// The name of the [PipelineFixture] file in a fixture directory.
+pub const PIPELINE_FIXTURE_FILE: &str = "pipeline.json";
+
+/// The name of the directory holding the recorded L1 blocks in a fixture directory.
+pub const L1_FIXTURE_DIR: &str = "l1";
+
+/// The name of the golden derived attributes file in a fixture directory.
+pub const GOLDEN_ATTRIBUTES_FILE: &str = "attributes.json";
+
+/// A recorded derivation range.
+///
+/// A fixture directory holds the [PipelineFixture] in [PIPELINE_FIXTURE_FILE], the recorded L1
+/// blocks of the range in [L1_FIXTURE_DIR], served by a [FixtureProvider], and the golden
+/// [OpAttributesWithParent] derived from them in [GOLDEN_ATTRIBUTES_FILE].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PipelineFixture {
+    /// The rollup config of the chain.
+    pub rollup_config: RollupConfig,
+    /// The L1 block to start deriving from.
+    pub l1_origin: BlockInfo,
+    /// The L2 safe head to start deriving from.
+    pub l2_safe_head: L2BlockInfo,
+    /// The canonical L2 blocks following the safe head, in order, which the derived attributes
+    /// are built on top of.
+    pub l2_blocks: Vec<L2BlockInfo>,
+    /// The system configs of the safe head and the canonical L2 blocks, by block number.
+    pub system_configs: HashMap<u64, SystemConfig>,
+}
+
+impl PipelineFixture {
+    /// Reads the [PipelineFixture] of the given fixture directory.
+    pub fn read(dir: impl AsRef<Path>) -> Result<Self, PipelineFixtureError> {
+        let fixture = std::fs::read(dir.as_ref().join(PIPELINE_FIXTURE_FILE))?;
+        Ok(serde_json::from_slice(&fixture)?)
+    }
+
+    /// Writes the [PipelineFixture] to the given fixture directory.
+    pub fn write(&self, dir: impl AsRef<Path>) -> Result<(), PipelineFixtureError> {
+        std::fs::write(dir.as_ref().join(PIPELINE_FIXTURE_FILE), serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Returns a [TestL2ChainProvider] serving the safe head, the canonical L2 blocks and their
+    /// system configs.
+    pub fn l2_chain_provider(&self) -> TestL2ChainProvider {
+        let blocks = core::iter::once(self.l2_safe_head).chain(self.l2_blocks.iter().copied());
+        TestL2ChainProvider::new(blocks.collect(), Vec::new(), self.system_configs.clone())
+    }
+
+    /// Runs the derivation pipeline over the recorded L1 blocks of the fixture directory.
+    ///
+    /// See [PipelineFixture::derive_with].
+    pub async fn derive(
+        &self,
+        dir: impl AsRef<Path>,
+    ) -> Result<Vec<OpAttributesWithParent>, PipelineFixtureError> {
+        self.derive_with(FixtureProvider::from_dir(dir.as_ref().join(L1_FIXTURE_DIR))?).await
+    }
+
+    /// Runs the derivation pipeline over the L1 blocks served by the given provider, building each
+    /// derived payload on top of the next canonical L2 block.
+    ///
+    /// Derivation stops once attributes have been derived for every canonical L2 block, or when
+    /// the provider runs out of L1 blocks. Resets are handled in the same way as by the derivation
+    /// driver.
+    pub async fn derive_with<P>(
+        &self,
+        l1_provider: P,
+    ) -> Result<Vec<OpAttributesWithParent>, PipelineFixtureError>
+    where
+        P: ChainProvider + BlobProvider + Clone + Send + Sync + Debug,
+    {
+        let cfg = Arc::new(self.rollup_config.clone());
+        let l2_provider = self.l2_chain_provider();
+
+        let dap =
+            EthereumDataSource::new_from_parts(l1_provider.clone(), l1_provider.clone(), &cfg);
+        let builder =
+            StatefulAttributesBuilder::new(cfg.clone(), l2_provider.clone(), l1_provider.clone());
+        let mut pipeline = PipelineBuilder::new()
+            .rollup_config(cfg)
+            .dap_source(dap)
+            .l2_chain_provider(l2_provider)
+            .chain_provider(l1_provider)
+            .builder(builder)
+            .origin(self.l1_origin)
+            .build();
+
+        // Start from the system config of the safe head, as the rollup node does on startup.
+        let system_config =
+            pipeline.system_config_by_number(self.l2_safe_head.block_info.number).await?;
+        pipeline
+            .signal(
+                ResetSignal {
+                    l2_safe_head: self.l2_safe_head,
+                    l1_origin: self.l1_origin,
+                    system_config: Some(system_config),
+                }
+                .signal(),
+            )
+            .await?;
+
+        let mut cursor = self.l2_safe_head;
+        let mut derived = Vec::with_capacity(self.l2_blocks.len());
+        while derived.len() < self.l2_blocks.len() {
+            match pipeline.step(cursor).await {
+                StepResult::PreparedAttributes => {
+                    let Some(attributes) = pipeline.next() else { continue };
+                    derived.push(attributes);
+                    cursor = self.l2_blocks[derived.len() - 1];
+                }
+                StepResult::AdvancedOrigin => {}
+                // The provider has run out of L1 blocks.
+                StepResult::OriginAdvanceErr(PipelineErrorKind::Temporary(
+                    PipelineError::Provider(_),
+                )) => break,
+                StepResult::OriginAdvanceErr(e) | StepResult::StepFailed(e) => match e {
+                    PipelineErrorKind::Temporary(_) => {}
+                    PipelineErrorKind::Reset(e) => {
+                        let system_config =
+                            pipeline.system_config_by_number(cursor.block_info.number).await?;
+                        let l1_origin =
+                            pipeline.origin().ok_or(PipelineError::MissingOrigin.crit())?;
+                        let signal = if matches!(e, ResetError::HoloceneActivation) {
+                            ActivationSignal {
+                                l2_safe_head: cursor,
+                                l1_origin,
+                                system_config: Some(system_config),
+                            }
+                            .signal()
+                        } else {
+                            ResetSignal {
+                                l2_safe_head: cursor,
+                                l1_origin,
+                                system_config: Some(system_config),
+                            }
+                            .signal()
+                        };
+                        pipeline.signal(signal).await?;
+                    }
+                    PipelineErrorKind::Critical(_) => return Err(e.into()),
+                },
+            }
+        }
+
+        Ok(derived)
+    }
+}
+
+/// Returns the starting L1 origin of the pipeline for the given L2 safe head, walking back from
+/// its L1 origin by the channel timeout so that the channels of the following L2 blocks are fully
+/// captured, as the derivation driver does.
+pub async fn pipeline_origin<P: ChainProvider>(
+    rollup_config: &RollupConfig,
+    l2_safe_head: &L2BlockInfo,
+    l1_provider: &mut P,
+) -> Result<BlockInfo, P::Error> {
+    let channel_timeout = rollup_config.channel_timeout(l2_safe_head.block_info.timestamp);
+    let number = l2_safe_head
+        .l1_origin
+        .number
+        .saturating_sub(channel_timeout)
+        .max(rollup_config.genesis.l1.number);
+    l1_provider.block_info_by_number(number).await
+}
+
+/// The serialized form of a golden [OpAttributesWithParent].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GoldenAttributes {
+    /// The payload attributes.
+    pub attributes: OpPayloadAttributes,
+    /// The parent block reference.
+    pub parent: L2BlockInfo,
+    /// Whether the batch is the last in its span.
+    pub is_last_in_span: bool,
+}
+
+impl From<OpAttributesWithParent> for GoldenAttributes {
+    fn from(attributes: OpAttributesWithParent) -> Self {
+        Self {
+            attributes: attributes.attributes,
+            parent: attributes.parent,
+            is_last_in_span: attributes.is_last_in_span,
+        }
+    }
+}
+
+impl From<GoldenAttributes> for OpAttributesWithParent {
+    fn from(golden: GoldenAttributes) -> Self {
+        Self::new(golden.attributes, golden.parent, golden.is_last_in_span)
+    }
+}
+
+/// Reads the golden [OpAttributesWithParent] of the given fixture directory.
+pub fn read_golden_attributes(
+    dir: impl AsRef<Path>,
+) -> Result<Vec<OpAttributesWithParent>, PipelineFixtureError> {
+    let golden: Vec<GoldenAttributes> =
+        serde_json::from_slice(&std::fs::read(dir.as_ref().join(GOLDEN_ATTRIBUTES_FILE))?)?;
+    Ok(golden.into_iter().map(Into::into).collect())
+}
+
+/// Writes the golden [OpAttributesWithParent] to the given fixture directory.
+pub fn write_golden_attributes(
+    dir: impl AsRef<Path>,
+    attributes: &[OpAttributesWithParent],
+) -> Result<(), PipelineFixtureError> {
+    let golden = attributes.iter().cloned().map(GoldenAttributes::from).collect::<Vec<_>>();
+    std::fs::write(dir.as_ref().join(GOLDEN_ATTRIBUTES_FILE), serde_json::to_vec_pretty(&golden)?)?;
+    Ok(())
+}
+
+/// An error for the [PipelineFixture].
+#[derive(Error, Debug)]
+pub enum PipelineFixtureError {
+    /// The fixture could not be read or written.
+    #[error("Fixture IO error: {0}")]
+    Io(#[from] std::io::Error),
+    /// The fixture could not be (de)serialized.
+    #[error("Fixture serialization error: {0}")]
+    Serde(#[from] serde_json::Error),
+    /// The recorded L1 blocks could not be loaded.
+    #[error(transparent)]
+    Provider(#[from] FixtureProviderError),
+    /// The pipeline failed to derive the attributes.
+    #[error("Pipeline error: {0}")]
+    Pipeline(#[from] PipelineErrorKind),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    /// Returns the fixture directories under `testdata/pipeline`.
+    fn fixture_dirs() -> Vec<PathBuf> {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("testdata/pipeline");
+        let mut dirs = std::fs::read_dir(root)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.is_dir())
+            .collect::<Vec<_>>();
+        dirs.sort();
+        dirs
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_fixtures_match_golden_attributes() {
+        let dirs = fixture_dirs();
+        assert!(!dirs.is_empty());
+
+        for dir in dirs {
+            let fixture = PipelineFixture::read(&dir).unwrap();
+            let derived = fixture.derive(&dir).await.unwrap();
+            let golden = read_golden_attributes(&dir).unwrap();
+            assert_eq!(derived, golden, "{}", dir.display());
+            assert_eq!(derived.len(), fixture.l2_blocks.len(), "{}", dir.display());
+        }
+    }
+}
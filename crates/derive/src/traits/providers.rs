@@ -31,6 +31,11 @@ pub trait ChainProvider {
         &mut self,
         hash: B256,
     ) -> Result<(BlockInfo, Vec<TxEnvelope>), Self::Error>;
+
+    /// Called by the [L1Traversal](crate::stages::L1Traversal) stage when the pipeline is reset,
+    /// e.g. after an L1 reorg. Providers that hold data by block number must drop it, as it may
+    /// no longer be canonical. Does nothing by default.
+    fn reset(&mut self) {}
 }
 
 /// Describes the functionality of a data source that fetches safe blocks.
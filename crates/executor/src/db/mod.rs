@@ -1,7 +1,11 @@
 //! This module contains an implementation of an in-memory Trie DB for [revm], that allows for
 //! incremental updates through fetching node preimages on the fly during execution.
 
-use crate::errors::{TrieDBError, TrieDBResult};
+use crate::{
+    errors::{TrieDBError, TrieDBResult},
+    trace::ProfiledProvider,
+    BlockProfiler,
+};
 use alloc::{string::ToString, vec::Vec};
 use alloy_consensus::{Header, Sealed, EMPTY_ROOT_HASH};
 use alloy_primitives::{keccak256, Address, B256, U256};
@@ -86,7 +90,7 @@ where
     /// The parent block hash of the current block.
     parent_block_header: Sealed<Header>,
     /// The [TrieDBProvider]
-    fetcher: F,
+    fetcher: ProfiledProvider<F>,
     /// The [TrieHinter]
     hinter: H,
 }
@@ -102,7 +106,7 @@ where
             root_node: TrieNode::new_blinded(root),
             storage_roots: Default::default(),
             parent_block_header,
-            fetcher,
+            fetcher: ProfiledProvider::new(fetcher),
             hinter,
         }
     }
@@ -122,6 +126,11 @@ where
         &self.storage_roots
     }
 
+    /// Sets the [BlockProfiler] recording the preimages fetched through the [TrieDBProvider].
+    pub(crate) fn set_profiler(&mut self, profiler: BlockProfiler) {
+        self.fetcher.set_profiler(profiler);
+    }
+
     /// Returns a reference to the current parent block header of the trie DB.
     pub const fn parent_block_header(&self) -> &Sealed<Header> {
         &self.parent_block_header
@@ -195,6 +204,42 @@ where
             .map(Some)
     }
 
+    /// Fetches the value of a storage slot of an account from the trie DB, reflecting the
+    /// changesets applied with [Self::state_root].
+    ///
+    /// ## Takes
+    /// - `address`: The address of the account.
+    /// - `slot`: The storage slot.
+    /// - `block_number`: The number of the block to hint the account proof for.
+    ///
+    /// ## Returns
+    /// - `Ok(U256)`: The value of the storage slot, or zero if the account or slot does not exist.
+    /// - `Err(_)`: If the storage slot could not be fetched.
+    pub fn get_storage_slot(
+        &mut self,
+        address: &Address,
+        slot: U256,
+        block_number: u64,
+    ) -> TrieDBResult<U256> {
+        if !self.storage_roots.contains_key(address) {
+            let Some(trie_account) = self.get_trie_account(address, block_number)? else {
+                return Ok(U256::ZERO);
+            };
+            self.storage_roots.insert(*address, TrieNode::new_blinded(trie_account.storage_root));
+        }
+        let Some(storage_root) = self.storage_roots.get_mut(address) else {
+            return Ok(U256::ZERO);
+        };
+
+        let hashed_slot_key = keccak256(slot.to_be_bytes::<32>().as_slice());
+        storage_root.open(&Nibbles::unpack(hashed_slot_key), &self.fetcher)?.map_or(
+            Ok(U256::ZERO),
+            |slot_value| {
+                Ok(U256::decode(&mut slot_value.as_ref()).map_err(TrieNodeError::RLPError)?)
+            },
+        )
+    }
+
     /// Modifies the accounts in the storage trie with the given [BundleState] changeset.
     ///
     /// ## Takes
@@ -290,7 +335,7 @@ where
         storage_root: &mut TrieNode,
         hashed_key: B256,
         value: &StorageSlot,
-        fetcher: &F,
+        fetcher: &ProfiledProvider<F>,
         hinter: &H,
     ) -> TrieDBResult<()> {
         if !value.is_changed() {
@@ -1,6 +1,11 @@
 //! Errors for the `kona-executor` crate.
 
-use alloc::string::String;
+use crate::BlockDiff;
+use alloc::{
+    boxed::Box,
+    string::{String, ToString},
+};
+use alloy_primitives::B256;
 use kona_mpt::TrieNodeError;
 use revm::primitives::EVMError;
 use thiserror::Error;
@@ -46,6 +51,9 @@ pub enum ExecutorError {
     /// Missing the executor.
     #[error("Missing the executor")]
     MissingExecutor,
+    /// The produced block does not match the expected block.
+    #[error("Block mismatch: {0}")]
+    BlockMismatch(Box<BlockDiff>),
 }
 
 /// A [Result] type for the [ExecutorError] enum.
@@ -72,3 +80,27 @@ pub enum TrieDBError {
     #[error("Trie provider error: {0}")]
     Provider(String),
 }
+
+/// An error type for the [WitnessProvider] and the [WitnessRecorder].
+///
+/// [WitnessProvider]: crate::WitnessProvider
+/// [WitnessRecorder]: crate::WitnessRecorder
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum WitnessError {
+    /// The preimage is missing from the witness.
+    #[error("Preimage not found in witness: {0}")]
+    MissingPreimage(B256),
+    /// The preimage could not be decoded.
+    #[error("Failed to decode preimage: {0}")]
+    Rlp(alloy_rlp::Error),
+    /// The recorded provider failed to serve the preimage.
+    #[error("Witness provider error: {0}")]
+    Provider(String),
+}
+
+impl WitnessError {
+    /// Creates a [WitnessError::Provider] from an error of the recorded provider.
+    pub fn provider(err: impl ToString) -> Self {
+        Self::Provider(err.to_string())
+    }
+}
@@ -0,0 +1,295 @@
+//! Local block building with the [StatelessL2BlockExecutor], including transactions from a
+//! candidate pool on top of the payload's transactions.
+
+use super::{ExecutionArtifacts, StatelessL2BlockExecutor};
+use crate::{db::TrieDB, trace::TransactionHooks, ExecutorError, ExecutorResult, TrieDBProvider};
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use alloy_consensus::Transaction;
+use alloy_eips::eip2718::{Decodable2718, Eip2718Error};
+use alloy_primitives::{keccak256, Bytes, SignatureError};
+use kona_mpt::TrieHinter;
+use op_alloy_consensus::{OpReceiptEnvelope, OpTxEnvelope, OpTxType};
+use op_alloy_rpc_types_engine::OpPayloadAttributes;
+use revm::{
+    db::State,
+    primitives::{EVMError, ExecutionResult, InvalidTransaction},
+    DatabaseCommit, Evm, L1_BLOCK_CONTRACT,
+};
+use thiserror::Error;
+
+/// A block built by [StatelessL2BlockExecutor::build_block].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BuiltBlock {
+    /// The sealed header and receipts of the block.
+    pub artifacts: ExecutionArtifacts,
+    /// The encoded transactions of the block, in order of execution.
+    pub transactions: Vec<Bytes>,
+    /// The pool transactions that were not included in the block.
+    pub skipped: Vec<SkippedTransaction>,
+}
+
+/// A pool transaction that was not included in a [BuiltBlock].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkippedTransaction {
+    /// The encoded transaction.
+    pub transaction: Bytes,
+    /// The reason the transaction was skipped.
+    pub reason: SkipReason,
+}
+
+/// The reason a pool transaction was not included in a [BuiltBlock].
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum SkipReason {
+    /// The transaction could not be decoded.
+    #[error("Failed to decode transaction: {0}")]
+    Decode(String),
+    /// Deposit transactions can only be included through the payload attributes.
+    #[error("Deposit transactions cannot be included from the pool")]
+    Deposit,
+    /// The transaction's gas limit exceeds the gas remaining in the block.
+    #[error("Transaction gas limit {gas_limit} exceeds the available block gas {available}")]
+    GasLimit {
+        /// The gas limit of the transaction.
+        gas_limit: u64,
+        /// The gas remaining in the block.
+        available: u64,
+    },
+    /// The transaction type is not supported at the block's timestamp.
+    #[error("Unsupported transaction type: {0}")]
+    UnsupportedTransactionType(u8),
+    /// The transaction's signer could not be recovered.
+    #[error("Invalid signature: {0}")]
+    Signature(String),
+    /// The transaction is invalid in the current state, e.g. due to its nonce or balance.
+    #[error("Invalid transaction: {0}")]
+    Invalid(InvalidTransaction),
+}
+
+impl From<Eip2718Error> for SkipReason {
+    fn from(err: Eip2718Error) -> Self {
+        Self::Decode(err.to_string())
+    }
+}
+
+impl From<SignatureError> for SkipReason {
+    fn from(err: SignatureError) -> Self {
+        Self::Signature(err.to_string())
+    }
+}
+
+/// The outcome of executing the transactions of a block.
+#[derive(Debug)]
+pub(crate) struct ExecutedTransactions {
+    /// The receipts of the executed transactions.
+    pub(crate) receipts: Vec<OpReceiptEnvelope>,
+    /// The cumulative gas used by the executed transactions.
+    pub(crate) cumulative_gas_used: u64,
+    /// The pool transactions that were included after the payload's transactions.
+    pub(crate) included: Vec<Bytes>,
+    /// The pool transactions that were skipped.
+    pub(crate) skipped: Vec<SkippedTransaction>,
+}
+
+impl<F, H> StatelessL2BlockExecutor<'_, F, H>
+where
+    F: TrieDBProvider,
+    H: TrieHinter,
+{
+    /// Builds a block on top of the parent header, as a sequencer would.
+    ///
+    /// The transactions of the payload, e.g. its deposits, are executed first and must all be
+    /// valid. Then, the transactions from the `pool` are included greedily, in order, as long as
+    /// they fit in the block's gas limit. Pool transactions that cannot be decoded, are deposits,
+    /// or are invalid in the current state are skipped rather than aborting the block. Reverted
+    /// transactions are valid, and are included.
+    ///
+    /// Like [Self::execute_payload], the executor's state advances to the built block.
+    pub fn build_block(
+        &mut self,
+        mut payload: OpPayloadAttributes,
+        pool: &[Bytes],
+    ) -> ExecutorResult<BuiltBlock> {
+        payload.transactions.get_or_insert_with(Vec::new);
+        self.execute_block(payload, pool)
+    }
+
+    /// Executes a transaction from the pool, committing its state changes if it is valid.
+    ///
+    /// ## Returns
+    /// - `Ok(Ok(_))`: The type and result of the executed transaction.
+    /// - `Ok(Err(_))`: The reason the transaction was skipped.
+    /// - `Err(_)`: If the transaction could not be executed due to a database error.
+    pub(crate) fn execute_pool_transaction<EXT: TransactionHooks>(
+        evm: &mut Evm<'_, EXT, State<&mut TrieDB<F, H>>>,
+        is_isthmus: bool,
+        raw_transaction: &Bytes,
+        available_gas: u64,
+    ) -> ExecutorResult<Result<(OpTxType, ExecutionResult), SkipReason>> {
+        let transaction = match OpTxEnvelope::decode_2718(&mut raw_transaction.as_ref()) {
+            Ok(transaction) => transaction,
+            Err(e) => return Ok(Err(e.into())),
+        };
+        if transaction.is_deposit() {
+            return Ok(Err(SkipReason::Deposit));
+        }
+        if transaction.gas_limit() > available_gas {
+            return Ok(Err(SkipReason::GasLimit {
+                gas_limit: transaction.gas_limit(),
+                available: available_gas,
+            }));
+        }
+        if !is_isthmus && matches!(transaction, OpTxEnvelope::Eip7702(_)) {
+            return Ok(Err(SkipReason::UnsupportedTransactionType(transaction.tx_type() as u8)));
+        }
+
+        *evm.tx_mut() = match Self::prepare_tx_env(&transaction, raw_transaction) {
+            Ok(tx_env) => tx_env,
+            Err(ExecutorError::SignatureError(e)) => return Ok(Err(e.into())),
+            Err(e) => return Err(e),
+        };
+
+        // The EVM expects the L1 block account to be loaded before it reads the L1 block info while
+        // validating the transaction, which is otherwise done by the deposit transaction at the
+        // start of the block.
+        evm.db_mut().load_cache_account(L1_BLOCK_CONTRACT)?;
+
+        // Validate the transaction against the current state before notifying the hooks, so that
+        // the hooks only observe transactions that are executed. The transaction is validated
+        // again when it is executed, as the L1 block info fetched during validation is cleared
+        // afterwards.
+        if let Err(e) = evm.preverify_transaction() {
+            return match e {
+                EVMError::Transaction(e) => Ok(Err(SkipReason::Invalid(e))),
+                e => Err(ExecutorError::ExecutionError(e)),
+            };
+        }
+
+        let tx_hash = keccak256(raw_transaction);
+        evm.context.external.transaction_start(tx_hash);
+        let result_and_state = evm.transact().map_err(ExecutorError::ExecutionError)?;
+        evm.db_mut().commit(result_and_state.state);
+        evm.context.external.transaction_end(tx_hash, &result_and_state.result);
+
+        Ok(Ok((transaction.tx_type(), result_and_state.result)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{CallTracer, NoopTrieDBProvider, UpgradeAction, UpgradeRegistry};
+    use alloy_consensus::{Sealable, SignableTransaction, TxEip1559};
+    use alloy_eips::eip2718::Encodable2718;
+    use alloy_primitives::{address, Address, PrimitiveSignature as Signature, TxKind};
+    use alloy_rpc_types_engine::PayloadAttributes;
+    use kona_mpt::NoopTrieHinter;
+    use maili_genesis::RollupConfig;
+    use op_alloy_consensus::TxDeposit;
+
+    const REVERTER: Address = address!("0000000000000000000000000000000000000bad");
+
+    /// `PUSH1 0 PUSH1 0 REVERT`
+    const REVERT: [u8; 5] = [0x60, 0x00, 0x60, 0x00, 0xfd];
+
+    /// Returns an encoded EIP-1559 transaction with the given nonce, gas limit and recipient. The
+    /// base fee of the test blocks is zero, so the unfunded signer can pay for the transaction. The
+    /// signature is fixed, so every distinct transaction is sent by a different account.
+    fn pool_transaction(config: &RollupConfig, nonce: u64, gas_limit: u64, to: Address) -> Bytes {
+        let tx = TxEip1559 {
+            chain_id: config.l2_chain_id,
+            nonce,
+            gas_limit,
+            to: TxKind::Call(to),
+            ..Default::default()
+        };
+        OpTxEnvelope::Eip1559(tx.into_signed(Signature::test_signature())).encoded_2718().into()
+    }
+
+    fn payload(gas_limit: u64) -> OpPayloadAttributes {
+        OpPayloadAttributes {
+            payload_attributes: PayloadAttributes { timestamp: 1, ..Default::default() },
+            gas_limit: Some(gas_limit),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_build_block_skips_invalid_pool_transactions() {
+        let config = RollupConfig::default();
+        let mut executor =
+            StatelessL2BlockExecutor::builder(&config, NoopTrieDBProvider, NoopTrieHinter).build();
+
+        let deposit = OpTxEnvelope::Deposit(TxDeposit::default().seal_slow()).encoded_2718();
+        let pool = [Bytes::from_static(&[0xFF, 0x00]), Bytes::from(deposit)];
+
+        let built = executor.build_block(payload(30_000_000), &pool).unwrap();
+        assert!(built.transactions.is_empty());
+        assert!(built.artifacts.receipts.is_empty());
+        assert_eq!(built.artifacts.block_header.number, 1);
+        assert!(matches!(built.skipped[0].reason, SkipReason::Decode(_)));
+        assert_eq!(built.skipped[1].reason, SkipReason::Deposit);
+    }
+
+    #[test]
+    fn test_build_block_includes_pool_transactions_greedily() {
+        let config = RollupConfig::default();
+        let mut tracer = CallTracer::default();
+        let mut executor =
+            StatelessL2BlockExecutor::builder(&config, NoopTrieDBProvider, NoopTrieHinter)
+                .with_upgrade_registry(UpgradeRegistry::new().with_upgrade(
+                    1,
+                    [UpgradeAction::DeployCode {
+                        address: REVERTER,
+                        code: Bytes::from_static(&REVERT),
+                    }],
+                ))
+                .with_inspector(&mut tracer)
+                .build();
+
+        let reverted = pool_transaction(&config, 0, 50_000, REVERTER);
+        let too_large = pool_transaction(&config, 0, 80_000, Address::ZERO);
+        let transfer = pool_transaction(&config, 0, 21_000, Address::ZERO);
+        let replayed = reverted.clone();
+        let pool = [reverted.clone(), too_large.clone(), transfer.clone(), replayed.clone()];
+
+        let built = executor.build_block(payload(100_000), &pool).unwrap();
+
+        // The reverted transaction is valid and included, and the block continues past the
+        // transaction that does not fit in the remaining gas.
+        assert_eq!(built.transactions, vec![reverted.clone(), transfer.clone()]);
+        let [first, second] = &built.artifacts.receipts[..] else {
+            panic!("expected two receipts")
+        };
+        assert!(!first.is_success());
+        assert!(second.is_success());
+        assert_eq!(second.cumulative_gas_used(), built.artifacts.block_header.gas_used);
+
+        assert_eq!(
+            built.skipped,
+            vec![
+                SkippedTransaction {
+                    transaction: too_large,
+                    reason: SkipReason::GasLimit {
+                        gas_limit: 80_000,
+                        available: 100_000 - first.cumulative_gas_used(),
+                    },
+                },
+                SkippedTransaction {
+                    transaction: replayed,
+                    reason: SkipReason::Invalid(InvalidTransaction::NonceTooLow {
+                        tx: 0,
+                        state: 1
+                    }),
+                },
+            ]
+        );
+
+        // Only the included transactions are traced.
+        drop(executor);
+        let traced = tracer.traces().iter().map(|(hash, _)| *hash).collect::<Vec<_>>();
+        assert_eq!(traced, vec![keccak256(&reverted), keccak256(&transfer)]);
+    }
+}
@@ -1,15 +1,23 @@
 //! Contains the builder pattern for the [StatelessL2BlockExecutor].
 
 use super::StatelessL2BlockExecutor;
-use crate::db::{TrieDB, TrieDBProvider};
+use crate::{
+    db::{TrieDB, TrieDBProvider},
+    BlockProfiler, DynKonaInspector, KonaEvmContext, UpgradeRegistry,
+};
 use alloy_consensus::{Header, Sealable, Sealed};
 use kona_mpt::TrieHinter;
 use maili_genesis::RollupConfig;
-use revm::{db::State, handler::register::EvmHandler};
+use revm::{
+    db::{CacheState, State},
+    handler::register::EvmHandler,
+};
 
 /// A type alias for the [revm::handler::register::HandleRegister] for kona's block executor.
+///
+/// The register is applied to the EVM whether or not a [DynKonaInspector] is attached.
 pub type KonaHandleRegister<F, H> =
-    for<'i> fn(&mut EvmHandler<'i, (), &mut State<&mut TrieDB<F, H>>>);
+    for<'i, 'c> fn(&mut EvmHandler<'i, KonaEvmContext<'c, F, H>, State<&mut TrieDB<F, H>>>);
 
 /// The builder pattern for the [StatelessL2BlockExecutor].
 #[derive(Debug)]
@@ -28,6 +36,14 @@ where
     parent_header: Option<Sealed<Header>>,
     /// The [KonaHandleRegister] to use during execution.
     handler_register: Option<KonaHandleRegister<F, H>>,
+    /// The [DynKonaInspector] to attach to the EVM during execution.
+    inspector: Option<&'a mut DynKonaInspector<'a, F, H>>,
+    /// Whether to retain the account and storage cache across executed blocks.
+    persistent_cache: bool,
+    /// The [UpgradeRegistry] of the chain's custom hardfork upgrades and precompiles.
+    upgrades: UpgradeRegistry,
+    /// The [BlockProfiler] to profile execution with.
+    profiler: Option<BlockProfiler>,
 }
 
 impl<'a, F, H> StatelessL2BlockExecutorBuilder<'a, F, H>
@@ -37,7 +53,17 @@ where
 {
     /// Instantiate a new builder with the given [RollupConfig].
     pub fn new(config: &'a RollupConfig, provider: F, hinter: H) -> Self {
-        Self { config, provider, hinter, parent_header: None, handler_register: None }
+        Self {
+            config,
+            provider,
+            hinter,
+            parent_header: None,
+            handler_register: None,
+            inspector: None,
+            persistent_cache: false,
+            upgrades: UpgradeRegistry::new(),
+            profiler: None,
+        }
     }
 
     /// Set the [Header] to begin execution from.
@@ -52,6 +78,40 @@ where
         self
     }
 
+    /// Set the [DynKonaInspector] to attach to the EVM during execution.
+    ///
+    /// Execution with an inspector is considerably slower, and is intended for debugging.
+    pub fn with_inspector(mut self, inspector: &'a mut DynKonaInspector<'a, F, H>) -> Self {
+        self.inspector = Some(inspector);
+        self
+    }
+
+    /// Set the [UpgradeRegistry] of the chain's custom hardfork upgrades and precompiles, applied
+    /// on top of the OP Stack's hardforks.
+    pub fn with_upgrade_registry(mut self, upgrades: UpgradeRegistry) -> Self {
+        self.upgrades = upgrades;
+        self
+    }
+
+    /// Set the [BlockProfiler] to profile the executed blocks with. The profiles are read back
+    /// from a clone of the profiler.
+    ///
+    /// Profiling attaches an inspector to the EVM, and is considerably slower than plain
+    /// execution.
+    pub fn with_profiler(mut self, profiler: BlockProfiler) -> Self {
+        self.profiler = Some(profiler);
+        self
+    }
+
+    /// Retain the account and storage cache of the [State] across executed blocks, rather than
+    /// reading the state touched by each block back from the [TrieDB].
+    ///
+    /// Intended for executing long ranges of consecutive blocks with the same executor.
+    pub const fn with_persistent_cache(mut self) -> Self {
+        self.persistent_cache = true;
+        self
+    }
+
     /// Build the [StatelessL2BlockExecutor] from the builder configuration.
     pub fn build(self) -> StatelessL2BlockExecutor<'a, F, H> {
         let parent_header = self.parent_header.unwrap_or_else(|| {
@@ -59,12 +119,19 @@ where
             default_header.seal_slow()
         });
 
-        let trie_db =
+        let mut trie_db =
             TrieDB::new(parent_header.state_root, parent_header, self.provider, self.hinter);
+        if let Some(profiler) = self.profiler.as_ref() {
+            trie_db.set_profiler(profiler.clone());
+        }
         StatelessL2BlockExecutor {
             config: self.config,
             trie_db,
             handler_register: self.handler_register,
+            inspector: self.inspector,
+            state_cache: self.persistent_cache.then(CacheState::default),
+            upgrades: self.upgrades,
+            profiler: self.profiler,
         }
     }
 }
@@ -80,8 +147,9 @@ mod tests {
         let config = RollupConfig::default();
         let parent_header = Header::default().seal_slow();
 
-        fn test_handler_register<F, H>(_: &mut EvmHandler<'_, (), &mut State<&mut TrieDB<F, H>>>)
-        where
+        fn test_handler_register<F, H>(
+            _: &mut EvmHandler<'_, KonaEvmContext<'_, F, H>, State<&mut TrieDB<F, H>>>,
+        ) where
             F: TrieDBProvider,
             H: TrieHinter,
         {
@@ -5,10 +5,12 @@ use crate::{
     db::TrieDB,
     errors::TrieDBError,
     syscalls::{
-        ensure_create2_deployer_canyon, pre_block_beacon_root_contract_call,
-        pre_block_block_hash_contract_call,
+        apply_hardfork_upgrades, ensure_create2_deployer_canyon,
+        pre_block_beacon_root_contract_call, pre_block_block_hash_contract_call,
     },
-    ExecutorError, ExecutorResult, TrieDBProvider,
+    trace::{NoOpKonaInspector, ProfilingInspector, TransactionHooks},
+    BlockProfiler, DynKonaInspector, ExecutorError, ExecutorResult, KonaEvmContext, TrieDBProvider,
+    UpgradeRegistry,
 };
 use alloc::vec::Vec;
 use alloy_consensus::{
@@ -21,7 +23,11 @@ use maili_genesis::RollupConfig;
 use op_alloy_consensus::{OpReceiptEnvelope, OpTxEnvelope};
 use op_alloy_rpc_types_engine::OpPayloadAttributes;
 use revm::{
-    db::{states::bundle_state::BundleRetention, State},
+    db::{
+        states::{bundle_state::BundleRetention, CacheState},
+        AccountStatus, State,
+    },
+    inspector_handle_register,
     primitives::{calc_excess_blob_gas, EnvWithHandlerCfg},
     Evm,
 };
@@ -34,6 +40,13 @@ mod env;
 mod util;
 use util::encode_holocene_eip_1559_params;
 
+mod build;
+use build::ExecutedTransactions;
+pub use build::{BuiltBlock, SkipReason, SkippedTransaction};
+
+mod validate;
+pub use validate::{BlockDiff, HeaderField, HeaderFieldDiff, ReceiptDivergence};
+
 /// The [ExecutionArtifacts] holds the produced block header and receipts from the execution of a
 /// block.
 #[derive(Default, Debug, Clone, PartialEq, Eq)]
@@ -58,6 +71,14 @@ where
     trie_db: TrieDB<F, H>,
     /// The [KonaHandleRegister] to use during execution.
     handler_register: Option<KonaHandleRegister<F, H>>,
+    /// The [DynKonaInspector] to attach to the EVM during execution.
+    inspector: Option<&'a mut DynKonaInspector<'a, F, H>>,
+    /// The account and storage cache retained across executed blocks, if enabled.
+    state_cache: Option<CacheState>,
+    /// The [UpgradeRegistry] of the chain's custom hardfork upgrades and precompiles.
+    upgrades: UpgradeRegistry,
+    /// The [BlockProfiler] profiling the executed blocks, if enabled.
+    profiler: Option<BlockProfiler>,
 }
 
 impl<'a, F, H> StatelessL2BlockExecutor<'a, F, H>
@@ -74,6 +95,20 @@ where
         StatelessL2BlockExecutorBuilder::new(config, provider, hinter)
     }
 
+    /// Returns the header of the last executed block, or the parent header the executor was
+    /// built with if no block has been executed yet.
+    pub const fn parent_block_header(&self) -> &Sealed<Header> {
+        self.trie_db.parent_block_header()
+    }
+
+    /// Returns a mutable reference to the [TrieDB], holding the state of the last executed block.
+    ///
+    /// Intended for inspecting the state produced by the executor, e.g. with
+    /// [TrieDB::get_trie_account] and [TrieDB::get_storage_slot].
+    pub fn trie_db_mut(&mut self) -> &mut TrieDB<F, H> {
+        &mut self.trie_db
+    }
+
     /// Fetches the L2 to L1 message passer account from the cache or underlying trie.
     fn message_passer_account(
         db: &mut TrieDB<F, H>,
@@ -108,6 +143,19 @@ where
         &mut self,
         payload: OpPayloadAttributes,
     ) -> ExecutorResult<ExecutionArtifacts> {
+        self.execute_block(payload, &[]).map(|built| built.artifacts)
+    }
+
+    /// Executes the transactions of the payload, followed by the transactions from the `pool`
+    /// that can be included in the block, and seals the resulting block.
+    ///
+    /// The transactions of the payload must all be valid, whereas invalid transactions from the
+    /// `pool` are skipped. See [Self::execute_payload] and [Self::build_block].
+    fn execute_block(
+        &mut self,
+        payload: OpPayloadAttributes,
+        pool: &[Bytes],
+    ) -> ExecutorResult<BuiltBlock> {
         // Prepare the `revm` environment.
         let base_fee_params = Self::active_base_fee_params(
             self.config,
@@ -136,9 +184,16 @@ where
         );
 
         let parent_block_hash: B256 = self.trie_db.parent_block_header().seal();
+        if let Some(profiler) = self.profiler.as_ref() {
+            profiler.start_block(block_number);
+        }
 
-        let mut state =
-            State::builder().with_database(&mut self.trie_db).with_bundle_update().build();
+        let cache = self.state_cache.as_mut().map(core::mem::take).unwrap_or_default();
+        let mut state = State::builder()
+            .with_database(&mut self.trie_db)
+            .with_cached_prestate(cache)
+            .with_bundle_update()
+            .build();
 
         // Apply the pre-block EIP-4788 contract call.
         pre_block_beacon_root_contract_call(
@@ -168,129 +223,76 @@ where
             payload.payload_attributes.timestamp,
         )?;
 
-        let mut cumulative_gas_used = 0u64;
-        let mut receipts: Vec<OpReceiptEnvelope> = Vec::with_capacity(transactions.len());
-        let is_regolith = self.config.is_regolith_active(payload.payload_attributes.timestamp);
-
-        // Construct the block-scoped EVM with the given configuration.
-        // The transaction environment is set within the loop for each transaction.
-        let mut evm = {
-            let mut base = Evm::builder().with_db(&mut state).with_env_with_handler_cfg(
-                EnvWithHandlerCfg::new_with_cfg_env(
-                    initialized_cfg.clone(),
-                    initialized_block_env.clone(),
-                    Default::default(),
-                ),
-            );
-
-            // If a handler register is provided, append it to the base EVM.
-            if let Some(handler) = self.handler_register {
-                base = base.append_handler_register(handler);
-            }
-
-            base.build()
-        };
-
-        let is_isthmus = self.config.is_isthmus_active(payload.payload_attributes.timestamp);
-
-        // Execute the transactions in the payload.
-        let decoded_txs = transactions
-            .iter()
-            .map(|raw_tx| {
-                let tx = OpTxEnvelope::decode_2718(&mut raw_tx.as_ref())
-                    .map_err(ExecutorError::RLPError)?;
-                Ok((tx, raw_tx.as_ref()))
-            })
-            .collect::<ExecutorResult<Vec<_>>>()?;
-        for (transaction, raw_transaction) in decoded_txs {
-            // The sum of the transaction’s gas limit, Tg, and the gas utilized in this block prior,
-            // must be no greater than the block’s gasLimit.
-            let block_available_gas = (gas_limit - cumulative_gas_used) as u128;
-            if (transaction.gas_limit() as u128) > block_available_gas &&
-                (is_regolith || !transaction.is_system_transaction())
-            {
-                return Err(ExecutorError::BlockGasLimitExceeded);
-            }
-
-            // Prevent EIP-7702 transactions pre-isthmus hardfork.
-            if !is_isthmus && matches!(transaction, OpTxEnvelope::Eip7702(_)) {
-                return Err(ExecutorError::UnsupportedTransactionType(transaction.tx_type() as u8));
-            }
-
-            // Modify the transaction environment with the current transaction.
-            evm = evm
-                .modify()
-                .with_tx_env(Self::prepare_tx_env(&transaction, raw_transaction)?)
-                .build();
-
-            // If the transaction is a deposit, cache the depositor account.
-            //
-            // This only needs to be done post-Regolith, as deposit nonces were not included in
-            // Bedrock. In addition, non-deposit transactions do not have deposit
-            // nonces.
-            let depositor = is_regolith
-                .then(|| {
-                    if let OpTxEnvelope::Deposit(deposit) = &transaction {
-                        evm.db_mut().load_cache_account(deposit.from).ok().cloned()
-                    } else {
-                        None
-                    }
-                })
-                .flatten();
+        // Apply the chain's custom hardfork upgrades that activate in this block.
+        apply_hardfork_upgrades(
+            &mut state,
+            &self.upgrades,
+            &initialized_cfg,
+            &initialized_block_env,
+            payload.payload_attributes.timestamp,
+        )?;
 
-            // Execute the transaction.
-            let tx_hash = keccak256(raw_transaction);
-            debug!(
-                target: "client_executor",
-                "Executing transaction: {tx_hash}",
-            );
-            let result = evm.transact_commit().map_err(ExecutorError::ExecutionError)?;
-            debug!(
-                target: "client_executor",
-                "Transaction executed: {tx_hash} | Gas used: {gas_used} | Success: {status}",
-                gas_used = result.gas_used(),
-                status = result.is_success()
-            );
+        // If profiling, attach the profiler to the EVM alongside the inspector, if any.
+        let mut profiling;
+        let inspector: Option<&mut DynKonaInspector<'_, F, H>> =
+            match (self.profiler.as_mut(), self.inspector.as_deref_mut()) {
+                (Some(profiler), inner) => {
+                    profiler.start_transactions();
+                    profiling = ProfilingInspector::new(profiler, inner);
+                    Some(&mut profiling)
+                }
+                (None, Some(inner)) => Some(inner),
+                (None, None) => None,
+            };
+
+        // Construct the block-scoped EVM with the given configuration, and execute the
+        // transactions in the payload. The transaction environment is set for each transaction.
+        // Without an inspector, the external context of the EVM is a no-op inspector that is
+        // never invoked, so that the handler register applies to the EVM either way.
+        let env = EnvWithHandlerCfg::new_with_cfg_env(
+            initialized_cfg.clone(),
+            initialized_block_env.clone(),
+            Default::default(),
+        );
+        let is_inspected = inspector.is_some();
+        let mut noop = NoOpKonaInspector::default();
+        let context: KonaEvmContext<'_, F, H> = inspector.unwrap_or(&mut noop);
+        // The EVM takes ownership of the state while executing the transactions, which allows
+        // inspectors implemented for any database to be attached.
+        let mut base = Evm::builder()
+            .with_db(state)
+            .with_external_context(context)
+            .with_env_with_handler_cfg(env);
+
+        // If inspecting, install the inspector handles before the other registers.
+        if is_inspected {
+            base = base.append_handler_register(inspector_handle_register);
+        }
 
-            // Accumulate the gas used by the transaction.
-            cumulative_gas_used += result.gas_used();
+        // If a handler register is provided, append it to the base EVM.
+        if let Some(handler) = self.handler_register {
+            base = base.append_handler_register(handler);
+        }
 
-            // Create receipt envelope.
-            let receipt = OpReceiptEnvelope::<Log>::from_parts(
-                result.is_success(),
-                cumulative_gas_used,
-                result.logs(),
-                transaction.tx_type(),
-                depositor
-                    .as_ref()
-                    .map(|depositor| depositor.account_info().unwrap_or_default().nonce),
-                depositor
-                    .is_some()
-                    .then(|| {
-                        self.config
-                            .is_canyon_active(payload.payload_attributes.timestamp)
-                            .then_some(1)
-                    })
-                    .flatten(),
-            );
-            // Ensure the receipt is not an EIP-7702 receipt.
-            if matches!(receipt, OpReceiptEnvelope::Eip7702(_)) && !is_isthmus {
-                panic!(
-                    "EIP-7702 receipts are not supported by the fault proof program before Isthmus"
-                );
-            }
-            receipts.push(receipt);
+        // Load the chain's custom precompiles on top of the others.
+        if let Some(register) =
+            self.upgrades.precompiles_register(payload.payload_attributes.timestamp)
+        {
+            base = base.append_handler_register_box(register);
         }
 
+        let mut evm = base.build();
+        let executed =
+            Self::execute_transactions(&mut evm, self.config, &payload, pool, gas_limit)?;
+        let (mut state, _) = evm.into_db_and_env_with_handler_cfg();
+
+        let ExecutedTransactions { receipts, cumulative_gas_used, included, skipped } = executed;
         info!(
             target: "client_executor",
             "Transaction execution complete | Cumulative gas used: {cumulative_gas_used}",
             cumulative_gas_used = cumulative_gas_used
         );
 
-        // Drop the EVM to free the exclusive reference to the database.
-        drop(evm);
-
         // Merge all state transitions into the cache state.
         debug!(target: "client_executor", "Merging state transitions");
         state.merge_transitions(BundleRetention::Reverts);
@@ -299,9 +301,17 @@ where
         let bundle = state.take_bundle();
 
         // Recompute the header roots.
+        if let Some(profiler) = self.profiler.as_ref() {
+            profiler.start_state_root();
+        }
         let state_root = state.database.state_root(&bundle)?;
+        if let Some(profiler) = self.profiler.as_ref() {
+            profiler.finish_block(cumulative_gas_used);
+        }
 
-        let transactions_root = Self::compute_transactions_root(transactions.as_slice());
+        let block_transactions: Vec<Bytes> =
+            transactions.iter().chain(included.iter()).cloned().collect();
+        let transactions_root = Self::compute_transactions_root(&block_transactions);
         let receipts_root = Self::compute_receipts_root(
             &receipts,
             self.config,
@@ -405,9 +415,161 @@ where
             receipts_root = header.receipts_root,
         );
 
+        // Retain the account and storage cache for the next block, if enabled. The accounts are
+        // marked as freshly loaded, as their changes have been committed to the trie.
+        if let Some(cache) = self.state_cache.as_mut() {
+            *cache = core::mem::take(&mut state.cache);
+            cache.accounts.values_mut().for_each(|account| {
+                account.status = if account.account.is_some() {
+                    AccountStatus::Loaded
+                } else {
+                    AccountStatus::LoadedNotExisting
+                };
+            });
+        }
+
         // Update the parent block hash in the state database.
         state.database.set_parent_block_header(header.clone());
-        Ok(ExecutionArtifacts { block_header: header, receipts })
+        Ok(BuiltBlock {
+            artifacts: ExecutionArtifacts { block_header: header, receipts },
+            transactions: block_transactions,
+            skipped,
+        })
+    }
+
+    /// Executes the transactions of the payload in the given [Evm], followed by the transactions
+    /// from the `pool` that can be included in the block.
+    fn execute_transactions<EXT: TransactionHooks>(
+        evm: &mut Evm<'_, EXT, State<&mut TrieDB<F, H>>>,
+        config: &RollupConfig,
+        payload: &OpPayloadAttributes,
+        pool: &[Bytes],
+        gas_limit: u64,
+    ) -> ExecutorResult<ExecutedTransactions> {
+        let timestamp = payload.payload_attributes.timestamp;
+        let transactions =
+            payload.transactions.as_ref().ok_or(ExecutorError::MissingTransactions)?;
+        let is_regolith = config.is_regolith_active(timestamp);
+        let is_isthmus = config.is_isthmus_active(timestamp);
+
+        let mut cumulative_gas_used = 0u64;
+        let mut receipts: Vec<OpReceiptEnvelope> = Vec::with_capacity(transactions.len());
+
+        // Execute the transactions in the payload.
+        let decoded_txs = transactions
+            .iter()
+            .map(|raw_tx| {
+                let tx = OpTxEnvelope::decode_2718(&mut raw_tx.as_ref())
+                    .map_err(ExecutorError::RLPError)?;
+                Ok((tx, raw_tx.as_ref()))
+            })
+            .collect::<ExecutorResult<Vec<_>>>()?;
+        for (transaction, raw_transaction) in decoded_txs {
+            // The sum of the transaction’s gas limit, Tg, and the gas utilized in this block prior,
+            // must be no greater than the block’s gasLimit.
+            let block_available_gas = (gas_limit - cumulative_gas_used) as u128;
+            if (transaction.gas_limit() as u128) > block_available_gas &&
+                (is_regolith || !transaction.is_system_transaction())
+            {
+                return Err(ExecutorError::BlockGasLimitExceeded);
+            }
+
+            // Prevent EIP-7702 transactions pre-isthmus hardfork.
+            if !is_isthmus && matches!(transaction, OpTxEnvelope::Eip7702(_)) {
+                return Err(ExecutorError::UnsupportedTransactionType(transaction.tx_type() as u8));
+            }
+
+            // Modify the transaction environment with the current transaction.
+            *evm.tx_mut() = Self::prepare_tx_env(&transaction, raw_transaction)?;
+
+            // If the transaction is a deposit, cache the depositor account.
+            //
+            // This only needs to be done post-Regolith, as deposit nonces were not included in
+            // Bedrock. In addition, non-deposit transactions do not have deposit
+            // nonces.
+            let depositor = is_regolith
+                .then(|| {
+                    if let OpTxEnvelope::Deposit(deposit) = &transaction {
+                        evm.db_mut().load_cache_account(deposit.from).ok().cloned()
+                    } else {
+                        None
+                    }
+                })
+                .flatten();
+
+            // Execute the transaction.
+            let tx_hash = keccak256(raw_transaction);
+            debug!(
+                target: "client_executor",
+                "Executing transaction: {tx_hash}",
+            );
+            evm.context.external.transaction_start(tx_hash);
+            let result = evm.transact_commit().map_err(ExecutorError::ExecutionError)?;
+            evm.context.external.transaction_end(tx_hash, &result);
+            debug!(
+                target: "client_executor",
+                "Transaction executed: {tx_hash} | Gas used: {gas_used} | Success: {status}",
+                gas_used = result.gas_used(),
+                status = result.is_success()
+            );
+
+            // Accumulate the gas used by the transaction.
+            cumulative_gas_used += result.gas_used();
+
+            // Create receipt envelope.
+            let receipt = OpReceiptEnvelope::<Log>::from_parts(
+                result.is_success(),
+                cumulative_gas_used,
+                result.logs(),
+                transaction.tx_type(),
+                depositor
+                    .as_ref()
+                    .map(|depositor| depositor.account_info().unwrap_or_default().nonce),
+                depositor
+                    .is_some()
+                    .then(|| config.is_canyon_active(timestamp).then_some(1))
+                    .flatten(),
+            );
+            // Ensure the receipt is not an EIP-7702 receipt.
+            if matches!(receipt, OpReceiptEnvelope::Eip7702(_)) && !is_isthmus {
+                panic!(
+                    "EIP-7702 receipts are not supported by the fault proof program before Isthmus"
+                );
+            }
+            receipts.push(receipt);
+        }
+
+        // Include the transactions from the pool that fit in the block, skipping invalid ones.
+        let mut included = Vec::new();
+        let mut skipped = Vec::new();
+        for raw_transaction in pool {
+            let available_gas = gas_limit - cumulative_gas_used;
+            match Self::execute_pool_transaction(evm, is_isthmus, raw_transaction, available_gas)? {
+                Ok((tx_type, result)) => {
+                    cumulative_gas_used += result.gas_used();
+                    receipts.push(OpReceiptEnvelope::<Log>::from_parts(
+                        result.is_success(),
+                        cumulative_gas_used,
+                        result.logs(),
+                        tx_type,
+                        None,
+                        None,
+                    ));
+                    included.push(raw_transaction.clone());
+                }
+                Err(reason) => {
+                    debug!(
+                        target: "client_executor",
+                        "Skipping pool transaction {tx_hash}: {reason}",
+                        tx_hash = keccak256(raw_transaction),
+                    );
+                    skipped
+                        .push(SkippedTransaction { transaction: raw_transaction.clone(), reason });
+                }
+            }
+        }
+
+        Ok(ExecutedTransactions { receipts, cumulative_gas_used, included, skipped })
     }
 
     /// Computes the current output root of the executor, based on the parent header and the
@@ -508,21 +670,14 @@ where
 
 #[cfg(test)]
 mod test {
-    use crate::test_utils::run_test_fixture;
+    use crate::test_utils::{
+        run_test_fixture, run_test_fixture_inspected, run_test_fixture_recorded,
+    };
     use rstest::rstest;
     use std::path::PathBuf;
 
-    // To create new test fixtures, uncomment the following test and run it with parameters filled.
-    //
-    // #[tokio::test(flavor = "multi_thread")]
-    // async fn create_fixture() {
-    //     let fixture_creator = crate::test_utils::ExecutorTestFixtureCreator::new(
-    //         "<l2_archive_el_rpc_url>",
-    //         <block_number>,
-    //         PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("testdata"),
-    //     );
-    //     fixture_creator.create_static_fixture().await;
-    // }
+    // Fixtures for new blocks are recorded from an L2 archive node with `kona-executor-fixture
+    // create`, and replayed with `kona-executor-fixture replay`.
 
     #[rstest]
     #[case::small_block(22884230)]
@@ -540,4 +695,28 @@ mod test {
 
         run_test_fixture(fixture_dir).await;
     }
+
+    #[rstest]
+    #[case::small_block(22884230)]
+    #[case::medium_block(22886464)]
+    #[tokio::test]
+    async fn test_inspected_execution_matches_plain_execution(#[case] block_number: u64) {
+        let fixture_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("testdata")
+            .join(format!("block-{block_number}.tar.gz"));
+
+        run_test_fixture_inspected(fixture_dir).await;
+    }
+
+    #[rstest]
+    #[case::small_block(22884230)]
+    #[case::medium_block(22886464)]
+    #[tokio::test]
+    async fn test_recorded_witness_replays_block(#[case] block_number: u64) {
+        let fixture_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("testdata")
+            .join(format!("block-{block_number}.tar.gz"));
+
+        run_test_fixture_recorded(fixture_dir).await;
+    }
 }
@@ -0,0 +1,325 @@
+//! Validation of executed blocks against an expected header, reporting the differences.
+
+use super::{ExecutionArtifacts, StatelessL2BlockExecutor};
+use crate::{ExecutorError, ExecutorResult, TrieDBProvider};
+use alloc::{boxed::Box, format, string::String, vec::Vec};
+use alloy_consensus::Header;
+use alloy_primitives::B256;
+use core::fmt;
+use kona_mpt::TrieHinter;
+use op_alloy_consensus::OpReceiptEnvelope;
+use op_alloy_rpc_types_engine::OpPayloadAttributes;
+
+impl<F, H> StatelessL2BlockExecutor<'_, F, H>
+where
+    F: TrieDBProvider,
+    H: TrieHinter,
+{
+    /// Executes the given payload, and validates the produced block against the expected header.
+    ///
+    /// If the produced block does not match, an [ExecutorError::BlockMismatch] is returned with a
+    /// [BlockDiff] of the differing header fields. If the receipts of the expected block are
+    /// given, the [BlockDiff] also holds the first diverging receipt.
+    ///
+    /// Note that the executor's state has advanced to the produced block, even if it does not
+    /// match the expected header.
+    pub fn execute_and_validate(
+        &mut self,
+        payload: OpPayloadAttributes,
+        expected_header: &Header,
+        expected_receipts: Option<&[OpReceiptEnvelope]>,
+    ) -> ExecutorResult<ExecutionArtifacts> {
+        let artifacts = self.execute_payload(payload)?;
+        if artifacts.block_header.hash() == expected_header.hash_slow() {
+            return Ok(artifacts);
+        }
+
+        Err(ExecutorError::BlockMismatch(Box::new(BlockDiff::new(
+            expected_header,
+            artifacts.block_header.inner(),
+            expected_receipts,
+            &artifacts.receipts,
+        ))))
+    }
+}
+
+/// A field of the block [Header].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeaderField {
+    /// The parent hash.
+    ParentHash,
+    /// The ommers hash.
+    OmmersHash,
+    /// The beneficiary.
+    Beneficiary,
+    /// The state root.
+    StateRoot,
+    /// The transactions root.
+    TransactionsRoot,
+    /// The receipts root.
+    ReceiptsRoot,
+    /// The logs bloom.
+    LogsBloom,
+    /// The difficulty.
+    Difficulty,
+    /// The block number.
+    Number,
+    /// The gas limit.
+    GasLimit,
+    /// The gas used.
+    GasUsed,
+    /// The timestamp.
+    Timestamp,
+    /// The extra data.
+    ExtraData,
+    /// The mix hash.
+    MixHash,
+    /// The nonce.
+    Nonce,
+    /// The base fee per gas.
+    BaseFeePerGas,
+    /// The withdrawals root.
+    WithdrawalsRoot,
+    /// The blob gas used.
+    BlobGasUsed,
+    /// The excess blob gas.
+    ExcessBlobGas,
+    /// The parent beacon block root.
+    ParentBeaconBlockRoot,
+    /// The requests hash.
+    RequestsHash,
+}
+
+impl HeaderField {
+    /// Returns the name of the field.
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::ParentHash => "parent hash",
+            Self::OmmersHash => "ommers hash",
+            Self::Beneficiary => "beneficiary",
+            Self::StateRoot => "state root",
+            Self::TransactionsRoot => "transactions root",
+            Self::ReceiptsRoot => "receipts root",
+            Self::LogsBloom => "logs bloom",
+            Self::Difficulty => "difficulty",
+            Self::Number => "number",
+            Self::GasLimit => "gas limit",
+            Self::GasUsed => "gas used",
+            Self::Timestamp => "timestamp",
+            Self::ExtraData => "extra data",
+            Self::MixHash => "mix hash",
+            Self::Nonce => "nonce",
+            Self::BaseFeePerGas => "base fee per gas",
+            Self::WithdrawalsRoot => "withdrawals root",
+            Self::BlobGasUsed => "blob gas used",
+            Self::ExcessBlobGas => "excess blob gas",
+            Self::ParentBeaconBlockRoot => "parent beacon block root",
+            Self::RequestsHash => "requests hash",
+        }
+    }
+}
+
+impl fmt::Display for HeaderField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A [HeaderField] that differs between the expected and the produced header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeaderFieldDiff {
+    /// The differing field.
+    pub field: HeaderField,
+    /// The value of the field in the expected header.
+    pub expected: String,
+    /// The value of the field in the produced header.
+    pub actual: String,
+}
+
+/// The first receipt that differs between the expected and the produced block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReceiptDivergence {
+    /// The index of the receipt within the block.
+    pub index: usize,
+    /// The expected receipt, or [None] if the expected block has fewer receipts.
+    pub expected: Option<OpReceiptEnvelope>,
+    /// The produced receipt, or [None] if the produced block has fewer receipts.
+    pub actual: Option<OpReceiptEnvelope>,
+}
+
+impl ReceiptDivergence {
+    /// Finds the first diverging receipt, if any.
+    fn find(expected: &[OpReceiptEnvelope], actual: &[OpReceiptEnvelope]) -> Option<Self> {
+        let index =
+            (0..expected.len().max(actual.len())).find(|&i| expected.get(i) != actual.get(i))?;
+        Some(Self {
+            index,
+            expected: expected.get(index).cloned(),
+            actual: actual.get(index).cloned(),
+        })
+    }
+}
+
+impl fmt::Display for ReceiptDivergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let describe = |receipt: &Option<OpReceiptEnvelope>| {
+            receipt.as_ref().map_or_else(
+                || String::from("no receipt"),
+                |r| {
+                    format!(
+                        "status {}, cumulative gas {}, {} logs",
+                        r.status(),
+                        r.cumulative_gas_used(),
+                        r.logs().len()
+                    )
+                },
+            )
+        };
+        write!(
+            f,
+            "receipt #{}: expected {}, got {}",
+            self.index,
+            describe(&self.expected),
+            describe(&self.actual)
+        )
+    }
+}
+
+/// The differences between an expected block and the block produced by the
+/// [StatelessL2BlockExecutor].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockDiff {
+    /// The hash of the expected header.
+    pub expected_hash: B256,
+    /// The hash of the produced header.
+    pub actual_hash: B256,
+    /// The header fields that differ.
+    pub fields: Vec<HeaderFieldDiff>,
+    /// The first diverging receipt, if the expected receipts were given.
+    pub receipt: Option<ReceiptDivergence>,
+}
+
+impl BlockDiff {
+    /// Computes the [BlockDiff] between the expected and the produced block.
+    pub fn new(
+        expected: &Header,
+        actual: &Header,
+        expected_receipts: Option<&[OpReceiptEnvelope]>,
+        actual_receipts: &[OpReceiptEnvelope],
+    ) -> Self {
+        let mut fields = Vec::new();
+        macro_rules! diff {
+            ($($field:ident => $variant:ident),* $(,)?) => {
+                $(
+                    if expected.$field != actual.$field {
+                        fields.push(HeaderFieldDiff {
+                            field: HeaderField::$variant,
+                            expected: format!("{:?}", expected.$field),
+                            actual: format!("{:?}", actual.$field),
+                        });
+                    }
+                )*
+            };
+        }
+        diff!(
+            parent_hash => ParentHash,
+            ommers_hash => OmmersHash,
+            beneficiary => Beneficiary,
+            state_root => StateRoot,
+            transactions_root => TransactionsRoot,
+            receipts_root => ReceiptsRoot,
+            logs_bloom => LogsBloom,
+            difficulty => Difficulty,
+            number => Number,
+            gas_limit => GasLimit,
+            gas_used => GasUsed,
+            timestamp => Timestamp,
+            extra_data => ExtraData,
+            mix_hash => MixHash,
+            nonce => Nonce,
+            base_fee_per_gas => BaseFeePerGas,
+            withdrawals_root => WithdrawalsRoot,
+            blob_gas_used => BlobGasUsed,
+            excess_blob_gas => ExcessBlobGas,
+            parent_beacon_block_root => ParentBeaconBlockRoot,
+            requests_hash => RequestsHash,
+        );
+
+        Self {
+            expected_hash: expected.hash_slow(),
+            actual_hash: actual.hash_slow(),
+            fields,
+            receipt: expected_receipts
+                .and_then(|expected| ReceiptDivergence::find(expected, actual_receipts)),
+        }
+    }
+
+    /// Returns the diff of the given [HeaderField], if it differs.
+    pub fn field(&self, field: HeaderField) -> Option<&HeaderFieldDiff> {
+        self.fields.iter().find(|diff| diff.field == field)
+    }
+}
+
+impl fmt::Display for BlockDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "expected block {}, got {}", self.expected_hash, self.actual_hash)?;
+        for diff in &self.fields {
+            write!(f, "; {}: expected {}, got {}", diff.field, diff.expected, diff.actual)?;
+        }
+        if let Some(receipt) = &self.receipt {
+            write!(f, "; first diverging {receipt}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloy_consensus::{Receipt, ReceiptWithBloom};
+    use alloy_primitives::{Address, Log};
+
+    fn receipt(cumulative_gas_used: u64, logs: usize) -> OpReceiptEnvelope {
+        OpReceiptEnvelope::Legacy(ReceiptWithBloom::new(
+            Receipt { status: true.into(), cumulative_gas_used, logs: vec![Log::empty(); logs] },
+            Default::default(),
+        ))
+    }
+
+    #[test]
+    fn test_block_diff() {
+        let expected = Header { gas_used: 100, base_fee_per_gas: Some(7), ..Default::default() };
+        let actual = Header {
+            gas_used: 120,
+            base_fee_per_gas: Some(7),
+            beneficiary: Address::with_last_byte(1),
+            ..Default::default()
+        };
+        let expected_receipts = [receipt(50, 1), receipt(100, 2)];
+        let actual_receipts = [receipt(50, 1), receipt(120, 1)];
+
+        let diff = BlockDiff::new(&expected, &actual, Some(&expected_receipts), &actual_receipts);
+        assert_eq!(diff.fields.len(), 2);
+        assert_eq!(diff.field(HeaderField::GasUsed).unwrap().expected, "100");
+        assert_eq!(diff.field(HeaderField::GasUsed).unwrap().actual, "120");
+        assert!(diff.field(HeaderField::Beneficiary).is_some());
+        assert!(diff.field(HeaderField::BaseFeePerGas).is_none());
+
+        let receipt = diff.receipt.as_ref().unwrap();
+        assert_eq!(receipt.index, 1);
+        assert_eq!(receipt.actual.as_ref().unwrap().cumulative_gas_used(), 120);
+        assert!(diff.to_string().contains(
+            "receipt #1: expected status true, cumulative gas 100, 2 logs, got status true, cumulative gas 120, 1 logs"
+        ));
+    }
+
+    #[test]
+    fn test_receipt_divergence_length() {
+        let expected = [receipt(50, 0)];
+        let actual = [receipt(50, 0), receipt(60, 0)];
+        let divergence = ReceiptDivergence::find(&expected, &actual).unwrap();
+        assert_eq!(divergence.index, 1);
+        assert!(divergence.expected.is_none());
+        assert!(ReceiptDivergence::find(&expected, &expected).is_none());
+    }
+}
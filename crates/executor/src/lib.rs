@@ -13,17 +13,31 @@ extern crate alloc;
 extern crate tracing;
 
 mod errors;
-pub use errors::{ExecutorError, ExecutorResult, TrieDBError, TrieDBResult};
+pub use errors::{ExecutorError, ExecutorResult, TrieDBError, TrieDBResult, WitnessError};
 
 mod executor;
 pub use executor::{
-    ExecutionArtifacts, KonaHandleRegister, StatelessL2BlockExecutor,
+    BlockDiff, BuiltBlock, ExecutionArtifacts, HeaderField, HeaderFieldDiff, KonaHandleRegister,
+    ReceiptDivergence, SkipReason, SkippedTransaction, StatelessL2BlockExecutor,
     StatelessL2BlockExecutorBuilder,
 };
 
 mod db;
 pub use db::{NoopTrieDBProvider, TrieDB, TrieDBProvider};
 
+mod trace;
+pub use trace::{
+    BlockProfile, BlockProfiler, CallFrame, CallKind, CallLog, CallTracer, DynKonaInspector,
+    KonaEvmContext, KonaInspector, PrecompileCalls, PrestateAccount, PrestateTracer,
+    ProviderFetches, TransactionProfile,
+};
+
+mod upgrades;
+pub use upgrades::{ExtraPrecompile, HardforkUpgrade, UpgradeAction, UpgradeRegistry};
+
+mod witness;
+pub use witness::{ExecutionWitness, RawTrieDBProvider, WitnessProvider, WitnessRecorder};
+
 mod constants;
 mod syscalls;
 
@@ -9,5 +9,8 @@ pub(crate) use eip4788::pre_block_beacon_root_contract_call;
 mod canyon;
 pub(crate) use canyon::ensure_create2_deployer_canyon;
 
+mod upgrades;
+pub(crate) use upgrades::apply_hardfork_upgrades;
+
 mod tx_env;
 pub(crate) use tx_env::fill_tx_env_for_contract_call;
@@ -0,0 +1,230 @@
+//! Contains the logic for applying the [UpgradeAction]s of an [UpgradeRegistry].
+
+use crate::{
+    db::TrieDB,
+    errors::{ExecutorError, ExecutorResult},
+    syscalls::fill_tx_env_for_contract_call,
+    TrieDBProvider, UpgradeAction, UpgradeRegistry,
+};
+use alloy_primitives::{Address, Bytes, U256};
+use kona_mpt::TrieHinter;
+use revm::{
+    db::State,
+    primitives::{
+        Account, BlockEnv, Bytecode, CfgEnvWithHandlerCfg, EnvWithHandlerCfg, EvmStorageSlot,
+        HashMap,
+    },
+    Database, DatabaseCommit, Evm, L1_BLOCK_CONTRACT,
+};
+
+/// Applies the [UpgradeAction]s of the [UpgradeRegistry] that activate in the block with the given
+/// timestamp.
+pub(crate) fn apply_hardfork_upgrades<F, H>(
+    db: &mut State<&mut TrieDB<F, H>>,
+    registry: &UpgradeRegistry,
+    initialized_cfg: &CfgEnvWithHandlerCfg,
+    initialized_block_env: &BlockEnv,
+    timestamp: u64,
+) -> ExecutorResult<()>
+where
+    F: TrieDBProvider,
+    H: TrieHinter,
+{
+    let parent_timestamp = db.database.parent_block_header().timestamp;
+    for action in registry.actions_at(parent_timestamp, timestamp) {
+        apply_upgrade_action(db, action, initialized_cfg, initialized_block_env)?;
+    }
+    Ok(())
+}
+
+/// Applies a single [UpgradeAction] to the state.
+pub(crate) fn apply_upgrade_action<F, H>(
+    db: &mut State<&mut TrieDB<F, H>>,
+    action: &UpgradeAction,
+    initialized_cfg: &CfgEnvWithHandlerCfg,
+    initialized_block_env: &BlockEnv,
+) -> ExecutorResult<()>
+where
+    F: TrieDBProvider,
+    H: TrieHinter,
+{
+    match action {
+        UpgradeAction::DeployCode { address, code } => deploy_code(db, *address, code.clone()),
+        UpgradeAction::SetStorage { address, slot, value } => {
+            set_storage(db, *address, *slot, *value)
+        }
+        UpgradeAction::SystemCall { caller, target, data } => {
+            system_call(db, initialized_cfg, initialized_block_env, *caller, *target, data.clone())
+        }
+    }
+}
+
+/// Sets the code of the account by directly committing it to the state.
+pub(crate) fn deploy_code<F, H>(
+    db: &mut State<&mut TrieDB<F, H>>,
+    address: Address,
+    code: Bytes,
+) -> ExecutorResult<()>
+where
+    F: TrieDBProvider,
+    H: TrieHinter,
+{
+    // Load the account from the cache.
+    let acc = db.load_cache_account(address)?;
+
+    // Update the account info with the new codehash and bytecode.
+    let mut acc_info = acc.account_info().unwrap_or_default();
+    let bytecode = Bytecode::new_raw(code);
+    acc_info.code_hash = bytecode.hash_slow();
+    acc_info.code = Some(bytecode);
+
+    // Convert the cache account back into a revm account and mark it as touched.
+    let mut revm_acc: Account = acc_info.into();
+    revm_acc.mark_touch();
+
+    db.commit(HashMap::from_iter([(address, revm_acc)]));
+    Ok(())
+}
+
+/// Writes a storage slot of the account by directly committing it to the state.
+fn set_storage<F, H>(
+    db: &mut State<&mut TrieDB<F, H>>,
+    address: Address,
+    slot: U256,
+    value: U256,
+) -> ExecutorResult<()>
+where
+    F: TrieDBProvider,
+    H: TrieHinter,
+{
+    // Load the account and the original value of the slot.
+    let acc_info = db.load_cache_account(address)?.account_info().unwrap_or_default();
+    let original = db.storage(address, slot)?;
+
+    let mut revm_acc: Account = acc_info.into();
+    revm_acc.storage.insert(slot, EvmStorageSlot::new_changed(original, value));
+    revm_acc.mark_touch();
+
+    db.commit(HashMap::from_iter([(address, revm_acc)]));
+    Ok(())
+}
+
+/// Performs a system call to the target contract, committing its state changes.
+fn system_call<F, H>(
+    db: &mut State<&mut TrieDB<F, H>>,
+    initialized_cfg: &CfgEnvWithHandlerCfg,
+    initialized_block_env: &BlockEnv,
+    caller: Address,
+    target: Address,
+    data: Bytes,
+) -> ExecutorResult<()>
+where
+    F: TrieDBProvider,
+    H: TrieHinter,
+{
+    // Prior to Ecotone, the EVM expects the L1 block account to be loaded before it reads the L1
+    // block info, which is otherwise done by the deposit transaction at the start of the block.
+    db.load_cache_account(L1_BLOCK_CONTRACT)?;
+
+    let mut evm = Evm::builder()
+        .with_db(db)
+        .with_env_with_handler_cfg(EnvWithHandlerCfg::new_with_cfg_env(
+            initialized_cfg.clone(),
+            initialized_block_env.clone(),
+            Default::default(),
+        ))
+        .build();
+    fill_tx_env_for_contract_call(&mut evm.context.evm.env, caller, target, data);
+
+    let mut state = evm.transact().map_err(ExecutorError::ExecutionError)?.state;
+    state.remove(&caller);
+    state.remove(&evm.block().coinbase);
+    evm.context.evm.db.commit(state);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{NoopTrieDBProvider, StatelessL2BlockExecutor, UpgradeAction, UpgradeRegistry};
+    use alloy_primitives::{address, b256, Address, Bytes, B256, U256};
+    use alloy_rpc_types_engine::PayloadAttributes;
+    use kona_mpt::NoopTrieHinter;
+    use maili_genesis::RollupConfig;
+    use op_alloy_rpc_types_engine::OpPayloadAttributes;
+
+    const CONTRACT: Address = address!("0000000000000000000000000000000000000abc");
+
+    /// `PUSH1 0 CALLDATALOAD PUSH1 0 SSTORE STOP`, storing the first calldata word in slot 0.
+    const STORE_CALLDATA: [u8; 7] = [0x60, 0x00, 0x35, 0x60, 0x00, 0x55, 0x00];
+
+    /// Executes two empty blocks with the given registry, returning their state roots.
+    fn execute(registry: UpgradeRegistry) -> [B256; 2] {
+        let config = RollupConfig::default();
+        let mut executor =
+            StatelessL2BlockExecutor::builder(&config, NoopTrieDBProvider, NoopTrieHinter)
+                .with_upgrade_registry(registry)
+                .build();
+
+        [1, 2].map(|timestamp| {
+            let payload = OpPayloadAttributes {
+                payload_attributes: PayloadAttributes { timestamp, ..Default::default() },
+                gas_limit: Some(30_000_000),
+                transactions: Some(Vec::new()),
+                ..Default::default()
+            };
+            executor.execute_payload(payload).unwrap().block_header.state_root
+        })
+    }
+
+    #[test]
+    fn test_upgrade_actions_apply_at_activation() {
+        let deploy = UpgradeAction::DeployCode {
+            address: CONTRACT,
+            code: Bytes::from_static(&STORE_CALLDATA),
+        };
+        let value = b256!("0000000000000000000000000000000000000000000000000000000000000007");
+
+        let [empty, _] = execute(UpgradeRegistry::new());
+        let [deployed, after] = execute(UpgradeRegistry::new().with_upgrade(1, [deploy.clone()]));
+        assert_ne!(deployed, empty);
+        assert_eq!(after, deployed);
+
+        // A system call to the deployed contract and a direct storage write are equivalent.
+        let [called, _] = execute(UpgradeRegistry::new().with_upgrade(
+            1,
+            [
+                deploy.clone(),
+                UpgradeAction::SystemCall {
+                    caller: alloy_eips::eip4788::SYSTEM_ADDRESS,
+                    target: CONTRACT,
+                    data: value.into(),
+                },
+            ],
+        ));
+        let [written, _] = execute(UpgradeRegistry::new().with_upgrade(
+            1,
+            [
+                deploy,
+                UpgradeAction::SetStorage {
+                    address: CONTRACT,
+                    slot: U256::ZERO,
+                    value: value.into(),
+                },
+            ],
+        ));
+        assert_ne!(called, deployed);
+        assert_eq!(called, written);
+
+        // An upgrade activating after the first block is applied in the second.
+        let [first, second] = execute(UpgradeRegistry::new().with_upgrade(
+            2,
+            [UpgradeAction::DeployCode {
+                address: CONTRACT,
+                code: Bytes::from_static(&STORE_CALLDATA),
+            }],
+        ));
+        assert_eq!(first, empty);
+        assert_eq!(second, deployed);
+    }
+}
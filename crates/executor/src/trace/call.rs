@@ -0,0 +1,390 @@
+//! Contains the [CallTracer], which records the call frames of each transaction in the format of
+//! geth's `callTracer`.
+
+use super::KonaInspector;
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use alloy_primitives::{Address, Bytes, Log, B256, U256};
+use revm::{
+    interpreter::{
+        CallInputs, CallOutcome, CallScheme, CreateInputs, CreateOutcome, CreateScheme,
+        InstructionResult, Interpreter, InterpreterResult,
+    },
+    primitives::ExecutionResult,
+    Database, EvmContext, Inspector,
+};
+
+/// The selector of the `Error(string)` revert reason.
+const ERROR_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+
+/// The kind of a [CallFrame].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "UPPERCASE"))]
+pub enum CallKind {
+    /// A `CALL`, or the top-level call of a transaction.
+    #[default]
+    Call,
+    /// A `CALLCODE`.
+    CallCode,
+    /// A `DELEGATECALL`.
+    DelegateCall,
+    /// A `STATICCALL`.
+    StaticCall,
+    /// A `CREATE`, or the top-level call of a contract creation transaction.
+    Create,
+    /// A `CREATE2`.
+    Create2,
+    /// A `SELFDESTRUCT`.
+    SelfDestruct,
+}
+
+impl From<CallScheme> for CallKind {
+    fn from(scheme: CallScheme) -> Self {
+        match scheme {
+            CallScheme::Call | CallScheme::ExtCall => Self::Call,
+            CallScheme::CallCode => Self::CallCode,
+            CallScheme::DelegateCall | CallScheme::ExtDelegateCall => Self::DelegateCall,
+            CallScheme::StaticCall | CallScheme::ExtStaticCall => Self::StaticCall,
+        }
+    }
+}
+
+/// A log emitted within a [CallFrame].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CallLog {
+    /// The address of the contract that emitted the log.
+    pub address: Address,
+    /// The topics of the log.
+    pub topics: Vec<B256>,
+    /// The data of the log.
+    pub data: Bytes,
+    /// The number of sub-calls of the frame made before the log was emitted.
+    #[cfg_attr(feature = "serde", serde(with = "alloy_serde::quantity"))]
+    pub position: u64,
+}
+
+/// A call frame, in the format of geth's `callTracer`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct CallFrame {
+    /// The kind of the call.
+    #[cfg_attr(feature = "serde", serde(rename = "type"))]
+    pub kind: CallKind,
+    /// The address of the caller.
+    pub from: Address,
+    /// The address of the callee, or of the created contract.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub to: Option<Address>,
+    /// The value transferred by the call, if any.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub value: Option<U256>,
+    /// The gas provided to the call.
+    #[cfg_attr(feature = "serde", serde(with = "alloy_serde::quantity"))]
+    pub gas: u64,
+    /// The gas used by the call.
+    #[cfg_attr(feature = "serde", serde(with = "alloy_serde::quantity"))]
+    pub gas_used: u64,
+    /// The input of the call, or the init code of the created contract.
+    pub input: Bytes,
+    /// The output of the call, or the code of the created contract.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "super::is_empty_bytes"))]
+    pub output: Bytes,
+    /// The error that the call failed with, if any.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub error: Option<String>,
+    /// The decoded `Error(string)` reason of a reverted call, if any.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub revert_reason: Option<String>,
+    /// The sub-calls made by the call.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Vec::is_empty"))]
+    pub calls: Vec<CallFrame>,
+    /// The logs emitted by the call, if the [CallTracer] records logs.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Vec::is_empty"))]
+    pub logs: Vec<CallLog>,
+}
+
+/// A [KonaInspector] that records the call frames of each transaction, in the format of geth's
+/// `callTracer`.
+#[derive(Debug, Clone, Default)]
+pub struct CallTracer {
+    /// Whether to record the logs emitted by each call.
+    with_logs: bool,
+    /// The call frames of the current transaction that have not yet returned.
+    stack: Vec<CallFrame>,
+    /// The top-level call frame of the current transaction, once it has returned.
+    root: Option<CallFrame>,
+    /// The call frames of the executed transactions, keyed by transaction hash.
+    traces: Vec<(B256, CallFrame)>,
+}
+
+impl CallTracer {
+    /// Records the logs emitted by each call, as with geth's `withLog` option.
+    pub const fn with_logs(mut self) -> Self {
+        self.with_logs = true;
+        self
+    }
+
+    /// Returns the call frames of the executed transactions, keyed by transaction hash.
+    pub fn traces(&self) -> &[(B256, CallFrame)] {
+        &self.traces
+    }
+
+    /// Returns the top-level call frame of the transaction with the given hash, if it was executed.
+    pub fn trace(&self, tx_hash: B256) -> Option<&CallFrame> {
+        self.traces.iter().find(|(hash, _)| *hash == tx_hash).map(|(_, frame)| frame)
+    }
+
+    /// Consumes the tracer, returning the call frames of the executed transactions.
+    pub fn into_traces(self) -> Vec<(B256, CallFrame)> {
+        self.traces
+    }
+
+    /// Pops the current call frame, filling in its result, and attaches it to its parent.
+    fn end_frame(&mut self, result: &InterpreterResult, created: Option<Address>) {
+        let Some(mut frame) = self.stack.pop() else {
+            return;
+        };
+
+        frame.gas_used = frame.gas.saturating_sub(result.gas.remaining());
+        if matches!(frame.kind, CallKind::Create | CallKind::Create2) {
+            frame.to = created;
+        }
+        if result.is_ok() {
+            frame.output = result.output.clone();
+        } else {
+            frame.error = Some(error_message(result.result));
+            if result.result == InstructionResult::Revert {
+                frame.output = result.output.clone();
+                frame.revert_reason = decode_revert_reason(&result.output);
+            }
+        }
+
+        match self.stack.last_mut() {
+            Some(parent) => parent.calls.push(frame),
+            None => self.root = Some(frame),
+        }
+    }
+}
+
+impl<DB: Database> Inspector<DB> for CallTracer {
+    fn log(&mut self, _: &mut Interpreter, _: &mut EvmContext<DB>, log: &Log) {
+        if !self.with_logs {
+            return;
+        }
+        if let Some(frame) = self.stack.last_mut() {
+            frame.logs.push(CallLog {
+                address: log.address,
+                topics: log.topics().to_vec(),
+                data: log.data.data.clone(),
+                position: frame.calls.len() as u64,
+            });
+        }
+    }
+
+    fn call(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        inputs: &mut CallInputs,
+    ) -> Option<CallOutcome> {
+        let kind = CallKind::from(inputs.scheme);
+        let gas = if self.stack.is_empty() { context.env.tx.gas_limit } else { inputs.gas_limit };
+        self.stack.push(CallFrame {
+            kind,
+            from: inputs.caller,
+            to: Some(inputs.bytecode_address),
+            value: matches!(kind, CallKind::Call | CallKind::CallCode).then(|| inputs.value.get()),
+            gas,
+            input: inputs.input.clone(),
+            ..Default::default()
+        });
+        None
+    }
+
+    fn call_end(
+        &mut self,
+        _: &mut EvmContext<DB>,
+        _: &CallInputs,
+        outcome: CallOutcome,
+    ) -> CallOutcome {
+        self.end_frame(&outcome.result, None);
+        outcome
+    }
+
+    fn create(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        inputs: &mut CreateInputs,
+    ) -> Option<CreateOutcome> {
+        let gas = if self.stack.is_empty() { context.env.tx.gas_limit } else { inputs.gas_limit };
+        self.stack.push(CallFrame {
+            kind: match inputs.scheme {
+                CreateScheme::Create => CallKind::Create,
+                CreateScheme::Create2 { .. } => CallKind::Create2,
+            },
+            from: inputs.caller,
+            value: Some(inputs.value),
+            gas,
+            input: inputs.init_code.clone(),
+            ..Default::default()
+        });
+        None
+    }
+
+    fn create_end(
+        &mut self,
+        _: &mut EvmContext<DB>,
+        _: &CreateInputs,
+        outcome: CreateOutcome,
+    ) -> CreateOutcome {
+        self.end_frame(&outcome.result, outcome.address);
+        outcome
+    }
+
+    fn selfdestruct(&mut self, contract: Address, target: Address, value: U256) {
+        if let Some(frame) = self.stack.last_mut() {
+            frame.calls.push(CallFrame {
+                kind: CallKind::SelfDestruct,
+                from: contract,
+                to: Some(target),
+                value: Some(value),
+                ..Default::default()
+            });
+        }
+    }
+}
+
+impl<DB: Database> KonaInspector<DB> for CallTracer {
+    fn transaction_start(&mut self, _: B256) {
+        self.stack.clear();
+        self.root = None;
+    }
+
+    fn transaction_end(&mut self, tx_hash: B256, result: &ExecutionResult) {
+        if let Some(mut root) = self.root.take() {
+            // The gas used by the top-level call includes the intrinsic gas and refunds.
+            root.gas_used = result.gas_used();
+            self.traces.push((tx_hash, root));
+        }
+    }
+}
+
+/// Returns the geth error message for a failed [InstructionResult].
+fn error_message(result: InstructionResult) -> String {
+    match result {
+        InstructionResult::Revert => "execution reverted",
+        InstructionResult::OutOfGas |
+        InstructionResult::MemoryOOG |
+        InstructionResult::MemoryLimitOOG |
+        InstructionResult::PrecompileOOG |
+        InstructionResult::InvalidOperandOOG => "out of gas",
+        InstructionResult::CallTooDeep => "max call depth exceeded",
+        InstructionResult::OutOfFunds => "insufficient balance for transfer",
+        InstructionResult::OpcodeNotFound | InstructionResult::InvalidFEOpcode => "invalid opcode",
+        InstructionResult::InvalidJump => "invalid jump destination",
+        InstructionResult::StateChangeDuringStaticCall |
+        InstructionResult::CallNotAllowedInsideStatic => "write protection",
+        InstructionResult::StackUnderflow => "stack underflow",
+        InstructionResult::StackOverflow => "stack limit reached 1024 (1023)",
+        InstructionResult::OutOfOffset => "return data out of bounds",
+        InstructionResult::CreateCollision => "contract address collision",
+        InstructionResult::NonceOverflow => "nonce uint64 overflow",
+        InstructionResult::CreateContractSizeLimit => "max code size exceeded",
+        InstructionResult::CreateInitCodeSizeLimit => "max initcode size exceeded",
+        InstructionResult::CreateContractStartingWithEF => "invalid code: must not begin with 0xef",
+        InstructionResult::PrecompileError => "precompile failed",
+        other => return format!("{other:?}"),
+    }
+    .to_string()
+}
+
+/// Decodes the reason of an `Error(string)` revert, if the output is one.
+fn decode_revert_reason(output: &[u8]) -> Option<String> {
+    let data = output.strip_prefix(&ERROR_SELECTOR)?;
+    let offset = usize::try_from(U256::try_from_be_slice(data.get(..32)?)?).ok()?;
+    let len_word = data.get(offset..offset.checked_add(32)?)?;
+    let len = usize::try_from(U256::try_from_be_slice(len_word)?).ok()?;
+    let start = offset + 32;
+    let reason = data.get(start..start.checked_add(len)?)?;
+    core::str::from_utf8(reason).ok().map(ToString::to_string)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use revm::{db::EmptyDB, interpreter::Gas};
+
+    const fn result(result: InstructionResult, output: Bytes, remaining: u64) -> InterpreterResult {
+        InterpreterResult { result, output, gas: Gas::new(remaining) }
+    }
+
+    #[test]
+    fn test_decode_revert_reason() {
+        let mut output = ERROR_SELECTOR.to_vec();
+        output.extend_from_slice(&U256::from(32).to_be_bytes::<32>());
+        output.extend_from_slice(&U256::from(4).to_be_bytes::<32>());
+        output.extend_from_slice(b"nope");
+        output.resize(4 + 96, 0);
+
+        assert_eq!(decode_revert_reason(&output), Some("nope".to_string()));
+        assert_eq!(decode_revert_reason(&output[..40]), None);
+        assert_eq!(decode_revert_reason(&[0xde, 0xad, 0xbe, 0xef]), None);
+    }
+
+    #[test]
+    fn test_nested_frames() {
+        let mut tracer = CallTracer::default();
+        let (a, b, c) = (Address::with_last_byte(1), Address::with_last_byte(2), Address::ZERO);
+        KonaInspector::<EmptyDB>::transaction_start(&mut tracer, B256::ZERO);
+
+        tracer.stack.push(CallFrame { from: a, to: Some(b), gas: 100_000, ..Default::default() });
+        tracer.stack.push(CallFrame {
+            kind: CallKind::StaticCall,
+            from: b,
+            to: Some(c),
+            gas: 1_000,
+            ..Default::default()
+        });
+        tracer.end_frame(&result(InstructionResult::Revert, Bytes::new(), 400), None);
+        Inspector::<EmptyDB>::selfdestruct(&mut tracer, b, a, U256::from(1));
+        tracer
+            .end_frame(&result(InstructionResult::Return, Bytes::from_static(&[1]), 90_000), None);
+
+        let root = tracer.root.as_ref().unwrap();
+        assert_eq!(root.gas_used, 10_000);
+        assert_eq!(root.output, Bytes::from_static(&[1]));
+        assert_eq!(root.calls.len(), 2);
+        assert_eq!(root.calls[0].gas_used, 600);
+        assert_eq!(root.calls[0].error.as_deref(), Some("execution reverted"));
+        assert_eq!(root.calls[1].kind, CallKind::SelfDestruct);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serialize_geth_format() {
+        let frame = CallFrame {
+            from: Address::with_last_byte(1),
+            to: Some(Address::with_last_byte(2)),
+            value: Some(U256::from(16)),
+            gas: 21_000,
+            gas_used: 21_000,
+            ..Default::default()
+        };
+        let json = serde_json::to_value(&frame).unwrap();
+        assert_eq!(json["type"], "CALL");
+        assert_eq!(json["gas"], "0x5208");
+        assert_eq!(json["gasUsed"], "0x5208");
+        assert_eq!(json["value"], "0x10");
+        assert_eq!(json["input"], "0x");
+        assert!(json.get("output").is_none());
+        assert!(json.get("calls").is_none());
+
+        let kind = serde_json::to_value(CallKind::DelegateCall).unwrap();
+        assert_eq!(kind, "DELEGATECALL");
+    }
+}
@@ -0,0 +1,116 @@
+//! Contains the [KonaInspector] trait for tracing the execution of the
+//! [StatelessL2BlockExecutor], as well as tracers producing output compatible with geth's
+//! `debug_traceTransaction` RPC method.
+//!
+//! [StatelessL2BlockExecutor]: crate::StatelessL2BlockExecutor
+
+use crate::{TrieDB, TrieDBProvider};
+use alloy_primitives::B256;
+use core::{
+    fmt::{self, Debug, Formatter},
+    marker::PhantomData,
+};
+use kona_mpt::TrieHinter;
+use revm::{primitives::ExecutionResult, Database, Inspector, State};
+
+mod call;
+pub use call::{CallFrame, CallKind, CallLog, CallTracer};
+
+mod prestate;
+pub use prestate::{PrestateAccount, PrestateTracer};
+
+mod profile;
+pub use profile::{
+    BlockProfile, BlockProfiler, PrecompileCalls, ProviderFetches, TransactionProfile,
+};
+pub(crate) use profile::{ProfiledProvider, ProfilingInspector};
+
+/// A revm [Inspector] that is notified of the transaction boundaries through
+/// [Self::transaction_start] and [Self::transaction_end].
+///
+/// A [KonaInspector] implemented for every [Database] can be attached to the
+/// [StatelessL2BlockExecutor] with [StatelessL2BlockExecutorBuilder::with_inspector]. The inspector
+/// is invoked for every transaction in the executed block. System calls made before the
+/// transactions are executed are not inspected.
+///
+/// [StatelessL2BlockExecutor]: crate::StatelessL2BlockExecutor
+/// [StatelessL2BlockExecutorBuilder::with_inspector]: crate::StatelessL2BlockExecutorBuilder::with_inspector
+pub trait KonaInspector<DB: Database>: Debug + Inspector<DB> {
+    /// Called before the transaction with the given hash is executed.
+    fn transaction_start(&mut self, _tx_hash: B256) {}
+
+    /// Called after the transaction with the given hash has been executed.
+    fn transaction_end(&mut self, _tx_hash: B256, _result: &ExecutionResult) {}
+}
+
+/// A [KonaInspector] trait object over the database of the [StatelessL2BlockExecutor].
+///
+/// [StatelessL2BlockExecutor]: crate::StatelessL2BlockExecutor
+pub type DynKonaInspector<'a, F, H> =
+    dyn for<'t> KonaInspector<State<&'t mut TrieDB<F, H>>> + Send + Sync + 'a;
+
+/// The external context of the EVM that the [StatelessL2BlockExecutor] executes transactions in.
+///
+/// Without an attached [KonaInspector], the context is a no-op inspector that is never invoked.
+///
+/// [StatelessL2BlockExecutor]: crate::StatelessL2BlockExecutor
+pub type KonaEvmContext<'a, F, H> = &'a mut DynKonaInspector<'a, F, H>;
+
+/// A [KonaInspector] that ignores every event, used as the external context of the EVM when no
+/// [KonaInspector] is attached to the executor.
+pub(crate) struct NoOpKonaInspector<F, H>(PhantomData<fn() -> (F, H)>);
+
+impl<F, H> Default for NoOpKonaInspector<F, H> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<F, H> Debug for NoOpKonaInspector<F, H> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("NoOpKonaInspector")
+    }
+}
+
+impl<'t, F, H> Inspector<State<&'t mut TrieDB<F, H>>> for NoOpKonaInspector<F, H>
+where
+    F: TrieDBProvider,
+    H: TrieHinter,
+{
+}
+
+impl<'t, F, H> KonaInspector<State<&'t mut TrieDB<F, H>>> for NoOpKonaInspector<F, H>
+where
+    F: TrieDBProvider,
+    H: TrieHinter,
+{
+}
+
+/// Returns `true` if the bytes are empty, in which case geth omits them.
+#[cfg(feature = "serde")]
+fn is_empty_bytes(bytes: &alloy_primitives::Bytes) -> bool {
+    bytes.is_empty()
+}
+
+/// The transaction hooks invoked by the executor on the external context of the EVM.
+pub(crate) trait TransactionHooks {
+    /// Called before the transaction with the given hash is executed.
+    fn transaction_start(&mut self, _tx_hash: B256) {}
+
+    /// Called after the transaction with the given hash has been executed.
+    fn transaction_end(&mut self, _tx_hash: B256, _result: &ExecutionResult) {}
+}
+
+impl<F, H> TransactionHooks for &mut DynKonaInspector<'_, F, H>
+where
+    F: TrieDBProvider,
+    H: TrieHinter,
+{
+    fn transaction_start(&mut self, tx_hash: B256) {
+        (**self).transaction_start(tx_hash)
+    }
+
+    fn transaction_end(&mut self, tx_hash: B256, result: &ExecutionResult) {
+        (**self).transaction_end(tx_hash, result)
+    }
+}
@@ -0,0 +1,207 @@
+//! Contains the [PrestateTracer], which records the state touched by each transaction in the
+//! format of geth's `prestateTracer`.
+
+use super::KonaInspector;
+use alloc::{collections::BTreeMap, vec::Vec};
+use alloy_primitives::{Address, Bytes, B256, U256};
+use revm::{
+    interpreter::{opcode, CallInputs, CallOutcome, CreateInputs, CreateOutcome, Interpreter},
+    primitives::{ExecutionResult, KECCAK_EMPTY},
+    Database, EvmContext, Inspector,
+};
+
+/// The state of an account prior to the execution of a transaction, in the format of geth's
+/// `prestateTracer`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PrestateAccount {
+    /// The balance of the account.
+    pub balance: U256,
+    /// The nonce of the account.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "is_zero"))]
+    pub nonce: u64,
+    /// The code of the account.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "super::is_empty_bytes"))]
+    pub code: Bytes,
+    /// The storage slots of the account read or written by the transaction.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "BTreeMap::is_empty"))]
+    pub storage: BTreeMap<B256, B256>,
+}
+
+/// Returns `true` if the nonce is zero, in which case geth omits it.
+#[cfg(feature = "serde")]
+const fn is_zero(nonce: &u64) -> bool {
+    *nonce == 0
+}
+
+/// A [KonaInspector] that records the state of every account and storage slot touched by each
+/// transaction, prior to its execution, in the format of geth's `prestateTracer`.
+///
+/// Accounts created by a transaction have no prior state, and are not recorded.
+#[derive(Debug, Clone, Default)]
+pub struct PrestateTracer {
+    /// The prestate of the current transaction.
+    current: BTreeMap<Address, PrestateAccount>,
+    /// The prestates of the executed transactions, keyed by transaction hash.
+    traces: Vec<(B256, BTreeMap<Address, PrestateAccount>)>,
+}
+
+impl PrestateTracer {
+    /// Returns the prestates of the executed transactions, keyed by transaction hash.
+    pub fn traces(&self) -> &[(B256, BTreeMap<Address, PrestateAccount>)] {
+        &self.traces
+    }
+
+    /// Returns the prestate of the transaction with the given hash, if it was executed.
+    pub fn trace(&self, tx_hash: B256) -> Option<&BTreeMap<Address, PrestateAccount>> {
+        self.traces.iter().find(|(hash, _)| *hash == tx_hash).map(|(_, prestate)| prestate)
+    }
+
+    /// Consumes the tracer, returning the prestates of the executed transactions.
+    pub fn into_traces(self) -> Vec<(B256, BTreeMap<Address, PrestateAccount>)> {
+        self.traces
+    }
+
+    /// Records the state of the account at `address` prior to the transaction, if it has not
+    /// been recorded yet.
+    ///
+    /// The account is read from the database, which does not observe the uncommitted changes of
+    /// the current transaction.
+    fn touch_account<DB: Database>(&mut self, db: &mut DB, address: Address) {
+        if self.current.contains_key(&address) {
+            return;
+        }
+        // Accounts that do not exist yet are recorded as empty, as geth does.
+        let Ok(info) = db.basic(address).map(Option::unwrap_or_default) else {
+            return;
+        };
+        let code = match info.code {
+            Some(code) => code.original_bytes(),
+            None if info.code_hash != KECCAK_EMPTY => db
+                .code_by_hash(info.code_hash)
+                .map(|code| code.original_bytes())
+                .unwrap_or_default(),
+            None => Bytes::new(),
+        };
+        self.current.insert(
+            address,
+            PrestateAccount {
+                balance: info.balance,
+                nonce: info.nonce,
+                code,
+                ..Default::default()
+            },
+        );
+    }
+
+    /// Records the value of the storage slot of the account at `address` prior to the
+    /// transaction, if it has not been recorded yet.
+    fn touch_slot<DB: Database>(&mut self, db: &mut DB, address: Address, slot: U256) {
+        self.touch_account(db, address);
+        let Some(account) = self.current.get_mut(&address) else {
+            return;
+        };
+        let key = B256::from(slot);
+        if account.storage.contains_key(&key) {
+            return;
+        }
+        if let Ok(value) = db.storage(address, slot) {
+            account.storage.insert(key, value.into());
+        }
+    }
+}
+
+impl<DB: Database> Inspector<DB> for PrestateTracer {
+    fn step(&mut self, interp: &mut Interpreter, context: &mut EvmContext<DB>) {
+        let Ok(top) = interp.stack().peek(0) else {
+            return;
+        };
+        match interp.current_opcode() {
+            opcode::SLOAD | opcode::SSTORE => {
+                self.touch_slot(&mut context.db, interp.contract.target_address, top)
+            }
+            opcode::BALANCE |
+            opcode::EXTCODESIZE |
+            opcode::EXTCODECOPY |
+            opcode::EXTCODEHASH |
+            opcode::SELFDESTRUCT => {
+                self.touch_account(&mut context.db, Address::from_word(top.into()))
+            }
+            _ => {}
+        }
+    }
+
+    fn call(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        inputs: &mut CallInputs,
+    ) -> Option<CallOutcome> {
+        if self.current.is_empty() {
+            let coinbase = context.env.block.coinbase;
+            self.touch_account(&mut context.db, coinbase);
+        }
+        self.touch_account(&mut context.db, inputs.caller);
+        self.touch_account(&mut context.db, inputs.target_address);
+        self.touch_account(&mut context.db, inputs.bytecode_address);
+        None
+    }
+
+    fn create(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        inputs: &mut CreateInputs,
+    ) -> Option<CreateOutcome> {
+        if self.current.is_empty() {
+            let coinbase = context.env.block.coinbase;
+            self.touch_account(&mut context.db, coinbase);
+        }
+        self.touch_account(&mut context.db, inputs.caller);
+        None
+    }
+}
+
+impl<DB: Database> KonaInspector<DB> for PrestateTracer {
+    fn transaction_start(&mut self, _: B256) {
+        self.current.clear();
+    }
+
+    fn transaction_end(&mut self, tx_hash: B256, _: &ExecutionResult) {
+        self.traces.push((tx_hash, core::mem::take(&mut self.current)));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use revm::{
+        db::{CacheDB, EmptyDB},
+        primitives::AccountInfo,
+    };
+
+    #[test]
+    fn test_touch_records_prestate_once() {
+        let address = Address::with_last_byte(1);
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(
+            address,
+            AccountInfo { balance: U256::from(10), nonce: 2, ..Default::default() },
+        );
+        db.insert_account_storage(address, U256::from(1), U256::from(5)).unwrap();
+
+        let mut tracer = PrestateTracer::default();
+        tracer.touch_slot(&mut db, address, U256::from(1));
+        tracer.touch_slot(&mut db, address, U256::from(2));
+
+        // Later changes to the database are not reflected in the recorded prestate.
+        db.insert_account_storage(address, U256::from(1), U256::from(6)).unwrap();
+        tracer.touch_slot(&mut db, address, U256::from(1));
+        tracer.touch_account(&mut db, Address::with_last_byte(2));
+
+        let account = &tracer.current[&address];
+        assert_eq!(account.balance, U256::from(10));
+        assert_eq!(account.nonce, 2);
+        assert_eq!(account.storage[&B256::with_last_byte(1)], B256::with_last_byte(5));
+        assert_eq!(account.storage[&B256::with_last_byte(2)], B256::ZERO);
+        assert_eq!(tracer.current.len(), 2);
+    }
+}
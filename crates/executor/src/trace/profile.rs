@@ -0,0 +1,670 @@
+//! Contains the [BlockProfiler], which reports the gas used, precompile calls, state accesses and
+//! provider fetches of each transaction executed by the [StatelessL2BlockExecutor].
+//!
+//! [StatelessL2BlockExecutor]: crate::StatelessL2BlockExecutor
+
+use super::KonaInspector;
+use crate::{DynKonaInspector, TrieDB, TrieDBProvider};
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    sync::Arc,
+    vec::Vec,
+};
+use alloy_consensus::Header;
+use alloy_primitives::{Address, Bytes, Log, B256, U256};
+use core::{
+    fmt::{self, Debug, Formatter},
+    ops::Add,
+};
+use kona_mpt::{TrieHinter, TrieNode, TrieProvider};
+use revm::{
+    interpreter::{
+        opcode, CallInputs, CallOutcome, CreateInputs, CreateOutcome, EOFCreateInputs, Interpreter,
+    },
+    primitives::ExecutionResult,
+    Database, EvmContext, Inspector, State,
+};
+use spin::Mutex;
+
+/// The number of preimages fetched through the [TrieDBProvider].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct ProviderFetches {
+    /// The number of trie nodes fetched.
+    pub trie_nodes: u64,
+    /// The number of bytecodes fetched.
+    pub bytecodes: u64,
+    /// The number of headers fetched.
+    pub headers: u64,
+}
+
+impl ProviderFetches {
+    /// Returns the fetches made since the `earlier` snapshot of the running totals.
+    pub const fn since(&self, earlier: &Self) -> Self {
+        Self {
+            trie_nodes: self.trie_nodes - earlier.trie_nodes,
+            bytecodes: self.bytecodes - earlier.bytecodes,
+            headers: self.headers - earlier.headers,
+        }
+    }
+}
+
+impl Add for ProviderFetches {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            trie_nodes: self.trie_nodes + other.trie_nodes,
+            bytecodes: self.bytecodes + other.bytecodes,
+            headers: self.headers + other.headers,
+        }
+    }
+}
+
+/// The calls made to a precompile within a transaction.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct PrecompileCalls {
+    /// The number of calls.
+    pub calls: u64,
+    /// The gas spent by the calls.
+    pub gas_used: u64,
+}
+
+/// The profile of a single executed transaction.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct TransactionProfile {
+    /// The hash of the transaction.
+    pub tx_hash: B256,
+    /// The gas used by the transaction.
+    pub gas_used: u64,
+    /// Whether the transaction succeeded.
+    pub success: bool,
+    /// The calls made to precompiles, keyed by precompile address.
+    pub precompile_calls: BTreeMap<Address, PrecompileCalls>,
+    /// The number of distinct accounts touched.
+    pub accounts_touched: u64,
+    /// The number of distinct storage slots read or written.
+    pub storage_slots_touched: u64,
+    /// The number of executed `SLOAD` instructions.
+    pub sloads: u64,
+    /// The number of executed `SSTORE` instructions.
+    pub sstores: u64,
+    /// The preimages fetched while executing the transaction.
+    pub fetches: ProviderFetches,
+}
+
+/// The profile of a single executed block.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct BlockProfile {
+    /// The number of the block.
+    pub number: u64,
+    /// The gas used by the block.
+    pub gas_used: u64,
+    /// The profiles of the transactions in the block, in execution order.
+    pub transactions: Vec<TransactionProfile>,
+    /// The preimages fetched by the system calls and upgrades applied before the transactions.
+    pub pre_execution_fetches: ProviderFetches,
+    /// The preimages fetched while computing the state root of the block.
+    pub state_root_fetches: ProviderFetches,
+}
+
+impl BlockProfile {
+    /// Returns the total preimages fetched while executing the block.
+    pub fn total_fetches(&self) -> ProviderFetches {
+        self.transactions
+            .iter()
+            .fold(self.pre_execution_fetches + self.state_root_fetches, |total, tx| {
+                total + tx.fetches
+            })
+    }
+}
+
+/// The profiling state shared between the clones of a [BlockProfiler].
+#[derive(Debug, Default)]
+struct SharedProfile {
+    /// The running totals of the preimages fetched through the provider.
+    fetches: ProviderFetches,
+    /// The running totals at the start of the current phase of the block.
+    phase_start: ProviderFetches,
+    /// The profile of the block being executed.
+    current: Option<BlockProfile>,
+    /// The profiles of the executed blocks.
+    profiles: Vec<BlockProfile>,
+}
+
+/// The accesses of the transaction being executed.
+#[derive(Debug, Clone, Default)]
+struct TransactionAccesses {
+    /// The running totals of the fetches at the start of the transaction.
+    fetches_start: ProviderFetches,
+    /// The calls made to precompiles.
+    precompile_calls: BTreeMap<Address, PrecompileCalls>,
+    /// The accounts touched.
+    accounts: BTreeSet<Address>,
+    /// The storage slots read or written.
+    slots: BTreeSet<(Address, U256)>,
+    /// The number of executed `SLOAD` instructions.
+    sloads: u64,
+    /// The number of executed `SSTORE` instructions.
+    sstores: u64,
+}
+
+/// A profiler reporting the gas used, precompile calls, touched accounts and storage slots, and
+/// the trie nodes, bytecodes and headers fetched through the [TrieDBProvider] for each transaction
+/// executed by the [StatelessL2BlockExecutor].
+///
+/// Profiling is enabled with [StatelessL2BlockExecutorBuilder::with_profiler]. Clones of the
+/// [BlockProfiler] share the recorded profiles, so that a clone kept by the caller can read them
+/// back after execution. Profiling attaches an inspector to the EVM, and is considerably slower
+/// than plain execution.
+///
+/// [StatelessL2BlockExecutor]: crate::StatelessL2BlockExecutor
+/// [StatelessL2BlockExecutorBuilder::with_profiler]: crate::StatelessL2BlockExecutorBuilder::with_profiler
+#[derive(Debug, Clone, Default)]
+pub struct BlockProfiler {
+    /// The state shared between clones.
+    shared: Arc<Mutex<SharedProfile>>,
+    /// The accesses of the transaction being executed.
+    accesses: TransactionAccesses,
+}
+
+impl BlockProfiler {
+    /// Creates a new [BlockProfiler].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a copy of the [BlockProfile]s of the blocks executed so far.
+    pub fn profiles(&self) -> Vec<BlockProfile> {
+        self.shared.lock().profiles.clone()
+    }
+
+    /// Takes the [BlockProfile]s of the blocks executed so far, leaving none in their place.
+    pub fn take_profiles(&self) -> Vec<BlockProfile> {
+        core::mem::take(&mut self.shared.lock().profiles)
+    }
+
+    /// Starts profiling the block with the given number.
+    pub(crate) fn start_block(&self, number: u64) {
+        let mut shared = self.shared.lock();
+        shared.phase_start = shared.fetches;
+        shared.current = Some(BlockProfile { number, ..Default::default() });
+    }
+
+    /// Marks the end of the pre-execution system calls of the current block.
+    pub(crate) fn start_transactions(&self) {
+        let mut shared = self.shared.lock();
+        let fetches = shared.fetches.since(&shared.phase_start);
+        if let Some(block) = shared.current.as_mut() {
+            block.pre_execution_fetches = fetches;
+        }
+    }
+
+    /// Marks the start of the state root computation of the current block.
+    pub(crate) fn start_state_root(&self) {
+        let mut shared = self.shared.lock();
+        shared.phase_start = shared.fetches;
+    }
+
+    /// Completes the profile of the current block, which used the given amount of gas.
+    pub(crate) fn finish_block(&self, gas_used: u64) {
+        let mut shared = self.shared.lock();
+        let fetches = shared.fetches.since(&shared.phase_start);
+        if let Some(mut block) = shared.current.take() {
+            block.gas_used = gas_used;
+            block.state_root_fetches = fetches;
+            shared.profiles.push(block);
+        }
+    }
+
+    /// Records a fetch through the provider.
+    fn record_fetch(&self, record: impl FnOnce(&mut ProviderFetches)) {
+        record(&mut self.shared.lock().fetches);
+    }
+
+    /// Records a call to a precompile that spent the given amount of gas.
+    fn record_precompile_call(&mut self, address: Address, gas_used: u64) {
+        let calls = self.accesses.precompile_calls.entry(address).or_default();
+        calls.calls += 1;
+        calls.gas_used += gas_used;
+    }
+}
+
+impl<DB: Database> Inspector<DB> for BlockProfiler {
+    fn step(&mut self, interp: &mut Interpreter, _: &mut EvmContext<DB>) {
+        let Ok(top) = interp.stack().peek(0) else {
+            return;
+        };
+        match interp.current_opcode() {
+            opcode::SLOAD | opcode::SSTORE => {
+                if interp.current_opcode() == opcode::SLOAD {
+                    self.accesses.sloads += 1;
+                } else {
+                    self.accesses.sstores += 1;
+                }
+                self.accesses.slots.insert((interp.contract.target_address, top));
+            }
+            opcode::BALANCE |
+            opcode::EXTCODESIZE |
+            opcode::EXTCODECOPY |
+            opcode::EXTCODEHASH |
+            opcode::SELFDESTRUCT => {
+                self.accesses.accounts.insert(Address::from_word(top.into()));
+            }
+            _ => {}
+        }
+    }
+
+    fn call(&mut self, _: &mut EvmContext<DB>, inputs: &mut CallInputs) -> Option<CallOutcome> {
+        self.accesses.accounts.extend([
+            inputs.caller,
+            inputs.target_address,
+            inputs.bytecode_address,
+        ]);
+        None
+    }
+
+    fn call_end(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        inputs: &CallInputs,
+        outcome: CallOutcome,
+    ) -> CallOutcome {
+        if context.precompiles.contains(&inputs.bytecode_address) {
+            self.record_precompile_call(inputs.bytecode_address, outcome.result.gas.spent());
+        }
+        outcome
+    }
+
+    fn create(
+        &mut self,
+        _: &mut EvmContext<DB>,
+        inputs: &mut CreateInputs,
+    ) -> Option<CreateOutcome> {
+        self.accesses.accounts.insert(inputs.caller);
+        None
+    }
+
+    fn create_end(
+        &mut self,
+        _: &mut EvmContext<DB>,
+        _: &CreateInputs,
+        outcome: CreateOutcome,
+    ) -> CreateOutcome {
+        self.accesses.accounts.extend(outcome.address);
+        outcome
+    }
+
+    fn eofcreate_end(
+        &mut self,
+        _: &mut EvmContext<DB>,
+        _: &EOFCreateInputs,
+        outcome: CreateOutcome,
+    ) -> CreateOutcome {
+        self.accesses.accounts.extend(outcome.address);
+        outcome
+    }
+
+    fn selfdestruct(&mut self, contract: Address, target: Address, _: U256) {
+        self.accesses.accounts.extend([contract, target]);
+    }
+}
+
+impl<DB: Database> KonaInspector<DB> for BlockProfiler {
+    fn transaction_start(&mut self, _: B256) {
+        self.accesses =
+            TransactionAccesses { fetches_start: self.shared.lock().fetches, ..Default::default() };
+    }
+
+    fn transaction_end(&mut self, tx_hash: B256, result: &ExecutionResult) {
+        let accesses = core::mem::take(&mut self.accesses);
+        let mut shared = self.shared.lock();
+        let fetches = shared.fetches.since(&accesses.fetches_start);
+        if let Some(block) = shared.current.as_mut() {
+            block.transactions.push(TransactionProfile {
+                tx_hash,
+                gas_used: result.gas_used(),
+                success: result.is_success(),
+                precompile_calls: accesses.precompile_calls,
+                accounts_touched: accesses.accounts.len() as u64,
+                storage_slots_touched: accesses.slots.len() as u64,
+                sloads: accesses.sloads,
+                sstores: accesses.sstores,
+                fetches,
+            });
+        }
+    }
+}
+
+/// A [TrieDBProvider] wrapper that records the fetches made through the inner provider with the
+/// [BlockProfiler], if one is set.
+#[derive(Debug, Clone)]
+pub(crate) struct ProfiledProvider<F> {
+    /// The inner provider.
+    inner: F,
+    /// The [BlockProfiler] recording the fetches.
+    profiler: Option<BlockProfiler>,
+}
+
+impl<F> ProfiledProvider<F> {
+    /// Creates a new [ProfiledProvider] that does not record fetches.
+    pub(crate) const fn new(inner: F) -> Self {
+        Self { inner, profiler: None }
+    }
+
+    /// Sets the [BlockProfiler] recording the fetches.
+    pub(crate) fn set_profiler(&mut self, profiler: BlockProfiler) {
+        self.profiler = Some(profiler);
+    }
+
+    /// Records a fetch with the [BlockProfiler], if one is set.
+    fn record(&self, record: impl FnOnce(&mut ProviderFetches)) {
+        if let Some(profiler) = self.profiler.as_ref() {
+            profiler.record_fetch(record);
+        }
+    }
+}
+
+impl<F: TrieProvider> TrieProvider for ProfiledProvider<F> {
+    type Error = F::Error;
+
+    fn trie_node_by_hash(&self, key: B256) -> Result<TrieNode, Self::Error> {
+        self.record(|fetches| fetches.trie_nodes += 1);
+        self.inner.trie_node_by_hash(key)
+    }
+}
+
+impl<F: TrieDBProvider> TrieDBProvider for ProfiledProvider<F> {
+    fn bytecode_by_hash(&self, code_hash: B256) -> Result<Bytes, Self::Error> {
+        self.record(|fetches| fetches.bytecodes += 1);
+        self.inner.bytecode_by_hash(code_hash)
+    }
+
+    fn header_by_hash(&self, hash: B256) -> Result<Header, Self::Error> {
+        self.record(|fetches| fetches.headers += 1);
+        self.inner.header_by_hash(hash)
+    }
+}
+
+/// A [KonaInspector] that feeds the EVM's events to the executor's [BlockProfiler], as well as to
+/// the [DynKonaInspector] attached to the executor, if any.
+pub(crate) struct ProfilingInspector<'i, 'a, F, H>
+where
+    F: TrieDBProvider,
+    H: TrieHinter,
+{
+    /// The [BlockProfiler].
+    profiler: &'i mut BlockProfiler,
+    /// The attached [DynKonaInspector], if any.
+    inner: Option<&'i mut DynKonaInspector<'a, F, H>>,
+}
+
+impl<'i, 'a, F, H> ProfilingInspector<'i, 'a, F, H>
+where
+    F: TrieDBProvider,
+    H: TrieHinter,
+{
+    /// Creates a new [ProfilingInspector].
+    pub(crate) fn new(
+        profiler: &'i mut BlockProfiler,
+        inner: Option<&'i mut DynKonaInspector<'a, F, H>>,
+    ) -> Self {
+        Self { profiler, inner }
+    }
+}
+
+impl<F, H> Debug for ProfilingInspector<'_, '_, F, H>
+where
+    F: TrieDBProvider,
+    H: TrieHinter,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProfilingInspector")
+            .field("profiler", &self.profiler)
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+
+impl<'t, F, H> Inspector<State<&'t mut TrieDB<F, H>>> for ProfilingInspector<'_, '_, F, H>
+where
+    F: TrieDBProvider,
+    H: TrieHinter,
+{
+    fn initialize_interp(
+        &mut self,
+        interp: &mut Interpreter,
+        context: &mut EvmContext<State<&'t mut TrieDB<F, H>>>,
+    ) {
+        if let Some(inner) = self.inner.as_deref_mut() {
+            inner.initialize_interp(interp, context);
+        }
+    }
+
+    fn step(
+        &mut self,
+        interp: &mut Interpreter,
+        context: &mut EvmContext<State<&'t mut TrieDB<F, H>>>,
+    ) {
+        self.profiler.step(interp, context);
+        if let Some(inner) = self.inner.as_deref_mut() {
+            inner.step(interp, context);
+        }
+    }
+
+    fn step_end(
+        &mut self,
+        interp: &mut Interpreter,
+        context: &mut EvmContext<State<&'t mut TrieDB<F, H>>>,
+    ) {
+        if let Some(inner) = self.inner.as_deref_mut() {
+            inner.step_end(interp, context);
+        }
+    }
+
+    fn log(
+        &mut self,
+        interp: &mut Interpreter,
+        context: &mut EvmContext<State<&'t mut TrieDB<F, H>>>,
+        log: &Log,
+    ) {
+        if let Some(inner) = self.inner.as_deref_mut() {
+            inner.log(interp, context, log);
+        }
+    }
+
+    fn call(
+        &mut self,
+        context: &mut EvmContext<State<&'t mut TrieDB<F, H>>>,
+        inputs: &mut CallInputs,
+    ) -> Option<CallOutcome> {
+        self.profiler.call(context, inputs);
+        self.inner.as_deref_mut().and_then(|inner| inner.call(context, inputs))
+    }
+
+    fn call_end(
+        &mut self,
+        context: &mut EvmContext<State<&'t mut TrieDB<F, H>>>,
+        inputs: &CallInputs,
+        outcome: CallOutcome,
+    ) -> CallOutcome {
+        let outcome = match self.inner.as_deref_mut() {
+            Some(inner) => inner.call_end(context, inputs, outcome),
+            None => outcome,
+        };
+        self.profiler.call_end(context, inputs, outcome)
+    }
+
+    fn create(
+        &mut self,
+        context: &mut EvmContext<State<&'t mut TrieDB<F, H>>>,
+        inputs: &mut CreateInputs,
+    ) -> Option<CreateOutcome> {
+        self.profiler.create(context, inputs);
+        self.inner.as_deref_mut().and_then(|inner| inner.create(context, inputs))
+    }
+
+    fn create_end(
+        &mut self,
+        context: &mut EvmContext<State<&'t mut TrieDB<F, H>>>,
+        inputs: &CreateInputs,
+        outcome: CreateOutcome,
+    ) -> CreateOutcome {
+        let outcome = match self.inner.as_deref_mut() {
+            Some(inner) => inner.create_end(context, inputs, outcome),
+            None => outcome,
+        };
+        self.profiler.create_end(context, inputs, outcome)
+    }
+
+    fn eofcreate(
+        &mut self,
+        context: &mut EvmContext<State<&'t mut TrieDB<F, H>>>,
+        inputs: &mut EOFCreateInputs,
+    ) -> Option<CreateOutcome> {
+        self.inner.as_deref_mut().and_then(|inner| inner.eofcreate(context, inputs))
+    }
+
+    fn eofcreate_end(
+        &mut self,
+        context: &mut EvmContext<State<&'t mut TrieDB<F, H>>>,
+        inputs: &EOFCreateInputs,
+        outcome: CreateOutcome,
+    ) -> CreateOutcome {
+        let outcome = match self.inner.as_deref_mut() {
+            Some(inner) => inner.eofcreate_end(context, inputs, outcome),
+            None => outcome,
+        };
+        self.profiler.eofcreate_end(context, inputs, outcome)
+    }
+
+    fn selfdestruct(&mut self, contract: Address, target: Address, value: U256) {
+        Inspector::<State<&'t mut TrieDB<F, H>>>::selfdestruct(
+            self.profiler,
+            contract,
+            target,
+            value,
+        );
+        if let Some(inner) = self.inner.as_deref_mut() {
+            Inspector::<State<&'t mut TrieDB<F, H>>>::selfdestruct(inner, contract, target, value);
+        }
+    }
+}
+
+impl<'t, F, H> KonaInspector<State<&'t mut TrieDB<F, H>>> for ProfilingInspector<'_, '_, F, H>
+where
+    F: TrieDBProvider,
+    H: TrieHinter,
+{
+    fn transaction_start(&mut self, tx_hash: B256) {
+        KonaInspector::<State<&'t mut TrieDB<F, H>>>::transaction_start(self.profiler, tx_hash);
+        if let Some(inner) = self.inner.as_deref_mut() {
+            inner.transaction_start(tx_hash);
+        }
+    }
+
+    fn transaction_end(&mut self, tx_hash: B256, result: &ExecutionResult) {
+        KonaInspector::<State<&'t mut TrieDB<F, H>>>::transaction_end(
+            self.profiler,
+            tx_hash,
+            result,
+        );
+        if let Some(inner) = self.inner.as_deref_mut() {
+            inner.transaction_end(tx_hash, result);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{NoopTrieDBProvider, StatelessL2BlockExecutor, UpgradeAction, UpgradeRegistry};
+    use alloy_consensus::Sealable;
+    use alloy_eips::eip2718::Encodable2718;
+    use alloy_primitives::{address, TxKind};
+    use alloy_rpc_types_engine::PayloadAttributes;
+    use kona_mpt::NoopTrieHinter;
+    use maili_genesis::RollupConfig;
+    use op_alloy_consensus::{OpTxEnvelope, TxDeposit};
+    use op_alloy_rpc_types_engine::OpPayloadAttributes;
+
+    const CONTRACT: Address = address!("0000000000000000000000000000000000000abc");
+    const IDENTITY: Address = address!("0000000000000000000000000000000000000004");
+
+    /// Calls the identity precompile with empty input, then writes and reads back slot 0.
+    const CALL_IDENTITY_AND_STORE: [u8; 23] = [
+        0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x04, 0x5a, 0xfa, 0x50, 0x60, 0x01,
+        0x60, 0x00, 0x55, 0x60, 0x00, 0x54, 0x50, 0x00,
+    ];
+
+    #[test]
+    fn test_profiled_provider_counts_fetches() {
+        let profiler = BlockProfiler::new();
+        let mut provider = ProfiledProvider::new(NoopTrieDBProvider);
+        provider.trie_node_by_hash(B256::ZERO).unwrap();
+
+        provider.set_profiler(profiler.clone());
+        provider.trie_node_by_hash(B256::ZERO).unwrap();
+        provider.trie_node_by_hash(B256::ZERO).unwrap();
+        provider.bytecode_by_hash(B256::ZERO).unwrap();
+
+        assert_eq!(
+            profiler.shared.lock().fetches,
+            ProviderFetches { trie_nodes: 2, bytecodes: 1, headers: 0 }
+        );
+    }
+
+    #[test]
+    fn test_profile_block() {
+        let config = RollupConfig::default();
+        let profiler = BlockProfiler::new();
+        let mut executor =
+            StatelessL2BlockExecutor::builder(&config, NoopTrieDBProvider, NoopTrieHinter)
+                .with_upgrade_registry(UpgradeRegistry::new().with_upgrade(
+                    1,
+                    [UpgradeAction::DeployCode {
+                        address: CONTRACT,
+                        code: Bytes::from_static(&CALL_IDENTITY_AND_STORE),
+                    }],
+                ))
+                .with_profiler(profiler.clone())
+                .build();
+
+        let deposit = OpTxEnvelope::Deposit(
+            TxDeposit {
+                from: Address::with_last_byte(1),
+                to: TxKind::Call(CONTRACT),
+                gas_limit: 1_000_000,
+                ..Default::default()
+            }
+            .seal_slow(),
+        );
+        let payload = OpPayloadAttributes {
+            payload_attributes: PayloadAttributes { timestamp: 1, ..Default::default() },
+            gas_limit: Some(30_000_000),
+            transactions: Some(vec![deposit.encoded_2718().into()]),
+            ..Default::default()
+        };
+        let artifacts = executor.execute_payload(payload).unwrap();
+
+        let [block] = &profiler.take_profiles()[..] else { panic!("expected a single profile") };
+        assert_eq!(block.number, 1);
+        assert_eq!(block.gas_used, artifacts.block_header.gas_used);
+
+        let [tx] = &block.transactions[..] else { panic!("expected a single transaction") };
+        assert!(tx.success);
+        assert_eq!(tx.tx_hash, deposit.tx_hash());
+        assert_eq!(tx.precompile_calls[&IDENTITY], PrecompileCalls { calls: 1, gas_used: 15 });
+        assert_eq!(tx.accounts_touched, 3);
+        assert_eq!(tx.storage_slots_touched, 1);
+        assert_eq!((tx.sloads, tx.sstores), (1, 1));
+        assert!(profiler.profiles().is_empty());
+    }
+}
@@ -0,0 +1,253 @@
+//! Contains the [UpgradeRegistry], which holds the hardfork-time state changes and additional
+//! precompiles of chains that extend the OP Stack.
+
+use alloc::{boxed::Box, sync::Arc, vec::Vec};
+use alloy_primitives::{Address, Bytes, U256};
+use revm::{
+    handler::register::{EvmHandler, HandleRegisterBox},
+    precompile::PrecompileWithAddress,
+    Database,
+};
+
+/// An irregular state transition applied at the activation of a hardfork, prior to executing the
+/// transactions of the activation block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UpgradeAction {
+    /// Sets the code of the account, retaining its balance, nonce and storage.
+    DeployCode {
+        /// The address of the account.
+        address: Address,
+        /// The raw bytecode to deploy.
+        code: Bytes,
+    },
+    /// Writes a storage slot of the account.
+    SetStorage {
+        /// The address of the account.
+        address: Address,
+        /// The storage slot to write.
+        slot: U256,
+        /// The value to write to the slot.
+        value: U256,
+    },
+    /// Calls a contract as a system call, in the same manner as the EIP-4788 beacon root call. The
+    /// call does not consume block gas, and a reverted call is ignored.
+    SystemCall {
+        /// The caller of the system call.
+        caller: Address,
+        /// The contract to call.
+        target: Address,
+        /// The calldata of the system call.
+        data: Bytes,
+    },
+}
+
+/// A set of [UpgradeAction]s applied at the first block at or after the activation timestamp.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HardforkUpgrade {
+    /// The activation timestamp of the upgrade.
+    pub activation_timestamp: u64,
+    /// The actions to apply, in order.
+    pub actions: Vec<UpgradeAction>,
+}
+
+impl HardforkUpgrade {
+    /// Returns `true` if the upgrade activates in the block with the given timestamp, i.e. if it
+    /// is active in the block but was not active in its parent.
+    pub const fn activates_at(&self, parent_timestamp: u64, timestamp: u64) -> bool {
+        parent_timestamp < self.activation_timestamp && timestamp >= self.activation_timestamp
+    }
+}
+
+/// A precompile that is available from its activation timestamp onwards.
+#[derive(Debug, Clone)]
+pub struct ExtraPrecompile {
+    /// The activation timestamp of the precompile.
+    pub activation_timestamp: u64,
+    /// The precompile and its address.
+    pub precompile: PrecompileWithAddress,
+}
+
+/// A registry of the [HardforkUpgrade]s and [ExtraPrecompile]s of a chain, applied by the
+/// [StatelessL2BlockExecutor] on top of the OP Stack's own hardfork rules.
+///
+/// Upgrades are applied after the OP Stack's pre-block system calls and irregular state
+/// transitions, in the order they were registered. Extra precompiles are loaded after the
+/// precompiles of the active spec and of any [KonaHandleRegister], and replace precompiles at the
+/// same address.
+///
+/// As with the OP Stack's hardforks, an upgrade activating at the genesis timestamp is never
+/// applied, since the genesis block is not executed.
+///
+/// [StatelessL2BlockExecutor]: crate::StatelessL2BlockExecutor
+/// [KonaHandleRegister]: crate::KonaHandleRegister
+#[derive(Debug, Clone, Default)]
+pub struct UpgradeRegistry {
+    /// The registered hardfork upgrades.
+    upgrades: Vec<HardforkUpgrade>,
+    /// The registered precompiles.
+    precompiles: Vec<ExtraPrecompile>,
+}
+
+impl UpgradeRegistry {
+    /// Creates an empty [UpgradeRegistry].
+    pub const fn new() -> Self {
+        Self { upgrades: Vec::new(), precompiles: Vec::new() }
+    }
+
+    /// Registers a [HardforkUpgrade] with the given actions, activating at the given timestamp.
+    pub fn with_upgrade(
+        mut self,
+        activation_timestamp: u64,
+        actions: impl IntoIterator<Item = UpgradeAction>,
+    ) -> Self {
+        self.upgrades
+            .push(HardforkUpgrade { activation_timestamp, actions: actions.into_iter().collect() });
+        self
+    }
+
+    /// Registers a precompile, available from the given timestamp onwards.
+    pub fn with_precompile(
+        mut self,
+        activation_timestamp: u64,
+        precompile: PrecompileWithAddress,
+    ) -> Self {
+        self.precompiles.push(ExtraPrecompile { activation_timestamp, precompile });
+        self
+    }
+
+    /// Returns the registered [HardforkUpgrade]s.
+    pub fn upgrades(&self) -> &[HardforkUpgrade] {
+        &self.upgrades
+    }
+
+    /// Returns the registered [ExtraPrecompile]s.
+    pub fn precompiles(&self) -> &[ExtraPrecompile] {
+        &self.precompiles
+    }
+
+    /// Returns `true` if no upgrades or precompiles are registered.
+    pub fn is_empty(&self) -> bool {
+        self.upgrades.is_empty() && self.precompiles.is_empty()
+    }
+
+    /// Returns the [UpgradeAction]s to apply in the block with the given timestamp, in order.
+    pub fn actions_at(
+        &self,
+        parent_timestamp: u64,
+        timestamp: u64,
+    ) -> impl Iterator<Item = &UpgradeAction> {
+        self.upgrades
+            .iter()
+            .filter(move |upgrade| upgrade.activates_at(parent_timestamp, timestamp))
+            .flat_map(|upgrade| upgrade.actions.iter())
+    }
+
+    /// Returns the precompiles that are available in the block with the given timestamp.
+    pub fn precompiles_at(&self, timestamp: u64) -> impl Iterator<Item = &PrecompileWithAddress> {
+        self.precompiles
+            .iter()
+            .filter(move |precompile| timestamp >= precompile.activation_timestamp)
+            .map(|precompile| &precompile.precompile)
+    }
+
+    /// Returns a handle register that loads the precompiles available in the block with the given
+    /// timestamp on top of the EVM's precompiles, or `None` if there are none.
+    pub(crate) fn precompiles_register<'a, EXT, DB: Database>(
+        &self,
+        timestamp: u64,
+    ) -> Option<HandleRegisterBox<'a, EXT, DB>> {
+        let precompiles = self.precompiles_at(timestamp).cloned().collect::<Vec<_>>();
+        if precompiles.is_empty() {
+            return None;
+        }
+
+        Some(Box::new(move |handler| extend_precompiles(handler, precompiles.clone())))
+    }
+}
+
+/// Extends the precompiles loaded by the [EvmHandler] with the given precompiles.
+fn extend_precompiles<EXT, DB: Database>(
+    handler: &mut EvmHandler<'_, EXT, DB>,
+    precompiles: Vec<PrecompileWithAddress>,
+) {
+    let load_precompiles = handler.pre_execution.load_precompiles.clone();
+    handler.pre_execution.load_precompiles = Arc::new(move || {
+        let mut loaded = load_precompiles();
+        loaded.extend(precompiles.iter().cloned());
+        loaded
+    });
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloy_primitives::address;
+    use revm::{
+        db::EmptyDB,
+        precompile::{Precompile, PrecompileOutput, PrecompileResult},
+        primitives::{HandlerCfg, SpecId},
+    };
+
+    const EXTRA_ADDRESS: Address = address!("0000000000000000000000000000000000000abc");
+
+    fn identity(input: &Bytes, _gas_limit: u64) -> PrecompileResult {
+        Ok(PrecompileOutput::new(0, input.clone()))
+    }
+
+    fn registry() -> UpgradeRegistry {
+        UpgradeRegistry::new()
+            .with_upgrade(
+                10,
+                [UpgradeAction::DeployCode {
+                    address: EXTRA_ADDRESS,
+                    code: Bytes::from_static(&[0x00]),
+                }],
+            )
+            .with_upgrade(
+                20,
+                [UpgradeAction::SetStorage {
+                    address: EXTRA_ADDRESS,
+                    slot: U256::ZERO,
+                    value: U256::from(1),
+                }],
+            )
+            .with_precompile(
+                20,
+                PrecompileWithAddress(EXTRA_ADDRESS, Precompile::Standard(identity)),
+            )
+    }
+
+    #[test]
+    fn test_actions_at_activation_block_only() {
+        let registry = registry();
+        assert_eq!(registry.actions_at(0, 9).count(), 0);
+        assert!(matches!(
+            registry.actions_at(9, 11).collect::<Vec<_>>()[..],
+            [UpgradeAction::DeployCode { .. }]
+        ));
+        assert_eq!(registry.actions_at(11, 12).count(), 0);
+
+        // A block skipping over both activations applies both upgrades, in order.
+        assert!(matches!(
+            registry.actions_at(8, 22).collect::<Vec<_>>()[..],
+            [UpgradeAction::DeployCode { .. }, UpgradeAction::SetStorage { .. }]
+        ));
+    }
+
+    #[test]
+    fn test_precompiles_register() {
+        let registry = registry();
+        assert_eq!(registry.precompiles_at(19).count(), 0);
+        assert!(registry.precompiles_register::<(), EmptyDB>(19).is_none());
+
+        let mut handler = EvmHandler::<(), EmptyDB>::new(HandlerCfg::new(SpecId::LATEST));
+        let register = registry.precompiles_register::<(), EmptyDB>(20).unwrap();
+        let builtin = (handler.pre_execution.load_precompiles)();
+        register(&mut handler);
+        let loaded = (handler.pre_execution.load_precompiles)();
+
+        assert!(!builtin.contains(&EXTRA_ADDRESS));
+        assert!(loaded.contains(&EXTRA_ADDRESS));
+        assert_eq!(loaded.addresses().len(), builtin.addresses().len() + 1);
+    }
+}
@@ -0,0 +1,365 @@
+//! Contains the [WitnessRecorder], which records the preimages touched by the
+//! [StatelessL2BlockExecutor] into an [ExecutionWitness], and the [WitnessProvider], which serves
+//! them back for offline execution.
+//!
+//! [StatelessL2BlockExecutor]: crate::StatelessL2BlockExecutor
+
+use crate::{TrieDBProvider, WitnessError};
+use alloc::{sync::Arc, vec::Vec};
+use alloy_consensus::Header;
+use alloy_primitives::{keccak256, map::HashMap, Address, Bytes, B256, U256};
+use alloy_rlp::Decodable;
+use kona_mpt::{TrieHinter, TrieNode, TrieProvider};
+use spin::Mutex;
+
+/// The preimages required to statelessly execute a block, in the format of the
+/// `debug_executionWitness` RPC method.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExecutionWitness {
+    /// The RLP-encoded trie nodes, keyed by their hash.
+    ///
+    /// `keccak(rlp(node)) => rlp(node)`
+    pub state: HashMap<B256, Bytes>,
+    /// The contract bytecodes, keyed by their hash.
+    ///
+    /// `keccak(bytecode) => bytecode`
+    pub codes: HashMap<B256, Bytes>,
+    /// The unhashed account addresses and storage slots, keyed by their hash.
+    ///
+    /// `keccak(address|slot) => address|slot`
+    pub keys: HashMap<B256, Bytes>,
+    /// The RLP-encoded headers of the ancestor blocks.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub headers: Vec<Bytes>,
+}
+
+impl ExecutionWitness {
+    /// Returns an iterator over all keccak256 preimages in the witness, keyed by their hash.
+    pub fn preimages(&self) -> impl Iterator<Item = (B256, &Bytes)> {
+        self.state
+            .iter()
+            .chain(self.codes.iter())
+            .chain(self.keys.iter())
+            .map(|(hash, preimage)| (*hash, preimage))
+            .chain(self.headers.iter().map(|header| (keccak256(header), header)))
+    }
+
+    /// Returns the hashes of the trie nodes, bytecodes and keys in `other` that are missing from
+    /// this witness.
+    ///
+    /// A witness that covers another, e.g. the one returned by an L2 node for the same block, has
+    /// no missing preimages.
+    pub fn missing_from(&self, other: &Self) -> Vec<B256> {
+        let mut missing =
+            [(&self.state, &other.state), (&self.codes, &other.codes), (&self.keys, &other.keys)]
+                .into_iter()
+                .flat_map(|(ours, theirs)| theirs.keys().filter(|hash| !ours.contains_key(*hash)))
+                .copied()
+                .collect::<Vec<_>>();
+        missing.sort_unstable();
+        missing
+    }
+}
+
+/// A [TrieDBProvider] that can also serve the raw preimages of the trie nodes and headers it
+/// decodes, such as an L2 node's database, so that they can be recorded byte-for-byte.
+pub trait RawTrieDBProvider: TrieDBProvider {
+    /// Fetches the RLP-encoded trie node with the given hash, as stored by the provider.
+    fn raw_trie_node_by_hash(&self, key: B256) -> Result<Bytes, Self::Error>;
+
+    /// Fetches the RLP-encoded [Header] with the given hash, as stored by the provider.
+    fn raw_header_by_hash(&self, hash: B256) -> Result<Bytes, Self::Error>;
+}
+
+/// A [TrieDBProvider] and [TrieHinter] wrapper that records the raw preimages of every trie node,
+/// bytecode and header served to the executor, as well as every hinted key, into an
+/// [ExecutionWitness].
+///
+/// Clones of the [WitnessRecorder] share the same [ExecutionWitness], so that a single recorder
+/// can be passed as both the provider and the hinter of the [StatelessL2BlockExecutor].
+///
+/// The executor is given the parent header directly rather than reading it through the provider,
+/// so it must be recorded with [WitnessRecorder::record_header] for the witness to be complete.
+///
+/// [StatelessL2BlockExecutor]: crate::StatelessL2BlockExecutor
+#[derive(Debug, Clone)]
+pub struct WitnessRecorder<P> {
+    /// The inner provider.
+    inner: P,
+    /// The witness recorded so far.
+    witness: Arc<Mutex<ExecutionWitness>>,
+}
+
+impl<P> WitnessRecorder<P> {
+    /// Creates a new [WitnessRecorder] wrapping the given provider.
+    pub fn new(inner: P) -> Self {
+        Self { inner, witness: Arc::new(Mutex::new(ExecutionWitness::default())) }
+    }
+
+    /// Returns a reference to the inner provider.
+    pub const fn inner(&self) -> &P {
+        &self.inner
+    }
+
+    /// Returns a copy of the [ExecutionWitness] recorded so far.
+    pub fn witness(&self) -> ExecutionWitness {
+        self.witness.lock().clone()
+    }
+
+    /// Takes the [ExecutionWitness] recorded so far, leaving an empty one in its place.
+    pub fn take_witness(&self) -> ExecutionWitness {
+        core::mem::take(&mut *self.witness.lock())
+    }
+}
+
+impl<P: RawTrieDBProvider> WitnessRecorder<P> {
+    /// Fetches and records the header with the given hash, such as the parent header of the
+    /// executed block.
+    pub fn record_header(&self, hash: B256) -> Result<Header, WitnessError> {
+        self.header_by_hash(hash)
+    }
+}
+
+impl<P: RawTrieDBProvider> TrieProvider for WitnessRecorder<P> {
+    type Error = WitnessError;
+
+    fn trie_node_by_hash(&self, key: B256) -> Result<TrieNode, Self::Error> {
+        let raw = self.inner.raw_trie_node_by_hash(key).map_err(WitnessError::provider)?;
+        let node = TrieNode::decode(&mut raw.as_ref()).map_err(WitnessError::Rlp)?;
+        if node != TrieNode::Empty {
+            self.witness.lock().state.insert(key, raw);
+        }
+        Ok(node)
+    }
+}
+
+impl<P: RawTrieDBProvider> TrieDBProvider for WitnessRecorder<P> {
+    fn bytecode_by_hash(&self, code_hash: B256) -> Result<Bytes, Self::Error> {
+        let code = self.inner.bytecode_by_hash(code_hash).map_err(WitnessError::provider)?;
+        self.witness.lock().codes.insert(code_hash, code.clone());
+        Ok(code)
+    }
+
+    fn header_by_hash(&self, hash: B256) -> Result<Header, Self::Error> {
+        let raw = self.inner.raw_header_by_hash(hash).map_err(WitnessError::provider)?;
+        let header = Header::decode(&mut raw.as_ref()).map_err(WitnessError::Rlp)?;
+        let mut witness = self.witness.lock();
+        if !witness.headers.contains(&raw) {
+            witness.headers.push(raw);
+        }
+        Ok(header)
+    }
+}
+
+impl<P: TrieHinter> TrieHinter for WitnessRecorder<P> {
+    type Error = P::Error;
+
+    fn hint_trie_node(&self, hash: B256) -> Result<(), Self::Error> {
+        self.inner.hint_trie_node(hash)
+    }
+
+    fn hint_account_proof(&self, address: Address, block_number: u64) -> Result<(), Self::Error> {
+        self.witness
+            .lock()
+            .keys
+            .insert(keccak256(address), Bytes::copy_from_slice(address.as_ref()));
+        self.inner.hint_account_proof(address, block_number)
+    }
+
+    fn hint_storage_proof(
+        &self,
+        address: Address,
+        slot: U256,
+        block_number: u64,
+    ) -> Result<(), Self::Error> {
+        let slot_bytes = B256::from(slot);
+        let mut witness = self.witness.lock();
+        witness.keys.insert(keccak256(address), Bytes::copy_from_slice(address.as_ref()));
+        witness.keys.insert(keccak256(slot_bytes), Bytes::copy_from_slice(slot_bytes.as_ref()));
+        drop(witness);
+        self.inner.hint_storage_proof(address, slot, block_number)
+    }
+}
+
+/// A [TrieDBProvider] serving the preimages of an [ExecutionWitness], allowing the block it was
+/// recorded for to be executed offline.
+#[derive(Debug, Clone, Default)]
+pub struct WitnessProvider {
+    /// The preimages of the witness, keyed by their hash.
+    preimages: HashMap<B256, Bytes>,
+}
+
+impl WitnessProvider {
+    /// Creates a new [WitnessProvider] serving the preimages of the given [ExecutionWitness].
+    pub fn new(witness: &ExecutionWitness) -> Self {
+        Self {
+            preimages: witness
+                .preimages()
+                .map(|(hash, preimage)| (hash, preimage.clone()))
+                .collect(),
+        }
+    }
+
+    /// Returns the preimage of the given hash.
+    fn preimage(&self, hash: B256) -> Result<&Bytes, WitnessError> {
+        self.preimages.get(&hash).ok_or(WitnessError::MissingPreimage(hash))
+    }
+}
+
+impl From<&ExecutionWitness> for WitnessProvider {
+    fn from(witness: &ExecutionWitness) -> Self {
+        Self::new(witness)
+    }
+}
+
+impl TrieProvider for WitnessProvider {
+    type Error = WitnessError;
+
+    fn trie_node_by_hash(&self, key: B256) -> Result<TrieNode, Self::Error> {
+        TrieNode::decode(&mut self.preimage(key)?.as_ref()).map_err(WitnessError::Rlp)
+    }
+}
+
+impl TrieDBProvider for WitnessProvider {
+    fn bytecode_by_hash(&self, code_hash: B256) -> Result<Bytes, Self::Error> {
+        self.preimage(code_hash).cloned()
+    }
+
+    fn header_by_hash(&self, hash: B256) -> Result<Header, Self::Error> {
+        Header::decode(&mut self.preimage(hash)?.as_ref()).map_err(WitnessError::Rlp)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::string::String;
+    use alloy_primitives::address;
+    use kona_mpt::NoopTrieHinter;
+
+    /// A [TrieDBProvider] serving preimages from an in-memory map.
+    #[derive(Debug, Clone, Default)]
+    struct MapProvider(HashMap<B256, Bytes>);
+
+    impl TrieProvider for MapProvider {
+        type Error = String;
+
+        fn trie_node_by_hash(&self, key: B256) -> Result<TrieNode, Self::Error> {
+            let rlp = self.0.get(&key).ok_or_else(|| String::from("missing node"))?;
+            TrieNode::decode(&mut rlp.as_ref()).map_err(|e| e.to_string())
+        }
+    }
+
+    impl TrieDBProvider for MapProvider {
+        fn bytecode_by_hash(&self, code_hash: B256) -> Result<Bytes, Self::Error> {
+            self.0.get(&code_hash).cloned().ok_or_else(|| String::from("missing code"))
+        }
+
+        fn header_by_hash(&self, hash: B256) -> Result<Header, Self::Error> {
+            let rlp = self.0.get(&hash).ok_or_else(|| String::from("missing header"))?;
+            Header::decode(&mut rlp.as_ref()).map_err(|e| e.to_string())
+        }
+    }
+
+    impl RawTrieDBProvider for MapProvider {
+        fn raw_trie_node_by_hash(&self, key: B256) -> Result<Bytes, Self::Error> {
+            self.0.get(&key).cloned().ok_or_else(|| String::from("missing node"))
+        }
+
+        fn raw_header_by_hash(&self, hash: B256) -> Result<Bytes, Self::Error> {
+            self.0.get(&hash).cloned().ok_or_else(|| String::from("missing header"))
+        }
+    }
+
+    impl TrieHinter for MapProvider {
+        type Error = String;
+
+        fn hint_trie_node(&self, hash: B256) -> Result<(), Self::Error> {
+            NoopTrieHinter.hint_trie_node(hash)
+        }
+
+        fn hint_account_proof(&self, address: Address, block: u64) -> Result<(), Self::Error> {
+            NoopTrieHinter.hint_account_proof(address, block)
+        }
+
+        fn hint_storage_proof(
+            &self,
+            address: Address,
+            slot: U256,
+            block: u64,
+        ) -> Result<(), Self::Error> {
+            NoopTrieHinter.hint_storage_proof(address, slot, block)
+        }
+    }
+
+    #[test]
+    fn test_records_touched_preimages() {
+        let node =
+            TrieNode::Leaf { prefix: Default::default(), value: Bytes::from_static(b"leaf") };
+        let node_rlp = Bytes::from(alloy_rlp::encode(&node));
+        let code = Bytes::from_static(&[0x60, 0x00]);
+        let header = Header { number: 1, ..Default::default() };
+        let header_rlp = Bytes::from(alloy_rlp::encode(&header));
+        let parent = Header::default();
+        let parent_rlp = Bytes::from(alloy_rlp::encode(&parent));
+
+        let mut preimages = HashMap::default();
+        preimages.insert(keccak256(&node_rlp), node_rlp.clone());
+        preimages.insert(keccak256(&code), code.clone());
+        preimages.insert(header.hash_slow(), header_rlp.clone());
+        preimages.insert(parent.hash_slow(), parent_rlp.clone());
+
+        let recorder = WitnessRecorder::new(MapProvider(preimages));
+        let hinter = recorder.clone();
+        assert_eq!(recorder.trie_node_by_hash(keccak256(&node_rlp)).unwrap(), node);
+        assert_eq!(recorder.bytecode_by_hash(keccak256(&code)).unwrap(), code);
+        assert_eq!(recorder.header_by_hash(header.hash_slow()).unwrap(), header);
+        assert_eq!(recorder.record_header(parent.hash_slow()).unwrap(), parent);
+        assert_eq!(
+            recorder.trie_node_by_hash(B256::ZERO),
+            Err(WitnessError::Provider(String::from("missing node")))
+        );
+
+        let addr = address!("4200000000000000000000000000000000000016");
+        hinter.hint_storage_proof(addr, U256::from(1), 0).unwrap();
+        drop(hinter);
+
+        let witness = recorder.take_witness();
+        assert_eq!(witness.state.len(), 1);
+        assert_eq!(witness.state[&keccak256(&node_rlp)], node_rlp);
+        assert_eq!(witness.codes[&keccak256(&code)], code);
+        assert_eq!(witness.headers, vec![header_rlp, parent_rlp]);
+        assert_eq!(witness.keys[&keccak256(addr)].as_ref(), addr.as_slice());
+        assert_eq!(witness.keys.len(), 2);
+        assert!(witness.preimages().all(|(hash, preimage)| keccak256(preimage) == hash));
+        assert_eq!(recorder.witness(), ExecutionWitness::default());
+
+        // The recorded witness serves the same preimages offline.
+        let provider = WitnessProvider::new(&witness);
+        assert_eq!(provider.trie_node_by_hash(keccak256(&node_rlp)).unwrap(), node);
+        assert_eq!(provider.bytecode_by_hash(keccak256(&code)).unwrap(), code);
+        assert_eq!(provider.header_by_hash(header.hash_slow()).unwrap(), header);
+        assert_eq!(provider.header_by_hash(parent.hash_slow()).unwrap(), parent);
+        assert_eq!(
+            provider.trie_node_by_hash(B256::ZERO),
+            Err(WitnessError::MissingPreimage(B256::ZERO))
+        );
+    }
+
+    #[test]
+    fn test_missing_from() {
+        let mut ours = ExecutionWitness::default();
+        let mut theirs = ExecutionWitness::default();
+        ours.state.insert(B256::with_last_byte(1), Bytes::new());
+        theirs.state.insert(B256::with_last_byte(1), Bytes::new());
+        theirs.codes.insert(B256::with_last_byte(2), Bytes::new());
+        theirs.keys.insert(B256::with_last_byte(3), Bytes::new());
+
+        assert_eq!(
+            ours.missing_from(&theirs),
+            vec![B256::with_last_byte(2), B256::with_last_byte(3)]
+        );
+        assert!(theirs.missing_from(&ours).is_empty());
+    }
+}
@@ -0,0 +1,55 @@
+//! Contains the [MemoryBackend], an in-memory [PreimageServerBackend] for tests.
+//!
+//! [PreimageServerBackend]: kona_preimage::PreimageServerBackend
+
+use async_trait::async_trait;
+use kona_preimage::{
+    errors::{PreimageOracleError, PreimageOracleResult},
+    HintRouter, PreimageFetcher, PreimageKey,
+};
+use std::{collections::HashMap, sync::Mutex};
+
+/// A [PreimageServerBackend] that serves preimages from an in-memory map, and records the hints
+/// that it receives.
+///
+/// [PreimageServerBackend]: kona_preimage::PreimageServerBackend
+#[derive(Debug, Default)]
+pub struct MemoryBackend {
+    /// The preimages served by the backend.
+    preimages: HashMap<PreimageKey, Vec<u8>>,
+    /// The hints received by the backend, in order.
+    hints: Mutex<Vec<String>>,
+}
+
+impl MemoryBackend {
+    /// Creates a new [MemoryBackend] serving the given preimages.
+    pub fn new(preimages: impl IntoIterator<Item = (PreimageKey, Vec<u8>)>) -> Self {
+        Self { preimages: preimages.into_iter().collect(), hints: Default::default() }
+    }
+
+    /// Adds a preimage to the backend.
+    pub fn with_preimage(mut self, key: PreimageKey, value: Vec<u8>) -> Self {
+        self.preimages.insert(key, value);
+        self
+    }
+
+    /// Returns the hints received by the backend, in order.
+    pub fn hints(&self) -> Vec<String> {
+        self.hints.lock().expect("lock poisoned").clone()
+    }
+}
+
+#[async_trait]
+impl PreimageFetcher for MemoryBackend {
+    async fn get_preimage(&self, key: PreimageKey) -> PreimageOracleResult<Vec<u8>> {
+        self.preimages.get(&key).cloned().ok_or(PreimageOracleError::KeyNotFound)
+    }
+}
+
+#[async_trait]
+impl HintRouter for MemoryBackend {
+    async fn route_hint(&self, hint: String) -> PreimageOracleResult<()> {
+        self.hints.lock().expect("lock poisoned").push(hint);
+        Ok(())
+    }
+}
@@ -0,0 +1,183 @@
+//! Contains a minimal loader for statically linked ELF64 programs.
+
+use crate::{
+    errors::{EmulatorError, EmulatorResult},
+    Memory,
+};
+
+/// The `e_machine` value for MIPS.
+const EM_MIPS: u16 = 8;
+/// The `e_machine` value for RISC-V.
+const EM_RISCV: u16 = 243;
+/// The `p_type` value for loadable segments.
+const PT_LOAD: u32 = 1;
+/// The size of an ELF64 program header, in bytes.
+const PHDR_SIZE: usize = 56;
+
+/// The instruction set architecture of a program.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arch {
+    /// 64-bit little-endian RISC-V (`riscv64imac`), run by Asterisc.
+    RiscV64,
+    /// 64-bit big-endian MIPS (`mips64r2`), run by Cannon.
+    Mips64,
+}
+
+/// A loadable segment of an [ElfImage].
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Segment {
+    /// The virtual address of the segment.
+    vaddr: u64,
+    /// The initialized data of the segment. The rest of the segment's memory is zeroed.
+    data: Vec<u8>,
+}
+
+/// A parsed, statically linked ELF64 program.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ElfImage {
+    /// The instruction set architecture of the program.
+    pub arch: Arch,
+    /// The entry point of the program.
+    pub entry: u64,
+    /// The first address past the end of the highest loadable segment.
+    pub program_break: u64,
+    /// The loadable segments of the program.
+    segments: Vec<Segment>,
+}
+
+impl ElfImage {
+    /// Parses an ELF64 program for one of the supported [Arch]s.
+    pub fn parse(elf: &[u8]) -> EmulatorResult<Self> {
+        if elf.len() < 64 || elf[..4] != *b"\x7fELF" {
+            return Err(EmulatorError::InvalidElf("bad magic"));
+        }
+        if elf[4] != 2 {
+            return Err(EmulatorError::InvalidElf("not a 64-bit ELF"));
+        }
+        let big_endian = match elf[5] {
+            1 => false,
+            2 => true,
+            _ => return Err(EmulatorError::InvalidElf("bad data encoding")),
+        };
+
+        let reader = Reader { elf, big_endian };
+        let arch = match (reader.u16(18)?, big_endian) {
+            (EM_RISCV, false) => Arch::RiscV64,
+            (EM_MIPS, true) => Arch::Mips64,
+            _ => return Err(EmulatorError::InvalidElf("unsupported machine")),
+        };
+        let entry = reader.u64(24)?;
+        let ph_offset = reader.u64(32)? as usize;
+        let ph_entry_size = reader.u16(54)? as usize;
+        let ph_count = reader.u16(56)? as usize;
+        if ph_entry_size < PHDR_SIZE {
+            return Err(EmulatorError::InvalidElf("bad program header size"));
+        }
+
+        let mut segments = Vec::new();
+        let mut program_break = 0;
+        for i in 0..ph_count {
+            let phdr = ph_offset + i * ph_entry_size;
+            if reader.u32(phdr)? != PT_LOAD {
+                continue;
+            }
+            let offset = reader.u64(phdr + 8)? as usize;
+            let vaddr = reader.u64(phdr + 16)?;
+            let file_size = reader.u64(phdr + 32)? as usize;
+            let mem_size = reader.u64(phdr + 40)?;
+            if file_size as u64 > mem_size {
+                return Err(EmulatorError::InvalidElf("segment file size exceeds memory size"));
+            }
+            let data = elf
+                .get(offset..offset + file_size)
+                .ok_or(EmulatorError::InvalidElf("segment out of bounds"))?
+                .to_vec();
+
+            program_break = program_break.max(vaddr + mem_size);
+            segments.push(Segment { vaddr, data });
+        }
+        if segments.is_empty() {
+            return Err(EmulatorError::InvalidElf("no loadable segments"));
+        }
+
+        Ok(Self { arch, entry, program_break, segments })
+    }
+
+    /// Loads the program's segments into `memory`.
+    ///
+    /// Uninitialized data (e.g. `.bss`, which holds the heap of `alloc_heap!`) is left to the
+    /// zero-initialized pages of the [Memory].
+    pub fn load(&self, memory: &mut Memory) {
+        for segment in &self.segments {
+            memory.write(segment.vaddr, &segment.data);
+        }
+    }
+}
+
+/// An endian-aware reader over the raw bytes of an ELF file.
+struct Reader<'a> {
+    elf: &'a [u8],
+    big_endian: bool,
+}
+
+impl Reader<'_> {
+    fn bytes<const N: usize>(&self, offset: usize) -> EmulatorResult<[u8; N]> {
+        let mut out: [u8; N] = self
+            .elf
+            .get(offset..offset + N)
+            .and_then(|b| b.try_into().ok())
+            .ok_or(EmulatorError::InvalidElf("header out of bounds"))?;
+        if self.big_endian {
+            out.reverse();
+        }
+        Ok(out)
+    }
+
+    fn u16(&self, offset: usize) -> EmulatorResult<u16> {
+        self.bytes(offset).map(u16::from_le_bytes)
+    }
+
+    fn u32(&self, offset: usize) -> EmulatorResult<u32> {
+        self.bytes(offset).map(u32::from_le_bytes)
+    }
+
+    fn u64(&self, offset: usize) -> EmulatorResult<u64> {
+        self.bytes(offset).map(u64::from_le_bytes)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_utils::build_elf;
+
+    #[test]
+    fn test_parse_and_load_riscv() {
+        let elf = build_elf(Arch::RiscV64, 0x10000, &[0x13, 0, 0, 0], 0x2000);
+        let image = ElfImage::parse(&elf).unwrap();
+        assert_eq!(image.arch, Arch::RiscV64);
+        assert_eq!(image.entry, 0x10000);
+        assert_eq!(image.program_break, 0x12000);
+
+        let mut memory = Memory::default();
+        image.load(&mut memory);
+        assert_eq!(memory.read_u32_le(0x10000), 0x13);
+    }
+
+    #[test]
+    fn test_parse_mips() {
+        let elf = build_elf(Arch::Mips64, 0x20000, &[0, 0, 0, 0], 4);
+        let image = ElfImage::parse(&elf).unwrap();
+        assert_eq!(image.arch, Arch::Mips64);
+        assert_eq!(image.entry, 0x20000);
+    }
+
+    #[test]
+    fn test_parse_rejects_garbage() {
+        assert!(matches!(ElfImage::parse(&[0u8; 64]), Err(EmulatorError::InvalidElf("bad magic"))));
+
+        let mut elf = build_elf(Arch::RiscV64, 0x10000, &[0x13, 0, 0, 0], 4);
+        elf[5] = 2;
+        assert!(ElfImage::parse(&elf).is_err());
+    }
+}
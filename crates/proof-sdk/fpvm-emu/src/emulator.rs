@@ -0,0 +1,260 @@
+//! Contains the [Emulator], which runs an FPVM program to completion.
+
+use crate::{
+    errors::{EmulatorError, EmulatorResult},
+    kernel::{Kernel, Step, SyscallOutcome, STACK_TOP},
+    Arch, ElfImage, Memory, Mips64Cpu, RiscV64Cpu,
+};
+use kona_preimage::{
+    errors::PreimageOracleError, BidirectionalChannel, Channel, HintReader, HintReaderServer,
+    OracleServer, PreimageOracleServer, PreimageServerBackend,
+};
+use kona_std_fpvm::exit::ClientExitReason;
+
+/// The CPU of an [Emulator], selected by the [Arch] of the program.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Cpu {
+    RiscV64(RiscV64Cpu),
+    Mips64(Mips64Cpu),
+}
+
+/// The exit status of a program run by the [Emulator].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExitStatus {
+    /// The exit code passed to the `exit` system call.
+    pub code: u64,
+    /// The number of instructions executed.
+    pub steps: u64,
+    /// The data written by the program to standard output.
+    pub stdout: Vec<u8>,
+    /// The data written by the program to standard error.
+    pub stderr: Vec<u8>,
+}
+
+impl ExitStatus {
+    /// Returns `true` if the program exited with code `0`.
+    pub const fn success(&self) -> bool {
+        self.code == 0
+    }
+
+    /// Returns the [ClientExitReason] reported by the program.
+    ///
+    /// The last exit record written to standard error takes precedence over the exit code, which
+    /// is used as a fallback for programs that do not write one.
+    pub fn reason(&self) -> Option<ClientExitReason> {
+        ClientExitReason::from_record(&String::from_utf8_lossy(&self.stderr))
+            .or_else(|| u8::try_from(self.code).ok().and_then(ClientExitReason::from_code))
+    }
+}
+
+/// A userspace emulator for FPVM programs.
+///
+/// The hint and preimage file descriptors of the program are wired to the client ends of the
+/// given [Channel]s, which are expected to be served by a [PreimageOracleServer] and a
+/// [HintReaderServer] on the host ends. See [run_with_backend] for a convenience wrapper.
+#[derive(Debug)]
+pub struct Emulator<C> {
+    /// The instruction set architecture of the program.
+    arch: Arch,
+    /// The CPU running the program.
+    cpu: Cpu,
+    /// The memory of the program.
+    memory: Memory,
+    /// The kernel handling the program's system calls.
+    kernel: Kernel<C>,
+    /// The maximum number of instructions to execute, if any.
+    step_limit: Option<u64>,
+}
+
+impl<C: Channel + Send + Sync> Emulator<C> {
+    /// Creates a new [Emulator] for the given ELF program, with its hint and preimage file
+    /// descriptors wired to the client ends of the `hint` and `preimage` [Channel]s.
+    pub fn new(elf: &[u8], hint: C, preimage: C) -> EmulatorResult<Self> {
+        let image = ElfImage::parse(elf)?;
+        let mut memory = Memory::default();
+        image.load(&mut memory);
+
+        let cpu = match image.arch {
+            Arch::RiscV64 => Cpu::RiscV64(RiscV64Cpu::new(image.entry, STACK_TOP)),
+            Arch::Mips64 => Cpu::Mips64(Mips64Cpu::new(image.entry, STACK_TOP)),
+        };
+
+        Ok(Self {
+            arch: image.arch,
+            cpu,
+            memory,
+            kernel: Kernel::new(image.arch, hint, preimage, image.program_break),
+            step_limit: None,
+        })
+    }
+
+    /// Sets the maximum number of instructions to execute before [Self::run] fails with
+    /// [EmulatorError::StepLimitExceeded].
+    pub const fn with_step_limit(mut self, step_limit: u64) -> Self {
+        self.step_limit = Some(step_limit);
+        self
+    }
+
+    /// Returns the instruction set architecture of the program.
+    pub const fn arch(&self) -> Arch {
+        self.arch
+    }
+
+    /// Runs the program until it exits.
+    pub async fn run(mut self) -> EmulatorResult<ExitStatus> {
+        let step_limit = self.step_limit.unwrap_or(u64::MAX);
+        let mut steps = 0u64;
+
+        loop {
+            if steps == step_limit {
+                return Err(EmulatorError::StepLimitExceeded(step_limit));
+            }
+            steps += 1;
+
+            let step = match &mut self.cpu {
+                Cpu::RiscV64(cpu) => cpu.step(&mut self.memory)?,
+                Cpu::Mips64(cpu) => cpu.step(&mut self.memory)?,
+            };
+            if step == Step::Continue {
+                continue;
+            }
+
+            let (number, args) = match &self.cpu {
+                Cpu::RiscV64(cpu) => cpu.syscall_args(),
+                Cpu::Mips64(cpu) => cpu.syscall_args(),
+            };
+            let kind = match self.arch {
+                Arch::RiscV64 => RiscV64Cpu::syscall_kind(number),
+                Arch::Mips64 => Mips64Cpu::syscall_kind(number),
+            }
+            .ok_or(EmulatorError::UnsupportedSyscall(number))?;
+
+            self.kernel.steps = steps;
+            let result = match self.kernel.syscall(kind, args, &mut self.memory).await? {
+                SyscallOutcome::Return(value) => Ok(value),
+                SyscallOutcome::Error(errno) => Err(errno),
+                SyscallOutcome::Exit(code) => {
+                    debug!(target: "fpvm_emu", "Program exited with code {code} after {steps} steps");
+                    return Ok(ExitStatus {
+                        code,
+                        steps,
+                        stdout: core::mem::take(&mut self.kernel.stdout),
+                        stderr: core::mem::take(&mut self.kernel.stderr),
+                    });
+                }
+            };
+            match &mut self.cpu {
+                Cpu::RiscV64(cpu) => cpu.set_syscall_result(result),
+                Cpu::Mips64(cpu) => cpu.set_syscall_result(result),
+            }
+        }
+    }
+}
+
+/// Runs the given ELF program in an [Emulator], serving its hint and preimage requests from
+/// `backend`, in the same way as the host's native mode.
+///
+/// If the backend fails to serve a request, the run is aborted with [EmulatorError::Server].
+pub async fn run_with_backend<B>(elf: &[u8], backend: &B) -> EmulatorResult<ExitStatus>
+where
+    B: PreimageServerBackend + Send + Sync,
+{
+    let hint = BidirectionalChannel::new().expect("infallible");
+    let preimage = BidirectionalChannel::new().expect("infallible");
+    let emulator = Emulator::new(elf, hint.client, preimage.client)?;
+
+    let oracle_server = OracleServer::new(preimage.host);
+    let hint_reader = HintReader::new(hint.host);
+    let serve_preimages = async {
+        loop {
+            match oracle_server.next_preimage_request(backend).await {
+                Ok(_) => continue,
+                Err(PreimageOracleError::IOError(_)) => return Ok(()),
+                Err(e) => return Err(EmulatorError::Server(e)),
+            }
+        }
+    };
+    let route_hints = async {
+        loop {
+            match hint_reader.next_hint(backend).await {
+                Ok(_) => continue,
+                Err(PreimageOracleError::IOError(_)) => return Ok(()),
+                Err(e) => return Err(EmulatorError::Server(e)),
+            }
+        }
+    };
+
+    tokio::select! {
+        status = emulator.run() => status,
+        Err(e) = serve_preimages => Err(e),
+        Err(e) = route_hints => Err(e),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        test_utils::{build_elf, echo_key, echo_program},
+        MemoryBackend,
+    };
+
+    #[tokio::test]
+    async fn test_run_with_backend() {
+        for arch in [Arch::RiscV64, Arch::Mips64] {
+            let backend = MemoryBackend::default().with_preimage(echo_key(), b"hello".to_vec());
+            let status = run_with_backend(&echo_program(arch), &backend).await.unwrap();
+
+            assert_eq!(status.code, 7, "{arch:?}");
+            assert!(!status.success());
+            assert_eq!(status.stdout, b"hihello", "{arch:?}");
+            assert!(status.stderr.is_empty());
+            assert_eq!(backend.hints(), vec!["test".to_string()], "{arch:?}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_run_with_backend_missing_preimage() {
+        for arch in [Arch::RiscV64, Arch::Mips64] {
+            let backend = MemoryBackend::default();
+            let err = run_with_backend(&echo_program(arch), &backend).await.unwrap_err();
+            assert!(
+                matches!(err, EmulatorError::Server(PreimageOracleError::KeyNotFound)),
+                "{arch:?}: {err}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_exit_status_reason() {
+        let mut status = ExitStatus { code: 2, steps: 1, stdout: Vec::new(), stderr: Vec::new() };
+        assert_eq!(status.reason(), Some(ClientExitReason::Panic));
+
+        status.stderr = ClientExitReason::OutOfMemory.record().into_bytes();
+        status.stderr.extend_from_slice(b"Panic: memory allocation failed");
+        assert_eq!(status.reason(), Some(ClientExitReason::OutOfMemory));
+
+        status.code = 42;
+        status.stderr.clear();
+        assert_eq!(status.reason(), None);
+    }
+
+    #[tokio::test]
+    async fn test_run_step_limit_and_unsupported_syscall() {
+        let channel = BidirectionalChannel::new().unwrap();
+
+        // riscv64: `j .`
+        let elf = build_elf(Arch::RiscV64, 0x10000, &0x0000_006fu32.to_le_bytes(), 4);
+        let emulator = Emulator::new(&elf, channel.client.clone(), channel.client.clone()).unwrap();
+        assert_eq!(emulator.arch(), Arch::RiscV64);
+        assert!(matches!(
+            emulator.with_step_limit(100).run().await,
+            Err(EmulatorError::StepLimitExceeded(100))
+        ));
+
+        // mips64: `syscall` with $v0 = 0
+        let elf = build_elf(Arch::Mips64, 0x10000, &0x0000_000cu32.to_be_bytes(), 4);
+        let emulator = Emulator::new(&elf, channel.client.clone(), channel.client).unwrap();
+        assert!(matches!(emulator.run().await, Err(EmulatorError::UnsupportedSyscall(0))));
+    }
+}
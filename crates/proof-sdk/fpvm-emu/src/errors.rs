@@ -0,0 +1,44 @@
+//! Errors for the `kona-fpvm-emu` crate.
+
+use kona_preimage::errors::{ChannelError, PreimageOracleError};
+use thiserror::Error;
+
+/// An error that can occur while loading or running a program in the emulator.
+#[derive(Error, Debug)]
+pub enum EmulatorError {
+    /// The program is not a valid ELF file for any of the supported targets.
+    #[error("Invalid ELF: {0}")]
+    InvalidElf(&'static str),
+    /// The CPU fetched an instruction that it does not implement.
+    #[error("Unsupported instruction {insn:#010x} at pc {pc:#x}")]
+    UnsupportedInstruction {
+        /// The program counter of the instruction.
+        pc: u64,
+        /// The raw instruction.
+        insn: u32,
+    },
+    /// The program executed a breakpoint or trap instruction.
+    #[error("Trap at pc {0:#x}")]
+    Trap(u64),
+    /// The program jumped to a misaligned address.
+    #[error("Misaligned instruction fetch at {0:#x}")]
+    MisalignedFetch(u64),
+    /// The program issued a system call that the kernel does not implement.
+    #[error("Unsupported syscall {0}")]
+    UnsupportedSyscall(u64),
+    /// The program read from a hint or preimage file descriptor with no pending data.
+    #[error("Read from file descriptor {0} with no pending data")]
+    EmptyRead(u64),
+    /// The program did not exit within the configured number of steps.
+    #[error("Step limit of {0} instructions exceeded")]
+    StepLimitExceeded(u64),
+    /// An error occurred while communicating with the preimage server.
+    #[error("Channel error: {0}")]
+    Channel(#[from] ChannelError),
+    /// The preimage server failed to serve a request from the program.
+    #[error("Preimage server error: {0}")]
+    Server(PreimageOracleError),
+}
+
+/// A [Result] type for the [EmulatorError].
+pub type EmulatorResult<T> = Result<T, EmulatorError>;
@@ -0,0 +1,393 @@
+//! Contains the emulated [Kernel], which implements the subset of Linux system calls used by
+//! `kona-std-fpvm`.
+
+use crate::{
+    errors::{EmulatorError, EmulatorResult},
+    Arch, Memory, PAGE_SIZE,
+};
+use kona_preimage::Channel;
+use std::collections::VecDeque;
+
+/// The top of the program's stack. The stack grows downwards from this address.
+pub(crate) const STACK_TOP: u64 = 0x7f_ffff_f000;
+/// The base address of anonymous memory mappings.
+pub(crate) const MMAP_BASE: u64 = 0x20_0000_0000;
+
+/// The `EBADF` error number.
+const EBADF: u64 = 9;
+/// The `EINVAL` error number.
+const EINVAL: u64 = 22;
+
+/// The size of a preimage key, in bytes.
+const PREIMAGE_KEY_SIZE: usize = 32;
+
+/// The `CLOCK_MONOTONIC` clock ID.
+const CLOCK_MONOTONIC: u64 = 1;
+/// The number of steps per second of the monotonic clock, matching Cannon's.
+const HZ: u64 = 10_000_000;
+
+/// A system call supported by the [Kernel], decoded from an architecture-specific syscall number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SyscallKind {
+    /// `read(fd, buf, count)`
+    Read,
+    /// `write(fd, buf, count)`
+    Write,
+    /// `exit(code)` or `exit_group(code)`
+    Exit,
+    /// `mmap(addr, length, ...)`, for anonymous mappings only.
+    Mmap,
+    /// `brk(addr)`
+    Brk,
+    /// `clock_gettime(clock, timespec)`, for the monotonic clock only.
+    ClockGetTime,
+}
+
+/// The outcome of executing a single instruction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Step {
+    /// The instruction was executed.
+    Continue,
+    /// The instruction is a system call, which must be handled by the [Kernel] before resuming.
+    Syscall,
+}
+
+/// The result of a system call handled by the [Kernel].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SyscallOutcome {
+    /// The system call succeeded with the given return value.
+    Return(u64),
+    /// The system call failed with the given error number.
+    Error(u64),
+    /// The program exited with the given code.
+    Exit(u64),
+}
+
+/// The file descriptors of the FPVM I/O interface.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Fd {
+    StdIn = 0,
+    StdOut = 1,
+    StdErr = 2,
+    HintRead = 3,
+    HintWrite = 4,
+    PreimageRead = 5,
+    PreimageWrite = 6,
+}
+
+impl Fd {
+    const fn from_raw(fd: u64) -> Option<Self> {
+        Some(match fd {
+            0 => Self::StdIn,
+            1 => Self::StdOut,
+            2 => Self::StdErr,
+            3 => Self::HintRead,
+            4 => Self::HintWrite,
+            5 => Self::PreimageRead,
+            6 => Self::PreimageWrite,
+            _ => return None,
+        })
+    }
+}
+
+/// The emulated kernel of an FPVM program.
+///
+/// The hint and preimage file descriptors are adapted to the message-based framing of the
+/// [kona_preimage] host handles: complete hints and preimage keys written by the program are
+/// forwarded to the hint and preimage [Channel]s, and the responses are buffered for the program to
+/// read back.
+#[derive(Debug)]
+pub(crate) struct Kernel<C> {
+    /// The instruction set architecture of the program, which determines its byte order.
+    arch: Arch,
+    /// The client end of the hint channel.
+    hint: C,
+    /// The client end of the preimage channel.
+    preimage: C,
+    /// Hint bytes written by the program that have not yet formed a complete hint.
+    hint_out: Vec<u8>,
+    /// Hint acknowledgements pending a read by the program.
+    hint_in: VecDeque<u8>,
+    /// Preimage key bytes written by the program that have not yet formed a complete key.
+    preimage_out: Vec<u8>,
+    /// The length-prefixed preimage pending a read by the program.
+    preimage_in: VecDeque<u8>,
+    /// The data written by the program to standard output.
+    pub(crate) stdout: Vec<u8>,
+    /// The data written by the program to standard error.
+    pub(crate) stderr: Vec<u8>,
+    /// The current program break.
+    program_break: u64,
+    /// The next free address for anonymous memory mappings.
+    mmap_next: u64,
+    /// The number of instructions executed by the program, which drives the monotonic clock.
+    pub(crate) steps: u64,
+}
+
+impl<C: Channel> Kernel<C> {
+    /// Creates a new [Kernel] for a program of the given [Arch], with the given hint and preimage
+    /// channels and initial program break.
+    pub(crate) const fn new(arch: Arch, hint: C, preimage: C, program_break: u64) -> Self {
+        Self {
+            arch,
+            hint,
+            preimage,
+            hint_out: Vec::new(),
+            hint_in: VecDeque::new(),
+            preimage_out: Vec::new(),
+            preimage_in: VecDeque::new(),
+            stdout: Vec::new(),
+            stderr: Vec::new(),
+            program_break,
+            mmap_next: MMAP_BASE,
+            steps: 0,
+        }
+    }
+
+    /// Handles a system call with the given arguments.
+    pub(crate) async fn syscall(
+        &mut self,
+        kind: SyscallKind,
+        args: [u64; 3],
+        memory: &mut Memory,
+    ) -> EmulatorResult<SyscallOutcome> {
+        let [a0, a1, a2] = args;
+        match kind {
+            SyscallKind::Exit => Ok(SyscallOutcome::Exit(a0)),
+            SyscallKind::Read => self.read(a0, a1, a2, memory),
+            SyscallKind::Write => self.write(a0, a1, a2, memory).await,
+            SyscallKind::Mmap => {
+                if a1 == 0 {
+                    return Ok(SyscallOutcome::Error(EINVAL));
+                }
+                if a0 != 0 {
+                    // Fixed mappings are always backed by the sparse memory.
+                    return Ok(SyscallOutcome::Return(a0));
+                }
+                let addr = self.mmap_next;
+                self.mmap_next += a1.next_multiple_of(PAGE_SIZE as u64);
+                Ok(SyscallOutcome::Return(addr))
+            }
+            SyscallKind::Brk => {
+                if a0 > self.program_break {
+                    self.program_break = a0;
+                }
+                Ok(SyscallOutcome::Return(self.program_break))
+            }
+            SyscallKind::ClockGetTime => {
+                if a0 != CLOCK_MONOTONIC {
+                    return Ok(SyscallOutcome::Error(EINVAL));
+                }
+                // As in Cannon, the monotonic clock advances with the number of executed steps.
+                let secs = self.steps / HZ;
+                let nsecs = (self.steps % HZ) * (1_000_000_000 / HZ);
+                let timespec = match self.arch {
+                    Arch::RiscV64 => [secs.to_le_bytes(), nsecs.to_le_bytes()],
+                    Arch::Mips64 => [secs.to_be_bytes(), nsecs.to_be_bytes()],
+                };
+                memory.write(a1, timespec.as_flattened());
+                Ok(SyscallOutcome::Return(0))
+            }
+        }
+    }
+
+    fn read(
+        &mut self,
+        fd: u64,
+        addr: u64,
+        len: u64,
+        memory: &mut Memory,
+    ) -> EmulatorResult<SyscallOutcome> {
+        let pending = match Fd::from_raw(fd) {
+            Some(Fd::StdIn) => return Ok(SyscallOutcome::Return(0)),
+            Some(Fd::HintRead) => &mut self.hint_in,
+            Some(Fd::PreimageRead) => &mut self.preimage_in,
+            _ => return Ok(SyscallOutcome::Error(EBADF)),
+        };
+        if len == 0 {
+            return Ok(SyscallOutcome::Return(0));
+        }
+        if pending.is_empty() {
+            return Err(EmulatorError::EmptyRead(fd));
+        }
+
+        let n = pending.len().min(len as usize);
+        let data = pending.drain(..n).collect::<Vec<_>>();
+        memory.write(addr, &data);
+        Ok(SyscallOutcome::Return(n as u64))
+    }
+
+    async fn write(
+        &mut self,
+        fd: u64,
+        addr: u64,
+        len: u64,
+        memory: &Memory,
+    ) -> EmulatorResult<SyscallOutcome> {
+        let mut data = vec![0u8; len as usize];
+        memory.read(addr, &mut data);
+
+        match Fd::from_raw(fd) {
+            Some(Fd::StdOut) => self.stdout.extend_from_slice(&data),
+            Some(Fd::StdErr) => self.stderr.extend_from_slice(&data),
+            Some(Fd::HintWrite) => {
+                self.hint_out.extend_from_slice(&data);
+                self.flush_hints().await?;
+            }
+            Some(Fd::PreimageWrite) => {
+                self.preimage_out.extend_from_slice(&data);
+                self.flush_preimage_keys().await?;
+            }
+            _ => return Ok(SyscallOutcome::Error(EBADF)),
+        }
+        Ok(SyscallOutcome::Return(len))
+    }
+
+    /// Forwards all complete hints written by the program to the hint channel, and buffers their
+    /// acknowledgements.
+    async fn flush_hints(&mut self) -> EmulatorResult<()> {
+        while self.hint_out.len() >= 4 {
+            let len = u32::from_be_bytes(self.hint_out[..4].try_into().expect("checked length"));
+            let end = 4 + len as usize;
+            if self.hint_out.len() < end {
+                break;
+            }
+
+            self.hint.write(&self.hint_out[..4]).await?;
+            self.hint.write(&self.hint_out[4..end]).await?;
+            self.hint_out.drain(..end);
+
+            let mut ack = [0u8; 1];
+            self.hint.read_exact(&mut ack).await?;
+            self.hint_in.extend(ack);
+        }
+        Ok(())
+    }
+
+    /// Forwards all complete preimage keys written by the program to the preimage channel, and
+    /// buffers the length-prefixed preimage of the last one.
+    async fn flush_preimage_keys(&mut self) -> EmulatorResult<()> {
+        while self.preimage_out.len() >= PREIMAGE_KEY_SIZE {
+            let key = self.preimage_out.drain(..PREIMAGE_KEY_SIZE).collect::<Vec<_>>();
+            self.preimage.write(&key).await?;
+
+            let mut len = [0u8; 8];
+            self.preimage.read_exact(&mut len).await?;
+            let mut data = vec![0u8; u64::from_be_bytes(len) as usize];
+            if !data.is_empty() {
+                self.preimage.read_exact(&mut data).await?;
+            }
+
+            // Setting a new key discards any unread data of the previous preimage.
+            self.preimage_in.clear();
+            self.preimage_in.extend(len);
+            self.preimage_in.extend(data);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use kona_preimage::{
+        BidirectionalChannel, HintReader, HintReaderServer, OracleServer, PreimageKey,
+        PreimageOracleServer,
+    };
+    use std::sync::Arc;
+
+    use crate::MemoryBackend;
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_kernel_preimage_and_hint_framing() {
+        let key = PreimageKey::new_keccak256([0xAA; 32]);
+        let backend = Arc::new(MemoryBackend::default().with_preimage(key, b"hello".to_vec()));
+
+        let hint = BidirectionalChannel::new().unwrap();
+        let preimage = BidirectionalChannel::new().unwrap();
+        let server_backend = backend.clone();
+        tokio::spawn(async move {
+            let server = OracleServer::new(preimage.host);
+            server.next_preimage_request(server_backend.as_ref()).await.unwrap();
+        });
+        let server_backend = backend.clone();
+        tokio::spawn(async move {
+            let reader = HintReader::new(hint.host);
+            reader.next_hint(server_backend.as_ref()).await.unwrap();
+        });
+
+        let mut kernel = Kernel::new(Arch::RiscV64, hint.client, preimage.client, 0);
+        let mut memory = Memory::default();
+
+        // Write a hint in two chunks, splitting the length prefix.
+        let raw_hint = [&4u32.to_be_bytes()[..], b"test"].concat();
+        memory.write(0x1000, &raw_hint);
+        kernel.syscall(SyscallKind::Write, [4, 0x1000, 2], &mut memory).await.unwrap();
+        assert!(kernel.hint_in.is_empty());
+        kernel.syscall(SyscallKind::Write, [4, 0x1002, 6], &mut memory).await.unwrap();
+        assert_eq!(
+            kernel.syscall(SyscallKind::Read, [3, 0x2000, 1], &mut memory).await.unwrap(),
+            SyscallOutcome::Return(1)
+        );
+        assert_eq!(backend.hints(), vec!["test".to_string()]);
+
+        // Request the preimage and read it back in two chunks.
+        let raw_key: [u8; 32] = key.into();
+        memory.write(0x3000, &raw_key);
+        kernel.syscall(SyscallKind::Write, [6, 0x3000, 32], &mut memory).await.unwrap();
+        kernel.syscall(SyscallKind::Read, [5, 0x4000, 8], &mut memory).await.unwrap();
+        assert_eq!(memory.read_array::<8>(0x4000), 5u64.to_be_bytes());
+        kernel.syscall(SyscallKind::Read, [5, 0x4000, 64], &mut memory).await.unwrap();
+        assert_eq!(&memory.read_array::<5>(0x4000), b"hello");
+
+        // All pending data has been consumed.
+        assert!(matches!(
+            kernel.syscall(SyscallKind::Read, [5, 0x4000, 1], &mut memory).await,
+            Err(EmulatorError::EmptyRead(5))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_kernel_stdio_and_memory() {
+        let channel = BidirectionalChannel::new().unwrap();
+        let mut kernel = Kernel::new(Arch::Mips64, channel.client.clone(), channel.client, 0x5000);
+        let mut memory = Memory::default();
+
+        memory.write(0x1000, b"out");
+        kernel.syscall(SyscallKind::Write, [1, 0x1000, 3], &mut memory).await.unwrap();
+        kernel.syscall(SyscallKind::Write, [2, 0x1000, 2], &mut memory).await.unwrap();
+        assert_eq!(kernel.stdout, b"out");
+        assert_eq!(kernel.stderr, b"ou");
+        assert_eq!(
+            kernel.syscall(SyscallKind::Write, [9, 0x1000, 1], &mut memory).await.unwrap(),
+            SyscallOutcome::Error(EBADF)
+        );
+
+        let first = kernel.syscall(SyscallKind::Mmap, [0, 100, 0], &mut memory).await.unwrap();
+        let second = kernel.syscall(SyscallKind::Mmap, [0, 100, 0], &mut memory).await.unwrap();
+        assert_eq!(first, SyscallOutcome::Return(MMAP_BASE));
+        assert_eq!(second, SyscallOutcome::Return(MMAP_BASE + PAGE_SIZE as u64));
+
+        assert_eq!(
+            kernel.syscall(SyscallKind::Brk, [0, 0, 0], &mut memory).await.unwrap(),
+            SyscallOutcome::Return(0x5000)
+        );
+
+        kernel.steps = 3 * HZ + 7;
+        assert_eq!(
+            kernel.syscall(SyscallKind::ClockGetTime, [1, 0x6000, 0], &mut memory).await.unwrap(),
+            SyscallOutcome::Return(0)
+        );
+        assert_eq!(memory.read_array::<8>(0x6000), 3u64.to_be_bytes());
+        assert_eq!(memory.read_array::<8>(0x6008), 700u64.to_be_bytes());
+        assert_eq!(
+            kernel.syscall(SyscallKind::ClockGetTime, [0, 0x6000, 0], &mut memory).await.unwrap(),
+            SyscallOutcome::Error(EINVAL)
+        );
+
+        assert_eq!(
+            kernel.syscall(SyscallKind::Exit, [3, 0, 0], &mut memory).await.unwrap(),
+            SyscallOutcome::Exit(3)
+        );
+    }
+}
@@ -0,0 +1,35 @@
+#![doc = include_str!("../README.md")]
+#![doc(
+    html_logo_url = "https://raw.githubusercontent.com/op-rs/kona/main/assets/square.png",
+    html_favicon_url = "https://raw.githubusercontent.com/op-rs/kona/main/assets/favicon.ico"
+)]
+#![cfg_attr(docsrs, feature(doc_cfg, doc_auto_cfg))]
+#![cfg_attr(not(test), warn(unused_crate_dependencies))]
+
+#[macro_use]
+extern crate tracing;
+
+pub mod errors;
+
+mod memory;
+pub use memory::{Memory, PAGE_SIZE};
+
+mod elf;
+pub use elf::{Arch, ElfImage};
+
+mod kernel;
+
+mod riscv64;
+pub use riscv64::RiscV64Cpu;
+
+mod mips64;
+pub use mips64::Mips64Cpu;
+
+mod emulator;
+pub use emulator::{run_with_backend, Emulator, ExitStatus};
+
+mod backend;
+pub use backend::MemoryBackend;
+
+#[cfg(test)]
+pub(crate) mod test_utils;
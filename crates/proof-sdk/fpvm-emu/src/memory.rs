@@ -0,0 +1,102 @@
+//! Contains the sparse, paged [Memory] of an emulated program.
+
+use std::collections::HashMap;
+
+/// The size of a memory page, in bytes.
+pub const PAGE_SIZE: usize = 4096;
+
+/// A page of memory.
+type Page = Box<[u8; PAGE_SIZE]>;
+
+/// The sparse, byte-addressable memory of an emulated program.
+///
+/// Pages are allocated lazily on first write. Reads from unallocated pages return zeroes, matching
+/// the FPVMs, which do not fault on unmapped memory.
+#[derive(Debug, Default, Clone)]
+pub struct Memory {
+    /// The allocated pages, keyed by page index.
+    pages: HashMap<u64, Page>,
+}
+
+impl Memory {
+    /// Returns the number of allocated pages.
+    pub fn page_count(&self) -> usize {
+        self.pages.len()
+    }
+
+    /// Reads `buf.len()` bytes starting at `addr`.
+    pub fn read(&self, mut addr: u64, mut buf: &mut [u8]) {
+        while !buf.is_empty() {
+            let offset = (addr % PAGE_SIZE as u64) as usize;
+            let len = (PAGE_SIZE - offset).min(buf.len());
+            match self.pages.get(&(addr / PAGE_SIZE as u64)) {
+                Some(page) => buf[..len].copy_from_slice(&page[offset..offset + len]),
+                None => buf[..len].fill(0),
+            }
+            buf = &mut buf[len..];
+            addr = addr.wrapping_add(len as u64);
+        }
+    }
+
+    /// Writes `data` starting at `addr`.
+    pub fn write(&mut self, mut addr: u64, mut data: &[u8]) {
+        while !data.is_empty() {
+            let offset = (addr % PAGE_SIZE as u64) as usize;
+            let len = (PAGE_SIZE - offset).min(data.len());
+            let page = self
+                .pages
+                .entry(addr / PAGE_SIZE as u64)
+                .or_insert_with(|| Box::new([0u8; PAGE_SIZE]));
+            page[offset..offset + len].copy_from_slice(&data[..len]);
+            data = &data[len..];
+            addr = addr.wrapping_add(len as u64);
+        }
+    }
+
+    /// Reads `N` bytes starting at `addr`.
+    pub fn read_array<const N: usize>(&self, addr: u64) -> [u8; N] {
+        let offset = (addr % PAGE_SIZE as u64) as usize;
+        let mut out = [0u8; N];
+        if offset + N <= PAGE_SIZE {
+            if let Some(page) = self.pages.get(&(addr / PAGE_SIZE as u64)) {
+                out.copy_from_slice(&page[offset..offset + N]);
+            }
+        } else {
+            self.read(addr, &mut out);
+        }
+        out
+    }
+
+    /// Reads a little-endian `u32` at `addr`.
+    pub fn read_u32_le(&self, addr: u64) -> u32 {
+        u32::from_le_bytes(self.read_array(addr))
+    }
+
+    /// Reads a big-endian `u32` at `addr`.
+    pub fn read_u32_be(&self, addr: u64) -> u32 {
+        u32::from_be_bytes(self.read_array(addr))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_memory_unallocated_reads_zero() {
+        let memory = Memory::default();
+        assert_eq!(memory.read_array::<8>(0xdead_beef), [0u8; 8]);
+        assert_eq!(memory.page_count(), 0);
+    }
+
+    #[test]
+    fn test_memory_cross_page_access() {
+        let mut memory = Memory::default();
+        let addr = PAGE_SIZE as u64 - 3;
+        memory.write(addr, &[1, 2, 3, 4, 5, 6]);
+        assert_eq!(memory.page_count(), 2);
+        assert_eq!(memory.read_array::<6>(addr), [1, 2, 3, 4, 5, 6]);
+        assert_eq!(memory.read_u32_be(addr + 1), 0x0203_0405);
+        assert_eq!(memory.read_u32_le(addr + 1), 0x0504_0302);
+    }
+}
@@ -0,0 +1,650 @@
+//! Contains the [Mips64Cpu], an interpreter for the big-endian `mips64r2` instruction set run by
+//! Cannon.
+
+use crate::{
+    errors::{EmulatorError, EmulatorResult},
+    kernel::{Step, SyscallKind},
+    Memory,
+};
+
+/// The index of the first result register (`$v0`), which also holds the syscall number.
+const V0: usize = 2;
+/// The index of the first argument register (`$a0`).
+const A0: usize = 4;
+/// The index of the syscall error flag register (`$a3`).
+const A3: usize = 7;
+/// The index of the stack pointer register (`$sp`).
+const SP: usize = 29;
+/// The index of the return address register (`$ra`).
+const RA: usize = 31;
+
+/// A big-endian `mips64r2` CPU with branch delay slots.
+///
+/// Floating point, branch-likely and coprocessor instructions are not implemented, as the
+/// soft-float client target does not emit them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mips64Cpu {
+    /// The general purpose registers.
+    regs: [u64; 32],
+    /// The `HI` multiply / divide register.
+    hi: u64,
+    /// The `LO` multiply / divide register.
+    lo: u64,
+    /// The program counter.
+    pc: u64,
+    /// The address of the instruction after `pc`. Differs from `pc + 4` in branch delay slots.
+    next_pc: u64,
+    /// The address reserved by the last `LL` / `LLD` instruction, if any.
+    reservation: Option<u64>,
+}
+
+impl Mips64Cpu {
+    /// Creates a new [Mips64Cpu] starting at `entry`, with the stack pointer set to `sp`.
+    pub const fn new(entry: u64, sp: u64) -> Self {
+        let mut regs = [0u64; 32];
+        regs[SP] = sp;
+        Self { regs, hi: 0, lo: 0, pc: entry, next_pc: entry + 4, reservation: None }
+    }
+
+    /// Returns the program counter.
+    pub const fn pc(&self) -> u64 {
+        self.pc
+    }
+
+    /// Returns the value of the general purpose register `index`.
+    pub const fn reg(&self, index: usize) -> u64 {
+        self.regs[index]
+    }
+
+    /// Maps a Cannon syscall number to a [SyscallKind].
+    pub(crate) const fn syscall_kind(number: u64) -> Option<SyscallKind> {
+        Some(match number {
+            5000 => SyscallKind::Read,
+            5001 => SyscallKind::Write,
+            5009 => SyscallKind::Mmap,
+            5012 => SyscallKind::Brk,
+            5058 | 5205 => SyscallKind::Exit,
+            5222 => SyscallKind::ClockGetTime,
+            _ => return None,
+        })
+    }
+
+    /// Returns the number and the first three arguments of the pending system call.
+    pub(crate) const fn syscall_args(&self) -> (u64, [u64; 3]) {
+        (self.regs[V0], [self.regs[A0], self.regs[A0 + 1], self.regs[A0 + 2]])
+    }
+
+    /// Sets the result of the pending system call. Errors are returned as a positive error number
+    /// in `$v0`, with the `$a3` error flag set.
+    pub(crate) fn set_syscall_result(&mut self, result: Result<u64, u64>) {
+        let (value, failed) = match result {
+            Ok(value) => (value, false),
+            Err(errno) => (errno, true),
+        };
+        self.regs[V0] = value;
+        self.regs[A3] = failed as u64;
+    }
+
+    fn set(&mut self, rd: usize, value: u64) {
+        if rd != 0 {
+            self.regs[rd] = value;
+        }
+    }
+
+    /// Executes a single instruction.
+    pub(crate) fn step(&mut self, memory: &mut Memory) -> EmulatorResult<Step> {
+        if self.pc % 4 != 0 {
+            return Err(EmulatorError::MisalignedFetch(self.pc));
+        }
+        let insn = memory.read_u32_be(self.pc);
+        let unsupported = EmulatorError::UnsupportedInstruction { pc: self.pc, insn };
+
+        let opcode = insn >> 26;
+        let rs_idx = ((insn >> 21) & 0x1f) as usize;
+        let rt_idx = ((insn >> 16) & 0x1f) as usize;
+        let rd = ((insn >> 11) & 0x1f) as usize;
+        let sa = (insn >> 6) & 0x1f;
+        let funct = insn & 0x3f;
+        let rs = self.regs[rs_idx];
+        let rt = self.regs[rt_idx];
+        let imm = insn as u16 as i16 as i64 as u64;
+        let branch_target = self.next_pc.wrapping_add(imm << 2);
+
+        let mut new_next_pc = self.next_pc.wrapping_add(4);
+        let mut step = Step::Continue;
+
+        match opcode {
+            // SPECIAL
+            0x00 => match funct {
+                // SLL, SRL / ROTR, SRA
+                0x00 => self.set(rd, sext32((rt as u32) << sa)),
+                0x02 if rs_idx == 1 => self.set(rd, sext32((rt as u32).rotate_right(sa))),
+                0x02 => self.set(rd, sext32((rt as u32) >> sa)),
+                0x03 => self.set(rd, sext32(((rt as i32) >> sa) as u32)),
+                // SLLV, SRLV / ROTRV, SRAV
+                0x04 => self.set(rd, sext32((rt as u32) << (rs & 0x1f))),
+                0x06 if sa == 1 => self.set(rd, sext32((rt as u32).rotate_right(rs as u32 & 0x1f))),
+                0x06 => self.set(rd, sext32((rt as u32) >> (rs & 0x1f))),
+                0x07 => self.set(rd, sext32(((rt as i32) >> (rs & 0x1f)) as u32)),
+                // JR, JALR
+                0x08 => new_next_pc = rs,
+                0x09 => {
+                    self.set(rd, self.pc.wrapping_add(8));
+                    new_next_pc = rs;
+                }
+                // MOVZ, MOVN
+                0x0a if rt == 0 => self.set(rd, rs),
+                0x0b if rt != 0 => self.set(rd, rs),
+                0x0a | 0x0b => {}
+                // SYSCALL
+                0x0c => step = Step::Syscall,
+                // BREAK
+                0x0d => return Err(EmulatorError::Trap(self.pc)),
+                // SYNC
+                0x0f => {}
+                // MFHI, MTHI, MFLO, MTLO
+                0x10 => self.set(rd, self.hi),
+                0x11 => self.hi = rs,
+                0x12 => self.set(rd, self.lo),
+                0x13 => self.lo = rs,
+                // DSLLV, DSRLV / DROTRV, DSRAV
+                0x14 => self.set(rd, rt << (rs & 0x3f)),
+                0x16 if sa == 1 => self.set(rd, rt.rotate_right(rs as u32 & 0x3f)),
+                0x16 => self.set(rd, rt >> (rs & 0x3f)),
+                0x17 => self.set(rd, ((rt as i64) >> (rs & 0x3f)) as u64),
+                // MULT, MULTU
+                0x18 => {
+                    let product = (rs as i32 as i64).wrapping_mul(rt as i32 as i64);
+                    self.lo = sext32(product as u32);
+                    self.hi = sext32((product >> 32) as u32);
+                }
+                0x19 => {
+                    let product = (rs as u32 as u64) * (rt as u32 as u64);
+                    self.lo = sext32(product as u32);
+                    self.hi = sext32((product >> 32) as u32);
+                }
+                // DIV, DIVU
+                0x1a => {
+                    if rt as u32 != 0 {
+                        let (a, b) = (rs as i32, rt as i32);
+                        self.lo = sext32(a.wrapping_div(b) as u32);
+                        self.hi = sext32(a.wrapping_rem(b) as u32);
+                    }
+                }
+                0x1b => {
+                    if rt as u32 != 0 {
+                        let (a, b) = (rs as u32, rt as u32);
+                        self.lo = sext32(a / b);
+                        self.hi = sext32(a % b);
+                    }
+                }
+                // DMULT, DMULTU
+                0x1c => {
+                    let product = (rs as i64 as i128) * (rt as i64 as i128);
+                    self.lo = product as u64;
+                    self.hi = (product >> 64) as u64;
+                }
+                0x1d => {
+                    let product = (rs as u128) * (rt as u128);
+                    self.lo = product as u64;
+                    self.hi = (product >> 64) as u64;
+                }
+                // DDIV, DDIVU
+                0x1e => {
+                    if rt != 0 {
+                        self.lo = (rs as i64).wrapping_div(rt as i64) as u64;
+                        self.hi = (rs as i64).wrapping_rem(rt as i64) as u64;
+                    }
+                }
+                0x1f => {
+                    if rt != 0 {
+                        self.lo = rs / rt;
+                        self.hi = rs % rt;
+                    }
+                }
+                // ADD, ADDU, SUB, SUBU
+                0x20 | 0x21 => self.set(rd, sext32((rs as u32).wrapping_add(rt as u32))),
+                0x22 | 0x23 => self.set(rd, sext32((rs as u32).wrapping_sub(rt as u32))),
+                // AND, OR, XOR, NOR
+                0x24 => self.set(rd, rs & rt),
+                0x25 => self.set(rd, rs | rt),
+                0x26 => self.set(rd, rs ^ rt),
+                0x27 => self.set(rd, !(rs | rt)),
+                // SLT, SLTU
+                0x2a => self.set(rd, ((rs as i64) < rt as i64) as u64),
+                0x2b => self.set(rd, (rs < rt) as u64),
+                // DADD, DADDU, DSUB, DSUBU
+                0x2c | 0x2d => self.set(rd, rs.wrapping_add(rt)),
+                0x2e | 0x2f => self.set(rd, rs.wrapping_sub(rt)),
+                // TGE, TGEU, TLT, TLTU, TEQ, TNE
+                0x30..=0x36 => {
+                    let trap = match funct {
+                        0x30 => rs as i64 >= rt as i64,
+                        0x31 => rs >= rt,
+                        0x32 => (rs as i64) < rt as i64,
+                        0x33 => rs < rt,
+                        0x34 => rs == rt,
+                        0x36 => rs != rt,
+                        _ => return Err(unsupported),
+                    };
+                    if trap {
+                        return Err(EmulatorError::Trap(self.pc));
+                    }
+                }
+                // DSLL, DSRL / DROTR, DSRA
+                0x38 => self.set(rd, rt << sa),
+                0x3a if rs_idx == 1 => self.set(rd, rt.rotate_right(sa)),
+                0x3a => self.set(rd, rt >> sa),
+                0x3b => self.set(rd, ((rt as i64) >> sa) as u64),
+                // DSLL32, DSRL32 / DROTR32, DSRA32
+                0x3c => self.set(rd, rt << (sa + 32)),
+                0x3e if rs_idx == 1 => self.set(rd, rt.rotate_right(sa + 32)),
+                0x3e => self.set(rd, rt >> (sa + 32)),
+                0x3f => self.set(rd, ((rt as i64) >> (sa + 32)) as u64),
+                _ => return Err(unsupported),
+            },
+            // REGIMM
+            0x01 => {
+                let taken = match rt_idx {
+                    0x00 | 0x10 => (rs as i64) < 0,
+                    0x01 | 0x11 => rs as i64 >= 0,
+                    _ => return Err(unsupported),
+                };
+                if rt_idx & 0x10 != 0 {
+                    self.regs[RA] = self.pc.wrapping_add(8);
+                }
+                if taken {
+                    new_next_pc = branch_target;
+                }
+            }
+            // J, JAL
+            0x02 | 0x03 => {
+                if opcode == 0x03 {
+                    self.regs[RA] = self.pc.wrapping_add(8);
+                }
+                new_next_pc = (self.next_pc & !0x0fff_ffff) | (((insn & 0x03ff_ffff) as u64) << 2);
+            }
+            // BEQ, BNE, BLEZ, BGTZ
+            0x04..=0x07 => {
+                let taken = match opcode {
+                    0x04 => rs == rt,
+                    0x05 => rs != rt,
+                    0x06 => rs as i64 <= 0,
+                    _ => rs as i64 > 0,
+                };
+                if taken {
+                    new_next_pc = branch_target;
+                }
+            }
+            // ADDI, ADDIU
+            0x08 | 0x09 => self.set(rt_idx, sext32((rs as u32).wrapping_add(imm as u32))),
+            // SLTI, SLTIU
+            0x0a => self.set(rt_idx, ((rs as i64) < imm as i64) as u64),
+            0x0b => self.set(rt_idx, (rs < imm) as u64),
+            // ANDI, ORI, XORI
+            0x0c => self.set(rt_idx, rs & (insn & 0xffff) as u64),
+            0x0d => self.set(rt_idx, rs | (insn & 0xffff) as u64),
+            0x0e => self.set(rt_idx, rs ^ (insn & 0xffff) as u64),
+            // LUI
+            0x0f => self.set(rt_idx, sext32((insn & 0xffff) << 16)),
+            // DADDI, DADDIU
+            0x18 | 0x19 => self.set(rt_idx, rs.wrapping_add(imm)),
+            // SPECIAL2
+            0x1c => match funct {
+                // MUL
+                0x02 => self.set(rd, sext32((rs as u32).wrapping_mul(rt as u32))),
+                // CLZ, CLO, DCLZ, DCLO
+                0x20 => self.set(rd, (rs as u32).leading_zeros() as u64),
+                0x21 => self.set(rd, (rs as u32).leading_ones() as u64),
+                0x24 => self.set(rd, rs.leading_zeros() as u64),
+                0x25 => self.set(rd, rs.leading_ones() as u64),
+                _ => return Err(unsupported),
+            },
+            // SPECIAL3
+            0x1f => {
+                let value = self.special3(insn, rs, rt).ok_or(unsupported)?;
+                self.set(if funct == 0x20 || funct == 0x24 { rd } else { rt_idx }, value);
+            }
+            // Loads
+            0x1a | 0x1b | 0x20..=0x27 | 0x30 | 0x34 | 0x37 => {
+                let addr = rs.wrapping_add(imm);
+                let value = match opcode {
+                    // LDL, LDR
+                    0x1a => {
+                        let shift = (addr & 7) * 8;
+                        let mem = u64::from_be_bytes(memory.read_array(addr & !7));
+                        let mask = u64::MAX << shift;
+                        (rt & !mask) | (mem << shift)
+                    }
+                    0x1b => {
+                        let shift = (7 - (addr & 7)) * 8;
+                        let mem = u64::from_be_bytes(memory.read_array(addr & !7));
+                        let mask = u64::MAX >> shift;
+                        (rt & !mask) | (mem >> shift)
+                    }
+                    // LB, LH, LW, LBU, LHU, LWU
+                    0x20 => i8::from_be_bytes(memory.read_array(addr)) as u64,
+                    0x21 => i16::from_be_bytes(memory.read_array(addr)) as u64,
+                    0x23 => i32::from_be_bytes(memory.read_array(addr)) as u64,
+                    0x24 => u8::from_be_bytes(memory.read_array(addr)) as u64,
+                    0x25 => u16::from_be_bytes(memory.read_array(addr)) as u64,
+                    0x27 => u32::from_be_bytes(memory.read_array(addr)) as u64,
+                    // LWL, LWR
+                    0x22 => {
+                        let shift = (addr & 3) * 8;
+                        let mem = memory.read_u32_be(addr & !3);
+                        let mask = u32::MAX << shift;
+                        sext32((rt as u32 & !mask) | (mem << shift))
+                    }
+                    0x26 => {
+                        let shift = (3 - (addr & 3)) * 8;
+                        let mem = memory.read_u32_be(addr & !3);
+                        let mask = u32::MAX >> shift;
+                        sext32((rt as u32 & !mask) | (mem >> shift))
+                    }
+                    // LL, LLD
+                    0x30 => {
+                        self.reservation = Some(addr);
+                        i32::from_be_bytes(memory.read_array(addr)) as u64
+                    }
+                    0x34 => {
+                        self.reservation = Some(addr);
+                        u64::from_be_bytes(memory.read_array(addr))
+                    }
+                    // LD
+                    _ => u64::from_be_bytes(memory.read_array(addr)),
+                };
+                self.set(rt_idx, value);
+            }
+            // Stores
+            0x28..=0x2e | 0x38 | 0x3c | 0x3f => {
+                let addr = rs.wrapping_add(imm);
+                match opcode {
+                    // SB, SH, SW
+                    0x28 => memory.write(addr, &(rt as u8).to_be_bytes()),
+                    0x29 => memory.write(addr, &(rt as u16).to_be_bytes()),
+                    0x2b => memory.write(addr, &(rt as u32).to_be_bytes()),
+                    // SWL, SWR
+                    0x2a => {
+                        let shift = (addr & 3) * 8;
+                        let mem = memory.read_u32_be(addr & !3);
+                        let mask = u32::MAX >> shift;
+                        let value = (mem & !mask) | ((rt as u32) >> shift);
+                        memory.write(addr & !3, &value.to_be_bytes());
+                    }
+                    0x2e => {
+                        let shift = (3 - (addr & 3)) * 8;
+                        let mem = memory.read_u32_be(addr & !3);
+                        let mask = u32::MAX << shift;
+                        let value = (mem & !mask) | ((rt as u32) << shift);
+                        memory.write(addr & !3, &value.to_be_bytes());
+                    }
+                    // SDL, SDR
+                    0x2c => {
+                        let shift = (addr & 7) * 8;
+                        let mem = u64::from_be_bytes(memory.read_array(addr & !7));
+                        let mask = u64::MAX >> shift;
+                        let value = (mem & !mask) | (rt >> shift);
+                        memory.write(addr & !7, &value.to_be_bytes());
+                    }
+                    0x2d => {
+                        let shift = (7 - (addr & 7)) * 8;
+                        let mem = u64::from_be_bytes(memory.read_array(addr & !7));
+                        let mask = u64::MAX << shift;
+                        let value = (mem & !mask) | (rt << shift);
+                        memory.write(addr & !7, &value.to_be_bytes());
+                    }
+                    // SC, SCD
+                    0x38 | 0x3c => {
+                        let success = self.reservation.take() == Some(addr);
+                        if success {
+                            let bytes = rt.to_be_bytes();
+                            memory.write(addr, if opcode == 0x38 { &bytes[4..] } else { &bytes });
+                        }
+                        self.set(rt_idx, success as u64);
+                    }
+                    // SD
+                    _ => memory.write(addr, &rt.to_be_bytes()),
+                }
+            }
+            // CACHE, PREF
+            0x2f | 0x33 => {}
+            _ => return Err(unsupported),
+        }
+
+        self.pc = self.next_pc;
+        self.next_pc = new_next_pc;
+        Ok(step)
+    }
+
+    /// Executes a `SPECIAL3` bit field or byte shuffle instruction, returning the value to write
+    /// to its destination register.
+    fn special3(&self, insn: u32, rs: u64, rt: u64) -> Option<u64> {
+        let msb = (insn >> 11) & 0x1f;
+        let lsb = (insn >> 6) & 0x1f;
+        let mask = |size: u32| if size >= 64 { u64::MAX } else { (1u64 << size) - 1 };
+
+        // Extracts `size` bits of `rs` starting at bit `pos`.
+        let extract = |pos: u32, size: u32| (rs >> pos) & mask(size);
+        // Inserts the low bits of `rs` into bits `lsb..=msb` of `rt`.
+        let insert = |lsb: u32, msb: u32| {
+            let field = mask(msb - lsb + 1) << lsb;
+            (rt & !field) | ((rs << lsb) & field)
+        };
+
+        Some(match insn & 0x3f {
+            // EXT, DEXTM, DEXTU, DEXT
+            0x00 => sext32(extract(lsb, msb + 1) as u32),
+            0x01 => extract(lsb, msb + 33),
+            0x02 => extract(lsb + 32, msb + 1),
+            0x03 => extract(lsb, msb + 1),
+            // INS, DINSM, DINSU, DINS
+            0x04 if msb >= lsb => sext32(insert(lsb, msb) as u32),
+            0x05 => insert(lsb, msb + 32),
+            0x06 => insert(lsb + 32, msb + 32),
+            0x07 if msb >= lsb => insert(lsb, msb),
+            // BSHFL
+            0x20 => match lsb {
+                // WSBH
+                0x02 => {
+                    let word = rt as u32;
+                    sext32(((word & 0x00ff_00ff) << 8) | ((word & 0xff00_ff00) >> 8))
+                }
+                // SEB, SEH
+                0x10 => rt as i8 as u64,
+                0x18 => rt as i16 as u64,
+                _ => return None,
+            },
+            // DBSHFL
+            0x24 => match lsb {
+                // DSBH
+                0x02 => ((rt & 0x00ff_00ff_00ff_00ff) << 8) | ((rt & 0xff00_ff00_ff00_ff00) >> 8),
+                // DSHD
+                0x05 => {
+                    let swapped = rt.rotate_left(32);
+                    ((swapped & 0x0000_ffff_0000_ffff) << 16) |
+                        ((swapped & 0xffff_0000_ffff_0000) >> 16)
+                }
+                _ => return None,
+            },
+            _ => return None,
+        })
+    }
+}
+
+/// Sign-extends a 32-bit value to 64 bits.
+const fn sext32(value: u32) -> u64 {
+    value as i32 as u64
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const SYSCALL: u32 = 0x0000_000c;
+    const NOP: u32 = 0;
+
+    const fn i_type(op: u32, rs: u32, rt: u32, imm: u16) -> u32 {
+        (op << 26) | (rs << 21) | (rt << 16) | imm as u32
+    }
+
+    const fn r_type(op: u32, rs: u32, rt: u32, rd: u32, sa: u32, funct: u32) -> u32 {
+        (op << 26) | (rs << 21) | (rt << 16) | (rd << 11) | (sa << 6) | funct
+    }
+
+    /// Runs the given program until it issues a system call.
+    fn run_with(program: &[u32], memory: &mut Memory) -> Mips64Cpu {
+        for (i, insn) in program.iter().enumerate() {
+            memory.write(0x1000 + i as u64 * 4, &insn.to_be_bytes());
+        }
+        let mut cpu = Mips64Cpu::new(0x1000, 0x8000);
+        for _ in 0..1000 {
+            if cpu.step(memory).unwrap() == Step::Syscall {
+                return cpu;
+            }
+        }
+        panic!("program did not issue a system call");
+    }
+
+    fn run(program: &[u32]) -> Mips64Cpu {
+        run_with(program, &mut Memory::default())
+    }
+
+    #[test]
+    fn test_mips_arithmetic() {
+        let cpu = run(&[
+            // addiu $8, $0, -7
+            i_type(0x09, 0, 8, -7i16 as u16),
+            // addiu $9, $0, 3
+            i_type(0x09, 0, 9, 3),
+            // mul $10, $8, $9
+            r_type(0x1c, 8, 9, 10, 0, 0x02),
+            // div $8, $9; mflo $11; mfhi $12
+            r_type(0, 8, 9, 0, 0, 0x1a),
+            r_type(0, 0, 0, 11, 0, 0x12),
+            r_type(0, 0, 0, 12, 0, 0x10),
+            // lui $13, 0x8000
+            i_type(0x0f, 0, 13, 0x8000),
+            // dsrl32 $14, $13, 0
+            r_type(0, 0, 13, 14, 0, 0x3e),
+            // ori $15, $0, 0xffff
+            i_type(0x0d, 0, 15, 0xffff),
+            // dmultu $13, $13; mfhi $16
+            r_type(0, 13, 13, 0, 0, 0x1d),
+            r_type(0, 0, 0, 16, 0, 0x10),
+            // clz $17, $9
+            r_type(0x1c, 9, 0, 17, 0, 0x20),
+            SYSCALL,
+        ]);
+        assert_eq!(cpu.reg(10) as i64, -21);
+        assert_eq!(cpu.reg(11) as i64, -2);
+        assert_eq!(cpu.reg(12) as i64, -1);
+        assert_eq!(cpu.reg(13), 0xffff_ffff_8000_0000);
+        assert_eq!(cpu.reg(14), 0xffff_ffff);
+        assert_eq!(cpu.reg(15), 0xffff);
+        assert_eq!(cpu.reg(16), 0xffff_ffff_0000_0000);
+        assert_eq!(cpu.reg(17), 30);
+    }
+
+    #[test]
+    fn test_mips_delay_slots() {
+        let cpu = run(&[
+            // addiu $8, $0, 10
+            i_type(0x09, 0, 8, 10),
+            // loop: addiu $8, $8, -1
+            i_type(0x09, 8, 8, 0xffff),
+            // bne $8, $0, loop
+            i_type(0x05, 8, 0, -2i16 as u16),
+            // addiu $9, $9, 2 (delay slot, executed on every iteration)
+            i_type(0x09, 9, 9, 2),
+            // jal +2 instructions
+            (0x03 << 26) | (0x1018 >> 2),
+            // addiu $10, $0, 1 (delay slot)
+            i_type(0x09, 0, 10, 1),
+            SYSCALL,
+        ]);
+        assert_eq!(cpu.reg(8), 0);
+        assert_eq!(cpu.reg(9), 20);
+        assert_eq!(cpu.reg(10), 1);
+        assert_eq!(cpu.reg(RA), 0x1018);
+    }
+
+    #[test]
+    fn test_mips_unaligned_memory() {
+        let mut memory = Memory::default();
+        memory.write(0x2000, &[0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99]);
+        let cpu = run_with(
+            &[
+                // ori $4, $0, 0x2001
+                i_type(0x0d, 0, 4, 0x2001),
+                // lwl $8, 0($4); lwr $8, 3($4)
+                i_type(0x22, 4, 8, 0),
+                i_type(0x26, 4, 8, 3),
+                // ldl $9, 0($4); ldr $9, 7($4)
+                i_type(0x1a, 4, 9, 0),
+                i_type(0x1b, 4, 9, 7),
+                // swl $8, 0x10($4); swr $8, 0x13($4)
+                i_type(0x2a, 4, 8, 0x10),
+                i_type(0x2e, 4, 8, 0x13),
+                // sdl $9, 0x20($4); sdr $9, 0x27($4)
+                i_type(0x2c, 4, 9, 0x20),
+                i_type(0x2d, 4, 9, 0x27),
+                // lld $10, 0x2000($0); scd $9, 0x2000($0)
+                i_type(0x34, 0, 10, 0x2000),
+                i_type(0x3c, 0, 9, 0x2000),
+                SYSCALL,
+            ],
+            &mut memory,
+        );
+        assert_eq!(cpu.reg(8), 0x2233_4455);
+        assert_eq!(cpu.reg(9), 1);
+        assert_eq!(cpu.reg(10), 0x1122_3344_5566_7788);
+        assert_eq!(memory.read_array::<4>(0x2011), [0x22, 0x33, 0x44, 0x55]);
+        assert_eq!(memory.read_array::<8>(0x2021), 0x2233_4455_6677_8899u64.to_be_bytes());
+        assert_eq!(
+            memory.read_array::<8>(0x2000),
+            0x2233_4455_6677_8899u64.to_be_bytes(),
+            "scd stores the value of $9 before it is overwritten with the success flag"
+        );
+    }
+
+    #[test]
+    fn test_mips_bit_manipulation() {
+        let cpu = run(&[
+            // lui $8, 0x1234; ori $8, $8, 0x5678
+            i_type(0x0f, 0, 8, 0x1234),
+            i_type(0x0d, 8, 8, 0x5678),
+            // ext $9, $8, 4, 8
+            r_type(0x1f, 8, 9, 7, 4, 0x00),
+            // ins $8, $0, 0, 4 -> clears the low nibble
+            r_type(0x1f, 0, 8, 3, 0, 0x04),
+            // wsbh $10, $8
+            r_type(0x1f, 0, 8, 10, 0x02, 0x20),
+            // seb $11, $8
+            r_type(0x1f, 0, 8, 11, 0x10, 0x20),
+            // dsll32 $12, $8, 0; dshd $13, $12
+            r_type(0, 0, 8, 12, 0, 0x3c),
+            r_type(0x1f, 0, 12, 13, 0x05, 0x24),
+            // dext $14, $12, 32, 16 (dextu, lsb - 32 = 0, size - 1 = 15)
+            r_type(0x1f, 12, 14, 15, 0, 0x02),
+            NOP,
+            SYSCALL,
+        ]);
+        assert_eq!(cpu.reg(9), 0x67);
+        assert_eq!(cpu.reg(8), 0x1234_5670);
+        assert_eq!(cpu.reg(10), 0x3412_7056);
+        assert_eq!(cpu.reg(11), 0x70);
+        assert_eq!(cpu.reg(12), 0x1234_5670_0000_0000);
+        assert_eq!(cpu.reg(13), 0x0000_0000_5670_1234);
+        assert_eq!(cpu.reg(14), 0x5670);
+    }
+
+    #[test]
+    fn test_mips_syscall_result() {
+        let mut cpu = Mips64Cpu::new(0x1000, 0x8000);
+        cpu.set_syscall_result(Err(9));
+        assert_eq!((cpu.reg(V0), cpu.reg(A3)), (9, 1));
+        cpu.set_syscall_result(Ok(4));
+        assert_eq!((cpu.reg(V0), cpu.reg(A3)), (4, 0));
+    }
+}
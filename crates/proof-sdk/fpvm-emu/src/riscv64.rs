@@ -0,0 +1,718 @@
+//! Contains the [RiscV64Cpu], an interpreter for the `rv64imac` instruction set run by Asterisc.
+
+use crate::{
+    errors::{EmulatorError, EmulatorResult},
+    kernel::{Step, SyscallKind},
+    Memory,
+};
+
+/// The index of the stack pointer register.
+const SP: usize = 2;
+/// The index of the first argument / return value register.
+const A0: usize = 10;
+/// The index of the syscall number register.
+const A7: usize = 17;
+
+/// A `rv64imac` CPU.
+///
+/// Floating point (`F`/`D`) and CSR instructions are not implemented, as the `riscv64imac` client
+/// target does not emit them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RiscV64Cpu {
+    /// The general purpose registers.
+    regs: [u64; 32],
+    /// The program counter.
+    pc: u64,
+    /// The address reserved by the last `LR` instruction, if any.
+    reservation: Option<u64>,
+}
+
+impl RiscV64Cpu {
+    /// Creates a new [RiscV64Cpu] starting at `entry`, with the stack pointer set to `sp`.
+    pub const fn new(entry: u64, sp: u64) -> Self {
+        let mut regs = [0u64; 32];
+        regs[SP] = sp;
+        Self { regs, pc: entry, reservation: None }
+    }
+
+    /// Returns the program counter.
+    pub const fn pc(&self) -> u64 {
+        self.pc
+    }
+
+    /// Returns the value of the general purpose register `index`.
+    pub const fn reg(&self, index: usize) -> u64 {
+        self.regs[index]
+    }
+
+    /// Maps a `riscv64` Linux syscall number to a [SyscallKind].
+    pub(crate) const fn syscall_kind(number: u64) -> Option<SyscallKind> {
+        Some(match number {
+            63 => SyscallKind::Read,
+            64 => SyscallKind::Write,
+            93 | 94 => SyscallKind::Exit,
+            113 => SyscallKind::ClockGetTime,
+            214 => SyscallKind::Brk,
+            222 => SyscallKind::Mmap,
+            _ => return None,
+        })
+    }
+
+    /// Returns the number and the first three arguments of the pending system call.
+    pub(crate) const fn syscall_args(&self) -> (u64, [u64; 3]) {
+        (self.regs[A7], [self.regs[A0], self.regs[A0 + 1], self.regs[A0 + 2]])
+    }
+
+    /// Sets the result of the pending system call. Errors are returned as negated error numbers.
+    pub(crate) fn set_syscall_result(&mut self, result: Result<u64, u64>) {
+        self.regs[A0] = match result {
+            Ok(value) => value,
+            Err(errno) => errno.wrapping_neg(),
+        };
+    }
+
+    fn set(&mut self, rd: usize, value: u64) {
+        if rd != 0 {
+            self.regs[rd] = value;
+        }
+    }
+
+    /// Executes a single instruction.
+    pub(crate) fn step(&mut self, memory: &mut Memory) -> EmulatorResult<Step> {
+        if self.pc % 2 != 0 {
+            return Err(EmulatorError::MisalignedFetch(self.pc));
+        }
+        let low = u16::from_le_bytes(memory.read_array(self.pc));
+        if low & 0b11 != 0b11 {
+            let insn = expand_compressed(low)
+                .ok_or(EmulatorError::UnsupportedInstruction { pc: self.pc, insn: low as u32 })?;
+            self.execute(insn, 2, memory)
+        } else {
+            let insn = memory.read_u32_le(self.pc);
+            self.execute(insn, 4, memory)
+        }
+    }
+
+    fn execute(&mut self, insn: u32, len: u64, memory: &mut Memory) -> EmulatorResult<Step> {
+        let unsupported = EmulatorError::UnsupportedInstruction { pc: self.pc, insn };
+        let opcode = insn & 0x7f;
+        let rd = ((insn >> 7) & 0x1f) as usize;
+        let funct3 = (insn >> 12) & 0x7;
+        let rs1 = self.regs[((insn >> 15) & 0x1f) as usize];
+        let rs2 = self.regs[((insn >> 20) & 0x1f) as usize];
+        let funct7 = insn >> 25;
+        let mut next_pc = self.pc.wrapping_add(len);
+
+        match opcode {
+            // LUI
+            0x37 => self.set(rd, imm_u(insn)),
+            // AUIPC
+            0x17 => self.set(rd, self.pc.wrapping_add(imm_u(insn))),
+            // JAL
+            0x6f => {
+                self.set(rd, next_pc);
+                next_pc = self.pc.wrapping_add(imm_j(insn));
+            }
+            // JALR
+            0x67 => {
+                let target = rs1.wrapping_add(imm_i(insn)) & !1;
+                self.set(rd, next_pc);
+                next_pc = target;
+            }
+            // BRANCH
+            0x63 => {
+                let taken = match funct3 {
+                    0 => rs1 == rs2,
+                    1 => rs1 != rs2,
+                    4 => (rs1 as i64) < rs2 as i64,
+                    5 => rs1 as i64 >= rs2 as i64,
+                    6 => rs1 < rs2,
+                    7 => rs1 >= rs2,
+                    _ => return Err(unsupported),
+                };
+                if taken {
+                    next_pc = self.pc.wrapping_add(imm_b(insn));
+                }
+            }
+            // LOAD
+            0x03 => {
+                let addr = rs1.wrapping_add(imm_i(insn));
+                let value = match funct3 {
+                    0 => i8::from_le_bytes(memory.read_array(addr)) as u64,
+                    1 => i16::from_le_bytes(memory.read_array(addr)) as u64,
+                    2 => i32::from_le_bytes(memory.read_array(addr)) as u64,
+                    3 => u64::from_le_bytes(memory.read_array(addr)),
+                    4 => u8::from_le_bytes(memory.read_array(addr)) as u64,
+                    5 => u16::from_le_bytes(memory.read_array(addr)) as u64,
+                    6 => u32::from_le_bytes(memory.read_array(addr)) as u64,
+                    _ => return Err(unsupported),
+                };
+                self.set(rd, value);
+            }
+            // STORE
+            0x23 => {
+                let addr = rs1.wrapping_add(imm_s(insn));
+                let bytes = rs2.to_le_bytes();
+                match funct3 {
+                    0..=3 => memory.write(addr, &bytes[..1 << funct3]),
+                    _ => return Err(unsupported),
+                }
+            }
+            // OP-IMM
+            0x13 => {
+                let imm = imm_i(insn);
+                let shamt = (insn >> 20) & 0x3f;
+                let value = match funct3 {
+                    0 => rs1.wrapping_add(imm),
+                    1 => rs1 << shamt,
+                    2 => ((rs1 as i64) < imm as i64) as u64,
+                    3 => (rs1 < imm) as u64,
+                    4 => rs1 ^ imm,
+                    5 if insn >> 26 == 0x10 => ((rs1 as i64) >> shamt) as u64,
+                    5 => rs1 >> shamt,
+                    6 => rs1 | imm,
+                    _ => rs1 & imm,
+                };
+                self.set(rd, value);
+            }
+            // OP-IMM-32
+            0x1b => {
+                let shamt = (insn >> 20) & 0x1f;
+                let value = match (funct3, funct7) {
+                    (0, _) => (rs1 as u32).wrapping_add(imm_i(insn) as u32),
+                    (1, 0x00) => (rs1 as u32) << shamt,
+                    (5, 0x00) => (rs1 as u32) >> shamt,
+                    (5, 0x20) => ((rs1 as i32) >> shamt) as u32,
+                    _ => return Err(unsupported),
+                };
+                self.set(rd, sext32(value));
+            }
+            // OP
+            0x33 => {
+                let value = match (funct7, funct3) {
+                    (0x00, 0) => rs1.wrapping_add(rs2),
+                    (0x20, 0) => rs1.wrapping_sub(rs2),
+                    (0x00, 1) => rs1 << (rs2 & 0x3f),
+                    (0x00, 2) => ((rs1 as i64) < rs2 as i64) as u64,
+                    (0x00, 3) => (rs1 < rs2) as u64,
+                    (0x00, 4) => rs1 ^ rs2,
+                    (0x00, 5) => rs1 >> (rs2 & 0x3f),
+                    (0x20, 5) => ((rs1 as i64) >> (rs2 & 0x3f)) as u64,
+                    (0x00, 6) => rs1 | rs2,
+                    (0x00, 7) => rs1 & rs2,
+                    (0x01, op) => mul_div(op, rs1, rs2),
+                    _ => return Err(unsupported),
+                };
+                self.set(rd, value);
+            }
+            // OP-32
+            0x3b => {
+                let (a, b) = (rs1 as u32, rs2 as u32);
+                let value = match (funct7, funct3) {
+                    (0x00, 0) => a.wrapping_add(b),
+                    (0x20, 0) => a.wrapping_sub(b),
+                    (0x00, 1) => a << (b & 0x1f),
+                    (0x00, 5) => a >> (b & 0x1f),
+                    (0x20, 5) => ((a as i32) >> (b & 0x1f)) as u32,
+                    (0x01, 0) => a.wrapping_mul(b),
+                    (0x01, 4) => match (a as i32, b as i32) {
+                        (_, 0) => u32::MAX,
+                        (a, b) => a.wrapping_div(b) as u32,
+                    },
+                    (0x01, 5) => a.checked_div(b).unwrap_or(u32::MAX),
+                    (0x01, 6) => match (a as i32, b as i32) {
+                        (a, 0) => a as u32,
+                        (a, b) => a.wrapping_rem(b) as u32,
+                    },
+                    (0x01, 7) => a.checked_rem(b).unwrap_or(a),
+                    _ => return Err(unsupported),
+                };
+                self.set(rd, sext32(value));
+            }
+            // AMO
+            0x2f => self.atomic(insn, rd, rs1, rs2, memory)?,
+            // MISC-MEM (FENCE, FENCE.I)
+            0x0f => {}
+            // SYSTEM
+            0x73 => {
+                if insn == 0x0000_0073 {
+                    self.pc = next_pc;
+                    return Ok(Step::Syscall);
+                }
+                return Err(if insn == 0x0010_0073 {
+                    EmulatorError::Trap(self.pc)
+                } else {
+                    unsupported
+                });
+            }
+            _ => return Err(unsupported),
+        }
+
+        self.pc = next_pc;
+        Ok(Step::Continue)
+    }
+
+    fn atomic(
+        &mut self,
+        insn: u32,
+        rd: usize,
+        addr: u64,
+        rs2: u64,
+        memory: &mut Memory,
+    ) -> EmulatorResult<()> {
+        let funct5 = insn >> 27;
+        let (loaded, width) = match (insn >> 12) & 0x7 {
+            2 => (i32::from_le_bytes(memory.read_array(addr)) as u64, 4),
+            3 => (u64::from_le_bytes(memory.read_array(addr)), 8),
+            _ => return Err(EmulatorError::UnsupportedInstruction { pc: self.pc, insn }),
+        };
+        let (signed_lhs, signed_rhs) = if width == 4 {
+            (loaded as i32 as i64, rs2 as i32 as i64)
+        } else {
+            (loaded as i64, rs2 as i64)
+        };
+        let (unsigned_lhs, unsigned_rhs) =
+            if width == 4 { (loaded as u32 as u64, rs2 as u32 as u64) } else { (loaded, rs2) };
+
+        let stored = match funct5 {
+            // LR
+            0x02 => {
+                self.reservation = Some(addr);
+                self.set(rd, loaded);
+                return Ok(());
+            }
+            // SC
+            0x03 => {
+                let success = self.reservation.take() == Some(addr);
+                if success {
+                    memory.write(addr, &rs2.to_le_bytes()[..width]);
+                }
+                self.set(rd, !success as u64);
+                return Ok(());
+            }
+            0x01 => rs2,
+            0x00 => loaded.wrapping_add(rs2),
+            0x04 => loaded ^ rs2,
+            0x0c => loaded & rs2,
+            0x08 => loaded | rs2,
+            0x10 => signed_lhs.min(signed_rhs) as u64,
+            0x14 => signed_lhs.max(signed_rhs) as u64,
+            0x18 => unsigned_lhs.min(unsigned_rhs),
+            0x1c => unsigned_lhs.max(unsigned_rhs),
+            _ => return Err(EmulatorError::UnsupportedInstruction { pc: self.pc, insn }),
+        };
+        memory.write(addr, &stored.to_le_bytes()[..width]);
+        self.set(rd, loaded);
+        Ok(())
+    }
+}
+
+/// Executes an `M` extension instruction on 64-bit operands.
+const fn mul_div(funct3: u32, a: u64, b: u64) -> u64 {
+    match funct3 {
+        0 => a.wrapping_mul(b),
+        1 => ((a as i64 as i128 * b as i64 as i128) >> 64) as u64,
+        2 => ((a as i64 as i128 * b as i128) >> 64) as u64,
+        3 => ((a as u128 * b as u128) >> 64) as u64,
+        4 => match b {
+            0 => u64::MAX,
+            _ => (a as i64).wrapping_div(b as i64) as u64,
+        },
+        5 => match b {
+            0 => u64::MAX,
+            _ => a / b,
+        },
+        6 => match b {
+            0 => a,
+            _ => (a as i64).wrapping_rem(b as i64) as u64,
+        },
+        _ => match b {
+            0 => a,
+            _ => a % b,
+        },
+    }
+}
+
+/// Sign-extends a 32-bit value to 64 bits.
+const fn sext32(value: u32) -> u64 {
+    value as i32 as u64
+}
+
+const fn imm_i(insn: u32) -> u64 {
+    (insn as i32 >> 20) as u64
+}
+
+const fn imm_s(insn: u32) -> u64 {
+    (((insn as i32 >> 25) << 5) | ((insn >> 7) & 0x1f) as i32) as u64
+}
+
+const fn imm_b(insn: u32) -> u64 {
+    (((insn as i32 >> 31) << 12) |
+        (((insn >> 7) & 1) << 11) as i32 |
+        (((insn >> 25) & 0x3f) << 5) as i32 |
+        (((insn >> 8) & 0xf) << 1) as i32) as u64
+}
+
+const fn imm_u(insn: u32) -> u64 {
+    (insn & 0xffff_f000) as i32 as u64
+}
+
+const fn imm_j(insn: u32) -> u64 {
+    (((insn as i32 >> 31) << 20) |
+        (insn & 0x000f_f000) as i32 |
+        (((insn >> 20) & 1) << 11) as i32 |
+        (((insn >> 21) & 0x3ff) << 1) as i32) as u64
+}
+
+/// Encoders for the base instruction formats, used to expand compressed instructions.
+pub(crate) mod encode {
+    pub(crate) const fn r(funct7: u32, rs2: u32, rs1: u32, funct3: u32, rd: u32, op: u32) -> u32 {
+        (funct7 << 25) | (rs2 << 20) | (rs1 << 15) | (funct3 << 12) | (rd << 7) | op
+    }
+
+    pub(crate) const fn i(imm: i32, rs1: u32, funct3: u32, rd: u32, op: u32) -> u32 {
+        ((imm as u32 & 0xfff) << 20) | (rs1 << 15) | (funct3 << 12) | (rd << 7) | op
+    }
+
+    pub(crate) const fn s(imm: i32, rs2: u32, rs1: u32, funct3: u32, op: u32) -> u32 {
+        let imm = imm as u32;
+        (((imm >> 5) & 0x7f) << 25) |
+            (rs2 << 20) |
+            (rs1 << 15) |
+            (funct3 << 12) |
+            ((imm & 0x1f) << 7) |
+            op
+    }
+
+    pub(crate) const fn b(imm: i32, rs2: u32, rs1: u32, funct3: u32) -> u32 {
+        let imm = imm as u32;
+        (((imm >> 12) & 1) << 31) |
+            (((imm >> 5) & 0x3f) << 25) |
+            (rs2 << 20) |
+            (rs1 << 15) |
+            (funct3 << 12) |
+            (((imm >> 1) & 0xf) << 8) |
+            (((imm >> 11) & 1) << 7) |
+            0x63
+    }
+
+    pub(crate) const fn u(imm: i32, rd: u32, op: u32) -> u32 {
+        (imm as u32 & 0xffff_f000) | (rd << 7) | op
+    }
+
+    pub(crate) const fn j(imm: i32, rd: u32) -> u32 {
+        let imm = imm as u32;
+        (((imm >> 20) & 1) << 31) |
+            (((imm >> 1) & 0x3ff) << 21) |
+            (((imm >> 11) & 1) << 20) |
+            (((imm >> 12) & 0xff) << 12) |
+            (rd << 7) |
+            0x6f
+    }
+}
+
+/// Expands a compressed (`C` extension) instruction into its 32-bit equivalent. Returns `None` if
+/// the instruction is reserved or uses the floating point registers.
+fn expand_compressed(c: u16) -> Option<u32> {
+    use encode::*;
+
+    let c = c as u32;
+    let bit = |n: u32| (c >> n) & 1;
+    let bits = |hi: u32, lo: u32| (c >> lo) & ((1 << (hi - lo + 1)) - 1);
+    let sext = |value: u32, width: u32| ((value << (32 - width)) as i32) >> (32 - width);
+
+    let funct3 = bits(15, 13);
+    let rd = bits(11, 7);
+    let rs2 = bits(6, 2);
+    // The 3-bit register fields of the compressed formats address x8-x15.
+    let rd_p = bits(4, 2) + 8;
+    let rs1_p = bits(9, 7) + 8;
+
+    Some(match (c & 0b11, funct3) {
+        // C.ADDI4SPN
+        (0, 0b000) => {
+            let imm = (bits(12, 11) << 4) | (bits(10, 7) << 6) | (bit(6) << 2) | (bit(5) << 3);
+            if imm == 0 {
+                return None;
+            }
+            i(imm as i32, 2, 0, rd_p, 0x13)
+        }
+        // C.LW
+        (0, 0b010) => {
+            let imm = (bits(12, 10) << 3) | (bit(6) << 2) | (bit(5) << 6);
+            i(imm as i32, rs1_p, 2, rd_p, 0x03)
+        }
+        // C.LD
+        (0, 0b011) => {
+            let imm = (bits(12, 10) << 3) | (bits(6, 5) << 6);
+            i(imm as i32, rs1_p, 3, rd_p, 0x03)
+        }
+        // C.SW
+        (0, 0b110) => {
+            let imm = (bits(12, 10) << 3) | (bit(6) << 2) | (bit(5) << 6);
+            s(imm as i32, rd_p, rs1_p, 2, 0x23)
+        }
+        // C.SD
+        (0, 0b111) => {
+            let imm = (bits(12, 10) << 3) | (bits(6, 5) << 6);
+            s(imm as i32, rd_p, rs1_p, 3, 0x23)
+        }
+        // C.ADDI / C.NOP
+        (1, 0b000) => i(sext((bit(12) << 5) | rs2, 6), rd, 0, rd, 0x13),
+        // C.ADDIW
+        (1, 0b001) if rd != 0 => i(sext((bit(12) << 5) | rs2, 6), rd, 0, rd, 0x1b),
+        // C.LI
+        (1, 0b010) => i(sext((bit(12) << 5) | rs2, 6), 0, 0, rd, 0x13),
+        // C.ADDI16SP
+        (1, 0b011) if rd == 2 => {
+            let imm =
+                (bit(12) << 9) | (bit(6) << 4) | (bit(5) << 6) | (bits(4, 3) << 7) | (bit(2) << 5);
+            if imm == 0 {
+                return None;
+            }
+            i(sext(imm, 10), 2, 0, 2, 0x13)
+        }
+        // C.LUI
+        (1, 0b011) => {
+            let imm = (bit(12) << 17) | (rs2 << 12);
+            if imm == 0 {
+                return None;
+            }
+            u(sext(imm, 18), rd, 0x37)
+        }
+        (1, 0b100) => {
+            let shamt = (bit(12) << 5) | rs2;
+            match bits(11, 10) {
+                // C.SRLI
+                0b00 => i(shamt as i32, rs1_p, 5, rs1_p, 0x13),
+                // C.SRAI
+                0b01 => i((0x400 | shamt) as i32, rs1_p, 5, rs1_p, 0x13),
+                // C.ANDI
+                0b10 => i(sext((bit(12) << 5) | rs2, 6), rs1_p, 7, rs1_p, 0x13),
+                _ => match (bit(12), bits(6, 5)) {
+                    // C.SUB
+                    (0, 0b00) => r(0x20, rd_p, rs1_p, 0, rs1_p, 0x33),
+                    // C.XOR
+                    (0, 0b01) => r(0x00, rd_p, rs1_p, 4, rs1_p, 0x33),
+                    // C.OR
+                    (0, 0b10) => r(0x00, rd_p, rs1_p, 6, rs1_p, 0x33),
+                    // C.AND
+                    (0, 0b11) => r(0x00, rd_p, rs1_p, 7, rs1_p, 0x33),
+                    // C.SUBW
+                    (1, 0b00) => r(0x20, rd_p, rs1_p, 0, rs1_p, 0x3b),
+                    // C.ADDW
+                    (1, 0b01) => r(0x00, rd_p, rs1_p, 0, rs1_p, 0x3b),
+                    _ => return None,
+                },
+            }
+        }
+        // C.J
+        (1, 0b101) => {
+            let imm = (bit(12) << 11) |
+                (bit(11) << 4) |
+                (bits(10, 9) << 8) |
+                (bit(8) << 10) |
+                (bit(7) << 6) |
+                (bit(6) << 7) |
+                (bits(5, 3) << 1) |
+                (bit(2) << 5);
+            j(sext(imm, 12), 0)
+        }
+        // C.BEQZ / C.BNEZ
+        (1, 0b110 | 0b111) => {
+            let imm = (bit(12) << 8) |
+                (bits(11, 10) << 3) |
+                (bits(6, 5) << 6) |
+                (bits(4, 3) << 1) |
+                (bit(2) << 5);
+            b(sext(imm, 9), 0, rs1_p, funct3 & 1)
+        }
+        // C.SLLI
+        (2, 0b000) => i(((bit(12) << 5) | rs2) as i32, rd, 1, rd, 0x13),
+        // C.LWSP
+        (2, 0b010) if rd != 0 => {
+            let imm = (bit(12) << 5) | (bits(6, 4) << 2) | (bits(3, 2) << 6);
+            i(imm as i32, 2, 2, rd, 0x03)
+        }
+        // C.LDSP
+        (2, 0b011) if rd != 0 => {
+            let imm = (bit(12) << 5) | (bits(6, 5) << 3) | (bits(4, 2) << 6);
+            i(imm as i32, 2, 3, rd, 0x03)
+        }
+        (2, 0b100) => match (bit(12), rd, rs2) {
+            // C.JR
+            (0, rs1, 0) if rs1 != 0 => i(0, rs1, 0, 0, 0x67),
+            // C.MV
+            (0, rd, rs2) => r(0, rs2, 0, 0, rd, 0x33),
+            // C.EBREAK
+            (1, 0, 0) => 0x0010_0073,
+            // C.JALR
+            (1, rs1, 0) => i(0, rs1, 0, 1, 0x67),
+            // C.ADD
+            (_, rd, rs2) => r(0, rs2, rd, 0, rd, 0x33),
+        },
+        // C.SWSP
+        (2, 0b110) => {
+            let imm = (bits(12, 9) << 2) | (bits(8, 7) << 6);
+            s(imm as i32, rs2, 2, 2, 0x23)
+        }
+        // C.SDSP
+        (2, 0b111) => {
+            let imm = (bits(12, 10) << 3) | (bits(9, 7) << 6);
+            s(imm as i32, rs2, 2, 3, 0x23)
+        }
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::{encode::*, *};
+
+    /// Runs the given program until it issues a system call.
+    fn run(program: &[u32]) -> RiscV64Cpu {
+        let mut memory = Memory::default();
+        for (i, insn) in program.iter().enumerate() {
+            memory.write(0x1000 + i as u64 * 4, &insn.to_le_bytes());
+        }
+        let mut cpu = RiscV64Cpu::new(0x1000, 0x8000);
+        for _ in 0..1000 {
+            if cpu.step(&mut memory).unwrap() == Step::Syscall {
+                return cpu;
+            }
+        }
+        panic!("program did not issue a system call");
+    }
+
+    const ECALL: u32 = 0x73;
+
+    #[test]
+    fn test_riscv_arithmetic() {
+        let cpu = run(&[
+            // addi x5, x0, -7
+            i(-7, 0, 0, 5, 0x13),
+            // addi x6, x0, 3
+            i(3, 0, 0, 6, 0x13),
+            // mul x7, x5, x6
+            r(1, 6, 5, 0, 7, 0x33),
+            // div x8, x5, x6
+            r(1, 6, 5, 4, 8, 0x33),
+            // rem x9, x5, x6
+            r(1, 6, 5, 6, 9, 0x33),
+            // divu x10, x5, x0
+            r(1, 0, 5, 5, 10, 0x33),
+            // lui x11, 0x80000
+            u(0x8000_0000u32 as i32, 11, 0x37),
+            // addiw x12, x11, 0
+            i(0, 11, 0, 12, 0x1b),
+            // srai x13, x5, 1
+            i(0x401, 5, 5, 13, 0x13),
+            // sltu x14, x6, x5
+            r(0, 5, 6, 3, 14, 0x33),
+            ECALL,
+        ]);
+        assert_eq!(cpu.reg(7) as i64, -21);
+        assert_eq!(cpu.reg(8) as i64, -2);
+        assert_eq!(cpu.reg(9) as i64, -1);
+        assert_eq!(cpu.reg(10), u64::MAX);
+        assert_eq!(cpu.reg(11), 0xffff_ffff_8000_0000);
+        assert_eq!(cpu.reg(12), 0xffff_ffff_8000_0000);
+        assert_eq!(cpu.reg(13) as i64, -4);
+        assert_eq!(cpu.reg(14), 1);
+    }
+
+    #[test]
+    fn test_riscv_memory_and_atomics() {
+        let cpu = run(&[
+            // addi x5, x0, -2
+            i(-2, 0, 0, 5, 0x13),
+            // sd x5, 0(sp)
+            s(0, 5, 2, 3, 0x23),
+            // lwu x6, 4(sp)
+            i(4, 2, 6, 6, 0x03),
+            // lb x7, 0(sp)
+            i(0, 2, 0, 7, 0x03),
+            // addi x8, x0, 5
+            i(5, 0, 0, 8, 0x13),
+            // amoadd.d x9, x8, (sp)
+            r(0x00 << 2, 8, 2, 3, 9, 0x2f),
+            // ld x10, 0(sp)
+            i(0, 2, 3, 10, 0x03),
+            // lr.d x11, (sp)
+            r(0x02 << 2, 0, 2, 3, 11, 0x2f),
+            // sc.d x12, x8, (sp)
+            r(0x03 << 2, 8, 2, 3, 12, 0x2f),
+            // sc.d x13, x8, (sp)
+            r(0x03 << 2, 8, 2, 3, 13, 0x2f),
+            ECALL,
+        ]);
+        assert_eq!(cpu.reg(6), 0xffff_ffff);
+        assert_eq!(cpu.reg(7) as i64, -2);
+        assert_eq!(cpu.reg(9) as i64, -2);
+        assert_eq!(cpu.reg(10), 3);
+        assert_eq!(cpu.reg(11), 3);
+        assert_eq!(cpu.reg(12), 0);
+        assert_eq!(cpu.reg(13), 1);
+    }
+
+    #[test]
+    fn test_riscv_control_flow() {
+        let cpu = run(&[
+            // addi x5, x0, 10
+            i(10, 0, 0, 5, 0x13),
+            // loop: addi x6, x6, 2
+            i(2, 6, 0, 6, 0x13),
+            // addi x5, x5, -1
+            i(-1, 5, 0, 5, 0x13),
+            // bne x5, x0, loop
+            b(-8, 0, 5, 1),
+            // jal x1, +8
+            j(8, 1),
+            // addi x6, x0, 0 (skipped)
+            i(0, 0, 0, 6, 0x13),
+            ECALL,
+        ]);
+        assert_eq!(cpu.reg(6), 20);
+        assert_eq!(cpu.reg(1), 0x1014);
+        assert_eq!(cpu.pc(), 0x101c);
+    }
+
+    #[test]
+    #[allow(clippy::unusual_byte_groupings)]
+    fn test_riscv_compressed() {
+        // Binary literals are grouped by instruction field.
+        // c.li x10, -3
+        let c_li = 0b010_1_01010_11101_01;
+        assert_eq!(expand_compressed(c_li), Some(i(-3, 0, 0, 10, 0x13)));
+        // c.addi16sp -64
+        let c_addi16sp = 0b011_1_00010_01110_01;
+        assert_eq!(expand_compressed(c_addi16sp), Some(i(-64, 2, 0, 2, 0x13)));
+        // c.sdsp x8, 8(sp)
+        let c_sdsp = 0b111_001000_01000_10;
+        assert_eq!(expand_compressed(c_sdsp), Some(s(8, 8, 2, 3, 0x23)));
+        // c.ldsp x8, 8(sp)
+        let c_ldsp = 0b011_0_01000_01000_10;
+        assert_eq!(expand_compressed(c_ldsp), Some(i(8, 2, 3, 8, 0x03)));
+        // c.j -4
+        let c_j = 0b101_1_1_11_1_1_1_110_1_01;
+        assert_eq!(expand_compressed(c_j), Some(j(-4, 0)));
+        // c.bnez x8, -2
+        let c_bnez = 0b111_1_11_000_11_11_1_01;
+        assert_eq!(expand_compressed(c_bnez), Some(b(-2, 0, 8, 1)));
+        // c.mv x5, x6
+        let c_mv = 0b100_0_00101_00110_10;
+        assert_eq!(expand_compressed(c_mv), Some(r(0, 6, 0, 0, 5, 0x33)));
+        // c.fld is unsupported.
+        assert_eq!(expand_compressed(0b001_000_000_00_000_00), None);
+
+        // Execute a mixed stream: c.li a0, -3; c.addi a0, 1; ecall.
+        let mut memory = Memory::default();
+        memory.write(0x1000, &c_li.to_le_bytes());
+        memory.write(0x1002, &0b000_0_01010_00001_01u16.to_le_bytes());
+        memory.write(0x1004, &ECALL.to_le_bytes());
+        let mut cpu = RiscV64Cpu::new(0x1000, 0x8000);
+        while cpu.step(&mut memory).unwrap() != Step::Syscall {}
+        assert_eq!(cpu.reg(A0) as i64, -2);
+        assert_eq!(cpu.pc(), 0x1008);
+    }
+}
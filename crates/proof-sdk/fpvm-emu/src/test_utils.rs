@@ -0,0 +1,135 @@
+//! Test utilities for building small FPVM programs.
+
+use crate::{
+    riscv64::encode::{i, u},
+    Arch,
+};
+use kona_preimage::PreimageKey;
+
+/// Returns the preimage key requested by the [echo_program].
+pub(crate) fn echo_key() -> PreimageKey {
+    PreimageKey::new_keccak256([0xAB; 32])
+}
+
+/// Builds a minimal ELF64 program for `arch`, with `code` loaded at `entry` in a single segment of
+/// `mem_size` bytes.
+pub(crate) fn build_elf(arch: Arch, entry: u64, code: &[u8], mem_size: u64) -> Vec<u8> {
+    let (data_encoding, machine) = match arch {
+        Arch::RiscV64 => (1u8, 243u16),
+        Arch::Mips64 => (2, 8),
+    };
+    let u16_bytes = |v: u16| if arch == Arch::Mips64 { v.to_be_bytes() } else { v.to_le_bytes() };
+    let u32_bytes = |v: u32| if arch == Arch::Mips64 { v.to_be_bytes() } else { v.to_le_bytes() };
+    let u64_bytes = |v: u64| if arch == Arch::Mips64 { v.to_be_bytes() } else { v.to_le_bytes() };
+
+    let mut elf = vec![0u8; 64 + 56];
+    elf[..4].copy_from_slice(b"\x7fELF");
+    elf[4] = 2;
+    elf[5] = data_encoding;
+    elf[6] = 1;
+    elf[16..18].copy_from_slice(&u16_bytes(2));
+    elf[18..20].copy_from_slice(&u16_bytes(machine));
+    elf[24..32].copy_from_slice(&u64_bytes(entry));
+    elf[32..40].copy_from_slice(&u64_bytes(64));
+    elf[52..54].copy_from_slice(&u16_bytes(64));
+    elf[54..56].copy_from_slice(&u16_bytes(56));
+    elf[56..58].copy_from_slice(&u16_bytes(1));
+
+    // PT_LOAD program header.
+    elf[64..68].copy_from_slice(&u32_bytes(1));
+    let offset = elf.len() as u64;
+    elf[72..80].copy_from_slice(&u64_bytes(offset));
+    elf[80..88].copy_from_slice(&u64_bytes(entry));
+    elf[96..104].copy_from_slice(&u64_bytes(code.len() as u64));
+    elf[104..112].copy_from_slice(&u64_bytes(mem_size.max(code.len() as u64)));
+
+    elf.extend_from_slice(code);
+    elf
+}
+
+/// An argument of a system call in a test program.
+#[derive(Debug, Clone, Copy)]
+enum Arg {
+    /// An immediate value.
+    Imm(i16),
+    /// An offset into the program's data.
+    Data(i16),
+    /// An offset from the stack pointer.
+    Stack(i16),
+}
+
+/// Builds a program for `arch` that:
+/// 1. Writes `"hi"` to standard output.
+/// 2. Sends the hint `"test"` and reads its acknowledgement.
+/// 3. Requests the preimage for [echo_key], and writes its 5 bytes of data to standard output.
+/// 4. Exits with code `7`.
+pub(crate) fn echo_program(arch: Arch) -> Vec<u8> {
+    const ENTRY: u64 = 0x10000;
+    const DATA: i16 = 0x200;
+    const HINT: i16 = DATA;
+    const KEY: i16 = DATA + 8;
+    const HI: i16 = KEY + 32;
+
+    let (read, write, exit) = match arch {
+        Arch::RiscV64 => (63, 64, 93),
+        Arch::Mips64 => (5000, 5001, 5205),
+    };
+    let calls = [
+        (write, [Arg::Imm(1), Arg::Data(HI), Arg::Imm(2)]),
+        (write, [Arg::Imm(4), Arg::Data(HINT), Arg::Imm(8)]),
+        (read, [Arg::Imm(3), Arg::Stack(-16), Arg::Imm(1)]),
+        (write, [Arg::Imm(6), Arg::Data(KEY), Arg::Imm(32)]),
+        (read, [Arg::Imm(5), Arg::Stack(-16), Arg::Imm(8)]),
+        (read, [Arg::Imm(5), Arg::Stack(-16), Arg::Imm(5)]),
+        (write, [Arg::Imm(1), Arg::Stack(-16), Arg::Imm(5)]),
+        (exit, [Arg::Imm(7), Arg::Imm(0), Arg::Imm(0)]),
+    ];
+
+    // (syscall number register, argument registers, data base register, stack pointer)
+    let (nr, args, base, sp) = match arch {
+        Arch::RiscV64 => (17, [10, 11, 12], 8, 2),
+        Arch::Mips64 => (2, [4, 5, 6], 16, 29),
+    };
+    let addi = |rd: u32, rs: u32, imm: i16| match arch {
+        Arch::RiscV64 => i(imm as i32, rs, 0, rd, 0x13),
+        // daddiu rd, rs, imm
+        Arch::Mips64 => (0x19 << 26) | (rs << 21) | (rd << 16) | imm as u16 as u32,
+    };
+
+    let mut insns = vec![match arch {
+        // lui s0, ENTRY
+        Arch::RiscV64 => u(ENTRY as i32, base, 0x37),
+        // lui $s0, ENTRY >> 16
+        Arch::Mips64 => (0x0f << 26) | (base << 16) | (ENTRY >> 16) as u32,
+    }];
+    for (number, call_args) in calls {
+        insns.push(addi(nr, 0, number));
+        for (reg, arg) in args.into_iter().zip(call_args) {
+            insns.push(match arg {
+                Arg::Imm(imm) => addi(reg, 0, imm),
+                Arg::Data(offset) => addi(reg, base, offset),
+                Arg::Stack(offset) => addi(reg, sp, offset),
+            });
+        }
+        insns.push(match arch {
+            Arch::RiscV64 => 0x0000_0073,
+            Arch::Mips64 => 0x0000_000c,
+        });
+    }
+
+    let mut code = insns
+        .into_iter()
+        .flat_map(|insn| match arch {
+            Arch::RiscV64 => insn.to_le_bytes(),
+            Arch::Mips64 => insn.to_be_bytes(),
+        })
+        .collect::<Vec<_>>();
+    assert!(code.len() <= DATA as usize);
+    code.resize(DATA as usize, 0);
+    code.extend_from_slice(&4u32.to_be_bytes());
+    code.extend_from_slice(b"test");
+    code.extend_from_slice(&<[u8; 32]>::from(echo_key()));
+    code.extend_from_slice(b"hi");
+
+    build_elf(arch, ENTRY, &code, code.len() as u64)
+}
@@ -3,14 +3,18 @@
 use crate::{HintType, PreState};
 use alloc::{boxed::Box, sync::Arc, vec::Vec};
 use alloy_consensus::Header;
-use alloy_eips::eip2718::Decodable2718;
+use alloy_eips::{eip2718::Decodable2718, eip2935::HISTORY_STORAGE_ADDRESS};
 use alloy_primitives::B256;
 use alloy_rlp::Decodable;
 use async_trait::async_trait;
 use kona_interop::InteropProvider;
 use kona_mpt::{OrderedListWalker, TrieNode, TrieProvider};
 use kona_preimage::{CommsClient, PreimageKey, PreimageKeyType};
-use kona_proof::errors::OracleProviderError;
+use kona_proof::{
+    errors::OracleProviderError,
+    l2::{history_block_hash, history_storage_slot, should_lookup_history},
+};
+use maili_genesis::RollupConfig;
 use maili_registry::HashMap;
 use op_alloy_consensus::OpReceiptEnvelope;
 use spin::RwLock;
@@ -22,6 +26,8 @@ pub struct OracleInteropProvider<T> {
     oracle: Arc<T>,
     /// The [PreState] for the current program execution.
     pre_state: PreState,
+    /// The [RollupConfig]s of the chains in the [PreState], keyed by chain ID.
+    rollup_configs: HashMap<u64, RollupConfig>,
     /// The safe head block header cache, keyed by chain ID.
     safe_head_cache: Arc<RwLock<HashMap<u64, Header>>>,
 }
@@ -30,9 +36,19 @@ impl<T> OracleInteropProvider<T>
 where
     T: CommsClient + Send + Sync,
 {
-    /// Creates a new [OracleInteropProvider] with the given oracle client and [PreState].
-    pub fn new(oracle: Arc<T>, pre_state: PreState) -> Self {
-        Self { oracle, pre_state, safe_head_cache: Arc::new(RwLock::new(HashMap::default())) }
+    /// Creates a new [OracleInteropProvider] with the given oracle client, [PreState] and the
+    /// [RollupConfig]s of the chains within it.
+    pub fn new(
+        oracle: Arc<T>,
+        pre_state: PreState,
+        rollup_configs: HashMap<u64, RollupConfig>,
+    ) -> Self {
+        Self {
+            oracle,
+            pre_state,
+            rollup_configs,
+            safe_head_cache: Arc::new(RwLock::new(HashMap::default())),
+        }
     }
 
     /// Fetch the [Header] for the block with the given hash.
@@ -97,8 +113,9 @@ where
         // Find the safe head for the given chain ID.
         //
         // If the safe head is not in the cache, we need to fetch it from the oracle.
-        let mut header = if let Some(header) = self.safe_head_cache.read().get(&chain_id) {
-            header.clone()
+        let cached = self.safe_head_cache.read().get(&chain_id).cloned();
+        let mut header = if let Some(header) = cached {
+            header
         } else {
             let pre_state = match &self.pre_state {
                 PreState::SuperRoot(super_root) => super_root,
@@ -136,6 +153,31 @@ where
             return Err(OracleProviderError::BlockNumberPastHead(number, header.number));
         }
 
+        // Attempt to look up the block hash in the history storage contract, which is only
+        // guaranteed to be deployed from Isthmus onwards. If the contract is not present in the
+        // safe head's state, fall back to walking back the block headers.
+        let isthmus_active = self
+            .rollup_configs
+            .get(&chain_id)
+            .is_some_and(|config| config.is_isthmus_active(header.timestamp));
+        if isthmus_active && should_lookup_history(&header, number) {
+            HintType::L2AccountStorageProof
+                .with_data(&[
+                    header.number.to_be_bytes().as_ref(),
+                    HISTORY_STORAGE_ADDRESS.as_slice(),
+                    history_storage_slot(number).to_be_bytes::<32>().as_ref(),
+                    chain_id.to_be_bytes().as_ref(),
+                ])
+                .send(self.oracle.as_ref())
+                .await?;
+            if let Some(hash) = history_block_hash(&header, number, self)? {
+                let historical = self.header_by_hash(chain_id, hash).await?;
+                if historical.number == number {
+                    return Ok(historical);
+                }
+            }
+        }
+
         // Walk back the block headers to the desired block number.
         while header.number > number {
             header = self.header_by_hash(chain_id, header.parent_hash).await?;
@@ -167,7 +209,7 @@ where
 
 impl<T> TrieProvider for OracleInteropProvider<T>
 where
-    T: CommsClient + Send + Sync + Clone,
+    T: CommsClient + Send + Sync,
 {
     type Error = OracleProviderError;
 
@@ -182,3 +224,105 @@ where
         })
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::{string::String, vec};
+    use alloy_consensus::EMPTY_ROOT_HASH;
+    use alloy_primitives::keccak256;
+    use alloy_rlp::Encodable;
+    use kona_interop::{OutputRootWithChain, SuperRoot};
+    use kona_preimage::{
+        errors::{PreimageOracleError, PreimageOracleResult},
+        HintWriterClient, PreimageOracleClient,
+    };
+    use spin::Mutex;
+
+    const CHAIN_ID: u64 = 10;
+
+    /// A [CommsClient] serving preimages from memory, recording the hints it receives.
+    #[derive(Debug, Default, Clone)]
+    struct MockOracle {
+        preimages: HashMap<PreimageKey, Vec<u8>>,
+        hints: Arc<Mutex<Vec<String>>>,
+    }
+
+    #[async_trait]
+    impl PreimageOracleClient for MockOracle {
+        async fn get(&self, key: PreimageKey) -> PreimageOracleResult<Vec<u8>> {
+            self.preimages.get(&key).cloned().ok_or(PreimageOracleError::KeyNotFound)
+        }
+
+        async fn get_exact(&self, key: PreimageKey, buf: &mut [u8]) -> PreimageOracleResult<()> {
+            buf.copy_from_slice(&self.get(key).await?);
+            Ok(())
+        }
+    }
+
+    #[async_trait]
+    impl HintWriterClient for MockOracle {
+        async fn write(&self, hint: &str) -> PreimageOracleResult<()> {
+            self.hints.lock().push(hint.into());
+            Ok(())
+        }
+    }
+
+    /// Builds an [OracleInteropProvider] over a chain of 33 empty-state blocks, whose safe head is
+    /// the last block.
+    fn provider(rollup_config: RollupConfig) -> OracleInteropProvider<MockOracle> {
+        let mut oracle = MockOracle::default();
+        let mut parent_hash = B256::ZERO;
+        for number in 0..=32 {
+            let header = Header {
+                number,
+                parent_hash,
+                timestamp: number * 2,
+                state_root: EMPTY_ROOT_HASH,
+                ..Default::default()
+            };
+            let mut rlp = Vec::new();
+            header.encode(&mut rlp);
+            parent_hash = keccak256(&rlp);
+            oracle.preimages.insert(PreimageKey::new_keccak256(*parent_hash), rlp);
+        }
+
+        let mut output = vec![0u8; 128];
+        output[96..].copy_from_slice(parent_hash.as_slice());
+        let output_root = keccak256(&output);
+        oracle.preimages.insert(PreimageKey::new_keccak256(*output_root), output);
+
+        let pre_state = PreState::SuperRoot(SuperRoot::new(
+            64,
+            vec![OutputRootWithChain::new(CHAIN_ID, output_root)],
+        ));
+        let rollup_configs = HashMap::from_iter([(CHAIN_ID, rollup_config)]);
+        OracleInteropProvider::new(Arc::new(oracle), pre_state, rollup_configs)
+    }
+
+    /// Returns the number of history storage proof hints sent to the oracle.
+    fn history_hints(provider: &OracleInteropProvider<MockOracle>) -> usize {
+        let prefix = HintType::L2AccountStorageProof.to_string();
+        provider.oracle.hints.lock().iter().filter(|hint| hint.starts_with(&prefix)).count()
+    }
+
+    #[test]
+    fn test_header_by_number_pre_isthmus() {
+        let provider = provider(RollupConfig::default());
+        let header = kona_proof::block_on(provider.header_by_number(CHAIN_ID, 4)).unwrap();
+
+        assert_eq!(header.number, 4);
+        assert_eq!(history_hints(&provider), 0);
+    }
+
+    #[test]
+    fn test_header_by_number_isthmus() {
+        let provider = provider(RollupConfig { isthmus_time: Some(0), ..Default::default() });
+        let header = kona_proof::block_on(provider.header_by_number(CHAIN_ID, 4)).unwrap();
+
+        // The history storage contract is absent from the empty state, so the provider falls
+        // back to walking back the block headers.
+        assert_eq!(header.number, 4);
+        assert_eq!(history_hints(&provider), 1);
+    }
+}
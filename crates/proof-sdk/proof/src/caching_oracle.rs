@@ -1,20 +1,32 @@
 //! Contains the [CachingOracle], which is a wrapper around an [OracleReader] and [HintWriter] that
-//! stores a configurable number of responses in an [LruCache] for quick retrieval.
+//! stores responses in byte-budgeted [LruCache]s for quick retrieval.
 //!
 //! [OracleReader]: kona_preimage::OracleReader
 //! [HintWriter]: kona_preimage::HintWriter
 
-use alloc::{boxed::Box, sync::Arc, vec::Vec};
+use alloc::{
+    boxed::Box,
+    collections::{BTreeMap, BTreeSet},
+    sync::Arc,
+    vec::Vec,
+};
 use async_trait::async_trait;
-use core::num::NonZeroUsize;
+use core::ops::Add;
 use kona_preimage::{
-    errors::PreimageOracleResult, HintWriterClient, PreimageKey, PreimageOracleClient,
+    errors::PreimageOracleResult, HintWriterClient, PreimageKey, PreimageKeyType,
+    PreimageOracleClient,
 };
 use lru::LruCache;
 use spin::Mutex;
 
-/// A wrapper around an [OracleReader] and [HintWriter] that stores a configurable number of
-/// responses in an [LruCache] for quick retrieval.
+/// A wrapper around an [OracleReader] and [HintWriter] that stores responses in a byte-budgeted
+/// cache for quick retrieval.
+///
+/// Responses are cached in a separate [LruCache] per [PreimageKeyType], each bounded by the total
+/// size of the preimages it holds. When inserting a preimage would exceed the budget of its key
+/// type, the least recently used preimages of that key type are evicted. Keys may be pinned with
+/// [CachingOracle::pin], in which case their preimages are held outside of the budget and are never
+/// evicted.
 ///
 /// [OracleReader]: kona_preimage::OracleReader
 /// [HintWriter]: kona_preimage::HintWriter
@@ -26,7 +38,7 @@ where
     HW: HintWriterClient,
 {
     /// The spin-locked cache that stores the responses from the oracle.
-    cache: Arc<Mutex<LruCache<PreimageKey, Vec<u8>>>>,
+    cache: Arc<Mutex<PreimageCache>>,
     /// Oracle reader type.
     oracle_reader: OR,
     /// Hint writer type.
@@ -38,17 +50,195 @@ where
     OR: PreimageOracleClient,
     HW: HintWriterClient,
 {
-    /// Creates a new [CachingOracle] that wraps the given [OracleReader] and stores up to `N`
-    /// responses in the cache.
+    /// Creates a new [CachingOracle] that wraps the given [OracleReader] and stores up to
+    /// `budget_bytes` bytes of preimages per [PreimageKeyType] in the cache. The budget of
+    /// individual key types can be overridden with [Self::with_budget].
     ///
     /// [OracleReader]: kona_preimage::OracleReader
-    pub fn new(cache_size: usize, oracle_reader: OR, hint_writer: HW) -> Self {
+    pub fn with_byte_budget(budget_bytes: usize, oracle_reader: OR, hint_writer: HW) -> Self {
+        let cache = Arc::new(Mutex::new(PreimageCache::new(budget_bytes)));
+        Self { cache, oracle_reader, hint_writer }
+    }
+
+    /// Sets the cache budget, in bytes, for preimages of the given [PreimageKeyType].
+    pub fn with_budget(self, key_type: PreimageKeyType, budget: usize) -> Self {
+        self.cache.lock().set_budget(key_type, budget);
+        self
+    }
+
+    /// Pins the given [PreimageKey]. The preimage of a pinned key is held outside of the budget of
+    /// its key type, and is never evicted or flushed from the cache.
+    pub fn pin(&self, key: PreimageKey) {
+        self.cache.lock().pin(key);
+    }
+
+    /// Returns `true` if the preimage for the given [PreimageKey] is held in the cache, without
+    /// updating its recency.
+    pub fn contains(&self, key: &PreimageKey) -> bool {
+        self.cache.lock().contains(key)
+    }
+
+    /// Returns the [CacheStats] of the cache, summed over all [PreimageKeyType]s.
+    pub fn stats(&self) -> CacheStats {
+        self.cache.lock().partitions.values().fold(CacheStats::default(), |acc, p| acc + p.stats)
+    }
+
+    /// Returns the [CacheStats] of the cache for preimages of the given [PreimageKeyType].
+    pub fn stats_for(&self, key_type: PreimageKeyType) -> CacheStats {
+        self.cache.lock().partitions.get(&key_type).map(|p| p.stats).unwrap_or_default()
+    }
+}
+
+/// Statistics about the usage of a [CachingOracle].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CacheStats {
+    /// The number of requests served from the cache.
+    pub hits: u64,
+    /// The number of requests forwarded to the oracle.
+    pub misses: u64,
+    /// The number of preimages evicted to stay within the budget.
+    pub evictions: u64,
+    /// The number of preimages currently held, including pinned preimages.
+    pub entries: u64,
+    /// The number of bytes currently held, including pinned preimages.
+    pub bytes: u64,
+}
+
+impl Add for CacheStats {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self {
+            hits: self.hits + rhs.hits,
+            misses: self.misses + rhs.misses,
+            evictions: self.evictions + rhs.evictions,
+            entries: self.entries + rhs.entries,
+            bytes: self.bytes + rhs.bytes,
+        }
+    }
+}
+
+/// The cached preimages of a single [PreimageKeyType].
+#[derive(Debug)]
+struct CachePartition {
+    /// The evictable preimages, in order of recency.
+    lru: LruCache<PreimageKey, Vec<u8>>,
+    /// The total size of the preimages in [Self::lru].
+    lru_bytes: usize,
+    /// The pinned preimages.
+    pinned: BTreeMap<PreimageKey, Vec<u8>>,
+    /// The maximum value of [Self::lru_bytes].
+    budget: usize,
+    /// The usage statistics of the partition.
+    stats: CacheStats,
+}
+
+impl CachePartition {
+    fn new(budget: usize) -> Self {
         Self {
-            cache: Arc::new(Mutex::new(LruCache::new(
-                NonZeroUsize::new(cache_size).expect("N must be greater than 0"),
-            ))),
-            oracle_reader,
-            hint_writer,
+            lru: LruCache::unbounded(),
+            lru_bytes: 0,
+            pinned: BTreeMap::new(),
+            budget,
+            stats: CacheStats::default(),
+        }
+    }
+
+    /// Evicts the least recently used preimages until the partition is within its budget.
+    fn evict_to_budget(&mut self) {
+        while self.lru_bytes > self.budget {
+            let Some((_, value)) = self.lru.pop_lru() else {
+                break;
+            };
+            self.lru_bytes -= value.len();
+            self.stats.evictions += 1;
+            self.stats.entries -= 1;
+            self.stats.bytes -= value.len() as u64;
+        }
+    }
+}
+
+/// The state of a [CachingOracle]'s cache.
+#[derive(Debug)]
+struct PreimageCache {
+    /// The budget of key types without an explicit budget.
+    default_budget: usize,
+    /// The partitions of the cache, keyed by [PreimageKeyType].
+    partitions: BTreeMap<PreimageKeyType, CachePartition>,
+    /// The pinned keys.
+    pins: BTreeSet<PreimageKey>,
+}
+
+impl PreimageCache {
+    const fn new(default_budget: usize) -> Self {
+        Self { default_budget, partitions: BTreeMap::new(), pins: BTreeSet::new() }
+    }
+
+    fn partition(&mut self, key_type: PreimageKeyType) -> &mut CachePartition {
+        let budget = self.default_budget;
+        self.partitions.entry(key_type).or_insert_with(|| CachePartition::new(budget))
+    }
+
+    fn set_budget(&mut self, key_type: PreimageKeyType, budget: usize) {
+        let partition = self.partition(key_type);
+        partition.budget = budget;
+        partition.evict_to_budget();
+    }
+
+    fn pin(&mut self, key: PreimageKey) {
+        self.pins.insert(key);
+        let partition = self.partition(key.key_type());
+        if let Some(value) = partition.lru.pop(&key) {
+            partition.lru_bytes -= value.len();
+            partition.pinned.insert(key, value);
+        }
+    }
+
+    fn contains(&self, key: &PreimageKey) -> bool {
+        self.partitions
+            .get(&key.key_type())
+            .is_some_and(|p| p.pinned.contains_key(key) || p.lru.contains(key))
+    }
+
+    /// Fetches a preimage from the cache, recording a hit or a miss.
+    fn get(&mut self, key: &PreimageKey) -> Option<&Vec<u8>> {
+        let partition = self.partition(key.key_type());
+        let value = partition.pinned.get(key).or_else(|| partition.lru.get(key));
+        if value.is_some() {
+            partition.stats.hits += 1;
+        } else {
+            partition.stats.misses += 1;
+        }
+        value
+    }
+
+    /// Inserts a preimage into the cache, evicting preimages of the same key type as needed.
+    /// Preimages larger than the budget of their key type are not cached.
+    fn insert(&mut self, key: PreimageKey, value: Vec<u8>) {
+        let pinned = self.pins.contains(&key);
+        let partition = self.partition(key.key_type());
+        if !pinned && value.len() > partition.budget {
+            return;
+        }
+
+        partition.stats.entries += 1;
+        partition.stats.bytes += value.len() as u64;
+        if pinned {
+            partition.pinned.insert(key, value);
+        } else {
+            partition.lru_bytes += value.len();
+            partition.lru.put(key, value);
+            partition.evict_to_budget();
+        }
+    }
+
+    /// Removes all unpinned preimages from the cache.
+    fn flush(&mut self) {
+        for partition in self.partitions.values_mut() {
+            partition.stats.entries -= partition.lru.len() as u64;
+            partition.stats.bytes -= partition.lru_bytes as u64;
+            partition.lru.clear();
+            partition.lru_bytes = 0;
         }
     }
 }
@@ -64,9 +254,9 @@ where
     OR: PreimageOracleClient,
     HW: HintWriterClient,
 {
-    /// Flushes the cache, removing all entries.
+    /// Flushes the cache, removing all entries that have not been pinned.
     fn flush(&self) {
-        self.cache.lock().clear();
+        self.cache.lock().flush();
     }
 }
 
@@ -82,7 +272,7 @@ where
             Ok(value.clone())
         } else {
             let value = self.oracle_reader.get(key).await?;
-            cache_lock.put(key, value.clone());
+            cache_lock.insert(key, value.clone());
             Ok(value)
         }
     }
@@ -96,7 +286,7 @@ where
             Ok(())
         } else {
             self.oracle_reader.get_exact(key, buf).await?;
-            cache_lock.put(key, buf.to_vec());
+            cache_lock.insert(key, buf.to_vec());
             Ok(())
         }
     }
@@ -112,3 +302,102 @@ where
         self.hint_writer.write(hint).await
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::vec;
+    use kona_preimage::errors::PreimageOracleError;
+
+    /// A [PreimageOracleClient] that serves a preimage of 100 bytes for every key.
+    #[derive(Debug, Default, Clone)]
+    struct MockOracle;
+
+    #[async_trait]
+    impl PreimageOracleClient for MockOracle {
+        async fn get(&self, _: PreimageKey) -> PreimageOracleResult<Vec<u8>> {
+            Ok(vec![0xFF; 100])
+        }
+
+        async fn get_exact(&self, _: PreimageKey, buf: &mut [u8]) -> PreimageOracleResult<()> {
+            if buf.len() != 100 {
+                return Err(PreimageOracleError::BufferLengthMismatch(100, buf.len()));
+            }
+            buf.fill(0xFF);
+            Ok(())
+        }
+    }
+
+    #[async_trait]
+    impl HintWriterClient for MockOracle {
+        async fn write(&self, _: &str) -> PreimageOracleResult<()> {
+            Ok(())
+        }
+    }
+
+    fn keccak_key(i: u8) -> PreimageKey {
+        PreimageKey::new([i; 32], PreimageKeyType::Keccak256)
+    }
+
+    #[test]
+    fn test_caching_oracle_byte_budget() {
+        let oracle = CachingOracle::with_byte_budget(250, MockOracle, MockOracle);
+
+        crate::block_on(async {
+            for i in 0..3 {
+                oracle.get(keccak_key(i)).await.unwrap();
+            }
+        });
+
+        // Only two 100 byte preimages fit in the 250 byte budget.
+        assert!(!oracle.contains(&keccak_key(0)));
+        assert!(oracle.contains(&keccak_key(1)));
+        assert!(oracle.contains(&keccak_key(2)));
+
+        crate::block_on(oracle.get(keccak_key(2))).unwrap();
+        assert_eq!(
+            oracle.stats(),
+            CacheStats { hits: 1, misses: 3, evictions: 1, entries: 2, bytes: 200 }
+        );
+    }
+
+    #[test]
+    fn test_caching_oracle_budget_per_key_type() {
+        let oracle = CachingOracle::with_byte_budget(250, MockOracle, MockOracle)
+            .with_budget(PreimageKeyType::Blob, 0);
+        let blob_key = PreimageKey::new([1; 32], PreimageKeyType::Blob);
+
+        crate::block_on(async {
+            oracle.get(blob_key).await.unwrap();
+            oracle.get(keccak_key(1)).await.unwrap();
+        });
+
+        assert!(!oracle.contains(&blob_key));
+        assert!(oracle.contains(&keccak_key(1)));
+        assert_eq!(oracle.stats_for(PreimageKeyType::Blob).misses, 1);
+        assert_eq!(oracle.stats_for(PreimageKeyType::Blob).entries, 0);
+        assert_eq!(oracle.stats_for(PreimageKeyType::Keccak256).entries, 1);
+    }
+
+    #[test]
+    fn test_caching_oracle_pinned_keys() {
+        let oracle = CachingOracle::with_byte_budget(150, MockOracle, MockOracle);
+        let local_key = PreimageKey::new_local(1);
+        oracle.pin(local_key);
+
+        crate::block_on(async {
+            let mut buf = [0u8; 100];
+            oracle.get_exact(local_key, &mut buf).await.unwrap();
+            oracle.get(keccak_key(1)).await.unwrap();
+            oracle.get(keccak_key(2)).await.unwrap();
+        });
+        oracle.flush();
+
+        assert!(oracle.contains(&local_key));
+        assert!(!oracle.contains(&keccak_key(2)));
+        assert_eq!(
+            oracle.stats(),
+            CacheStats { hits: 0, misses: 3, evictions: 1, entries: 1, bytes: 100 }
+        );
+    }
+}
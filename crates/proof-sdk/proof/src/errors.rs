@@ -2,7 +2,7 @@
 
 use alloc::string::{String, ToString};
 use kona_derive::errors::{PipelineError, PipelineErrorKind};
-use kona_mpt::OrderedListWalkerError;
+use kona_mpt::{OrderedListWalkerError, TrieNodeError};
 use kona_preimage::errors::PreimageOracleError;
 use maili_protocol::{FromBlockError, OpBlockConversionError};
 use thiserror::Error;
@@ -19,6 +19,9 @@ pub enum OracleProviderError {
     /// List walker error.
     #[error("Trie walker error: {0}")]
     TrieWalker(OrderedListWalkerError),
+    /// Trie node error.
+    #[error("Trie node error: {0}")]
+    TrieNode(TrieNodeError),
     /// BlockInfo error.
     #[error("From block error: {0}")]
     BlockInfo(FromBlockError),
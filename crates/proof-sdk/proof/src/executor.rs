@@ -6,7 +6,8 @@ use alloy_primitives::B256;
 use async_trait::async_trait;
 use kona_driver::Executor;
 use kona_executor::{
-    ExecutionArtifacts, KonaHandleRegister, StatelessL2BlockExecutor, TrieDBProvider,
+    BlockProfiler, ExecutionArtifacts, KonaHandleRegister, StatelessL2BlockExecutor,
+    TrieDBProvider, UpgradeRegistry,
 };
 use kona_mpt::TrieHinter;
 use maili_genesis::RollupConfig;
@@ -27,8 +28,17 @@ where
     trie_hinter: H,
     /// The handle register for the executor.
     handle_register: Option<KonaHandleRegister<P, H>>,
+    /// The chain's custom hardfork upgrades and precompiles.
+    upgrades: UpgradeRegistry,
+    /// The profiler of the executed blocks, if enabled.
+    profiler: Option<BlockProfiler>,
     /// The executor.
     inner: Option<StatelessL2BlockExecutor<'a, P, H>>,
+    /// The number of blocks to execute with the same [StatelessL2BlockExecutor] before it is
+    /// rebuilt from the safe head, if multi-block execution is enabled.
+    checkpoint_interval: Option<u64>,
+    /// The number of blocks executed since the [StatelessL2BlockExecutor] was last rebuilt.
+    blocks_since_checkpoint: u64,
 }
 
 impl<'a, P, H> KonaExecutor<'a, P, H>
@@ -44,7 +54,42 @@ where
         handle_register: Option<KonaHandleRegister<P, H>>,
         inner: Option<StatelessL2BlockExecutor<'a, P, H>>,
     ) -> Self {
-        Self { rollup_config, trie_provider, trie_hinter, handle_register, inner }
+        Self {
+            rollup_config,
+            trie_provider,
+            trie_hinter,
+            handle_register,
+            upgrades: UpgradeRegistry::new(),
+            profiler: None,
+            inner,
+            checkpoint_interval: None,
+            blocks_since_checkpoint: 0,
+        }
+    }
+
+    /// Sets the [UpgradeRegistry] of the chain's custom hardfork upgrades and precompiles.
+    pub fn with_upgrade_registry(mut self, upgrades: UpgradeRegistry) -> Self {
+        self.upgrades = upgrades;
+        self
+    }
+
+    /// Enables profiling of the executed blocks with the given [BlockProfiler]. The profiles are
+    /// read back from a clone of the profiler.
+    pub fn with_block_profiler(mut self, profiler: BlockProfiler) -> Self {
+        self.profiler = Some(profiler);
+        self
+    }
+
+    /// Enables multi-block execution, where consecutive blocks are executed with the same
+    /// [StatelessL2BlockExecutor] rather than one rebuilt from each new safe head.
+    ///
+    /// The executor keeps the trie nodes it has opened and its account and storage cache between
+    /// blocks, so that the state touched by earlier blocks is not fetched and decoded again. Every
+    /// header still commits to its full state root. Every `checkpoint_interval` blocks, or after a
+    /// failed execution, the executor is rebuilt from the safe head to bound its memory usage.
+    pub const fn with_checkpoint_interval(mut self, checkpoint_interval: u64) -> Self {
+        self.checkpoint_interval = Some(checkpoint_interval);
+        self
     }
 }
 
@@ -67,16 +112,33 @@ where
     /// Since the L2 block executor is stateless, on an update to the safe head,
     /// a new executor is created with the updated header.
     fn update_safe_head(&mut self, header: Sealed<Header>) {
+        // In multi-block execution, keep the executor if it has just executed the new safe head.
+        if let (Some(interval), Some(inner)) = (self.checkpoint_interval, self.inner.as_ref()) {
+            if self.blocks_since_checkpoint < interval &&
+                inner.parent_block_header().seal() == header.seal()
+            {
+                return;
+            }
+        }
+        self.blocks_since_checkpoint = 0;
+
         let mut builder = StatelessL2BlockExecutor::builder(
             self.rollup_config,
             self.trie_provider.clone(),
             self.trie_hinter.clone(),
         )
-        .with_parent_header(header);
+        .with_parent_header(header)
+        .with_upgrade_registry(self.upgrades.clone());
 
         if let Some(register) = self.handle_register {
             builder = builder.with_handle_register(register);
         }
+        if let Some(profiler) = self.profiler.as_ref() {
+            builder = builder.with_profiler(profiler.clone());
+        }
+        if self.checkpoint_interval.is_some() {
+            builder = builder.with_persistent_cache();
+        }
         self.inner = Some(builder.build());
     }
 
@@ -85,10 +147,18 @@ where
         &mut self,
         attributes: OpPayloadAttributes,
     ) -> Result<ExecutionArtifacts, Self::Error> {
-        self.inner.as_mut().map_or_else(
+        let result = self.inner.as_mut().map_or_else(
             || Err(kona_executor::ExecutorError::MissingExecutor),
             |e| e.execute_payload(attributes),
-        )
+        );
+
+        // A failed execution may leave the executor's state partially updated, so it must be
+        // rebuilt from the safe head before the next block.
+        match result {
+            Ok(_) => self.blocks_since_checkpoint += 1,
+            Err(_) => self.inner = None,
+        }
+        result
     }
 
     /// Computes the output root.
@@ -99,3 +169,123 @@ where
         )
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::{string::String, sync::Arc, vec, vec::Vec};
+    use alloy_consensus::Sealable;
+    use alloy_eips::eip2718::Encodable2718;
+    use alloy_primitives::{address, keccak256, map::HashMap, Address, Bytes, TxKind, U256};
+    use alloy_rlp::{Decodable, Encodable};
+    use kona_mpt::{NoopTrieHinter, TrieNode, TrieProvider};
+    use op_alloy_consensus::{OpTxEnvelope, TxDeposit};
+    use spin::Mutex;
+
+    /// A [TrieDBProvider] serving the trie nodes collected from a reference execution.
+    #[derive(Debug, Default, Clone)]
+    struct MapProvider(Arc<Mutex<HashMap<B256, Bytes>>>);
+
+    impl MapProvider {
+        /// Collects the opened nodes of the trie rooted at `node`.
+        fn collect(&self, node: &TrieNode) {
+            match node {
+                TrieNode::Empty | TrieNode::Blinded { .. } => return,
+                TrieNode::Leaf { .. } => {}
+                TrieNode::Extension { node, .. } => self.collect(node),
+                TrieNode::Branch { stack } => stack.iter().for_each(|node| self.collect(node)),
+            }
+            let mut rlp = Vec::new();
+            node.encode(&mut rlp);
+            self.0.lock().insert(keccak256(&rlp), rlp.into());
+        }
+    }
+
+    impl TrieProvider for MapProvider {
+        type Error = String;
+
+        fn trie_node_by_hash(&self, key: B256) -> Result<TrieNode, Self::Error> {
+            let rlp = self.0.lock().get(&key).cloned().ok_or_else(|| String::from("missing"))?;
+            TrieNode::decode(&mut rlp.as_ref()).map_err(|_| String::from("invalid node"))
+        }
+    }
+
+    impl TrieDBProvider for MapProvider {
+        fn bytecode_by_hash(&self, _: B256) -> Result<Bytes, Self::Error> {
+            Err(String::from("missing"))
+        }
+
+        fn header_by_hash(&self, _: B256) -> Result<Header, Self::Error> {
+            Err(String::from("missing"))
+        }
+    }
+
+    /// Creates the payload of block `number`, minting ether and transferring it to a new account
+    /// and to the L2 to L1 message passer.
+    fn payload(number: u64) -> OpPayloadAttributes {
+        let deposit = |to: Address, value: u64| {
+            let deposit = TxDeposit {
+                source_hash: B256::with_last_byte(number as u8),
+                from: Address::with_last_byte(0xAA),
+                to: TxKind::Call(to),
+                mint: Some(1_000_000),
+                value: U256::from(value),
+                gas_limit: 100_000,
+                ..Default::default()
+            };
+            OpTxEnvelope::Deposit(deposit.seal_slow()).encoded_2718().into()
+        };
+
+        let mut attributes = OpPayloadAttributes {
+            gas_limit: Some(30_000_000),
+            transactions: Some(vec![
+                deposit(Address::with_last_byte(number as u8), number),
+                deposit(address!("4200000000000000000000000000000000000016"), 1),
+            ]),
+            ..Default::default()
+        };
+        attributes.payload_attributes.timestamp = number * 2;
+        attributes
+    }
+
+    /// Executes blocks 1 through 6 with the [KonaExecutor], as the driver does, returning the
+    /// output root of each block.
+    fn output_roots(executor: &mut KonaExecutor<'_, MapProvider, NoopTrieHinter>) -> Vec<B256> {
+        let mut head = Header::default().seal_slow();
+        (1..=6)
+            .map(|number| {
+                executor.update_safe_head(head.clone());
+                head = crate::block_on(executor.execute_payload(payload(number)))
+                    .unwrap()
+                    .block_header;
+                executor.compute_output_root().unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_checkpointed_execution_matches_per_block_execution() {
+        let config = RollupConfig::default();
+        let provider = MapProvider::default();
+
+        // Execute the blocks once with a single executor, to collect the trie nodes that the
+        // per-block executors fetch from their parent state.
+        let mut reference =
+            StatelessL2BlockExecutor::builder(&config, provider.clone(), NoopTrieHinter).build();
+        for number in 1..=6 {
+            reference.execute_payload(payload(number)).unwrap();
+            let trie_db = reference.trie_db_mut();
+            provider.collect(trie_db.root());
+            trie_db.storage_roots().values().for_each(|root| provider.collect(root));
+        }
+
+        let mut per_block =
+            KonaExecutor::new(&config, provider.clone(), NoopTrieHinter, None, None);
+        let mut checkpointed = KonaExecutor::new(&config, provider, NoopTrieHinter, None, None)
+            .with_checkpoint_interval(4);
+
+        let expected = output_roots(&mut per_block);
+        assert_eq!(output_roots(&mut checkpointed), expected);
+        assert!(expected.windows(2).all(|roots| roots[0] != roots[1]));
+    }
+}
@@ -1,9 +1,13 @@
 //! Contains the concrete implementation of the [L2ChainProvider] trait for the client program.
 
-use crate::{errors::OracleProviderError, HintType};
+use crate::{
+    errors::OracleProviderError,
+    l2::{history_block_hash, history_storage_slot, should_lookup_history},
+    HintType,
+};
 use alloc::{boxed::Box, sync::Arc, vec::Vec};
 use alloy_consensus::{BlockBody, Header};
-use alloy_eips::eip2718::Decodable2718;
+use alloy_eips::{eip2718::Decodable2718, eip2935::HISTORY_STORAGE_ADDRESS};
 use alloy_primitives::{Address, Bytes, B256};
 use alloy_rlp::Decodable;
 use async_trait::async_trait;
@@ -58,8 +62,11 @@ impl<T: CommsClient> OracleL2ChainProvider<T> {
 }
 
 impl<T: CommsClient> OracleL2ChainProvider<T> {
-    /// Returns a [Header] corresponding to the given L2 block number, by walking back from the
-    /// L2 safe head.
+    /// Returns a [Header] corresponding to the given L2 block number.
+    ///
+    /// If Isthmus is active at the L2 safe head and the block is far enough behind it, the block
+    /// hash is looked up in the EIP-2935 history storage contract within the safe head's state.
+    /// Otherwise, the header is found by walking back from the L2 safe head.
     async fn header_by_number(&mut self, block_number: u64) -> Result<Header, OracleProviderError> {
         // Fetch the starting block header.
         let mut header = self.header_by_hash(self.l2_safe_head().await?)?;
@@ -69,6 +76,23 @@ impl<T: CommsClient> OracleL2ChainProvider<T> {
             return Err(OracleProviderError::BlockNumberPastHead(block_number, header.number));
         }
 
+        // Attempt to look up the block hash in the history storage contract.
+        if self.rollup_config.is_isthmus_active(header.timestamp) &&
+            should_lookup_history(&header, block_number)
+        {
+            self.hint_storage_proof(
+                HISTORY_STORAGE_ADDRESS,
+                history_storage_slot(block_number),
+                header.number,
+            )?;
+            if let Some(hash) = history_block_hash(&header, block_number, self)? {
+                let historical = self.header_by_hash(hash)?;
+                if historical.number == block_number {
+                    return Ok(historical);
+                }
+            }
+        }
+
         // Walk back the block headers to the desired block number.
         while header.number > block_number {
             header = self.header_by_hash(header.parent_hash)?;
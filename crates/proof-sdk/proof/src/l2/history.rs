@@ -0,0 +1,190 @@
+//! Contains utilities for looking up historical L2 block hashes through the [EIP-2935] history
+//! storage contract.
+//!
+//! [EIP-2935]: https://eips.ethereum.org/EIPS/eip-2935
+
+use crate::errors::OracleProviderError;
+use alloy_consensus::Header;
+use alloy_eips::eip2935::{HISTORY_SERVE_WINDOW, HISTORY_STORAGE_ADDRESS};
+use alloy_primitives::{keccak256, B256, U256};
+use alloy_rlp::Decodable;
+use alloy_trie::TrieAccount;
+use kona_mpt::{Nibbles, TrieNode, TrieProvider};
+
+/// The minimum distance, in blocks, between a trusted head and a requested block for which a
+/// history contract lookup is attempted.
+///
+/// A lookup opens the account and storage proofs of the history storage contract, which costs on
+/// the order of a dozen preimages. Below this distance, walking back through parent hashes is
+/// cheaper.
+pub const HISTORY_LOOKUP_MIN_DISTANCE: u64 = 16;
+
+/// Returns the storage slot within the [EIP-2935] history storage contract that holds the hash of
+/// the block with the given number.
+///
+/// [EIP-2935]: https://eips.ethereum.org/EIPS/eip-2935
+pub const fn history_storage_slot(block_number: u64) -> U256 {
+    U256::from_limbs([block_number % HISTORY_SERVE_WINDOW as u64, 0, 0, 0])
+}
+
+/// Returns `true` if the hash of the block with the given number is within the window served by
+/// the [EIP-2935] history storage contract in the state of `head`, and is far enough away from
+/// `head` for a lookup to be cheaper than walking back through parent hashes.
+///
+/// [EIP-2935]: https://eips.ethereum.org/EIPS/eip-2935
+pub const fn should_lookup_history(head: &Header, block_number: u64) -> bool {
+    block_number < head.number &&
+        head.number - block_number >= HISTORY_LOOKUP_MIN_DISTANCE &&
+        head.number - block_number <= HISTORY_SERVE_WINDOW as u64
+}
+
+/// Looks up the hash of the block with the given number in the [EIP-2935] history storage
+/// contract, by opening the contract's account and storage slot within the state trie of `head`.
+///
+/// The caller is expected to have hinted the storage proof for [history_storage_slot] at `head`
+/// prior to this call.
+///
+/// ## Takes
+/// - `head`: The trusted header whose state root is used to open the history storage contract.
+/// - `block_number`: The number of the block to look up.
+/// - `fetcher`: The [TrieProvider] used to fetch trie node preimages.
+///
+/// ## Returns
+/// - `Ok(Some(hash))`: The hash of the block, as committed to by `head`'s state.
+/// - `Ok(None)`: If the block is outside of the served window, the history storage contract does
+///   not exist in `head`'s state, or the slot has not been written.
+/// - `Err(_)`: If a trie node preimage could not be fetched or decoded.
+///
+/// [EIP-2935]: https://eips.ethereum.org/EIPS/eip-2935
+pub fn history_block_hash<F: TrieProvider>(
+    head: &Header,
+    block_number: u64,
+    fetcher: &F,
+) -> Result<Option<B256>, OracleProviderError> {
+    if block_number >= head.number || head.number - block_number > HISTORY_SERVE_WINDOW as u64 {
+        return Ok(None);
+    }
+
+    // Open the history storage contract's account within the state trie.
+    let mut state_root = TrieNode::new_blinded(head.state_root);
+    let Some(account_rlp) = state_root
+        .open(&Nibbles::unpack(keccak256(HISTORY_STORAGE_ADDRESS)), fetcher)
+        .map_err(OracleProviderError::TrieNode)?
+    else {
+        return Ok(None);
+    };
+    let account =
+        TrieAccount::decode(&mut account_rlp.as_ref()).map_err(OracleProviderError::Rlp)?;
+
+    // Open the storage slot holding the block hash within the account's storage trie.
+    let slot = history_storage_slot(block_number);
+    let mut storage_root = TrieNode::new_blinded(account.storage_root);
+    let Some(slot_rlp) = storage_root
+        .open(&Nibbles::unpack(keccak256(slot.to_be_bytes::<32>())), fetcher)
+        .map_err(OracleProviderError::TrieNode)?
+    else {
+        return Ok(None);
+    };
+    let value = U256::decode(&mut slot_rlp.as_ref()).map_err(OracleProviderError::Rlp)?;
+
+    Ok((!value.is_zero()).then(|| B256::from(value)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::{string::String, vec::Vec};
+    use alloy_consensus::EMPTY_ROOT_HASH;
+    use alloy_primitives::{map::HashMap, Bytes};
+    use alloy_rlp::Encodable;
+
+    /// A [TrieProvider] backed by an in-memory map of node preimages.
+    struct MapTrieProvider(HashMap<B256, TrieNode>);
+
+    impl TrieProvider for MapTrieProvider {
+        type Error = String;
+
+        fn trie_node_by_hash(&self, key: B256) -> Result<TrieNode, Self::Error> {
+            self.0.get(&key).cloned().ok_or_else(|| String::from("missing preimage"))
+        }
+    }
+
+    fn encode<T: Encodable>(value: T) -> Bytes {
+        let mut buf = Vec::new();
+        value.encode(&mut buf);
+        buf.into()
+    }
+
+    /// Builds a state trie containing only the history storage contract, with a single storage
+    /// slot set for `block_number`.
+    fn history_state(block_number: u64, hash: B256) -> (B256, MapTrieProvider) {
+        let storage = TrieNode::Leaf {
+            prefix: Nibbles::unpack(keccak256(
+                history_storage_slot(block_number).to_be_bytes::<32>(),
+            )),
+            value: encode(U256::from_be_bytes(hash.0)),
+        };
+        let storage_root = storage.blind();
+
+        let account = TrieAccount { storage_root, ..Default::default() };
+        let state = TrieNode::Leaf {
+            prefix: Nibbles::unpack(keccak256(HISTORY_STORAGE_ADDRESS)),
+            value: encode(account),
+        };
+        let state_root = state.blind();
+
+        let mut preimages = HashMap::default();
+        preimages.insert(storage_root, storage);
+        preimages.insert(state_root, state);
+        (state_root, MapTrieProvider(preimages))
+    }
+
+    #[test]
+    fn test_history_block_hash() {
+        let hash = B256::repeat_byte(0xAA);
+        let (state_root, provider) = history_state(9_000, hash);
+        let head = Header { number: 10_000, state_root, ..Default::default() };
+
+        assert_eq!(history_block_hash(&head, 9_000, &provider).unwrap(), Some(hash));
+    }
+
+    #[test]
+    fn test_history_block_hash_unwritten_slot() {
+        let (state_root, provider) = history_state(9_000, B256::repeat_byte(0xAA));
+        let head = Header { number: 10_000, state_root, ..Default::default() };
+
+        assert_eq!(history_block_hash(&head, 9_001, &provider).unwrap(), None);
+    }
+
+    #[test]
+    fn test_history_block_hash_out_of_window() {
+        let head = Header { number: 10_000, state_root: EMPTY_ROOT_HASH, ..Default::default() };
+        let provider = MapTrieProvider(HashMap::default());
+
+        assert_eq!(history_block_hash(&head, 10_000, &provider).unwrap(), None);
+        assert_eq!(history_block_hash(&head, 10_001, &provider).unwrap(), None);
+        assert_eq!(
+            history_block_hash(&head, 10_000 - HISTORY_SERVE_WINDOW as u64 - 1, &provider).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_history_block_hash_missing_contract() {
+        let head = Header { number: 10_000, state_root: EMPTY_ROOT_HASH, ..Default::default() };
+        let provider = MapTrieProvider(HashMap::default());
+
+        assert_eq!(history_block_hash(&head, 9_000, &provider).unwrap(), None);
+    }
+
+    #[test]
+    fn test_should_lookup_history() {
+        let head = Header { number: 10_000, ..Default::default() };
+
+        assert!(!should_lookup_history(&head, 10_000));
+        assert!(!should_lookup_history(&head, 10_000 - HISTORY_LOOKUP_MIN_DISTANCE + 1));
+        assert!(should_lookup_history(&head, 10_000 - HISTORY_LOOKUP_MIN_DISTANCE));
+        assert!(should_lookup_history(&head, 10_000 - HISTORY_SERVE_WINDOW as u64));
+        assert!(!should_lookup_history(&head, 10_000 - HISTORY_SERVE_WINDOW as u64 - 1));
+    }
+}
@@ -2,3 +2,8 @@
 
 mod chain_provider;
 pub use chain_provider::OracleL2ChainProvider;
+
+mod history;
+pub use history::{
+    history_block_hash, history_storage_slot, should_lookup_history, HISTORY_LOOKUP_MIN_DISTANCE,
+};
@@ -27,7 +27,7 @@ pub mod boot;
 pub use boot::BootInfo;
 
 mod caching_oracle;
-pub use caching_oracle::{CachingOracle, FlushableCache};
+pub use caching_oracle::{CacheStats, CachingOracle, FlushableCache};
 
 mod blocking_runtime;
 pub use blocking_runtime::block_on;
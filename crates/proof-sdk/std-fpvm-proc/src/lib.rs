@@ -34,8 +34,11 @@ pub fn client_entry(attr: TokenStream, input: TokenStream) -> TokenStream {
             match #fn_body {
                 Ok(_) => kona_std_fpvm::io::exit(0),
                 Err(e) => {
-                    kona_std_fpvm::io::print_err(alloc::format!("Program encountered fatal error: {:?}\n", e).as_ref());
-                    kona_std_fpvm::io::exit(1);
+                    let reason = kona_std_fpvm::exit::ExitReason::exit_reason(&e);
+                    kona_std_fpvm::exit::exit_with_reason(
+                        reason,
+                        alloc::format!("Program encountered fatal error: {:?}\n", e).as_ref(),
+                    );
                 }
             }
         }
@@ -53,9 +56,13 @@ pub fn client_entry(attr: TokenStream, input: TokenStream) -> TokenStream {
 
                 #[panic_handler]
                 fn panic(info: &core::panic::PanicInfo) -> ! {
+                    let reason = if kona_std_fpvm::malloc::heap_exhausted() {
+                        kona_std_fpvm::exit::ClientExitReason::OutOfMemory
+                    } else {
+                        kona_std_fpvm::exit::ClientExitReason::Panic
+                    };
                     let msg = alloc::format!("Panic: {}", info);
-                    kona_std_fpvm::io::print_err(msg.as_ref());
-                    kona_std_fpvm::io::exit(2)
+                    kona_std_fpvm::exit::exit_with_reason(reason, msg.as_ref())
                 }
             }
         }
@@ -0,0 +1,168 @@
+//! Contains the [ClientExitReason], a typed exit status for client programs.
+//!
+//! Exit codes `0` and `1` keep their meaning for the FPVM, which maps them to the `VALID` and
+//! `INVALID` statuses of the final state. Every other reason maps to the `PANIC` status, so that
+//! a client program that failed to run to completion can be told apart from one that found the
+//! claim to be invalid.
+
+use crate::io;
+use alloc::{format, string::String};
+use core::fmt::Display;
+
+/// The prefix of the machine-readable exit record written to standard error by
+/// [exit_with_reason].
+pub const EXIT_RECORD_PREFIX: &str = "kona-exit:";
+
+/// The reason that a client program exited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum ClientExitReason {
+    /// The claim was validated successfully.
+    Success = 0,
+    /// The program ran to completion, and the claim is invalid.
+    InvalidClaim = 1,
+    /// The program panicked.
+    Panic = 2,
+    /// The boot information of the program is missing or malformed.
+    BadBootInfo = 3,
+    /// The preimage oracle failed to serve a request, or served malformed data.
+    OracleFailure = 4,
+    /// The execution of an L2 block failed.
+    ExecutionFailure = 5,
+    /// The derivation pipeline ran out of L1 data before reaching the claimed L2 block.
+    DerivationExhausted = 6,
+    /// The program ran out of heap memory.
+    OutOfMemory = 7,
+    /// The program failed for a reason that is not otherwise classified.
+    Other = 8,
+}
+
+impl ClientExitReason {
+    /// All [ClientExitReason]s, in order of their exit codes.
+    pub const ALL: [Self; 9] = [
+        Self::Success,
+        Self::InvalidClaim,
+        Self::Panic,
+        Self::BadBootInfo,
+        Self::OracleFailure,
+        Self::ExecutionFailure,
+        Self::DerivationExhausted,
+        Self::OutOfMemory,
+        Self::Other,
+    ];
+
+    /// Returns the exit code of the [ClientExitReason].
+    pub const fn code(self) -> u8 {
+        self as u8
+    }
+
+    /// Returns the [ClientExitReason] with the given exit code, if any.
+    pub const fn from_code(code: u8) -> Option<Self> {
+        if (code as usize) < Self::ALL.len() {
+            Some(Self::ALL[code as usize])
+        } else {
+            None
+        }
+    }
+
+    /// Returns the name of the [ClientExitReason], as written in the exit record.
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Success => "success",
+            Self::InvalidClaim => "invalid-claim",
+            Self::Panic => "panic",
+            Self::BadBootInfo => "bad-boot-info",
+            Self::OracleFailure => "oracle-failure",
+            Self::ExecutionFailure => "execution-failure",
+            Self::DerivationExhausted => "derivation-exhausted",
+            Self::OutOfMemory => "out-of-memory",
+            Self::Other => "other",
+        }
+    }
+
+    /// Returns `true` if the program ran to completion and reached a verdict on the claim.
+    pub const fn is_verdict(self) -> bool {
+        matches!(self, Self::Success | Self::InvalidClaim)
+    }
+
+    /// Returns the machine-readable exit record for the [ClientExitReason], e.g.
+    /// `kona-exit: reason=invalid-claim code=1`.
+    pub fn record(self) -> String {
+        format!("{EXIT_RECORD_PREFIX} reason={} code={}\n", self.as_str(), self.code())
+    }
+
+    /// Parses the last exit record in the given output of a client program, if any.
+    pub fn from_record(output: &str) -> Option<Self> {
+        output.lines().rev().find_map(|line| {
+            let mut fields = line.strip_prefix(EXIT_RECORD_PREFIX)?.split_whitespace();
+            let reason = fields.next()?.strip_prefix("reason=")?;
+            let code = fields.next()?.strip_prefix("code=")?.parse().ok()?;
+            Self::from_code(code).filter(|r| r.as_str() == reason)
+        })
+    }
+}
+
+impl Display for ClientExitReason {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A trait for errors that can be classified into a [ClientExitReason].
+pub trait ExitReason {
+    /// Returns the [ClientExitReason] for the error.
+    fn exit_reason(&self) -> ClientExitReason;
+}
+
+impl ExitReason for String {
+    fn exit_reason(&self) -> ClientExitReason {
+        ClientExitReason::Other
+    }
+}
+
+/// Writes the exit record for `reason` and the given message to standard error, and exits the
+/// program with the exit code of `reason`.
+pub fn exit_with_reason(reason: ClientExitReason, message: &str) -> ! {
+    io::print_err(&reason.record());
+    if !message.is_empty() {
+        io::print_err(message);
+    }
+    io::exit(reason.code() as usize)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_code_roundtrip() {
+        for (i, reason) in ClientExitReason::ALL.into_iter().enumerate() {
+            assert_eq!(reason.code() as usize, i);
+            assert_eq!(ClientExitReason::from_code(reason.code()), Some(reason));
+        }
+        assert_eq!(ClientExitReason::from_code(9), None);
+        assert!(ClientExitReason::InvalidClaim.is_verdict());
+        assert!(!ClientExitReason::OracleFailure.is_verdict());
+    }
+
+    #[test]
+    fn test_record_roundtrip() {
+        for reason in ClientExitReason::ALL {
+            let output =
+                format!("some log line\n{}Program encountered fatal error\n", reason.record());
+            assert_eq!(ClientExitReason::from_record(&output), Some(reason));
+        }
+        assert_eq!(
+            ClientExitReason::InvalidClaim.record(),
+            "kona-exit: reason=invalid-claim code=1\n"
+        );
+    }
+
+    #[test]
+    fn test_from_record_rejects_malformed() {
+        assert_eq!(ClientExitReason::from_record(""), None);
+        assert_eq!(ClientExitReason::from_record("kona-exit: reason=panic code=1"), None);
+        assert_eq!(ClientExitReason::from_record("kona-exit: reason=panic code=x"), None);
+        assert_eq!(ClientExitReason::from_record("Panic: kona-exit: reason=panic code=2"), None);
+    }
+}
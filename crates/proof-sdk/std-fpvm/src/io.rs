@@ -81,3 +81,22 @@ pub fn read(fd: FileDescriptor, buf: &mut [u8]) -> IOResult<usize> {
 pub fn exit(code: usize) -> ! {
     ClientIO::exit(code)
 }
+
+/// Returns the number of instructions executed by the program so far, if the FPVM exposes it.
+///
+/// On `mips64`, the count is derived from Cannon's monotonic clock, which advances with the number
+/// of executed steps.
+#[cfg(target_arch = "mips64")]
+#[inline]
+pub fn cycles() -> Option<u64> {
+    crate::mips64::io::Mips64IO::cycles()
+}
+
+/// Returns the number of instructions executed by the program so far, if the FPVM exposes it.
+///
+/// Only the `mips64` target exposes an instruction count, so this always returns [None].
+#[cfg(not(target_arch = "mips64"))]
+#[inline]
+pub const fn cycles() -> Option<u64> {
+    None
+}
@@ -13,6 +13,8 @@ pub mod errors;
 
 pub mod io;
 
+pub mod exit;
+
 #[cfg(feature = "tracing")]
 pub mod tracing;
 
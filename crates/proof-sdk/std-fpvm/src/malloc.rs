@@ -7,11 +7,43 @@
 /// The global allocator for the program in embedded environments.
 #[cfg(any(target_arch = "mips64", target_arch = "riscv64"))]
 pub mod global_allocator {
+    use core::{
+        alloc::{GlobalAlloc, Layout},
+        sync::atomic::{AtomicBool, Ordering},
+    };
     use linked_list_allocator::LockedHeap;
 
     /// The global allocator for the program in other profiles uses the [SpinLockedAllocator].
     #[global_allocator]
-    static ALLOCATOR: LockedHeap = LockedHeap::empty();
+    static ALLOCATOR: TrackedHeap = TrackedHeap { heap: LockedHeap::empty() };
+
+    /// Set once an allocation fails, so that the panic raised by the allocation error handler can
+    /// be reported as an out-of-memory condition.
+    static EXHAUSTED: AtomicBool = AtomicBool::new(false);
+
+    /// A [LockedHeap] that records allocation failures.
+    struct TrackedHeap {
+        heap: LockedHeap,
+    }
+
+    unsafe impl GlobalAlloc for TrackedHeap {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            let ptr = self.heap.alloc(layout);
+            if ptr.is_null() {
+                EXHAUSTED.store(true, Ordering::Relaxed);
+            }
+            ptr
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            self.heap.dealloc(ptr, layout)
+        }
+    }
+
+    /// Returns `true` if an allocation has failed because the heap is exhausted.
+    pub fn is_exhausted() -> bool {
+        EXHAUSTED.load(Ordering::Relaxed)
+    }
 
     /// Initialize the [SpinLockedAllocator] with the following parameters:
     /// * `heap_start_addr` is the starting address of the heap memory region,
@@ -24,7 +56,23 @@ pub mod global_allocator {
     /// * After aligning the start and end addresses, the size of the heap must be > 0, or the
     ///   function will panic.
     pub unsafe fn init_allocator(heap_start_addr: *mut u8, heap_size: usize) {
-        ALLOCATOR.lock().init(heap_start_addr, heap_size)
+        ALLOCATOR.heap.lock().init(heap_start_addr, heap_size)
+    }
+}
+
+/// Returns `true` if an allocation has failed because the heap of the program is exhausted.
+///
+/// Always returns `false` in non-MIPS and non-RISC-V64 profiles, which use the system allocator.
+// Not `const`, as the atomic load on the FPVM targets is not.
+#[allow(clippy::missing_const_for_fn)]
+pub fn heap_exhausted() -> bool {
+    #[cfg(any(target_arch = "mips64", target_arch = "riscv64"))]
+    {
+        global_allocator::is_exhausted()
+    }
+    #[cfg(not(any(target_arch = "mips64", target_arch = "riscv64")))]
+    {
+        false
     }
 }
 
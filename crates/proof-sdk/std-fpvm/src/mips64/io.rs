@@ -21,6 +21,33 @@ pub(crate) enum SyscallNumber {
     Read = 5000,
     /// Similar behavior as Linux/MIPS with support for unaligned writes.
     Write = 5001,
+    /// Writes the time of the given clock. The monotonic clock advances with the step count.
+    ClockGetTime = 5222,
+}
+
+/// The `CLOCK_MONOTONIC` clock ID.
+const CLOCK_MONOTONIC: usize = 1;
+
+/// The number of steps per second of Cannon's monotonic clock.
+const HZ: u64 = 10_000_000;
+
+impl Mips64IO {
+    /// Returns the number of steps executed by the program, derived from Cannon's monotonic
+    /// clock.
+    pub(crate) fn cycles() -> Option<u64> {
+        let mut timespec = [0u64; 2];
+        unsafe {
+            crate::linux::from_ret(syscall::syscall3(
+                SyscallNumber::ClockGetTime as usize,
+                CLOCK_MONOTONIC,
+                timespec.as_mut_ptr() as usize,
+                0,
+            ))
+            .ok()?;
+        }
+        let [secs, nsecs] = timespec;
+        Some(secs * HZ + nsecs / (1_000_000_000 / HZ))
+    }
 }
 
 impl BasicKernelInterface for Mips64IO {
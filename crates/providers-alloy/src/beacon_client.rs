@@ -3,7 +3,7 @@
 use alloy_eips::eip4844::IndexedBlobHash;
 use alloy_rpc_types_beacon::sidecar::{BeaconBlobBundle, BlobData};
 use async_trait::async_trait;
-use reqwest::Client;
+use reqwest::{Client, StatusCode};
 use std::{
     boxed::Box,
     format,
@@ -80,7 +80,8 @@ pub trait BeaconClient {
 
     /// Fetches blob sidecars that were confirmed in the specified L1 block with the given indexed
     /// hashes. Order of the returned sidecars is guaranteed to be that of the hashes. Blob data is
-    /// not checked for validity.
+    /// not checked for validity. Sidecars that are not available, e.g. because they have been
+    /// pruned, are omitted.
     async fn beacon_blob_side_cars(
         &self,
         slot: u64,
@@ -132,6 +133,11 @@ impl BeaconClient for OnlineBeaconClient {
             .get(format!("{}/{}/{}", self.base, SIDECARS_METHOD_PREFIX, slot))
             .send()
             .await?;
+
+        // The beacon node does not hold the sidecars of the slot, e.g. because they were pruned.
+        if raw_response.status() == StatusCode::NOT_FOUND {
+            return Ok(Vec::new());
+        }
         let raw_response = raw_response.json::<BeaconBlobBundle>().await?;
 
         // Filter the sidecars by the hashes, in-order.
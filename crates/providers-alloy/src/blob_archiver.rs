@@ -0,0 +1,75 @@
+//! Contains the [BlobArchiverClient], a client for the blob archiver API.
+
+use crate::BlobSidecarProvider;
+use alloy_eips::eip4844::IndexedBlobHash;
+use alloy_rpc_types_beacon::sidecar::{BeaconBlobBundle, BlobData};
+use async_trait::async_trait;
+use kona_derive::errors::BlobProviderError;
+use reqwest::{Client, StatusCode};
+use std::{
+    boxed::Box,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+/// The blob sidecars api method prefix.
+const SIDECARS_METHOD_PREFIX: &str = "eth/v1/beacon/blob_sidecars";
+
+/// A [BlobSidecarProvider] for a blob archiver, which retains the blob sidecars pruned by beacon
+/// nodes and serves them over the beacon API's `blob_sidecars` endpoint.
+///
+/// Blob archiver spec: <https://github.com/base-org/blob-archiver>
+#[derive(Debug, Clone)]
+pub struct BlobArchiverClient {
+    /// The base URL of the blob archiver API.
+    pub base: String,
+    /// The inner reqwest client.
+    pub inner: Client,
+}
+
+impl BlobArchiverClient {
+    /// Creates a new [BlobArchiverClient] from the provided base URL.
+    pub fn new_http(mut base: String) -> Self {
+        // If base ends with a slash, remove it
+        if base.ends_with("/") {
+            base.remove(base.len() - 1);
+        }
+        Self { base, inner: Client::new() }
+    }
+}
+
+#[async_trait]
+impl BlobSidecarProvider for BlobArchiverClient {
+    async fn beacon_blob_side_cars(
+        &self,
+        slot: u64,
+        hashes: &[IndexedBlobHash],
+    ) -> Result<Vec<BlobData>, BlobProviderError> {
+        let response = self
+            .inner
+            .get(format!("{}/{}/{}", self.base, SIDECARS_METHOD_PREFIX, slot))
+            .send()
+            .await
+            .map_err(|e| BlobProviderError::Backend(e.to_string()))?;
+
+        // The archiver does not hold the sidecars of the slot.
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(Vec::new());
+        }
+
+        let bundle = response
+            .error_for_status()
+            .map_err(|e| BlobProviderError::Backend(e.to_string()))?
+            .json::<BeaconBlobBundle>()
+            .await
+            .map_err(|e| BlobProviderError::Backend(e.to_string()))?;
+
+        // Filter the sidecars by the hashes, in-order.
+        Ok(hashes
+            .iter()
+            .filter_map(|hash| bundle.data.iter().find(|sidecar| sidecar.index == hash.index))
+            .cloned()
+            .collect())
+    }
+}
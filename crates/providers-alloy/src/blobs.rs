@@ -6,9 +6,97 @@ use alloy_rpc_types_beacon::sidecar::BlobData;
 use async_trait::async_trait;
 use kona_derive::{errors::BlobProviderError, traits::BlobProvider};
 use maili_protocol::BlockInfo;
-use std::{boxed::Box, string::ToString, vec::Vec};
+use std::{
+    boxed::Box,
+    fmt,
+    string::{String, ToString},
+    sync::{Arc, Mutex, PoisonError},
+    time::{Duration, Instant},
+    vec::Vec,
+};
+
+/// The number of consecutive failures after which a sidecar source is considered unhealthy.
+const UNHEALTHY_THRESHOLD: u32 = 3;
+
+/// The duration for which an unhealthy sidecar source is tried after the healthy ones.
+const UNHEALTHY_COOLDOWN: Duration = Duration::from_secs(30);
+
+/// The name of the beacon client in the [SourceHealth] report of the [OnlineBlobProvider].
+const BEACON_SOURCE_NAME: &str = "beacon";
+
+/// The health of a blob sidecar source, as observed by the [OnlineBlobProvider].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SourceHealth {
+    /// The number of requests that returned all of the requested sidecars.
+    pub hits: u64,
+    /// The number of requests that returned only some, or none, of the requested sidecars.
+    pub misses: u64,
+    /// The number of requests that failed, or returned sidecars that do not match the requested
+    /// versioned hashes.
+    pub failures: u64,
+    /// The number of consecutive failed requests.
+    pub consecutive_failures: u32,
+    /// The time until which the source is tried after the healthy ones, if it is unhealthy.
+    pub unhealthy_until: Option<Instant>,
+}
+
+impl SourceHealth {
+    /// Returns `true` if the source is healthy at the given time.
+    pub fn is_healthy(&self, now: Instant) -> bool {
+        self.unhealthy_until.map_or(true, |until| now >= until)
+    }
+
+    /// Records a request that returned all of the requested sidecars.
+    fn record_hit(&mut self) {
+        self.hits += 1;
+        self.consecutive_failures = 0;
+        self.unhealthy_until = None;
+    }
+
+    /// Records a request that did not return all of the requested sidecars. A source lacking the
+    /// sidecars of a slot is not unhealthy.
+    fn record_miss(&mut self) {
+        self.misses += 1;
+        self.consecutive_failures = 0;
+        self.unhealthy_until = None;
+    }
+
+    /// Records a failed request at the given time.
+    fn record_failure(&mut self, now: Instant) {
+        self.failures += 1;
+        self.consecutive_failures += 1;
+        if self.consecutive_failures >= UNHEALTHY_THRESHOLD {
+            self.unhealthy_until = Some(now + UNHEALTHY_COOLDOWN);
+        }
+    }
+}
+
+/// A fallback blob sidecar source of the [OnlineBlobProvider].
+#[derive(Clone)]
+struct SidecarSource {
+    /// The name of the source.
+    name: String,
+    /// The source.
+    provider: Arc<dyn BlobSidecarProvider + Send + Sync>,
+    /// The observed health of the source.
+    health: Arc<Mutex<SourceHealth>>,
+}
+
+impl fmt::Debug for SidecarSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SidecarSource")
+            .field("name", &self.name)
+            .field("health", &self.health)
+            .finish_non_exhaustive()
+    }
+}
 
 /// An online implementation of the [BlobProvider] trait.
+///
+/// Sidecars are fetched from the beacon client, falling back to the [BlobSidecarProvider]s added
+/// with [OnlineBlobProvider::with_fallback], in order, when a source does not hold all of the
+/// sidecars of a slot or fails. Sources that fail repeatedly are tried after the healthy ones for
+/// a cooldown period.
 #[derive(Debug, Clone)]
 pub struct OnlineBlobProvider<B: BeaconClient> {
     /// The Beacon API client.
@@ -17,6 +105,10 @@ pub struct OnlineBlobProvider<B: BeaconClient> {
     pub genesis_time: u64,
     /// Slot interval used for the time to slot conversion.
     pub slot_interval: u64,
+    /// The observed health of the beacon client.
+    beacon_health: Arc<Mutex<SourceHealth>>,
+    /// The fallback sidecar sources, in order.
+    fallbacks: Vec<SidecarSource>,
 }
 
 impl<B: BeaconClient> OnlineBlobProvider<B> {
@@ -41,19 +133,101 @@ impl<B: BeaconClient> OnlineBlobProvider<B> {
             .map(|r| r.data.seconds_per_slot)
             .map_err(|e| BlobProviderError::Backend(e.to_string()))
             .expect("Failed to load slot interval from beacon client");
-        Self { beacon_client, genesis_time, slot_interval }
+        Self {
+            beacon_client,
+            genesis_time,
+            slot_interval,
+            beacon_health: Default::default(),
+            fallbacks: Vec::new(),
+        }
+    }
+
+    /// Adds a fallback [BlobSidecarProvider], such as a [BlobArchiverClient], consulted after the
+    /// beacon client and the previously added fallbacks.
+    ///
+    /// [BlobArchiverClient]: crate::BlobArchiverClient
+    pub fn with_fallback(
+        mut self,
+        name: impl Into<String>,
+        provider: impl BlobSidecarProvider + Send + Sync + 'static,
+    ) -> Self {
+        self.fallbacks.push(SidecarSource {
+            name: name.into(),
+            provider: Arc::new(provider),
+            health: Default::default(),
+        });
+        self
+    }
+
+    /// Returns the observed [SourceHealth] of the beacon client and the fallback sources, by name,
+    /// in order.
+    pub fn source_health(&self) -> Vec<(String, SourceHealth)> {
+        core::iter::once((BEACON_SOURCE_NAME.to_string(), &self.beacon_health))
+            .chain(self.fallbacks.iter().map(|source| (source.name.clone(), &source.health)))
+            .map(|(name, health)| (name, *health.lock().unwrap_or_else(PoisonError::into_inner)))
+            .collect()
     }
 
     /// Fetches blob sidecars for the given slot and blob hashes.
+    ///
+    /// The healthy sources are tried in order, followed by the unhealthy ones, until one of them
+    /// returns all of the sidecars. The sidecars of each source are verified against the requested
+    /// versioned hashes, and a source returning an invalid sidecar is treated as failed. Otherwise,
+    /// the most complete result is returned, or the last error if every source failed.
     pub async fn fetch_sidecars(
         &self,
         slot: u64,
         hashes: &[IndexedBlobHash],
     ) -> Result<Vec<BlobData>, BlobProviderError> {
-        self.beacon_client
-            .beacon_blob_side_cars(slot, hashes)
-            .await
-            .map_err(|e| BlobProviderError::Backend(e.to_string()))
+        // The beacon client is source 0, followed by the fallbacks.
+        let health = |source: usize| match source {
+            0 => &self.beacon_health,
+            i => &self.fallbacks[i - 1].health,
+        };
+        let now = Instant::now();
+        let mut order = (0..=self.fallbacks.len()).collect::<Vec<_>>();
+        order.sort_by_key(|source| {
+            !health(*source).lock().unwrap_or_else(PoisonError::into_inner).is_healthy(now)
+        });
+
+        let mut most_complete: Option<(usize, Vec<BlobData>)> = None;
+        let mut last_error = None;
+        for source in order {
+            let result = match source {
+                0 => BeaconClient::beacon_blob_side_cars(&self.beacon_client, slot, hashes)
+                    .await
+                    .map_err(|e| BlobProviderError::Backend(e.to_string())),
+                i => self.fallbacks[i - 1].provider.beacon_blob_side_cars(slot, hashes).await,
+            };
+            let result = result.and_then(|sidecars| {
+                let verified = verify_sidecars(&sidecars, hashes)?;
+                Ok((sidecars, verified))
+            });
+
+            let mut health = health(source).lock().unwrap_or_else(PoisonError::into_inner);
+            match result {
+                Ok((sidecars, verified)) if verified == hashes.len() => {
+                    health.record_hit();
+                    return Ok(sidecars);
+                }
+                Ok((sidecars, verified)) => {
+                    health.record_miss();
+                    if most_complete.as_ref().map_or(true, |(best, _)| verified > *best) {
+                        most_complete = Some((verified, sidecars));
+                    }
+                }
+                Err(e) => {
+                    health.record_failure(Instant::now());
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        match (most_complete, last_error) {
+            (Some((_, sidecars)), _) => Ok(sidecars),
+            (None, Some(e)) => Err(e),
+            (None, None) => Ok(Vec::new()),
+        }
     }
 
     /// Computes the slot for the given timestamp.
@@ -145,6 +319,30 @@ where
     }
 }
 
+/// Verifies the sidecars returned by a source against the requested versioned hashes, returning
+/// the number of requested sidecars that were returned.
+fn verify_sidecars(
+    sidecars: &[BlobData],
+    hashes: &[IndexedBlobHash],
+) -> Result<usize, BlobProviderError> {
+    let mut verified = 0;
+    for hash in hashes {
+        let Some(sidecar) = sidecars.iter().find(|sidecar| sidecar.index == hash.index) else {
+            continue;
+        };
+        BlobTransactionSidecarItem {
+            index: sidecar.index,
+            blob: sidecar.blob.clone(),
+            kzg_commitment: sidecar.kzg_commitment,
+            kzg_proof: sidecar.kzg_proof,
+        }
+        .verify_blob(hash)
+        .map_err(|e| BlobProviderError::Backend(e.to_string()))?;
+        verified += 1;
+    }
+    Ok(verified)
+}
+
 /// The minimal interface required to fetch sidecars from a remote blob store.
 #[async_trait]
 pub trait BlobSidecarProvider {
@@ -174,3 +372,144 @@ impl<B: BeaconClient + Send + Sync> BlobSidecarProvider for B {
             .map_err(|e| BlobProviderError::Backend(e.to_string()))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{APIConfigResponse, APIGenesisResponse, BlobArchiverClient, OnlineBeaconClient};
+    use alloy_eips::eip4844::{kzg_to_versioned_hash, Bytes48};
+    use alloy_primitives::Bytes;
+    use alloy_rpc_types_beacon::{
+        header::{BeaconBlockHeader, Header},
+        sidecar::BeaconBlobBundle,
+    };
+    use std::collections::HashMap;
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    /// The path of the blob sidecars of the test slot.
+    const SIDECARS_PATH: &str = "/eth/v1/beacon/blob_sidecars/5";
+
+    /// The compressed point at infinity, which is the KZG commitment and proof of the zero blob.
+    const POINT_AT_INFINITY: [u8; 48] = {
+        let mut point = [0u8; 48];
+        point[0] = 0xc0;
+        point
+    };
+
+    /// Serves the given responses, keyed by request path, over HTTP on a local port. Unknown paths
+    /// are answered with a 404. Returns the base URL of the server.
+    async fn serve(routes: HashMap<&'static str, (u16, String)>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut request = vec![0u8; 4096];
+                let read = stream.read(&mut request).await.unwrap();
+                let request = String::from_utf8_lossy(&request[..read]);
+                let path = request.split_whitespace().nth(1).unwrap_or_default();
+                let (status, body) = routes.get(path).cloned().unwrap_or((404, String::new()));
+                let response = format!(
+                    "HTTP/1.1 {status} STATUS\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        format!("http://{address}/")
+    }
+
+    /// Returns a bundle with a zero blob sidecar at index 0, committed to with the given
+    /// commitment.
+    fn sidecar_bundle(kzg_commitment: [u8; 48]) -> String {
+        let sidecar = BlobData {
+            index: 0,
+            blob: Box::default(),
+            kzg_commitment: Bytes48::from(kzg_commitment),
+            kzg_proof: Bytes48::from(POINT_AT_INFINITY),
+            signed_block_header: Header {
+                message: BeaconBlockHeader::default(),
+                signature: Bytes::new(),
+            },
+            kzg_commitment_inclusion_proof: Vec::new(),
+        };
+        serde_json::to_string(&BeaconBlobBundle { data: vec![sidecar] }).unwrap()
+    }
+
+    /// Serves a beacon node that has pruned the sidecars of every slot.
+    async fn pruned_beacon() -> OnlineBeaconClient {
+        let beacon = serve(HashMap::from([
+            (
+                "/eth/v1/beacon/genesis",
+                (200, serde_json::to_string(&APIGenesisResponse::new(0)).unwrap()),
+            ),
+            (
+                "/eth/v1/config/spec",
+                (200, serde_json::to_string(&APIConfigResponse::new(12)).unwrap()),
+            ),
+        ]))
+        .await;
+        OnlineBeaconClient::new_http(beacon)
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_fetch_sidecars_falls_back_to_archiver() {
+        let archiver =
+            serve(HashMap::from([(SIDECARS_PATH, (200, sidecar_bundle(POINT_AT_INFINITY)))])).await;
+        let failing = serve(HashMap::from([(SIDECARS_PATH, (500, String::new()))])).await;
+
+        let provider = OnlineBlobProvider::init(pruned_beacon().await)
+            .await
+            .with_fallback("failing", BlobArchiverClient::new_http(failing))
+            .with_fallback("archiver", BlobArchiverClient::new_http(archiver));
+        let hashes =
+            [IndexedBlobHash { index: 0, hash: kzg_to_versioned_hash(&POINT_AT_INFINITY) }];
+
+        for _ in 0..UNHEALTHY_THRESHOLD {
+            let sidecars = provider.fetch_sidecars(5, &hashes).await.unwrap();
+            assert_eq!(sidecars.len(), 1);
+        }
+        let health = provider.source_health();
+        assert_eq!(health[0].1.misses, UNHEALTHY_THRESHOLD as u64);
+        assert_eq!(health[1].1.failures, UNHEALTHY_THRESHOLD as u64);
+        assert!(!health[1].1.is_healthy(Instant::now()));
+        assert_eq!(health[2].1.hits, UNHEALTHY_THRESHOLD as u64);
+
+        // The unhealthy source is now tried after the archiver, which serves the sidecars.
+        provider.fetch_sidecars(5, &hashes).await.unwrap();
+        let health = provider.source_health();
+        assert_eq!(health[1].1.failures, UNHEALTHY_THRESHOLD as u64);
+        assert_eq!(health[2].1.hits, UNHEALTHY_THRESHOLD as u64 + 1);
+
+        // Without the archiver, the sidecars are missing.
+        let missing = provider.fetch_sidecars(6, &hashes).await.unwrap();
+        assert!(missing.is_empty());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_fetch_sidecars_fails_over_from_invalid_sidecars() {
+        let mut corrupted_commitment = POINT_AT_INFINITY;
+        corrupted_commitment[47] = 1;
+        let corrupted =
+            serve(HashMap::from([(SIDECARS_PATH, (200, sidecar_bundle(corrupted_commitment)))]))
+                .await;
+        let archiver =
+            serve(HashMap::from([(SIDECARS_PATH, (200, sidecar_bundle(POINT_AT_INFINITY)))])).await;
+
+        let provider = OnlineBlobProvider::init(pruned_beacon().await)
+            .await
+            .with_fallback("corrupted", BlobArchiverClient::new_http(corrupted))
+            .with_fallback("archiver", BlobArchiverClient::new_http(archiver));
+        let hashes =
+            [IndexedBlobHash { index: 0, hash: kzg_to_versioned_hash(&POINT_AT_INFINITY) }];
+
+        let sidecars = provider.fetch_sidecars(5, &hashes).await.unwrap();
+        assert_eq!(sidecars[0].kzg_commitment, Bytes48::from(POINT_AT_INFINITY));
+        let health = provider.source_health();
+        assert_eq!(health[1].1.failures, 1);
+        assert_eq!(health[1].1.hits, 0);
+        assert_eq!(health[2].1.hits, 1);
+    }
+}
@@ -0,0 +1,139 @@
+//! Contains the [ProviderCache], a bounded LRU cache of RPC responses that is shared between the
+//! clones of a provider.
+
+use lru::LruCache;
+use std::{
+    hash::Hash,
+    num::NonZeroUsize,
+    ops::Add,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+};
+
+/// The hit and miss counts of a provider's response caches.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// The number of lookups that were served from the cache.
+    pub hits: u64,
+    /// The number of lookups that were not found in the cache.
+    pub misses: u64,
+}
+
+impl CacheStats {
+    /// Returns the fraction of lookups that were served from the cache, or `0.0` if there were
+    /// no lookups.
+    pub fn hit_rate(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            return 0.0;
+        }
+        self.hits as f64 / lookups as f64
+    }
+}
+
+impl Add for CacheStats {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self { hits: self.hits + rhs.hits, misses: self.misses + rhs.misses }
+    }
+}
+
+/// The entries and stats of a [ProviderCache].
+#[derive(Debug)]
+struct CacheInner<K: Hash + Eq, V> {
+    /// The cached entries.
+    entries: LruCache<K, V>,
+    /// The hit and miss counts.
+    stats: CacheStats,
+}
+
+/// A bounded LRU cache of RPC responses.
+///
+/// The cache is shared between clones, so clones of a provider, such as those held by the
+/// stages of a pipeline and by its clones, serve each other's responses.
+#[derive(Debug, Clone)]
+pub(crate) struct ProviderCache<K: Hash + Eq, V> {
+    inner: Arc<Mutex<CacheInner<K, V>>>,
+}
+
+impl<K: Hash + Eq, V: Clone> ProviderCache<K, V> {
+    /// Creates a new [ProviderCache] holding up to `capacity` entries.
+    pub(crate) fn new(capacity: NonZeroUsize) -> Self {
+        let inner = CacheInner { entries: LruCache::new(capacity), stats: CacheStats::default() };
+        Self { inner: Arc::new(Mutex::new(inner)) }
+    }
+
+    /// Locks the cache.
+    fn lock(&self) -> MutexGuard<'_, CacheInner<K, V>> {
+        self.inner.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Returns the cached value for the given key, recording a hit or a miss.
+    pub(crate) fn get(&self, key: &K) -> Option<V> {
+        let mut inner = self.lock();
+        let value = inner.entries.get(key).cloned();
+        if value.is_some() {
+            inner.stats.hits += 1;
+        } else {
+            inner.stats.misses += 1;
+        }
+        value
+    }
+
+    /// Caches the value for the given key.
+    pub(crate) fn put(&self, key: K, value: V) {
+        self.lock().entries.put(key, value);
+    }
+
+    /// Drops all cached values. The stats are kept.
+    pub(crate) fn clear(&self) {
+        self.lock().entries.clear();
+    }
+
+    /// Returns the hit and miss counts of the cache.
+    pub(crate) fn stats(&self) -> CacheStats {
+        self.lock().stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_provider_cache_shared_between_clones() {
+        let cache = ProviderCache::<u64, u64>::new(NonZeroUsize::new(2).unwrap());
+        let clone = cache.clone();
+        clone.put(1, 10);
+
+        assert_eq!(cache.get(&1), Some(10));
+        assert_eq!(cache.get(&2), None);
+        assert_eq!(clone.stats(), CacheStats { hits: 1, misses: 1 });
+    }
+
+    #[test]
+    fn test_provider_cache_bounded() {
+        let cache = ProviderCache::<u64, u64>::new(NonZeroUsize::new(2).unwrap());
+        cache.put(1, 10);
+        cache.put(2, 20);
+        assert_eq!(cache.get(&1), Some(10));
+
+        // The least recently used entry is evicted.
+        cache.put(3, 30);
+        assert_eq!(cache.get(&2), None);
+        assert_eq!(cache.get(&1), Some(10));
+        assert_eq!(cache.get(&3), Some(30));
+
+        cache.clear();
+        assert_eq!(cache.get(&1), None);
+        assert_eq!(cache.stats(), CacheStats { hits: 3, misses: 2 });
+    }
+
+    #[test]
+    fn test_cache_stats_hit_rate() {
+        assert_eq!(CacheStats::default().hit_rate(), 0.0);
+        let stats = CacheStats { hits: 3, misses: 1 } + CacheStats { hits: 0, misses: 4 };
+        assert_eq!(stats, CacheStats { hits: 3, misses: 5 });
+        assert_eq!(stats.hit_rate(), 0.375);
+    }
+}
@@ -1,16 +1,15 @@
 //! Providers that use alloy provider types on the backend.
 
+use crate::{cache::ProviderCache, CacheStats, RpcPool, RpcPoolError};
 use alloy_consensus::{Block, Header, Receipt, ReceiptWithBloom, TxEnvelope, TxType};
 use alloy_primitives::{Bytes, B256, U64};
 use alloy_provider::{Provider, RootProvider};
 use alloy_rlp::{Buf, Decodable};
-use alloy_transport::{RpcError, TransportErrorKind};
 use async_trait::async_trait;
 use kona_derive::{
     errors::{PipelineError, PipelineErrorKind},
     traits::ChainProvider,
 };
-use lru::LruCache;
 use maili_protocol::BlockInfo;
 use std::{boxed::Box, num::NonZeroUsize, vec::Vec};
 
@@ -19,31 +18,46 @@ const CACHE_SIZE: usize = 16;
 /// The [AlloyChainProvider] is a concrete implementation of the [ChainProvider] trait, providing
 /// data over Ethereum JSON-RPC using an alloy provider as the backend.
 ///
+/// Requests are sent through an [RpcPool], which retries failed requests over one or more
+/// endpoints, and can check headers against a quorum of endpoints.
+///
+/// Responses are cached in bounded LRU caches keyed by block hash, which are shared between clones
+/// of the provider. Blocks requested by number are resolved through a cache of number to hash
+/// mappings, which is dropped when the pipeline is reset, through [ChainProvider::reset], as the
+/// mappings may no longer be canonical after an L1 reorg.
+///
 /// **Note**:
 /// This provider fetches data using the `debug_getRawHeader`, `debug_getRawReceipts`, and
 /// `debug_getRawBlock` methods. The RPC must support this namespace.
 #[derive(Debug, Clone)]
 pub struct AlloyChainProvider {
-    /// The inner Ethereum JSON-RPC provider.
-    inner: RootProvider,
+    /// The pool of Ethereum JSON-RPC endpoints.
+    inner: RpcPool,
     /// `header_by_hash` LRU cache.
-    header_by_hash_cache: LruCache<B256, Header>,
+    header_by_hash_cache: ProviderCache<B256, Header>,
+    /// `block_info_by_number` LRU cache of block hashes by number.
+    hash_by_number_cache: ProviderCache<u64, B256>,
     /// `receipts_by_hash_cache` LRU cache.
-    receipts_by_hash_cache: LruCache<B256, Vec<Receipt>>,
+    receipts_by_hash_cache: ProviderCache<B256, Vec<Receipt>>,
     /// `block_info_and_transactions_by_hash` LRU cache.
-    block_info_and_transactions_by_hash_cache: LruCache<B256, (BlockInfo, Vec<TxEnvelope>)>,
+    block_info_and_transactions_by_hash_cache: ProviderCache<B256, (BlockInfo, Vec<TxEnvelope>)>,
 }
 
 impl AlloyChainProvider {
-    /// Creates a new [AlloyChainProvider] with the given alloy provider.
-    pub fn new(inner: RootProvider) -> Self {
+    /// Creates a new [AlloyChainProvider] with the given alloy provider or [RpcPool].
+    pub fn new(inner: impl Into<RpcPool>) -> Self {
+        Self::new_with_cache_size(inner, NonZeroUsize::new(CACHE_SIZE).unwrap())
+    }
+
+    /// Creates a new [AlloyChainProvider] with the given alloy provider or [RpcPool], caching up
+    /// to `cache_size` responses of each kind.
+    pub fn new_with_cache_size(inner: impl Into<RpcPool>, cache_size: NonZeroUsize) -> Self {
         Self {
-            inner,
-            header_by_hash_cache: LruCache::new(NonZeroUsize::new(CACHE_SIZE).unwrap()),
-            receipts_by_hash_cache: LruCache::new(NonZeroUsize::new(CACHE_SIZE).unwrap()),
-            block_info_and_transactions_by_hash_cache: LruCache::new(
-                NonZeroUsize::new(CACHE_SIZE).unwrap(),
-            ),
+            inner: inner.into(),
+            header_by_hash_cache: ProviderCache::new(cache_size),
+            hash_by_number_cache: ProviderCache::new(cache_size),
+            receipts_by_hash_cache: ProviderCache::new(cache_size),
+            block_info_and_transactions_by_hash_cache: ProviderCache::new(cache_size),
         }
     }
 
@@ -54,13 +68,21 @@ impl AlloyChainProvider {
     }
 
     /// Returns the latest L2 block number.
-    pub async fn latest_block_number(&mut self) -> Result<u64, RpcError<TransportErrorKind>> {
-        self.inner.get_block_number().await
+    pub async fn latest_block_number(&mut self) -> Result<u64, RpcPoolError> {
+        self.inner.request(|provider| async move { provider.get_block_number().await }).await
     }
 
     /// Returns the chain ID.
-    pub async fn chain_id(&mut self) -> Result<u64, RpcError<TransportErrorKind>> {
-        self.inner.get_chain_id().await
+    pub async fn chain_id(&mut self) -> Result<u64, RpcPoolError> {
+        self.inner.request(|provider| async move { provider.get_chain_id().await }).await
+    }
+
+    /// Returns the combined hit and miss counts of the provider's caches.
+    pub fn cache_stats(&self) -> CacheStats {
+        self.header_by_hash_cache.stats() +
+            self.hash_by_number_cache.stats() +
+            self.receipts_by_hash_cache.stats() +
+            self.block_info_and_transactions_by_hash_cache.stats()
     }
 }
 
@@ -80,6 +102,19 @@ pub enum AlloyChainProviderError {
     /// Failed to decode the raw receipts.
     #[error("Failed to decode raw receipts for hash {0}")]
     RawReceiptsDecoding(B256),
+    /// The RPC endpoints returned different raw headers.
+    #[error("RPC endpoints disagree on the raw header for hash {0}")]
+    RawHeaderQuorumDisagreement(B256),
+}
+
+impl AlloyChainProviderError {
+    /// Returns the error for a failed raw header request for the given hash.
+    fn raw_header_fetch(e: RpcPoolError, hash: B256) -> Self {
+        match e {
+            RpcPoolError::QuorumDisagreement => Self::RawHeaderQuorumDisagreement(hash),
+            _ => Self::RawHeaderFetch(hash),
+        }
+    }
 }
 
 impl From<AlloyChainProviderError> for PipelineErrorKind {
@@ -97,6 +132,11 @@ impl From<AlloyChainProviderError> for PipelineErrorKind {
             AlloyChainProviderError::RawReceiptsDecoding(_) => PipelineErrorKind::Temporary(
                 PipelineError::Provider("Failed to decode raw receipts".to_string()),
             ),
+            AlloyChainProviderError::RawHeaderQuorumDisagreement(_) => {
+                PipelineErrorKind::Temporary(PipelineError::Provider(
+                    "RPC endpoints disagree on raw header".to_string(),
+                ))
+            }
         }
     }
 }
@@ -107,14 +147,16 @@ impl ChainProvider for AlloyChainProvider {
 
     async fn header_by_hash(&mut self, hash: B256) -> Result<Header, Self::Error> {
         if let Some(header) = self.header_by_hash_cache.get(&hash) {
-            return Ok(header.clone());
+            return Ok(header);
         }
 
         let raw_header: Bytes = self
             .inner
-            .raw_request("debug_getRawHeader".into(), [hash])
+            .request_quorum(|provider| async move {
+                provider.raw_request("debug_getRawHeader".into(), [hash]).await
+            })
             .await
-            .map_err(|_| AlloyChainProviderError::RawHeaderFetch(hash))?;
+            .map_err(|e| AlloyChainProviderError::raw_header_fetch(e, hash))?;
 
         let header = Header::decode(&mut raw_header.as_ref())
             .map_err(|_| AlloyChainProviderError::RawHeaderDecoding(hash))?;
@@ -124,11 +166,24 @@ impl ChainProvider for AlloyChainProvider {
     }
 
     async fn block_info_by_number(&mut self, number: u64) -> Result<BlockInfo, Self::Error> {
+        if let Some(hash) = self.hash_by_number_cache.get(&number) {
+            if let Some(header) = self.header_by_hash_cache.get(&hash) {
+                return Ok(BlockInfo {
+                    hash,
+                    number,
+                    parent_hash: header.parent_hash,
+                    timestamp: header.timestamp,
+                });
+            }
+        }
+
         let raw_header: Bytes = self
             .inner
-            .raw_request("debug_getRawHeader".into(), [U64::from(number)])
+            .request_quorum(|provider| async move {
+                provider.raw_request("debug_getRawHeader".into(), [U64::from(number)]).await
+            })
             .await
-            .map_err(|_| AlloyChainProviderError::RawHeaderFetch(B256::default()))?;
+            .map_err(|e| AlloyChainProviderError::raw_header_fetch(e, B256::default()))?;
         let header = Header::decode(&mut raw_header.as_ref())
             .map_err(|_| AlloyChainProviderError::RawHeaderDecoding(B256::default()))?;
 
@@ -138,17 +193,21 @@ impl ChainProvider for AlloyChainProvider {
             parent_hash: header.parent_hash,
             timestamp: header.timestamp,
         };
+        self.header_by_hash_cache.put(block_info.hash, header);
+        self.hash_by_number_cache.put(number, block_info.hash);
         Ok(block_info)
     }
 
     async fn receipts_by_hash(&mut self, hash: B256) -> Result<Vec<Receipt>, Self::Error> {
         if let Some(receipts) = self.receipts_by_hash_cache.get(&hash) {
-            return Ok(receipts.clone());
+            return Ok(receipts);
         }
 
         let raw_receipts: Vec<Bytes> = self
             .inner
-            .raw_request("debug_getRawReceipts".into(), [hash])
+            .request(|provider| async move {
+                provider.raw_request("debug_getRawReceipts".into(), [hash]).await
+            })
             .await
             .map_err(|_| AlloyChainProviderError::RawReceiptsFetch(hash))?;
 
@@ -177,12 +236,14 @@ impl ChainProvider for AlloyChainProvider {
     ) -> Result<(BlockInfo, Vec<TxEnvelope>), Self::Error> {
         if let Some(block_info_and_txs) = self.block_info_and_transactions_by_hash_cache.get(&hash)
         {
-            return Ok(block_info_and_txs.clone());
+            return Ok(block_info_and_txs);
         }
 
         let raw_block: Bytes = self
             .inner
-            .raw_request("debug_getRawBlock".into(), [hash])
+            .request(|provider| async move {
+                provider.raw_request("debug_getRawBlock".into(), [hash]).await
+            })
             .await
             .map_err(|_| AlloyChainProviderError::RawHeaderFetch(hash))?;
         let block: Block<TxEnvelope> = Block::decode(&mut raw_block.as_ref())
@@ -198,4 +259,75 @@ impl ChainProvider for AlloyChainProvider {
             .put(hash, (block_info, block.body.transactions.clone()));
         Ok((block_info, block.body.transactions))
     }
+
+    fn reset(&mut self) {
+        // Responses cached by hash remain valid, and are kept.
+        self.hash_by_number_cache.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RpcPoolConfig;
+    use kona_derive::{stages::L1Traversal, traits::SignalReceiver, types::ResetSignal};
+
+    /// Returns a provider whose RPC is unreachable, so that only cached responses are served.
+    fn offline_provider() -> AlloyChainProvider {
+        let config = RpcPoolConfig { max_retries: 0, ..Default::default() };
+        let pool =
+            RpcPool::new_http(vec!["http://127.0.0.1:1".parse().unwrap()]).with_config(config);
+        AlloyChainProvider::new(pool)
+    }
+
+    #[tokio::test]
+    async fn test_block_info_by_number_cached() {
+        let mut provider = offline_provider();
+        let header = Header { number: 10, timestamp: 120, ..Default::default() };
+        let hash = header.hash_slow();
+        provider.header_by_hash_cache.put(hash, header.clone());
+        provider.hash_by_number_cache.put(10, hash);
+
+        // Clones share the caches.
+        let mut clone = provider.clone();
+        let block_info = clone.block_info_by_number(10).await.unwrap();
+        assert_eq!(block_info.hash, hash);
+        assert_eq!(block_info.timestamp, 120);
+        assert_eq!(provider.header_by_hash(hash).await.unwrap(), header);
+        assert_eq!(provider.cache_stats(), CacheStats { hits: 3, misses: 0 });
+
+        assert!(provider.block_info_by_number(11).await.is_err());
+        assert_eq!(provider.cache_stats(), CacheStats { hits: 3, misses: 1 });
+    }
+
+    #[tokio::test]
+    async fn test_reset_drops_number_mappings() {
+        let mut provider = offline_provider();
+        let header = Header { number: 10, ..Default::default() };
+        let hash = header.hash_slow();
+        provider.header_by_hash_cache.put(hash, header);
+        provider.hash_by_number_cache.put(10, hash);
+        assert!(provider.block_info_by_number(10).await.is_ok());
+
+        // Resetting a clone, as the traversal stage does, drops the shared mappings.
+        provider.clone().reset();
+        assert!(provider.block_info_by_number(10).await.is_err());
+        assert!(provider.header_by_hash(hash).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_traversal_reset_drops_number_mappings() {
+        let provider = offline_provider();
+        let header = Header { number: 10, ..Default::default() };
+        provider.hash_by_number_cache.put(10, header.hash_slow());
+
+        let mut traversal = L1Traversal::new(provider.clone(), Default::default());
+        let signal = ResetSignal {
+            l2_safe_head: Default::default(),
+            l1_origin: Default::default(),
+            system_config: Some(Default::default()),
+        };
+        traversal.signal(signal.signal()).await.unwrap();
+        assert_eq!(provider.hash_by_number_cache.get(&10), None);
+    }
 }
@@ -0,0 +1,267 @@
+//! Contains the [L1HeadSubscriber], which subscribes to new L1 heads over WebSocket or IPC.
+
+use alloy_provider::{IpcConnect, Provider, ProviderBuilder, RootProvider, WsConnect};
+use alloy_transport::{RpcError, TransportErrorKind};
+use futures::{Stream, StreamExt};
+use kona_derive::traits::ChainProvider;
+use maili_protocol::BlockInfo;
+use std::{path::PathBuf, pin::pin};
+use thiserror::Error;
+use tokio::{sync::mpsc, task::JoinHandle};
+
+/// An error for the [L1HeadSubscriber].
+#[derive(Error, Debug)]
+pub enum L1HeadSubscriberError {
+    /// The connection or the subscription failed.
+    #[error("Subscription error: {0}")]
+    Rpc(#[from] RpcError<TransportErrorKind>),
+    /// A missed L1 head could not be backfilled.
+    #[error("Failed to backfill L1 head {0}: {1}")]
+    Backfill(u64, String),
+    /// The subscription was closed by the node.
+    #[error("The L1 head subscription ended")]
+    SubscriptionEnded,
+}
+
+/// Subscribes to new L1 heads over WebSocket or IPC, and forwards them to a channel, e.g. to wake
+/// a [PipelineStream](kona_derive::pipeline::PipelineStream) as the L1 chain advances.
+///
+/// Heads that are missed by the subscription, e.g. while the connection lags, are backfilled from
+/// the backfill [ChainProvider], so that the heads are forwarded in chain order without gaps.
+/// Heads that do not extend the last head, i.e. reorgs, are forwarded as is.
+#[derive(Debug, Clone)]
+pub struct L1HeadSubscriber<C> {
+    /// The provider that new L1 heads are subscribed to, over WebSocket or IPC.
+    pub subscriber: RootProvider,
+    /// The provider that missed L1 heads are backfilled from.
+    pub backfill: C,
+}
+
+impl<C: ChainProvider + Send> L1HeadSubscriber<C> {
+    /// Creates a new [L1HeadSubscriber] from a pubsub provider and a backfill provider.
+    pub const fn new(subscriber: RootProvider, backfill: C) -> Self {
+        Self { subscriber, backfill }
+    }
+
+    /// Connects a new [L1HeadSubscriber] to the WebSocket endpoint at the given URL.
+    pub async fn connect_ws(
+        url: impl Into<String>,
+        backfill: C,
+    ) -> Result<Self, L1HeadSubscriberError> {
+        let subscriber = ProviderBuilder::default().on_ws(WsConnect::new(url)).await?;
+        Ok(Self::new(subscriber, backfill))
+    }
+
+    /// Connects a new [L1HeadSubscriber] to the IPC endpoint at the given path.
+    pub async fn connect_ipc(
+        path: impl Into<PathBuf>,
+        backfill: C,
+    ) -> Result<Self, L1HeadSubscriberError> {
+        let subscriber = ProviderBuilder::default().on_ipc(IpcConnect::new(path.into())).await?;
+        Ok(Self::new(subscriber, backfill))
+    }
+
+    /// Subscribes to new L1 heads and forwards them to the given sender.
+    ///
+    /// Returns `Ok(())` once the receiver is dropped, or an error if the subscription fails or
+    /// ends.
+    pub async fn run(
+        mut self,
+        sender: mpsc::Sender<BlockInfo>,
+    ) -> Result<(), L1HeadSubscriberError> {
+        let subscription = self.subscriber.subscribe_blocks().await?;
+        let heads = pin!(subscription.into_stream().map(|header| BlockInfo {
+            hash: header.hash,
+            number: header.inner.number,
+            parent_hash: header.inner.parent_hash,
+            timestamp: header.inner.timestamp,
+        }));
+        forward_heads(heads, &mut self.backfill, &sender).await
+    }
+
+    /// Spawns the subscriber onto the tokio runtime, returning the receiver of the new L1 heads,
+    /// buffering up to `capacity` heads, and the handle of the spawned task.
+    pub fn spawn(
+        self,
+        capacity: usize,
+    ) -> (mpsc::Receiver<BlockInfo>, JoinHandle<Result<(), L1HeadSubscriberError>>)
+    where
+        C: 'static,
+    {
+        let (sender, receiver) = mpsc::channel(capacity);
+        (receiver, tokio::spawn(self.run(sender)))
+    }
+}
+
+/// Forwards the L1 heads from the stream to the sender, backfilling the heads that were skipped
+/// since the last head.
+async fn forward_heads<C: ChainProvider>(
+    mut heads: impl Stream<Item = BlockInfo> + Unpin,
+    backfill: &mut C,
+    sender: &mpsc::Sender<BlockInfo>,
+) -> Result<(), L1HeadSubscriberError> {
+    let mut last = None;
+    while let Some(head) = heads.next().await {
+        if let Some(last) = last {
+            for number in last + 1..head.number {
+                let missed = backfill
+                    .block_info_by_number(number)
+                    .await
+                    .map_err(|e| L1HeadSubscriberError::Backfill(number, e.to_string()))?;
+                if sender.send(missed).await.is_err() {
+                    return Ok(());
+                }
+            }
+        }
+        last = Some(head.number);
+        if sender.send(head).await.is_err() {
+            return Ok(());
+        }
+    }
+    Err(L1HeadSubscriberError::SubscriptionEnded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::B256;
+    use futures::stream;
+    use kona_derive::test_utils::TestChainProvider;
+
+    fn head(number: u64) -> BlockInfo {
+        BlockInfo { hash: B256::with_last_byte(number as u8), number, ..Default::default() }
+    }
+
+    async fn forward(heads: Vec<BlockInfo>, backfill: &mut TestChainProvider) -> Vec<BlockInfo> {
+        let (sender, mut receiver) = mpsc::channel(16);
+        let result = forward_heads(stream::iter(heads), backfill, &sender).await;
+        assert!(matches!(result, Err(L1HeadSubscriberError::SubscriptionEnded)));
+        drop(sender);
+
+        let mut forwarded = Vec::new();
+        while let Some(head) = receiver.recv().await {
+            forwarded.push(head);
+        }
+        forwarded
+    }
+
+    #[tokio::test]
+    async fn test_forward_heads_backfills_missed_heads() {
+        let mut backfill = TestChainProvider::default();
+        backfill.insert_block(2, head(2));
+        backfill.insert_block(3, head(3));
+
+        let forwarded = forward(vec![head(1), head(4), head(5)], &mut backfill).await;
+        assert_eq!(forwarded, (1..=5).map(head).collect::<Vec<_>>());
+    }
+
+    #[tokio::test]
+    async fn test_forward_heads_reorg() {
+        let mut backfill = TestChainProvider::default();
+        let reorged = BlockInfo { parent_hash: B256::with_last_byte(0xFF), ..head(2) };
+
+        let forwarded = forward(vec![head(1), head(2), reorged, head(3)], &mut backfill).await;
+        assert_eq!(forwarded, vec![head(1), head(2), reorged, head(3)]);
+    }
+
+    #[tokio::test]
+    async fn test_forward_heads_backfill_error() {
+        let (sender, _receiver) = mpsc::channel(16);
+        let heads = stream::iter(vec![head(1), head(3)]);
+        let result = forward_heads(heads, &mut TestChainProvider::default(), &sender).await;
+        assert!(matches!(result, Err(L1HeadSubscriberError::Backfill(2, _))));
+    }
+
+    #[tokio::test]
+    async fn test_connect_ws_unreachable() {
+        let result =
+            L1HeadSubscriber::connect_ws("ws://127.0.0.1:1", TestChainProvider::default()).await;
+        assert!(matches!(result, Err(L1HeadSubscriberError::Rpc(_))));
+    }
+
+    /// Writes a JSON-RPC message to the IPC stream.
+    #[cfg(unix)]
+    async fn write_message(stream: &mut tokio::net::UnixStream, message: serde_json::Value) {
+        use tokio::io::AsyncWriteExt;
+        stream.write_all(message.to_string().as_bytes()).await.unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_connect_ipc_forwards_heads() {
+        use alloy_consensus::Header;
+        use serde_json::{json, Value};
+        use tokio::io::AsyncReadExt;
+
+        let path = std::env::temp_dir().join(format!("kona-l1-heads-{}.ipc", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = tokio::net::UnixListener::bind(&path).unwrap();
+
+        let first = Header { number: 1, timestamp: 12, ..Default::default() };
+        let second = Header { number: 2, parent_hash: first.hash_slow(), ..Default::default() };
+        let expected = [&first, &second].map(|header| BlockInfo {
+            hash: header.hash_slow(),
+            number: header.number,
+            parent_hash: header.parent_hash,
+            timestamp: header.timestamp,
+        });
+
+        // Serves the `eth_subscribe` request, then notifies the subscription of both headers.
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buf = Vec::new();
+            let request = loop {
+                let mut chunk = [0u8; 1024];
+                let read = stream.read(&mut chunk).await.unwrap();
+                if read == 0 {
+                    return;
+                }
+                buf.extend_from_slice(&chunk[..read]);
+                if let Ok(request) = serde_json::from_slice::<Value>(&buf) {
+                    break request;
+                }
+            };
+            assert_eq!(request["method"], "eth_subscribe");
+            let id = B256::with_last_byte(1);
+            write_message(
+                &mut stream,
+                json!({ "jsonrpc": "2.0", "id": request["id"], "result": id }),
+            )
+            .await;
+
+            // The client registers the subscription after handling the response, so notifications
+            // sent along with it would be dropped.
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+            for header in [first, second] {
+                let mut result = serde_json::to_value(&header).unwrap();
+                result["hash"] = json!(header.hash_slow());
+                let params = json!({ "subscription": id, "result": result });
+                write_message(
+                    &mut stream,
+                    json!({ "jsonrpc": "2.0", "method": "eth_subscription", "params": params }),
+                )
+                .await;
+            }
+            std::future::pending::<()>().await;
+        });
+
+        let subscriber = L1HeadSubscriber::connect_ipc(path.clone(), TestChainProvider::default())
+            .await
+            .unwrap();
+        let (mut heads, handle) = subscriber.spawn(8);
+        assert_eq!(heads.recv().await, Some(expected[0]));
+        assert_eq!(heads.recv().await, Some(expected[1]));
+
+        handle.abort();
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn test_forward_heads_receiver_dropped() {
+        let (sender, receiver) = mpsc::channel(16);
+        drop(receiver);
+        let heads = stream::iter(vec![head(1)]);
+        let result = forward_heads(heads, &mut TestChainProvider::default(), &sender).await;
+        assert!(result.is_ok());
+    }
+}
@@ -1,9 +1,9 @@
 //! Providers that use alloy provider types on the backend.
 
-use alloy_primitives::{Bytes, U64};
+use crate::{cache::ProviderCache, CacheStats, RpcPool, RpcPoolError};
+use alloy_primitives::{keccak256, Bytes, B256, U64};
 use alloy_provider::{Provider, RootProvider};
 use alloy_rlp::Decodable;
-use alloy_transport::{RpcError, TransportErrorKind};
 use async_trait::async_trait;
 use kona_derive::{
     errors::{PipelineError, PipelineErrorKind},
@@ -12,36 +12,63 @@ use kona_derive::{
 use maili_genesis::{RollupConfig, SystemConfig};
 use maili_protocol::{to_system_config, BatchValidationProvider, L2BlockInfo};
 use op_alloy_consensus::OpBlock;
-use std::sync::Arc;
+use std::{num::NonZeroUsize, sync::Arc};
+
+const CACHE_SIZE: usize = 16;
 
 /// The [AlloyL2ChainProvider] is a concrete implementation of the [L2ChainProvider] trait,
 /// providing data over Ethereum JSON-RPC using an alloy provider as the backend.
 ///
+/// Requests are sent through an [RpcPool], which retries failed requests over one or more
+/// endpoints, and can check blocks against a quorum of endpoints.
+///
+/// Blocks are cached in a bounded LRU cache keyed by block hash, which is shared between clones of
+/// the provider. As L2 reorgs are not reported to the provider, blocks requested by number are
+/// always resolved to their hash through the RPC, by fetching the header, and only the block itself
+/// is served from the cache.
+///
 /// **Note**:
 /// This provider fetches data using the `debug_getRawBlock` method. The RPC must support this
 /// namespace.
 #[derive(Debug, Clone)]
 pub struct AlloyL2ChainProvider {
-    /// The inner Ethereum JSON-RPC provider.
-    inner: RootProvider,
+    /// The pool of Ethereum JSON-RPC endpoints.
+    inner: RpcPool,
     /// The rollup configuration.
     rollup_config: Arc<RollupConfig>,
+    /// `block_by_number` LRU cache of blocks by hash.
+    block_by_hash_cache: ProviderCache<B256, OpBlock>,
 }
 
 impl AlloyL2ChainProvider {
-    /// Creates a new [AlloyL2ChainProvider] with the given alloy provider and [RollupConfig].
-    pub fn new(inner: RootProvider, rollup_config: Arc<RollupConfig>) -> Self {
-        Self { inner, rollup_config }
+    /// Creates a new [AlloyL2ChainProvider] with the given alloy provider or [RpcPool], and
+    /// [RollupConfig].
+    pub fn new(inner: impl Into<RpcPool>, rollup_config: Arc<RollupConfig>) -> Self {
+        Self::new_with_cache_size(inner, rollup_config, NonZeroUsize::new(CACHE_SIZE).unwrap())
+    }
+
+    /// Creates a new [AlloyL2ChainProvider] with the given alloy provider or [RpcPool], and
+    /// [RollupConfig], caching up to `cache_size` blocks.
+    pub fn new_with_cache_size(
+        inner: impl Into<RpcPool>,
+        rollup_config: Arc<RollupConfig>,
+        cache_size: NonZeroUsize,
+    ) -> Self {
+        Self {
+            inner: inner.into(),
+            rollup_config,
+            block_by_hash_cache: ProviderCache::new(cache_size),
+        }
     }
 
     /// Returns the chain ID.
-    pub async fn chain_id(&mut self) -> Result<u64, RpcError<TransportErrorKind>> {
-        self.inner.get_chain_id().await
+    pub async fn chain_id(&mut self) -> Result<u64, RpcPoolError> {
+        self.inner.request(|provider| async move { provider.get_chain_id().await }).await
     }
 
     /// Returns the latest L2 block number.
-    pub async fn latest_block_number(&mut self) -> Result<u64, RpcError<TransportErrorKind>> {
-        self.inner.get_block_number().await
+    pub async fn latest_block_number(&mut self) -> Result<u64, RpcPoolError> {
+        self.inner.request(|provider| async move { provider.get_block_number().await }).await
     }
 
     /// Creates a new [AlloyL2ChainProvider] from the provided [reqwest::Url].
@@ -49,6 +76,11 @@ impl AlloyL2ChainProvider {
         let inner = RootProvider::new_http(url);
         Self::new(inner, rollup_config)
     }
+
+    /// Returns the hit and miss counts of the provider's block cache.
+    pub fn cache_stats(&self) -> CacheStats {
+        self.block_by_hash_cache.stats()
+    }
 }
 
 /// An error for the [AlloyL2ChainProvider].
@@ -66,6 +98,9 @@ pub enum AlloyL2ChainProviderError {
     /// Failed to convert the block into a [SystemConfig].
     #[error("Failed to convert block {0} into SystemConfig")]
     SystemConfigConversion(u64),
+    /// The RPC endpoints returned different blocks.
+    #[error("RPC endpoints disagree on block {0}")]
+    BlockQuorumDisagreement(u64),
 }
 
 impl From<AlloyL2ChainProviderError> for PipelineErrorKind {
@@ -83,6 +118,9 @@ impl From<AlloyL2ChainProviderError> for PipelineErrorKind {
             AlloyL2ChainProviderError::SystemConfigConversion(_) => PipelineErrorKind::Temporary(
                 PipelineError::Provider("system config conversion failed".to_string()),
             ),
+            AlloyL2ChainProviderError::BlockQuorumDisagreement(_) => PipelineErrorKind::Temporary(
+                PipelineError::Provider("rpc endpoints disagree on block".to_string()),
+            ),
         }
     }
 }
@@ -92,22 +130,44 @@ impl BatchValidationProvider for AlloyL2ChainProvider {
     type Error = AlloyL2ChainProviderError;
 
     async fn l2_block_info_by_number(&mut self, number: u64) -> Result<L2BlockInfo, Self::Error> {
-        let block = self
-            .block_by_number(number)
-            .await
-            .map_err(|_| AlloyL2ChainProviderError::BlockNotFound(number))?;
+        let block = self.block_by_number(number).await?;
         L2BlockInfo::from_block_and_genesis(&block, &self.rollup_config.genesis)
             .map_err(|_| AlloyL2ChainProviderError::L2BlockInfoConstruction(number))
     }
 
     async fn block_by_number(&mut self, number: u64) -> Result<OpBlock, Self::Error> {
+        let quorum_error = |e| match e {
+            RpcPoolError::QuorumDisagreement => {
+                AlloyL2ChainProviderError::BlockQuorumDisagreement(number)
+            }
+            _ => AlloyL2ChainProviderError::BlockNotFound(number),
+        };
+
+        // The block hash is the hash of the RLP encoded header.
+        let raw_header: Bytes = self
+            .inner
+            .request_quorum(|provider| async move {
+                provider.raw_request("debug_getRawHeader".into(), [U64::from(number)]).await
+            })
+            .await
+            .map_err(quorum_error)?;
+        let hash = keccak256(&raw_header);
+        if let Some(block) = self.block_by_hash_cache.get(&hash) {
+            return Ok(block);
+        }
+
         let raw_block: Bytes = self
             .inner
-            .raw_request("debug_getRawBlock".into(), [U64::from(number)])
+            .request_quorum(|provider| async move {
+                provider.raw_request("debug_getRawBlock".into(), [hash]).await
+            })
             .await
-            .map_err(|_| AlloyL2ChainProviderError::BlockNotFound(number))?;
-        OpBlock::decode(&mut raw_block.as_ref())
-            .map_err(|_| AlloyL2ChainProviderError::OpBlockDecode(number))
+            .map_err(quorum_error)?;
+        let block = OpBlock::decode(&mut raw_block.as_ref())
+            .map_err(|_| AlloyL2ChainProviderError::OpBlockDecode(number))?;
+
+        self.block_by_hash_cache.put(hash, block.clone());
+        Ok(block)
     }
 }
 
@@ -120,11 +180,57 @@ impl L2ChainProvider for AlloyL2ChainProvider {
         number: u64,
         rollup_config: Arc<RollupConfig>,
     ) -> Result<SystemConfig, <Self as BatchValidationProvider>::Error> {
-        let block = self
-            .block_by_number(number)
-            .await
-            .map_err(|_| AlloyL2ChainProviderError::BlockNotFound(number))?;
+        let block = self.block_by_number(number).await?;
         to_system_config(&block, &rollup_config)
             .map_err(|_| AlloyL2ChainProviderError::SystemConfigConversion(number))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{pool::tests::serve, RpcPoolConfig};
+    use alloy_consensus::Header;
+    use alloy_rlp::Encodable;
+    use serde_json::json;
+
+    /// Returns the RLP encoding of the given value.
+    fn rlp(value: &impl Encodable) -> Bytes {
+        let mut buf = Vec::new();
+        value.encode(&mut buf);
+        buf.into()
+    }
+
+    #[tokio::test]
+    async fn test_block_by_number_cached_by_hash() {
+        let block = OpBlock::default();
+        let reorged = OpBlock {
+            header: Header { extra_data: Bytes::from_static(b"reorg"), ..Default::default() },
+            ..Default::default()
+        };
+        let (header, raw_block) = (rlp(&block.header), rlp(&block));
+        let (reorged_header, raw_reorged) = (rlp(&reorged.header), rlp(&reorged));
+
+        // The canonical block 0 is replaced after the third request.
+        let (url, count) = serve(move |n, request| match request["method"].as_str()? {
+            "debug_getRawHeader" if n < 3 => Some(json!(header)),
+            "debug_getRawHeader" => Some(json!(reorged_header)),
+            "debug_getRawBlock" if n < 3 => Some(json!(raw_block)),
+            _ => Some(json!(raw_reorged)),
+        })
+        .await;
+        let pool = RpcPool::new_http(vec![url])
+            .with_config(RpcPoolConfig { max_retries: 0, ..Default::default() });
+        let mut provider = AlloyL2ChainProvider::new(pool, Arc::new(RollupConfig::default()));
+
+        // Clones share the cache, so only the header is fetched again.
+        assert_eq!(provider.block_by_number(0).await.unwrap(), block);
+        assert_eq!(provider.clone().block_by_number(0).await.unwrap(), block);
+        assert_eq!(count.load(std::sync::atomic::Ordering::SeqCst), 3);
+        assert_eq!(provider.cache_stats(), CacheStats { hits: 1, misses: 1 });
+
+        // After an L2 reorg, the new block is served rather than the cached one.
+        assert_eq!(provider.block_by_number(0).await.unwrap(), reorged);
+        assert_eq!(provider.cache_stats(), CacheStats { hits: 1, misses: 2 });
+    }
+}
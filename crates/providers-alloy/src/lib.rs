@@ -10,10 +10,22 @@ pub use beacon_client::{
 };
 
 mod blobs;
-pub use blobs::{BlobSidecarProvider, OnlineBlobProvider};
+pub use blobs::{BlobSidecarProvider, OnlineBlobProvider, SourceHealth};
+
+mod blob_archiver;
+pub use blob_archiver::BlobArchiverClient;
+
+mod pool;
+pub use pool::{RpcPool, RpcPoolConfig, RpcPoolError};
+
+mod cache;
+pub use cache::CacheStats;
 
 mod chain_provider;
 pub use chain_provider::AlloyChainProvider;
 
 mod l2_chain_provider;
 pub use l2_chain_provider::AlloyL2ChainProvider;
+
+mod heads;
+pub use heads::{L1HeadSubscriber, L1HeadSubscriberError};
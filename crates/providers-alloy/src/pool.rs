@@ -0,0 +1,427 @@
+//! Contains the [RpcPool], which spreads JSON-RPC requests over several endpoints with retries,
+//! rate limiting, and an optional quorum over responses.
+
+use alloy_provider::RootProvider;
+use alloy_transport::{RpcError, TransportErrorKind, TransportResult};
+use futures::future::join_all;
+use std::{
+    future::Future,
+    num::{NonZeroU32, NonZeroUsize},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex, PoisonError,
+    },
+    time::{Duration, Instant},
+    vec::Vec,
+};
+
+/// The configuration of an [RpcPool].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RpcPoolConfig {
+    /// The number of times a request that failed on a connection error, or with an HTTP 429 or 5xx
+    /// status, is retried. Other failures, including RPC error responses, are not retried.
+    pub max_retries: u32,
+    /// The backoff before the first retry. The backoff doubles with every retry.
+    pub initial_backoff: Duration,
+    /// The maximum backoff between retries.
+    pub max_backoff: Duration,
+    /// The maximum number of requests per second sent to each endpoint, if limited.
+    pub requests_per_second: Option<NonZeroU32>,
+    /// The number of endpoints that must return the same response to L1 and L2 header requests
+    /// and L2 block requests, if the pool is in quorum mode. In quorum mode, these requests are
+    /// sent to every endpoint, and fail if any two responses differ. Other requests, such as for
+    /// L1 blocks and receipts, are sent to a single endpoint.
+    pub quorum: Option<NonZeroUsize>,
+}
+
+impl Default for RpcPoolConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(2),
+            requests_per_second: None,
+            quorum: None,
+        }
+    }
+}
+
+impl RpcPoolConfig {
+    /// Returns the backoff before the given retry, starting from zero.
+    fn backoff(&self, retry: u32) -> Duration {
+        self.initial_backoff.saturating_mul(1 << retry.min(16)).min(self.max_backoff)
+    }
+}
+
+/// An error for the [RpcPool].
+#[derive(Debug, thiserror::Error)]
+pub enum RpcPoolError {
+    /// The pool has no endpoints.
+    #[error("The RPC pool has no endpoints")]
+    NoEndpoints,
+    /// The request failed.
+    #[error(transparent)]
+    Rpc(#[from] RpcError<TransportErrorKind>),
+    /// Endpoints returned different responses to a quorum request.
+    #[error("RPC endpoints returned different responses")]
+    QuorumDisagreement,
+    /// Too few endpoints responded to a quorum request.
+    #[error("RPC quorum not reached: {0} of {1} required responses")]
+    QuorumNotReached(usize, usize),
+}
+
+/// An endpoint of an [RpcPool].
+#[derive(Debug)]
+struct Endpoint {
+    /// The JSON-RPC provider of the endpoint.
+    provider: RootProvider,
+    /// The earliest time at which the next request may be sent to the endpoint.
+    next_slot: Mutex<Instant>,
+}
+
+/// A pool of JSON-RPC endpoints.
+///
+/// Requests are spread over the endpoints round-robin. A request that fails at the transport level
+/// is retried on the next endpoint, after an exponential backoff. Requests to each endpoint can be
+/// rate limited, and header and block requests can be checked against a quorum of endpoints, see
+/// [RpcPoolConfig].
+///
+/// The pool is shared between clones, so the rate limits hold across the clones of a provider.
+#[derive(Debug, Clone)]
+pub struct RpcPool {
+    /// The endpoints.
+    endpoints: Arc<Vec<Endpoint>>,
+    /// The index of the endpoint to send the next request to.
+    cursor: Arc<AtomicUsize>,
+    /// The pool configuration.
+    config: RpcPoolConfig,
+}
+
+impl RpcPool {
+    /// Creates a new [RpcPool] over the given providers, with the default [RpcPoolConfig].
+    pub fn new(providers: Vec<RootProvider>) -> Self {
+        let now = Instant::now();
+        let endpoints = providers
+            .into_iter()
+            .map(|provider| Endpoint { provider, next_slot: Mutex::new(now) })
+            .collect();
+        Self {
+            endpoints: Arc::new(endpoints),
+            cursor: Default::default(),
+            config: RpcPoolConfig::default(),
+        }
+    }
+
+    /// Creates a new [RpcPool] over HTTP providers for the given [reqwest::Url]s.
+    pub fn new_http(urls: Vec<reqwest::Url>) -> Self {
+        Self::new(urls.into_iter().map(RootProvider::new_http).collect())
+    }
+
+    /// Sets the [RpcPoolConfig].
+    pub const fn with_config(mut self, config: RpcPoolConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Returns the [RpcPoolConfig].
+    pub const fn config(&self) -> &RpcPoolConfig {
+        &self.config
+    }
+
+    /// Returns the number of endpoints in the pool.
+    pub fn len(&self) -> usize {
+        self.endpoints.len()
+    }
+
+    /// Returns `true` if the pool has no endpoints.
+    pub fn is_empty(&self) -> bool {
+        self.endpoints.is_empty()
+    }
+
+    /// Sends a request, built by `f` for a provider, to the next endpoint. Connection errors and
+    /// HTTP 429 or 5xx statuses are retried on the following endpoints.
+    pub async fn request<R, F, Fut>(&self, f: F) -> Result<R, RpcPoolError>
+    where
+        F: Fn(RootProvider) -> Fut,
+        Fut: Future<Output = TransportResult<R>>,
+    {
+        if self.endpoints.is_empty() {
+            return Err(RpcPoolError::NoEndpoints);
+        }
+        let start = self.cursor.fetch_add(1, Ordering::Relaxed);
+        Ok(self.send(&f, |retry| start.wrapping_add(retry as usize)).await?)
+    }
+
+    /// Sends a header or block request, built by `f` for a provider, honoring the quorum mode.
+    ///
+    /// In quorum mode, the request is sent to every endpoint, and the response is returned if at
+    /// least the quorum of endpoints returned it and no endpoint returned a different response.
+    /// Otherwise, this is the same as [RpcPool::request].
+    pub async fn request_quorum<R, F, Fut>(&self, f: F) -> Result<R, RpcPoolError>
+    where
+        R: PartialEq,
+        F: Fn(RootProvider) -> Fut,
+        Fut: Future<Output = TransportResult<R>>,
+    {
+        let Some(quorum) = self.config.quorum else {
+            return self.request(f).await;
+        };
+
+        let responses =
+            join_all((0..self.endpoints.len()).map(|index| self.send(&f, move |_| index))).await;
+        let mut agreed: Option<R> = None;
+        let mut count = 0;
+        for response in responses.into_iter().flatten() {
+            if agreed.as_ref().is_some_and(|agreed| *agreed != response) {
+                return Err(RpcPoolError::QuorumDisagreement);
+            }
+            agreed.get_or_insert(response);
+            count += 1;
+        }
+
+        match agreed {
+            Some(response) if count >= quorum.get() => Ok(response),
+            _ => Err(RpcPoolError::QuorumNotReached(count, quorum.get())),
+        }
+    }
+
+    /// Sends a request, built by `f` for a provider, to the endpoint picked for each attempt,
+    /// retrying connection errors and HTTP 429 or 5xx statuses with backoff.
+    async fn send<R, F, Fut>(&self, f: &F, pick: impl Fn(u32) -> usize) -> TransportResult<R>
+    where
+        F: Fn(RootProvider) -> Fut,
+        Fut: Future<Output = TransportResult<R>>,
+    {
+        let mut retry = 0;
+        loop {
+            let endpoint = &self.endpoints[pick(retry) % self.endpoints.len()];
+            self.throttle(endpoint).await;
+            match f(endpoint.provider.clone()).await {
+                Err(RpcError::Transport(ref e))
+                    if is_retryable(e) && retry < self.config.max_retries =>
+                {
+                    tokio::time::sleep(self.config.backoff(retry)).await;
+                    retry += 1;
+                }
+                response => return response,
+            }
+        }
+    }
+
+    /// Waits until the next request may be sent to the given endpoint, and reserves the slot.
+    async fn throttle(&self, endpoint: &Endpoint) {
+        let Some(rps) = self.config.requests_per_second else {
+            return;
+        };
+        let wait = {
+            let mut next_slot = endpoint.next_slot.lock().unwrap_or_else(PoisonError::into_inner);
+            let now = Instant::now();
+            let slot = (*next_slot).max(now);
+            *next_slot = slot + Duration::from_secs(1) / rps.get();
+            slot - now
+        };
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+/// Returns `true` if a request that failed with the given transport error is retried: on a
+/// connection error, or an HTTP 429 or 5xx status.
+fn is_retryable(e: &TransportErrorKind) -> bool {
+    match e {
+        TransportErrorKind::BackendGone => true,
+        TransportErrorKind::HttpError(e) => e.status == 429 || (500..600).contains(&e.status),
+        TransportErrorKind::Custom(e) => {
+            e.downcast_ref::<reqwest::Error>().is_some_and(|e| e.is_connect() || e.is_timeout())
+        }
+        _ => false,
+    }
+}
+
+impl From<RootProvider> for RpcPool {
+    fn from(provider: RootProvider) -> Self {
+        Self::new(vec![provider])
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use alloy_primitives::{Bytes, B256};
+    use alloy_provider::Provider;
+    use alloy_transport::HttpError;
+    use serde_json::{json, Value};
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    /// Serves JSON-RPC over HTTP on a local port. The handler is called with the request count and
+    /// the request, and returns the result, or `None` to respond with an HTTP 503. Returns the URL
+    /// of the server and the request count.
+    pub(crate) async fn serve(
+        handler: impl Fn(usize, &Value) -> Option<Value> + Send + Sync + 'static,
+    ) -> (reqwest::Url, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let count = Arc::new(AtomicUsize::new(0));
+        let requests = count.clone();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut buf = Vec::new();
+                let mut chunk = [0u8; 4096];
+                let request = loop {
+                    let read = stream.read(&mut chunk).await.unwrap_or_default();
+                    if read == 0 {
+                        break None;
+                    }
+                    buf.extend_from_slice(&chunk[..read]);
+                    let Some(end) = buf.windows(4).position(|w| w == b"\r\n\r\n") else {
+                        continue;
+                    };
+                    let head = String::from_utf8_lossy(&buf[..end]).to_lowercase();
+                    let length = head
+                        .lines()
+                        .find_map(|l| l.strip_prefix("content-length:"))
+                        .and_then(|l| l.trim().parse::<usize>().ok())
+                        .unwrap_or_default();
+                    if buf.len() >= end + 4 + length {
+                        break serde_json::from_slice::<Value>(&buf[end + 4..end + 4 + length]).ok();
+                    }
+                };
+                let Some(request) = request else { continue };
+
+                let n = requests.fetch_add(1, Ordering::SeqCst);
+                let (status, body) = handler(n, &request).map_or_else(
+                    || ("503 Service Unavailable", String::new()),
+                    |result| {
+                        let body =
+                            json!({ "jsonrpc": "2.0", "id": request["id"], "result": result });
+                        ("200 OK", body.to_string())
+                    },
+                );
+                let response = format!(
+                    "HTTP/1.1 {status}\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+        (format!("http://{address}").parse().unwrap(), count)
+    }
+
+    fn test_config() -> RpcPoolConfig {
+        RpcPoolConfig {
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(1),
+            ..Default::default()
+        }
+    }
+
+    async fn chain_id(pool: &RpcPool) -> Result<u64, RpcPoolError> {
+        pool.request(|provider| async move { provider.get_chain_id().await }).await
+    }
+
+    async fn raw_header(pool: &RpcPool) -> Result<Bytes, RpcPoolError> {
+        pool.request_quorum(|provider| async move {
+            provider.raw_request("debug_getRawHeader".into(), [B256::ZERO]).await
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_pool_retries_transport_errors() {
+        let (url, count) = serve(|n, _| (n >= 2).then_some(json!("0xa"))).await;
+        let pool = RpcPool::new_http(vec![url]).with_config(test_config());
+
+        assert_eq!(chain_id(&pool).await.unwrap(), 10);
+        assert_eq!(count.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_pool_gives_up_after_max_retries() {
+        let (url, count) = serve(|_, _| None).await;
+        let config = RpcPoolConfig { max_retries: 1, ..test_config() };
+        let pool = RpcPool::new_http(vec![url]).with_config(config);
+
+        assert!(matches!(chain_id(&pool).await, Err(RpcPoolError::Rpc(RpcError::Transport(_)))));
+        assert_eq!(count.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_pool_round_robin_and_failover() {
+        let (first, _) = serve(|_, _| Some(json!("0x1"))).await;
+        let (second, _) = serve(|_, _| Some(json!("0x2"))).await;
+        let pool = RpcPool::new_http(vec![first, second.clone()]).with_config(test_config());
+        assert_eq!(chain_id(&pool).await.unwrap(), 1);
+        assert_eq!(chain_id(&pool).await.unwrap(), 2);
+
+        // A failing endpoint is retried on the next one.
+        let (failing, _) = serve(|_, _| None).await;
+        let pool = RpcPool::new_http(vec![failing, second]).with_config(test_config());
+        assert_eq!(chain_id(&pool).await.unwrap(), 2);
+        assert_eq!(chain_id(&pool).await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_pool_rate_limit() {
+        let (url, _) = serve(|_, _| Some(json!("0x1"))).await;
+        let config = RpcPoolConfig { requests_per_second: NonZeroU32::new(20), ..test_config() };
+        let pool = RpcPool::new_http(vec![url]).with_config(config);
+
+        let start = Instant::now();
+        for _ in 0..3 {
+            chain_id(&pool).await.unwrap();
+        }
+        assert!(start.elapsed() >= Duration::from_millis(100));
+    }
+
+    #[tokio::test]
+    async fn test_pool_quorum() {
+        let (first, _) = serve(|_, _| Some(json!("0x01"))).await;
+        let (second, _) = serve(|_, _| Some(json!("0x01"))).await;
+        let (other, _) = serve(|_, _| Some(json!("0x02"))).await;
+        let (failing, _) = serve(|_, _| None).await;
+        let config =
+            RpcPoolConfig { quorum: NonZeroUsize::new(2), max_retries: 0, ..test_config() };
+
+        let pool = RpcPool::new_http(vec![first.clone(), second.clone(), failing.clone()])
+            .with_config(config);
+        assert_eq!(raw_header(&pool).await.unwrap(), Bytes::from_static(&[0x01]));
+
+        let pool = RpcPool::new_http(vec![first.clone(), second, other]).with_config(config);
+        assert!(matches!(raw_header(&pool).await, Err(RpcPoolError::QuorumDisagreement)));
+
+        let pool = RpcPool::new_http(vec![first, failing]).with_config(config);
+        assert!(matches!(raw_header(&pool).await, Err(RpcPoolError::QuorumNotReached(1, 2))));
+    }
+
+    #[tokio::test]
+    async fn test_is_retryable() {
+        let http_error =
+            |status| TransportErrorKind::HttpError(HttpError { status, body: String::new() });
+        assert!(is_retryable(&http_error(429)));
+        assert!(is_retryable(&http_error(500)));
+        assert!(is_retryable(&http_error(503)));
+        assert!(!is_retryable(&http_error(400)));
+        assert!(!is_retryable(&http_error(404)));
+        assert!(is_retryable(&TransportErrorKind::BackendGone));
+        assert!(!is_retryable(&TransportErrorKind::PubsubUnavailable));
+        assert!(!is_retryable(&TransportErrorKind::Custom("invalid response".into())));
+
+        // A connection refused by a closed port.
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        drop(listener);
+        let e = reqwest::get(format!("http://{address}")).await.unwrap_err();
+        assert!(is_retryable(&TransportErrorKind::Custom(Box::new(e))));
+    }
+
+    #[tokio::test]
+    async fn test_pool_no_endpoints() {
+        let pool = RpcPool::new(Vec::new());
+        assert!(matches!(chain_id(&pool).await, Err(RpcPoolError::NoEndpoints)));
+        assert!(matches!(raw_header(&pool).await, Err(RpcPoolError::NoEndpoints)));
+    }
+}